@@ -0,0 +1,65 @@
+use std::time::Duration;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use cache_loader_async::backing::{CacheBacking, HashMapBacking};
+#[cfg(feature = "lru-cache")]
+use cache_loader_async::backing::LruCacheBacking;
+#[cfg(feature = "ttl-cache")]
+use cache_loader_async::backing::TtlCacheBacking;
+use cache_loader_async::cache_api::CacheEntry;
+use cache_loader_async::loadgen::Workload;
+
+const OPERATIONS: usize = 10_000;
+
+fn workloads() -> Vec<(&'static str, Workload)> {
+    vec![
+        ("read_heavy", Workload::new(OPERATIONS).read_ratio(0.95).miss_ratio(0.01)),
+        ("mixed", Workload::new(OPERATIONS).read_ratio(0.5).miss_ratio(0.1)),
+        ("write_heavy", Workload::new(OPERATIONS).read_ratio(0.1).miss_ratio(0.1)),
+        ("miss_heavy", Workload::new(OPERATIONS).read_ratio(0.9).miss_ratio(0.5)),
+        ("concurrent", Workload::new(OPERATIONS).concurrency(8)),
+    ]
+}
+
+/// Benchmarks every workload against a fresh backing per iteration, the preloading of the key
+/// space isn't measured
+fn bench_backing<B, F>(c: &mut Criterion, name: &str, backing: F)
+    where B: CacheBacking<u64, CacheEntry<u64, ()>> + Send + 'static,
+          F: Fn() -> B {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group(name);
+    for (workload_name, workload) in workloads() {
+        group.bench_with_input(BenchmarkId::from_parameter(workload_name), &workload, |b, workload| {
+            b.iter_custom(|iterations| {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iterations {
+                    elapsed += runtime.block_on(workload.run(backing())).elapsed;
+                }
+                elapsed
+            });
+        });
+    }
+    group.finish();
+}
+
+fn hash_map(c: &mut Criterion) {
+    bench_backing(c, "hash_map", HashMapBacking::new);
+}
+
+#[cfg(feature = "lru-cache")]
+fn lru(c: &mut Criterion) {
+    bench_backing(c, "lru", || LruCacheBacking::new(512));
+}
+
+#[cfg(not(feature = "lru-cache"))]
+fn lru(_: &mut Criterion) {}
+
+#[cfg(feature = "ttl-cache")]
+fn ttl(c: &mut Criterion) {
+    bench_backing(c, "ttl", || TtlCacheBacking::new(Duration::from_secs(60)));
+}
+
+#[cfg(not(feature = "ttl-cache"))]
+fn ttl(_: &mut Criterion) {}
+
+criterion_group!(benches, hash_map, lru, ttl);
+criterion_main!(benches);
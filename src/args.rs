@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use futures::future::BoxFuture;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, CacheLoadingError, LoadingCache};
+
+pub(crate) type ArgsLoader<K, V, E, A> = Arc<dyn Fn(K, A) -> BoxFuture<'static, Result<V, E>> + Send + Sync + 'static>;
+// the arguments of the loads which are running, by key
+pub(crate) type RunningArgs<K, A> = Arc<Mutex<HashMap<K, A>>>;
+
+/// What `ArgsLoadingCache::get_with_args` does when a load of the key with other arguments is
+/// already running
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ArgsConflict {
+    /// Waits for the running load and receives its value, the arguments of the call which
+    /// started the load win, `A::default()` for a load started by the underlying cache
+    #[default]
+    Join,
+    /// Returns the cached value if there is one, otherwise calls the loader with its own
+    /// arguments without caching the result, e.g. if the arguments are an auth context whose
+    /// values must not be handed to other principals
+    Bypass,
+}
+
+/// A `LoadingCache` whose loader takes per-call arguments besides the key, like an auth context
+/// or a locale
+///
+/// Loads are still deduplicated by key only, `ArgsConflict` decides what happens to a call whose
+/// arguments differ from the ones of the running load of its key. Created by
+/// `LoadingCache::with_args_loader`, see there for details.
+pub struct ArgsLoadingCache<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    A,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> {
+    cache: LoadingCache<K, V, E, B>,
+    loader: ArgsLoader<K, V, E, A>,
+    conflict: ArgsConflict,
+    // the arguments of the calls which are loading their key
+    running: RunningArgs<K, A>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    A,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> Clone for ArgsLoadingCache<K, V, E, A, B> {
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            loader: self.loader.clone(),
+            conflict: self.conflict,
+            running: self.running.clone(),
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    A: Clone + PartialEq + Send + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> ArgsLoadingCache<K, V, E, A, B> {
+    pub(crate) fn new(cache: LoadingCache<K, V, E, B>, loader: ArgsLoader<K, V, E, A>, conflict: ArgsConflict, running: RunningArgs<K, A>) -> Self {
+        Self {
+            cache,
+            loader,
+            conflict,
+            running,
+        }
+    }
+
+    /// Returns the underlying cache, whose `get` loads with the default arguments
+    ///
+    /// Its loads count as running with `A::default()`, so `get_with_args` with other arguments
+    /// joins or bypasses them according to the `ArgsConflict`.
+    pub fn cache(&self) -> &LoadingCache<K, V, E, B> {
+        &self.cache
+    }
+
+    /// Retrieves the value for the specified key from the cache or loads it with the loader,
+    /// which receives the arguments of this call
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be loaded
+    /// * `args` - The arguments passed to the loader if this call loads the key
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V
+    /// Err - Error of type CacheLoadingError
+    pub async fn get_with_args(&self, key: K, args: A) -> Result<V, CacheLoadingError<E>> {
+        let joined = match register(&self.running, &key, &args) {
+            Ok(guard) => Some((args.clone(), Some(guard))),
+            Err(running_args) if running_args != args && self.conflict == ArgsConflict::Bypass => None,
+            // the joining call may reach the actor first, so it loads with the running arguments
+            Err(running_args) => Some((running_args, None)),
+        };
+        let (args, _guard) = match joined {
+            Some(joined) => joined,
+            None => {
+                if let Some(value) = self.cache.get_if_present(key.clone()).await? {
+                    return Ok(value);
+                }
+                return (self.loader)(key, args).await.map_err(CacheLoadingError::LoadingError);
+            }
+        };
+        let load = (self.loader)(key.clone(), args);
+        self.cache.get_or_insert_with(key, load).await
+    }
+}
+
+/// Wraps the loader for the underlying cache, which loads with `A::default()` unless a
+/// `get_with_args` call registered its arguments for the key and didn't reach the actor yet
+pub(crate) fn default_loader<K, V, E, A>(loader: ArgsLoader<K, V, E, A>, running: RunningArgs<K, A>) -> impl Fn(K) -> BoxFuture<'static, Result<V, E>> + Send + Sync + 'static
+    where K: Eq + Hash + Clone + Send + 'static,
+          V: Send + 'static,
+          E: Send + 'static,
+          A: Default + Clone + Send + 'static {
+    move |key: K| {
+        let (args, guard) = match register(&running, &key, &A::default()) {
+            Ok(guard) => (A::default(), Some(guard)),
+            Err(running_args) => (running_args, None),
+        };
+        let load = loader(key, args);
+        Box::pin(async move {
+            let _guard = guard;
+            load.await
+        })
+    }
+}
+
+/// Registers the arguments of a load of the key, or returns the arguments of the load which is
+/// running already
+fn register<K: Eq + Hash + Clone, A: Clone>(running: &RunningArgs<K, A>, key: &K, args: &A) -> Result<RunningGuard<K, A>, A> {
+    let mut registered = running.lock().unwrap();
+    if let Some(running_args) = registered.get(key) {
+        return Err(running_args.clone());
+    }
+    registered.insert(key.clone(), args.clone());
+    Ok(RunningGuard {
+        key: key.clone(),
+        running: running.clone(),
+    })
+}
+
+/// Forgets the arguments of the call which started a load once it completed or its caller
+/// stopped waiting
+struct RunningGuard<K: Eq + Hash, A> {
+    key: K,
+    running: RunningArgs<K, A>,
+}
+
+impl<K: Eq + Hash, A> Drop for RunningGuard<K, A> {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(&self.key);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt::Debug;
+use std::future::Future;
+use std::hash::Hash;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, CacheLoadingError, LoadingCache};
+use crate::mapped::MappedLoadingCache;
+
+/// A minimal asynchronous cache interface
+///
+/// Code written against this trait can use a `LoadingCache` (or a `MappedLoadingCache`) as well as
+/// other cache implementations, which eases moving existing code bases to or away from this crate.
+pub trait AsyncCache<K, V>: Clone + Send + Sync {
+    type Error;
+
+    /// Retrieves the value of the key, loading it if the cache is able to
+    fn get(&self, key: K) -> impl Future<Output = Result<V, Self::Error>> + Send;
+
+    /// Retrieves the value of the key if it is present, without loading it
+    fn get_if_present(&self, key: K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send;
+
+    /// Sets the value of the key, returning the previous value
+    fn set(&self, key: K, value: V) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send;
+
+    /// Removes the key, returning its value
+    fn remove(&self, key: K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send;
+
+    /// Removes all keys
+    fn clear(&self) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Sized + Send + Sync + 'static,
+    E: Clone + Sized + Send + Sync + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> AsyncCache<K, V> for LoadingCache<K, V, E, B> {
+    type Error = CacheLoadingError<E>;
+
+    fn get(&self, key: K) -> impl Future<Output = Result<V, Self::Error>> + Send {
+        LoadingCache::get(self, key)
+    }
+
+    fn get_if_present(&self, key: K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        LoadingCache::get_if_present(self, key)
+    }
+
+    fn set(&self, key: K, value: V) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        LoadingCache::set(self, key, value)
+    }
+
+    fn remove(&self, key: K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        LoadingCache::remove(self, key)
+    }
+
+    fn clear(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        LoadingCache::clear(self)
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Sized + Send + Sync + 'static,
+    E: Clone + Sized + Send + Sync + Debug + 'static,
+    F: Debug + Send + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> AsyncCache<K, V> for MappedLoadingCache<K, V, E, F, B> {
+    type Error = CacheLoadingError<F>;
+
+    fn get(&self, key: K) -> impl Future<Output = Result<V, Self::Error>> + Send {
+        MappedLoadingCache::get(self, key)
+    }
+
+    fn get_if_present(&self, key: K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        MappedLoadingCache::get_if_present(self, key)
+    }
+
+    fn set(&self, key: K, value: V) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        MappedLoadingCache::set(self, key, value)
+    }
+
+    fn remove(&self, key: K) -> impl Future<Output = Result<Option<V>, Self::Error>> + Send {
+        MappedLoadingCache::remove(self, key)
+    }
+
+    fn clear(&self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        MappedLoadingCache::clear(self)
+    }
+}
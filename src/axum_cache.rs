@@ -0,0 +1,153 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::Deref;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, CacheLoadingError, LoadingCache};
+
+/// A `LoadingCache` which can be shared with axum handlers as an `Extension`
+///
+/// Once registered with `.layer(Extension(shared_cache))`, handlers can take the `SharedCache`
+/// itself as an extractor.
+///
+/// # Examples
+///
+/// ```
+/// use axum::{Extension, Router};
+/// use axum::routing::get;
+/// use cache_loader_async::axum_cache::SharedCache;
+/// use cache_loader_async::backing::HashMapBacking;
+/// use cache_loader_async::cache_api::{CacheEntry, LoadingCache};
+///
+/// type UserCache = SharedCache<u64, String, u8, HashMapBacking<u64, CacheEntry<String, u8>>>;
+///
+/// async fn user(cache: UserCache) -> String {
+///     cache.get(1).await.unwrap_or_default()
+/// }
+///
+/// fn router() -> Router {
+///     let cache: UserCache = SharedCache::new(LoadingCache::new(move |id: u64| {
+///         async move {
+///             Ok(format!("user-{}", id))
+///         }
+///     }));
+///
+///     Router::new()
+///         .route("/user", get(user))
+///         .nest("/admin/cache", cache.admin_router())
+///         .layer(Extension(cache))
+/// }
+/// ```
+pub struct SharedCache<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+>(pub LoadingCache<K, V, E, B>);
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> SharedCache<K, V, E, B> {
+    pub fn new(cache: LoadingCache<K, V, E, B>) -> SharedCache<K, V, E, B> {
+        SharedCache(cache)
+    }
+
+    pub fn into_inner(self) -> LoadingCache<K, V, E, B> {
+        self.0
+    }
+
+    /// Creates a router with administrative endpoints for this cache, meant to be nested
+    /// into the application router
+    ///
+    /// * `POST /clear` - Removes all entries from the cache
+    /// * `GET /verify` - Lists the violated invariants of the backing, one per line, see
+    ///   `LoadingCache::verify`
+    /// * `POST /repair` - Lists the violated invariants like `GET /verify` and repairs them
+    pub fn admin_router<S: Clone + Send + Sync + 'static>(&self) -> Router<S> {
+        let cache = self.0.clone();
+        let verify_cache = self.0.clone();
+        let repair_cache = self.0.clone();
+        Router::new()
+            .route("/clear", post(move || {
+                let cache = cache.clone();
+                async move {
+                    cache.clear().await
+                        .map(|_| StatusCode::NO_CONTENT.into_response())
+                        .unwrap_or_else(|error| error_response(cache.name(), error))
+                }
+            }))
+            .route("/verify", get(move || verify_response(verify_cache.clone(), false)))
+            .route("/repair", post(move || verify_response(repair_cache.clone(), true)))
+    }
+}
+
+async fn verify_response<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+>(cache: LoadingCache<K, V, E, B>, repair: bool) -> Response {
+    match cache.verify(repair).await {
+        Ok(inconsistencies) => inconsistencies.iter()
+            .map(|inconsistency| format!("{}\n", inconsistency))
+            .collect::<String>()
+            .into_response(),
+        Err(error) => error_response(cache.name(), error),
+    }
+}
+
+fn error_response<E: Debug>(name: Option<&str>, error: CacheLoadingError<E>) -> Response {
+    let message = match name {
+        Some(name) => format!("Cache {}: {}", name, error),
+        None => error.to_string(),
+    };
+    (StatusCode::INTERNAL_SERVER_ERROR, message).into_response()
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> Clone for SharedCache<K, V, E, B> {
+    fn clone(&self) -> Self {
+        SharedCache(self.0.clone())
+    }
+}
+
+impl<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> Deref for SharedCache<K, V, E, B> {
+    type Target = LoadingCache<K, V, E, B>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<
+    S: Send + Sync,
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Sized + Send + Sync + 'static,
+    E: Clone + Sized + Send + Sync + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> FromRequestParts<S> for SharedCache<K, V, E, B> {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<SharedCache<K, V, E, B>>()
+            .cloned()
+            .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "The SharedCache extension is missing"))
+    }
+}
@@ -1,17 +1,71 @@
 use std::collections::HashMap;
+use std::iter::FromIterator;
 use std::hash::Hash;
 #[cfg(feature = "lru-cache")]
-use lru::LruCache;
-#[cfg(feature = "ttl-cache")]
-use std::collections::VecDeque;
+use lru::{DefaultHasher, LruCache};
+use std::hash::{BuildHasher, Hasher};
+use std::collections::BTreeMap;
+use std::collections::hash_map::RandomState;
 use std::fmt::Debug;
 #[cfg(feature = "ttl-cache")]
 use std::marker::PhantomData;
 use thiserror::Error;
 #[cfg(feature = "ttl-cache")]
-use std::ops::Add;
 #[cfg(feature = "ttl-cache")]
 use tokio::time::{Instant, Duration};
+use std::sync::Arc;
+use crate::codec::CodecError;
+use crate::stats::{BackingStats, MaintenanceReport, Occupancy};
+use crate::rng::XorShift;
+#[cfg(feature = "lru-cache")]
+use crate::stats::LruStats;
+#[cfg(feature = "ttl-cache")]
+use crate::stats::TtlStats;
+#[cfg(feature = "ttl-cache")]
+use std::sync::Mutex;
+#[cfg(feature = "ttl-cache")]
+use crate::clock::{Clock, ExpiryTime, TokioClock};
+#[cfg(feature = "ttl-cache")]
+use std::time::SystemTime;
+
+#[cfg(feature = "object-store")]
+mod object_store;
+mod recording;
+#[cfg(feature = "redis-cache")]
+mod redis;
+#[cfg(feature = "shared-memory")]
+mod shared_memory;
+#[cfg(feature = "sled-cache")]
+mod sled;
+
+#[cfg(feature = "object-store")]
+pub use object_store::{ColdTier, ObjectStore, ObjectStoreBacking};
+pub use recording::{BackingOperation, OperationLog, RecordingBacking};
+#[cfg(feature = "redis-cache")]
+pub use self::redis::RedisBacking;
+#[cfg(feature = "shared-memory")]
+pub use shared_memory::SharedMemoryBacking;
+#[cfg(feature = "sled-cache")]
+pub use self::sled::SledBacking;
+
+pub type BackingPredicate<K, V> = Box<dyn Fn((&K, &V)) -> bool + Send + Sync + 'static>;
+
+/// Why an entry was removed, passed to a `RemovalListener`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RemovalCause {
+    /// Removed by `LoadingCache::remove` or `LoadingCache::remove_if`
+    Explicit,
+    /// Removed by `LoadingCache::clear`
+    Cleared,
+    /// Evicted to stay within the capacity or weight of the backing, or by `CacheBacking::evict`
+    Evicted,
+    /// Removed after it expired
+    Expired,
+}
+
+/// Receives removed entries with the cause of their removal, see
+/// `CacheBacking::set_removal_listener` and `CacheOptions::removal_listener`
+pub type RemovalListener<K, V> = Arc<dyn Fn(K, V, RemovalCause) + Send + Sync>;
 
 pub trait CacheBacking<K, V>
     where K: Eq + Hash + Sized + Clone + Send,
@@ -23,54 +77,404 @@ pub trait CacheBacking<K, V>
     fn set(&mut self, key: K, value: V, meta: Option<Self::Meta>) -> Result<Option<V>, BackingError>;
     fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError>;
     fn contains_key(&mut self, key: &K) -> Result<bool, BackingError>;
-    fn remove_if(&mut self, predicate: Box<dyn Fn((&K, &V)) -> bool + Send + Sync + 'static>) -> Result<Vec<(K, V)>, BackingError>;
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError>;
     fn clear(&mut self) -> Result<(), BackingError>;
+
+    /// Stores the value of the key after it was changed through the reference returned by
+    /// `get_mut`, the cache calls this within the same operation. Backings whose `get_mut` hands
+    /// out a copy, e.g. of a value read from another process, write the copy back here, the
+    /// others don't need to override this.
+    fn write_back(&mut self, _key: &K) -> Result<(), BackingError> {
+        Ok(())
+    }
+
+    /// Returns the value of the key like `get`, but without marking it as recently used.
+    /// Backings without a recency order don't need to override this.
+    fn peek(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        self.get(key)
+    }
+
+    /// Removes up to `count` entries in the order the backing would evict them, least recently
+    /// used first, and returns them. Backings without an eviction order don't need to override
+    /// this.
+    fn evict(&mut self, _count: usize) -> Result<Vec<(K, V)>, BackingError> {
+        Ok(Vec::new())
+    }
+
+    /// Returns copies of all entries, most recently used first. Backings without a recency order
+    /// don't need to override this and return nothing.
+    fn entries_by_recency(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        Ok(Vec::new())
+    }
+
+    /// Returns copies of all entries, including keys which are still loading, in no particular
+    /// order. By default the chunks of `export` are collected, backings which can't be exported
+    /// return nothing.
+    fn entries(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        let mut entries = Vec::new();
+        let mut cursor = Some(0);
+        while let Some(position) = cursor {
+            let chunk = self.export(position, usize::MAX)?;
+            entries.extend(chunk.entries.into_iter().map(|(key, value, _)| (key, value)));
+            cursor = chunk.next;
+        }
+        Ok(entries)
+    }
+
+    /// Restores the internal invariants of the backing after it reported an error, e.g. by
+    /// rebuilding an index. Backings without such bookkeeping don't need to override this.
+    fn repair(&mut self) -> Result<(), BackingError> {
+        Ok(())
+    }
+
+    /// Cross-checks the internal invariants of the backing and returns those which are violated,
+    /// `repair` restores them. Backings wrapping another backing include its inconsistencies,
+    /// backings without such bookkeeping don't need to override this.
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        Ok(Vec::new())
+    }
+
+    /// Removes all expired entries right away instead of on the next access. Backings without
+    /// expiry don't need to override this.
+    fn remove_expired(&mut self) -> Result<(), BackingError> {
+        Ok(())
+    }
+
+    /// Removes all expired entries like `remove_expired` and returns them. Backings without expiry
+    /// don't need to override this.
+    fn drain_expired(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        Ok(Vec::new())
+    }
+
+    /// Returns an expired value the backing still retains and how long it has been expired, if
+    /// that is no longer than `max_staleness`. Backings without expiry don't need to override
+    /// this.
+    fn get_stale(&mut self, _key: &K, _max_staleness: std::time::Duration) -> Result<Option<(V, std::time::Duration)>, BackingError> {
+        Ok(None)
+    }
+
+    /// Returns how long the value of the key remains before it expires, `None` if the key isn't
+    /// stored or doesn't expire. Backings without expiry don't need to override this.
+    fn time_to_live(&mut self, _key: &K) -> Result<Option<std::time::Duration>, BackingError> {
+        Ok(None)
+    }
+
+    /// Returns how long remains until the first entry expires, `None` if no entry expires.
+    /// Backings without expiry don't need to override this.
+    fn next_expiry(&mut self) -> Result<Option<std::time::Duration>, BackingError> {
+        Ok(None)
+    }
+
+    /// Returns copies of up to `count` entries starting at `cursor`, with the meta to set them
+    /// into another backing with, and the cursor of the next chunk. The first chunk starts at
+    /// cursor `0`. Entries which are set or removed between two calls may be skipped or returned
+    /// twice. Backings which can't be iterated don't need to override this and return nothing.
+    fn export(&mut self, _cursor: usize, _count: usize) -> Result<ExportChunk<K, V, Self::Meta>, BackingError> {
+        Ok(ExportChunk::default())
+    }
+
+    /// Registers a listener which is called with the entries the backing removes on its own,
+    /// because they were evicted or expired, including the ones returned by `evict` and
+    /// `drain_expired`. Entries removed through `remove`, `remove_if` and `clear` aren't
+    /// reported, their caller knows about them. Backings wrapping another backing pass the
+    /// listener on, backings which never remove entries on their own don't need to override this.
+    fn set_removal_listener(&mut self, _listener: RemovalListener<K, V>) {}
+
+    /// Returns the statistics of the backing, backings wrapping another backing include its
+    /// statistics
+    fn stats(&self) -> BackingStats {
+        BackingStats::default()
+    }
+
+    /// Returns the `Maintenance` of a backing which stores its entries on disk, backings wrapping
+    /// another backing return its maintenance. In-memory backings don't need to override this.
+    fn maintenance(&mut self) -> Option<&mut dyn Maintenance> {
+        None
+    }
+}
+
+/// Upkeep of a backing which stores its entries on disk, keeping its size bounded
+///
+/// The cache runs `compact` and `vacuum` every `CacheOptions::maintenance` interval, or when
+/// `LoadingCache::maintain` is called.
+pub trait Maintenance: Send {
+    /// Rewrites the storage without the space taken by removed or replaced entries
+    fn compact(&mut self) -> Result<(), BackingError>;
+
+    /// Returns the free space of the storage to the file system
+    fn vacuum(&mut self) -> Result<(), BackingError>;
+
+    /// Reports the size of the storage
+    fn report(&self) -> MaintenanceReport;
+}
+
+/// A violated invariant of a backing, found by `CacheBacking::verify`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inconsistency {
+    /// The type of the backing whose invariant is violated
+    pub backing: &'static str,
+    pub description: String,
+}
+
+impl Inconsistency {
+    pub fn new<S: Into<String>>(backing: &'static str, description: S) -> Self {
+        Self {
+            backing,
+            description: description.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.backing, self.description)
+    }
+}
+
+/// An entry returned by `CacheBacking::export`
+pub type ExportedEntry<K, V, M> = (K, V, Option<M>);
+
+/// A chunk of the entries of a backing, see `CacheBacking::export`
+#[derive(Debug, Clone)]
+pub struct ExportChunk<K, V, M> {
+    pub entries: Vec<ExportedEntry<K, V, M>>,
+    /// The cursor of the next chunk, `None` once all entries have been exported
+    pub next: Option<usize>,
+}
+
+impl<K, V, M> Default for ExportChunk<K, V, M> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            next: None,
+        }
+    }
+}
+
+impl<K, V, M> ExportChunk<K, V, M> {
+    /// Takes the chunk at `cursor` from the entries of a backing, the cursor is their position
+    pub fn from_position<I>(entries: I, cursor: usize, count: usize) -> Self
+        where I: Iterator<Item = ExportedEntry<K, V, M>> {
+        let mut entries = entries.skip(cursor);
+        let chunk = entries.by_ref().take(count).collect::<Vec<_>>();
+        let next = entries.next().map(|_| cursor + chunk.len());
+        Self {
+            entries: chunk,
+            next,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Error)]
 pub enum BackingError {
     #[error(transparent)]
     TtlError(#[from] TtlError),
+    #[error(transparent)]
+    CodecError(#[from] CodecError),
+    /// A fault injected by a test, e.g. through the `FaultyBacking` of the `test-util` feature
+    #[error("Injected fault: {0}")]
+    InjectedFault(String),
+    /// Reading or writing the storage of a backing failed, e.g. a file of a disk backing
+    #[error("I/O error of {backing}: {source}")]
+    Io {
+        /// The type of the backing which failed
+        backing: &'static str,
+        source: Arc<std::io::Error>,
+    },
+    /// A remote backing couldn't be reached or didn't answer in time, retrying the operation
+    /// may succeed
+    #[error("{backing} is unavailable: {reason}")]
+    Unavailable {
+        /// The type of the backing which failed
+        backing: &'static str,
+        reason: String,
+    },
+    /// Any other failure of a backing, e.g. an error of the client library of a remote store
+    #[error("{backing} failed: {source}")]
+    Other {
+        /// The type of the backing which failed
+        backing: &'static str,
+        source: Arc<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl BackingError {
+    pub fn io(backing: &'static str, error: std::io::Error) -> Self {
+        BackingError::Io {
+            backing,
+            source: Arc::new(error),
+        }
+    }
+
+    pub fn unavailable<S: Into<String>>(backing: &'static str, reason: S) -> Self {
+        BackingError::Unavailable {
+            backing,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn other<E: std::error::Error + Send + Sync + 'static>(backing: &'static str, error: E) -> Self {
+        BackingError::Other {
+            backing,
+            source: Arc::new(error),
+        }
+    }
+
+    /// Returns the type of the backing which failed, `None` for errors raised by the cache itself
+    /// like `TtlError`, or by a codec
+    pub fn backing(&self) -> Option<&'static str> {
+        match self {
+            BackingError::Io { backing, .. }
+            | BackingError::Unavailable { backing, .. }
+            | BackingError::Other { backing, .. } => Some(backing),
+            _ => None,
+        }
+    }
+
+    /// Returns whether retrying the operation may succeed, because the backing was unavailable
+    /// or its I/O was interrupted or timed out
+    pub fn is_transient(&self) -> bool {
+        match self {
+            BackingError::Unavailable { .. } => true,
+            BackingError::Io { source, .. } => matches!(source.kind(),
+                std::io::ErrorKind::Interrupted | std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct NoMeta {}
 
 #[cfg(feature = "lru-cache")]
-pub struct LruCacheBacking<K, V> {
-    lru: LruCache<K, V>,
+pub struct LruCacheBacking<K, V, S = DefaultHasher> {
+    // every value is stored with its weight
+    lru: LruCache<K, (V, usize), S>,
+    // a key is either in the protected or the regular segment, never in both
+    protected: Option<LruCache<K, (V, usize), S>>,
+    protect_if: Option<ProtectPredicate<K>>,
+    eviction_listener: Option<EvictionListener<K, V>>,
+    removal_listener: Option<RemovalListener<K, V>>,
+    weigher: Option<Weigher<K, V>>,
+    max_weight: Option<usize>,
+    weight: usize,
+}
+
+/// Receives the entries an `LruCacheBacking` evicts to stay within its capacity, see
+/// `LruCacheBacking::on_evict`
+#[cfg(feature = "lru-cache")]
+pub type EvictionListener<K, V> = Box<dyn Fn(K, V) + Send + Sync>;
+
+/// Computes the weight of entries set without an `LruMeta`, see `LruCacheBacking::weigher`
+#[cfg(feature = "lru-cache")]
+pub type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize + Send + Sync>;
+
+/// Decides whether a key belongs to the protected segment, see `LruCacheBacking::protect_if`
+#[cfg(feature = "lru-cache")]
+pub type ProtectPredicate<K> = Box<dyn Fn(&K) -> bool + Send + Sync>;
+
+#[cfg(feature = "lru-cache")]
+#[derive(Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LruMeta {
+    /// The weight of the entry, computed by the weigher of the backing if `None`
+    pub weight: Option<usize>,
+    /// Whether the entry belongs to the protected segment, see
+    /// `LruCacheBacking::protected_segment`
+    pub protected: bool,
+}
+
+#[cfg(feature = "lru-cache")]
+impl LruMeta {
+    pub fn new() -> LruMeta {
+        Self::default()
+    }
+
+    pub fn with_weight(mut self, weight: usize) -> LruMeta {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Marks the entry as protected, so it's stored in the protected segment of the backing
+    pub fn protected(mut self) -> LruMeta {
+        self.protected = true;
+        self
+    }
+}
+
+#[cfg(feature = "lru-cache")]
+impl From<usize> for LruMeta {
+    fn from(weight: usize) -> Self {
+        Self::new().with_weight(weight)
+    }
 }
 
 #[cfg(feature = "lru-cache")]
 impl<
     K: Eq + Hash + Sized + Clone + Send,
-    V: Sized + Clone + Send
-> CacheBacking<K, V> for LruCacheBacking<K, V> {
-    type Meta = NoMeta;
+    V: Sized + Clone + Send,
+    S: BuildHasher,
+> CacheBacking<K, V> for LruCacheBacking<K, V, S> {
+    type Meta = LruMeta;
 
     fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
-        Ok(self.lru.get_mut(key))
+        Ok(self.segment(key).get_mut(key).map(|(value, _)| value))
     }
 
     fn get(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
-        Ok(self.lru.get(key))
+        Ok(self.segment(key).get(key).map(|(value, _)| value))
     }
 
-    fn set(&mut self, key: K, value: V, _meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
-        Ok(self.lru.put(key, value))
+    fn set(&mut self, key: K, value: V, meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
+        let meta = meta.unwrap_or_default();
+        let protect = self.protected.is_some()
+            && (meta.protected || self.protect_if.as_ref().is_some_and(|protect_if| protect_if(&key)));
+        let weight = meta.weight
+            .or_else(|| self.weigher.as_ref().map(|weigher| weigher(&key, &value)))
+            .unwrap_or(1);
+        let (segment, other) = match &mut self.protected {
+            Some(protected) if protect => (protected, Some(&mut self.lru)),
+            protected => (&mut self.lru, protected.as_mut()),
+        };
+        if segment.cap() == 0 {
+            return Ok(None);
+        }
+        self.weight += weight;
+        // a key changing its segment is replaced like a key within the segment
+        let moved = other.and_then(|other| other.pop(&key));
+        // push returns the replaced entry of the key as well as an evicted one
+        let replacing = segment.contains(&key);
+        let (replaced, evicted) = match segment.push(key, (value, weight)) {
+            Some((_, replaced)) if replacing => (Some(replaced), None),
+            Some(evicted) => (moved, Some(evicted)),
+            None => (moved, None),
+        };
+        let replaced = replaced.map(|(replaced, replaced_weight)| {
+            self.weight -= replaced_weight;
+            replaced
+        });
+        if let Some((evicted_key, (evicted, evicted_weight))) = evicted {
+            self.weight -= evicted_weight;
+            self.notify_eviction(evicted_key, evicted);
+        }
+        self.evict_overweight(!protect);
+        Ok(replaced)
     }
 
     fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError> {
-        Ok(self.lru.pop(key))
+        Ok(self.segment(key).pop(key).map(|(value, weight)| {
+            self.weight -= weight;
+            value
+        }))
     }
 
     fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
-        Ok(self.lru.contains(&key.clone()))
+        Ok(self.segment(key).contains(key))
     }
 
-    fn remove_if(&mut self, predicate: Box<dyn Fn((&K, &V)) -> bool + Send + Sync>) -> Result<Vec<(K, V)>, BackingError> {
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
         let mut removed = Vec::new();
-        let keys = self.lru.iter()
+        let keys = self.iter()
             .filter_map(|(key, value)| {
                 if predicate((key, value)) {
                     Some(key)
@@ -81,16 +485,113 @@ impl<
             .cloned()
             .collect::<Vec<K>>();
         for key in keys.into_iter() {
-            let val = self.lru.pop(&key);
-            removed.push((key, val.expect("LRU value is empty")))
+            let (val, weight) = self.segment(&key).pop(&key).expect("LRU value is empty");
+            self.weight -= weight;
+            removed.push((key, val))
         }
         Ok(removed)
     }
 
     fn clear(&mut self) -> Result<(), BackingError> {
         self.lru.clear();
+        if let Some(protected) = &mut self.protected {
+            protected.clear();
+        }
+        self.weight = 0;
+        Ok(())
+    }
+
+    fn peek(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        Ok(self.segment(key).peek(key).map(|(value, _)| value))
+    }
+
+    fn evict(&mut self, count: usize) -> Result<Vec<(K, V)>, BackingError> {
+        // protected entries are evicted once the regular segment is empty
+        let (lru, protected) = (&mut self.lru, &mut self.protected);
+        let evicted = std::iter::from_fn(|| lru.pop_lru())
+            .chain(std::iter::from_fn(|| protected.as_mut().and_then(|protected| protected.pop_lru())))
+            .take(count)
+            .collect::<Vec<_>>();
+        Ok(evicted.into_iter()
+            .map(|(key, (value, weight))| {
+                self.weight -= weight;
+                if let Some(listener) = &self.removal_listener {
+                    listener(key.clone(), value.clone(), RemovalCause::Evicted);
+                }
+                (key, value)
+            })
+            .collect())
+    }
+
+    fn entries_by_recency(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        Ok(self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, V, LruMeta>, BackingError> {
+        // least recently used first, so setting the entries in order restores their recency
+        let regular = self.lru.iter().rev().map(|entry| (entry, false));
+        let protected = self.protected.iter()
+            .flat_map(|protected| protected.iter().rev())
+            .map(|entry| (entry, true));
+        let entries = regular.chain(protected)
+            .map(|((key, (value, weight)), protected)| {
+                (key.clone(), value.clone(), Some(LruMeta { weight: Some(*weight), protected }))
+            });
+        Ok(ExportChunk::from_position(entries, cursor, count))
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        if let Some(protected) = &self.protected {
+            let duplicates = protected.iter()
+                .filter(|(key, _)| self.lru.contains(*key))
+                .map(|(key, _)| key.clone())
+                .collect::<Vec<_>>();
+            for key in duplicates {
+                self.lru.pop(&key);
+            }
+        }
+        self.weight = self.stored_weight();
         Ok(())
     }
+
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        let mut inconsistencies = Vec::new();
+        let duplicates = self.protected.iter()
+            .flat_map(|protected| protected.iter())
+            .filter(|(key, _)| self.lru.contains(*key))
+            .count();
+        if duplicates > 0 {
+            inconsistencies.push(Inconsistency::new("LruCacheBacking",
+                format!("{} keys are in both the regular and the protected segment", duplicates)));
+        }
+        let stored_weight = self.stored_weight();
+        if stored_weight != self.weight {
+            inconsistencies.push(Inconsistency::new("LruCacheBacking",
+                format!("The tracked weight is {}, the entries weigh {}", self.weight, stored_weight)));
+        }
+        Ok(inconsistencies)
+    }
+
+    fn set_removal_listener(&mut self, listener: RemovalListener<K, V>) {
+        self.removal_listener = Some(listener);
+    }
+
+    fn stats(&self) -> BackingStats {
+        BackingStats {
+            lru: Some(LruStats {
+                capacity: self.capacity(),
+                len: self.len(),
+                weight: self.weight,
+            }),
+            occupancy: Some(Occupancy {
+                entries: self.len(),
+                weight: self.weight,
+            }),
+            ..BackingStats::default()
+        }
+    }
 }
 
 #[cfg(feature = "lru-cache")]
@@ -99,47 +600,315 @@ impl<
     V: Sized + Clone + Send
 > LruCacheBacking<K, V> {
     pub fn new(size: usize) -> LruCacheBacking<K, V> {
-        LruCacheBacking {
-            lru: LruCache::new(size)
-        }
+        LruCacheBacking::from_lru(LruCache::new(size))
     }
 
     pub fn unbounded() -> LruCacheBacking<K, V> {
+        LruCacheBacking::from_lru(LruCache::unbounded())
+    }
+
+    /// Creates a backing holding as many entries as fit into roughly `budget` bytes
+    ///
+    /// `entry_heap_size` estimates the heap memory owned by an average entry, e.g. the bytes of
+    /// its strings, the memory the backing needs per entry in addition is accounted for. The
+    /// budget is converted into a capacity once, the size of the actual entries isn't tracked.
+    /// Use `max_weight` with a `weigher` returning the size of an entry to track it instead.
+    pub fn with_memory_budget(budget: usize, entry_heap_size: usize) -> LruCacheBacking<K, V> {
+        Self::new(Self::capacity_for_budget(budget, entry_heap_size))
+    }
+
+    fn capacity_for_budget(budget: usize, entry_heap_size: usize) -> usize {
+        // the entry is boxed with two links, the map holds two pointers and a control byte
+        let entry_size = std::mem::size_of::<(K, (V, usize))>() + 4 * std::mem::size_of::<usize>() + 1;
+        (budget / (entry_size + entry_heap_size)).max(1)
+    }
+}
+
+#[cfg(feature = "lru-cache")]
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+    S: BuildHasher,
+> LruCacheBacking<K, V, S> {
+    /// Creates a backing holding at most `size` entries, hashing the keys with the given hasher
+    pub fn with_hasher(size: usize, hash_builder: S) -> LruCacheBacking<K, V, S> {
+        LruCacheBacking::from_lru(LruCache::with_hasher(size, hash_builder))
+    }
+
+    pub fn unbounded_with_hasher(hash_builder: S) -> LruCacheBacking<K, V, S> {
+        LruCacheBacking::from_lru(LruCache::unbounded_with_hasher(hash_builder))
+    }
+
+    fn from_lru(lru: LruCache<K, (V, usize), S>) -> LruCacheBacking<K, V, S> {
         LruCacheBacking {
-            lru: LruCache::unbounded()
+            lru,
+            protected: None,
+            protect_if: None,
+            eviction_listener: None,
+            removal_listener: None,
+            weigher: None,
+            max_weight: None,
+            weight: 0,
+        }
+    }
+
+    /// Registers a listener which is called with every entry evicted to make room for a new key
+    ///
+    /// Only evictions due to the capacity or the `max_weight` are reported, not entries which are
+    /// replaced, removed
+    /// or evicted explicitly through `CacheBacking::evict`. Within a `LoadingCache` the values
+    /// are `CacheEntry`s, evicted `CacheEntry::Loading` entries belong to keys which were still
+    /// loading. The listener runs on the task of the cache and must not block it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::backing::LruCacheBacking;
+    /// use cache_loader_async::cache_api::{CacheEntry, LoadingCache};
+    ///
+    /// async fn example() {
+    ///     let backing = LruCacheBacking::new(1000)
+    ///         .on_evict(|key: String, entry: CacheEntry<usize, u8>| {
+    ///             if let CacheEntry::Loaded(value) = entry {
+    ///                 println!("evicted {} with value {}", key, value);
+    ///             }
+    ///         });
+    ///     let cache = LoadingCache::with_backing(backing, move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn on_evict<L: Fn(K, V) + Send + Sync + 'static>(mut self, listener: L) -> LruCacheBacking<K, V, S> {
+        self.eviction_listener = Some(Box::new(listener));
+        self
+    }
+
+    /// Limits the total weight of the entries, evicting the least recently used ones until the
+    /// entries fit into the limit again
+    ///
+    /// The weight of an entry is taken from its `LruMeta`, computed by the `weigher` or `1`
+    /// otherwise. The entry which was set last is never evicted for its weight, so an entry
+    /// heavier than the limit stays until the next entry is set. The capacity still limits the
+    /// number of entries, use `unbounded` to limit the entries by their weight only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::backing::LruCacheBacking;
+    /// use cache_loader_async::cache_api::{CacheEntry, LoadingCache};
+    ///
+    /// async fn example() {
+    ///     // holds up to 1 MiB of strings
+    ///     let backing = LruCacheBacking::unbounded()
+    ///         .max_weight(1 << 20)
+    ///         .weigher(|_: &String, entry: &CacheEntry<String, u8>| match entry {
+    ///             CacheEntry::Loaded(value) => value.len(),
+    ///             CacheEntry::Loading(_) => 0,
+    ///         });
+    ///     let cache = LoadingCache::with_backing(backing, move |key: String| {
+    ///         async move {
+    ///             Ok(key.repeat(1000))
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn max_weight(mut self, max_weight: usize) -> LruCacheBacking<K, V, S> {
+        self.max_weight = Some(max_weight);
+        self
+    }
+
+    /// Computes the weight of entries set without an `LruMeta`, see `max_weight`
+    ///
+    /// The weight is computed when the entry is set, changes through `get_mut` don't update it.
+    pub fn weigher<W: Fn(&K, &V) -> usize + Send + Sync + 'static>(mut self, weigher: W) -> LruCacheBacking<K, V, S> {
+        self.weigher = Some(Box::new(weigher));
+        self
+    }
+
+    /// Decides which keys belong to the protected segment, in addition to the ones set with a
+    /// protected `LruMeta`, see `protected_segment`
+    pub fn protect_if<P: Fn(&K) -> bool + Send + Sync + 'static>(mut self, predicate: P) -> LruCacheBacking<K, V, S> {
+        self.protect_if = Some(Box::new(predicate));
+        self
+    }
+
+    /// Returns the total weight of the entries
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Returns the maximum number of entries including the protected segment, `usize::MAX` for
+    /// unbounded backings
+    pub fn capacity(&self) -> usize {
+        self.lru.cap().saturating_add(self.protected.as_ref().map_or(0, |protected| protected.cap()))
+    }
+
+    /// Returns the number of entries, including the ones in the protected segment
+    pub fn len(&self) -> usize {
+        self.lru.len() + self.protected_len()
+    }
+
+    /// Returns the number of entries in the protected segment
+    pub fn protected_len(&self) -> usize {
+        self.protected.as_ref().map_or(0, |protected| protected.len())
+    }
+
+    fn stored_weight(&self) -> usize {
+        self.lru.iter()
+            .chain(self.protected.iter().flat_map(|protected| protected.iter()))
+            .map(|(_, (_, weight))| weight)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the entries from the most to the least recently used one, without changing
+    /// their recency. Use `rev` to start with the entry which is evicted next.
+    ///
+    /// The entries of the protected segment come first, each segment is ordered by itself.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> {
+        self.protected.iter()
+            .flat_map(|protected| protected.iter())
+            .chain(self.lru.iter())
+            .map(|(key, (value, _))| (key, value))
+    }
+
+    /// Returns the segment holding the key, the regular one if the key doesn't exist
+    fn segment(&mut self, key: &K) -> &mut LruCache<K, (V, usize), S> {
+        match &mut self.protected {
+            Some(protected) if protected.contains(key) => protected,
+            _ => &mut self.lru,
+        }
+    }
+
+    /// Evicts entries of the regular segment until the weight is within the limit again,
+    /// keeping its most recently used entry if it was set last
+    fn evict_overweight(&mut self, keep_last: bool) {
+        let max_weight = match self.max_weight {
+            Some(max_weight) => max_weight,
+            None => return,
+        };
+        let keep = usize::from(keep_last);
+        while self.weight > max_weight && self.lru.len() > keep {
+            if let Some((key, (value, weight))) = self.lru.pop_lru() {
+                self.weight -= weight;
+                self.notify_eviction(key, value);
+            }
+        }
+    }
+
+    fn notify_eviction(&self, key: K, value: V) {
+        if let Some(listener) = &self.removal_listener {
+            listener(key.clone(), value.clone(), RemovalCause::Evicted);
+        }
+        if let Some(listener) = &self.eviction_listener {
+            listener(key, value);
         }
     }
 }
 
+#[cfg(feature = "lru-cache")]
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+    S: BuildHasher + Default,
+> LruCacheBacking<K, V, S> {
+    /// Adds a protected segment holding up to `capacity` entries, which are only evicted by
+    /// other protected entries
+    ///
+    /// Keys are protected if they are set with `LruMeta::protected` or match the `protect_if`
+    /// predicate, the other keys are stored in the regular segment. This keeps a small set of
+    /// critical entries cached, while bulk traffic competes for the regular segment. Protected
+    /// entries count towards the `max_weight`, but are only evicted by the capacity of their
+    /// segment. A key moves to the other segment when it's set again and its protection changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::backing::LruCacheBacking;
+    /// use cache_loader_async::cache_api::LoadingCache;
+    ///
+    /// async fn example() {
+    ///     let backing = LruCacheBacking::new(10000)
+    ///         .protected_segment(100)
+    ///         .protect_if(|key: &String| key.starts_with("config:"));
+    ///     let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+    ///         async move {
+    ///             Ok(key.to_lowercase())
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn protected_segment(mut self, capacity: usize) -> LruCacheBacking<K, V, S> {
+        self.protected = Some(LruCache::with_hasher(capacity, S::default()));
+        self
+    }
+}
+
 #[cfg(feature = "ttl-cache")]
 pub struct TtlCacheBacking<
     K: Clone + Eq + Hash + Send,
     V: Clone + Sized + Send,
-    B: CacheBacking<K, (V, Instant)>
+    B: CacheBacking<K, (V, C::Time)>,
+    C: Clock = TokioClock,
 > {
     phantom: PhantomData<V>,
     ttl: Duration,
-    expiry_queue: VecDeque<TTlEntry<K>>,
+    access_ttl: Option<Duration>,
+    // ordered by expiry, the handles of the keys allow removing them without searching the queue
+    expiry_queue: BTreeMap<ExpiryHandle<C::Time>, K>,
+    expiry_index: HashMap<K, ExpiryHandle<C::Time>>,
+    expiry_sequence: u64,
     map: B,
+    clock: C,
+    expiration_mode: ExpirationMode,
+    ttl_from_value: Option<TtlFromValue<V>>,
+    ttl_per_key: Option<TtlPerKey<K>>,
+    stale_retention: Duration,
+    // expired values replaced while their key is reloaded
+    stale: HashMap<K, (V, C::Time)>,
+    purge_batch_size: Option<usize>,
+    expirations: u64,
+    removals: u64,
+    removal_listener: Option<RemovalListener<K, V>>,
 }
 
+/// Derives the TTL of an entry from its value, see `TtlCacheBacking::ttl_from_value`
 #[cfg(feature = "ttl-cache")]
-struct TTlEntry<K> {
-    key: K,
-    expiry: Instant,
+pub type TtlFromValue<V> = Box<dyn Fn(&V) -> Option<Duration> + Send + Sync>;
 
-}
+/// Returns the default TTL of a key, see `TtlCacheBacking::ttl_per_key`
+#[cfg(feature = "ttl-cache")]
+pub type TtlPerKey<K> = Box<dyn Fn(&K) -> Duration + Send + Sync>;
 
+/// When a `TtlCacheBacking` removes its expired entries
+///
+/// Expired entries are never returned, the modes only differ in when they are removed.
 #[cfg(feature = "ttl-cache")]
-impl<K> From<(K, Instant)> for TTlEntry<K> {
-    fn from(tuple: (K, Instant)) -> Self {
-        Self {
-            key: tuple.0,
-            expiry: tuple.1,
-        }
-    }
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ExpirationMode {
+    /// Every operation removes all expired entries first
+    #[default]
+    Eager,
+    /// An operation only removes the key it accesses, if it is expired
+    ///
+    /// This keeps the latency of an operation independent of the number of expired entries.
+    /// Entries which aren't accessed anymore stay in memory until `remove_expired` is called,
+    /// e.g. by `CacheOptions::expiry_sweep`.
+    Lazy,
+    /// Operations don't remove any entries, only `remove_expired` does, e.g. called by
+    /// `CacheOptions::expiry_sweep`
+    Background,
 }
 
+/// The position of a key in the expiry queue, the sequence number orders keys with equal expiry
+#[cfg(feature = "ttl-cache")]
+type ExpiryHandle<T> = (T, u64);
+
 #[derive(Debug, Clone, Error)]
 pub enum TtlError {
     #[error("The expiry for key not found")]
@@ -150,14 +919,41 @@ pub enum TtlError {
 
 #[cfg(feature = "ttl-cache")]
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TtlMeta {
     pub ttl: Duration,
+    /// The wall-clock time the entry expires at, which takes precedence over `ttl`
+    ///
+    /// Exported entries carry it, so entries restored after a restart keep their expiry instead
+    /// of living for another `ttl` or expiring at once because the monotonic clock started over.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub expires_at: Option<SystemTime>,
+}
+
+#[cfg(feature = "ttl-cache")]
+impl TtlMeta {
+    /// Expires the entry at the wall-clock `expires_at`, entries expiring in the past are set
+    /// as already expired
+    pub fn expiring_at(expires_at: SystemTime) -> Self {
+        Self {
+            ttl: expires_at.duration_since(SystemTime::now()).unwrap_or_default(),
+            expires_at: Some(expires_at),
+        }
+    }
+
+    /// The time until the entry expires, derived from `expires_at` if present
+    pub fn remaining(&self) -> Duration {
+        match self.expires_at {
+            Some(expires_at) => expires_at.duration_since(SystemTime::now()).unwrap_or_default(),
+            None => self.ttl,
+        }
+    }
 }
 
 #[cfg(feature = "ttl-cache")]
 impl From<Duration> for TtlMeta {
     fn from(ttl: Duration) -> Self {
-        Self { ttl }
+        Self { ttl, expires_at: None }
     }
 }
 
@@ -165,184 +961,838 @@ impl From<Duration> for TtlMeta {
 impl<
     K: Clone + Eq + Hash + Send + 'static,
     V: Clone + Sized + Send + 'static,
-    B: CacheBacking<K, (V, Instant)>
-> CacheBacking<K, V> for TtlCacheBacking<K, V, B> {
+    B: CacheBacking<K, (V, C::Time)>,
+    C: Clock,
+> CacheBacking<K, V> for TtlCacheBacking<K, V, B, C> {
     type Meta = TtlMeta;
 
     fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
-        self.remove_old()?;
+        let now = self.clock.now();
+        self.expire(key, now)?;
+        self.touch(key, now)?;
         Ok(self.map.get_mut(key)?
+            .filter(|(_, expiry)| now.lt(expiry))
             .map(|(value, _)| value))
     }
 
+    fn write_back(&mut self, key: &K) -> Result<(), BackingError> {
+        self.map.write_back(key)
+    }
+
     fn get(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
-        self.remove_old()?;
+        let now = self.clock.now();
+        self.expire(key, now)?;
+        self.touch(key, now)?;
         Ok(self.map.get(key)?
+            .filter(|(_, expiry)| now.lt(expiry))
             .map(|(value, _)| value))
     }
 
     fn set(&mut self, key: K, value: V, meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
-        self.remove_old()?;
+        let now = self.clock.now();
+        self.expire(&key, now)?;
+        let expired = self.is_expired(&key, now)?;
+        let retained_expiry = self.retained_expiry(&key, now)?;
         let ttl = if let Some(meta) = meta {
-            meta.ttl
+            meta.remaining()
         } else {
-            self.ttl
+            self.ttl_from_value.as_ref()
+                .and_then(|ttl_from_value| ttl_from_value(&value))
+                .unwrap_or_else(|| self.default_ttl(&key))
         };
-        let expiry = Instant::now().add(ttl);
+        let expiry = now.add_duration(ttl);
         let result = self.replace(key.clone(), value, expiry)?;
-        Ok(result)
+        if expired && result.is_some() {
+            self.expirations += 1;
+        }
+        match (result, retained_expiry) {
+            (Some(stale), Some(stale_expiry)) => {
+                self.stale.insert(key, (stale, stale_expiry));
+                Ok(None)
+            }
+            (Some(replaced), _) if expired => {
+                self.stale.remove(&key);
+                self.notify_expired(key, replaced);
+                Ok(None)
+            }
+            (result, _) => {
+                self.stale.remove(&key);
+                Ok(result)
+            }
+        }
     }
 
     fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError> {
-        self.remove_old()?;
-        Ok(self.remove_key(key)?)
+        let now = self.clock.now();
+        self.expire(key, now)?;
+        let expired = self.is_expired(key, now)?;
+        let removed = self.remove_key(key)?;
+        if removed.is_some() {
+            if expired {
+                self.expirations += 1;
+            } else {
+                self.removals += 1;
+            }
+        }
+        if let Some((stale, stale_expiry)) = self.stale.remove(key) {
+            // the key was removed while reloading, e.g. because the load failed, so the stale
+            // value stays available for the rest of its retention
+            self.replace(key.clone(), stale, stale_expiry)?;
+        }
+        match removed {
+            Some(removed) if expired => {
+                self.notify_expired(key.clone(), removed);
+                Ok(None)
+            }
+            removed => Ok(removed),
+        }
     }
 
     fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
-        self.remove_old()?;
-        Ok(self.map.get(key)?.is_some())
+        let now = self.clock.now();
+        self.expire(key, now)?;
+        Ok(self.map.get(key)?
+            .is_some_and(|(_, expiry)| now.lt(expiry)))
     }
 
-    fn remove_if(&mut self, predicate: Box<dyn Fn((&K, &V)) -> bool + Send + Sync>) -> Result<Vec<(K, V)>, BackingError> {
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
         let values = self.map.remove_if(Box::new(move |(key, (value, _))| predicate((key, value))))?;
+        let now = self.clock.now();
         let mut mapped = Vec::with_capacity(values.len());
-        for (key, (value, _)) in values {
-            // optimize looping through expiry_queue multiple times?
-            self.expiry_queue.retain(|entry| entry.key.ne(&key));
+        for (key, (value, expiry)) in values {
+            if now.lt(&expiry) {
+                self.removals += 1;
+            } else {
+                self.expirations += 1;
+            }
+            if let Some(handle) = self.expiry_index.remove(&key) {
+                self.expiry_queue.remove(&handle);
+            }
+            self.stale.remove(&key);
             mapped.push((key, value));
         }
         Ok(mapped)
     }
 
     fn clear(&mut self) -> Result<(), BackingError> {
+        self.removals += self.expiry_queue.len() as u64;
         self.expiry_queue.clear();
+        self.expiry_index.clear();
+        self.stale.clear();
         self.map.clear()?;
         Ok(())
     }
-}
 
-#[cfg(feature = "ttl-cache")]
-impl<
-    K: Eq + Hash + Sized + Clone + Send,
-    V: Sized + Clone + Send,
-> TtlCacheBacking<K, V, HashMapBacking<K, (V, Instant)>> {
-    pub fn new(ttl: Duration) -> TtlCacheBacking<K, V, HashMapBacking<K, (V, Instant)>> {
-        TtlCacheBacking {
-            phantom: Default::default(),
-            ttl,
-            map: HashMapBacking::new(),
-            expiry_queue: VecDeque::new(),
-        }
+    fn peek(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        let now = self.clock.now();
+        self.expire(key, now)?;
+        Ok(self.map.peek(key)?
+            .filter(|(_, expiry)| now.lt(expiry))
+            .map(|(value, _)| value))
     }
-}
 
-#[cfg(feature = "ttl-cache")]
-impl<
-    K: Eq + Hash + Sized + Clone + Send,
-    V: Sized + Clone + Send,
+    fn entries_by_recency(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        let now = self.clock.now();
+        Ok(self.map.entries_by_recency()?
+            .into_iter()
+            .filter(|(_, (_, expiry))| now.lt(expiry))
+            .map(|(key, (value, _))| (key, value))
+            .collect())
+    }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, V, TtlMeta>, BackingError> {
+        // the cursor is the one of the nested backing, expired entries are left out
+        let now = self.clock.now();
+        let chunk = self.map.export(cursor, count)?;
+        Ok(ExportChunk {
+            entries: chunk.entries.into_iter()
+                .filter(|(_, (_, expiry), _)| now.lt(expiry))
+                .map(|(key, (value, expiry), _)| {
+                    let remaining = expiry.saturating_duration_since(now);
+                    (key, value, Some(TtlMeta::expiring_at(SystemTime::now() + remaining)))
+                })
+                .collect(),
+            next: chunk.next,
+        })
+    }
+
+    fn evict(&mut self, count: usize) -> Result<Vec<(K, V)>, BackingError> {
+        let evicted = self.map.evict(count)?;
+        let mut mapped = Vec::with_capacity(evicted.len());
+        for (key, (value, _)) in evicted {
+            self.removals += 1;
+            if let Some(handle) = self.expiry_index.remove(&key) {
+                self.expiry_queue.remove(&handle);
+            }
+            self.stale.remove(&key);
+            mapped.push((key, value));
+        }
+        Ok(mapped)
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        self.map.repair()?;
+        let expiries = self.stored_expiries()?;
+        self.expiry_queue.clear();
+        self.expiry_index.clear();
+        for (key, expiry) in expiries {
+            self.insert_expiry(key, expiry);
+        }
+        Ok(())
+    }
+
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        let mut inconsistencies = self.map.verify()?;
+        let expiries = self.stored_expiries()?;
+        let (mut missing, mut mismatched) = (0, 0);
+        for (key, expiry) in &expiries {
+            match self.expiry_index.get(key) {
+                Some(handle) if handle.0 == *expiry && self.expiry_queue.get(handle) == Some(key) => {}
+                Some(_) => mismatched += 1,
+                None => missing += 1,
+            }
+        }
+        if missing > 0 {
+            inconsistencies.push(Inconsistency::new("TtlCacheBacking",
+                format!("{} entries have no expiry in the queue", missing)));
+        }
+        if mismatched > 0 {
+            inconsistencies.push(Inconsistency::new("TtlCacheBacking",
+                format!("{} entries expire at another time than queued", mismatched)));
+        }
+        // expiries of keys the nested backing evicted are expected, they are removed once expired
+        if self.expiry_queue.len() != self.expiry_index.len() {
+            inconsistencies.push(Inconsistency::new("TtlCacheBacking",
+                format!("The expiry queue holds {} expiries, its index {}", self.expiry_queue.len(), self.expiry_index.len())));
+        }
+        Ok(inconsistencies)
+    }
+
+    fn remove_expired(&mut self) -> Result<(), BackingError> {
+        let now = self.clock.now();
+        self.remove_old(None, now)
+    }
+
+    fn drain_expired(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        let now = self.clock.now();
+        let mut drained = Vec::new();
+        while let Some(entry) = self.expiry_queue.first_entry() {
+            let (expiry, _) = *entry.key();
+            if now.lt(&expiry) {
+                break;
+            }
+            let key = entry.remove();
+            self.expirations += 1;
+            self.expiry_index.remove(&key);
+            if let Some((value, _)) = self.map.remove(&key)? {
+                self.notify_expired(key.clone(), value.clone());
+                drained.push((key, value));
+            }
+        }
+        Ok(drained)
+    }
+
+    fn get_stale(&mut self, key: &K, max_staleness: Duration) -> Result<Option<(V, Duration)>, BackingError> {
+        let now = self.clock.now();
+        self.expire(key, now)?;
+        let stale = match self.map.get(key)? {
+            Some((value, expiry)) if !now.lt(expiry) => Some((value.clone(), *expiry)),
+            _ => self.stale.get(key).cloned(),
+        };
+        Ok(stale
+            .map(|(value, expiry)| (value, now.saturating_duration_since(expiry)))
+            .filter(|(_, staleness)| *staleness < self.stale_retention && *staleness <= max_staleness))
+    }
+
+    fn time_to_live(&mut self, key: &K) -> Result<Option<Duration>, BackingError> {
+        let now = self.clock.now();
+        self.expire(key, now)?;
+        Ok(self.map.peek(key)?
+            .filter(|(_, expiry)| now.lt(expiry))
+            .map(|(_, expiry)| expiry.saturating_duration_since(now)))
+    }
+
+    fn next_expiry(&mut self) -> Result<Option<Duration>, BackingError> {
+        let now = self.clock.now();
+        Ok(self.expiry_queue.keys().next()
+            .map(|(expiry, _)| expiry.saturating_duration_since(now)))
+    }
+
+    fn stats(&self) -> BackingStats {
+        let mut stats = self.map.stats();
+        stats.ttl = Some(TtlStats {
+            expirations: self.expirations,
+            removals: self.removals,
+            queue_length: self.expiry_queue.len(),
+        });
+        stats
+    }
+
+    fn set_removal_listener(&mut self, listener: RemovalListener<K, V>) {
+        // entries evicted by the nested backing are reported without their expiry
+        let nested = listener.clone();
+        self.map.set_removal_listener(Arc::new(move |key, (value, _), cause| nested(key, value, cause)));
+        self.removal_listener = Some(listener);
+    }
+
+    fn maintenance(&mut self) -> Option<&mut dyn Maintenance> {
+        self.map.maintenance()
+    }
+}
+
+#[cfg(feature = "ttl-cache")]
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+> TtlCacheBacking<K, V, HashMapBacking<K, (V, Instant)>> {
+    pub fn new(ttl: Duration) -> TtlCacheBacking<K, V, HashMapBacking<K, (V, Instant)>> {
+        TtlCacheBacking {
+            phantom: Default::default(),
+            ttl,
+            access_ttl: None,
+            map: HashMapBacking::new(),
+            expiry_queue: BTreeMap::new(),
+            expiry_index: HashMap::new(),
+            expiry_sequence: 0,
+            clock: TokioClock,
+            expiration_mode: ExpirationMode::Eager,
+            ttl_from_value: None,
+            ttl_per_key: None,
+            stale_retention: Duration::ZERO,
+            stale: HashMap::new(),
+            purge_batch_size: None,
+            expirations: 0,
+            removals: 0,
+            removal_listener: None,
+        }
+    }
+
+    /// Creates a backing expiring entries `ttl` after they were last read or written, instead of
+    /// after they were written, see `access_ttl`
+    pub fn with_access_ttl(ttl: Duration) -> TtlCacheBacking<K, V, HashMapBacking<K, (V, Instant)>> {
+        Self::new(ttl).access_ttl(ttl)
+    }
+}
+
+#[cfg(feature = "ttl-cache")]
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
     B: CacheBacking<K, (V, Instant)>
 > TtlCacheBacking<K, V, B> {
     pub fn with_backing(ttl: Duration, backing: B) -> TtlCacheBacking<K, V, B> {
+        TtlCacheBacking::with_clock(ttl, backing, TokioClock)
+    }
+}
+
+#[cfg(feature = "ttl-cache")]
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+    B: CacheBacking<K, (V, C::Time)>,
+    C: Clock,
+> TtlCacheBacking<K, V, B, C> {
+    /// Creates a backing which expires its entries based on the given `Clock` instead of
+    /// `tokio::time::Instant::now`
+    ///
+    /// The nested backing stores the expiries as the time of the clock, e.g. `SystemTime` for the
+    /// `SystemClock`.
+    pub fn with_clock(ttl: Duration, backing: B, clock: C) -> TtlCacheBacking<K, V, B, C> {
         TtlCacheBacking {
             phantom: Default::default(),
             ttl,
+            access_ttl: None,
             map: backing,
-            expiry_queue: VecDeque::new(),
+            expiry_queue: BTreeMap::new(),
+            expiry_index: HashMap::new(),
+            expiry_sequence: 0,
+            clock,
+            expiration_mode: ExpirationMode::Eager,
+            ttl_from_value: None,
+            ttl_per_key: None,
+            stale_retention: Duration::ZERO,
+            stale: HashMap::new(),
+            purge_batch_size: None,
+            expirations: 0,
+            removals: 0,
+            removal_listener: None,
         }
     }
 
-    fn remove_old(&mut self) -> Result<(), BackingError> {
-        let now = Instant::now();
-        while let Some(entry) = self.expiry_queue.pop_front() {
-            if now.lt(&entry.expiry) {
-                self.expiry_queue.push_front(entry);
-                break;
+    /// Sets when expired entries are removed, defaults to `ExpirationMode::Eager`
+    pub fn expiration_mode(mut self, expiration_mode: ExpirationMode) -> TtlCacheBacking<K, V, B, C> {
+        self.expiration_mode = expiration_mode;
+        self
+    }
+
+    /// Extends the expiry of an entry to `access_ttl` after every `get` of it, so entries which are
+    /// read regularly stay while idle ones expire
+    ///
+    /// An entry expires as set unless it is read, reads never shorten its expiry. Reads of an
+    /// expired entry don't bring it back. `peek` and `contains_key` don't count as reads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use cache_loader_async::backing::TtlCacheBacking;
+    /// use cache_loader_async::cache_api::LoadingCache;
+    ///
+    /// async fn example() {
+    ///     // sessions expire 5 minutes after their last request
+    ///     let backing = TtlCacheBacking::new(Duration::from_secs(300))
+    ///         .access_ttl(Duration::from_secs(300));
+    ///     let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+    ///         async move {
+    ///             Ok(key.to_lowercase())
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn access_ttl(mut self, access_ttl: Duration) -> TtlCacheBacking<K, V, B, C> {
+        self.access_ttl = Some(access_ttl);
+        self
+    }
+
+    /// Derives the TTL of entries set without a `TtlMeta` from their value, e.g. from the
+    /// `expires_in` field of a token
+    ///
+    /// A `TtlMeta` still takes precedence, values for which the function returns `None` use the
+    /// default TTL of the backing. Within a `LoadingCache` the values are `CacheEntry`s, where
+    /// only `CacheEntry::Loaded` carries a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use cache_loader_async::backing::TtlCacheBacking;
+    /// use cache_loader_async::cache_api::{CacheEntry, LoadingCache};
+    ///
+    /// #[derive(Clone)]
+    /// struct Token {
+    ///     expires_in: u64,
+    /// }
+    ///
+    /// async fn example() {
+    ///     let backing = TtlCacheBacking::new(Duration::from_secs(60))
+    ///         .ttl_from_value(|entry: &CacheEntry<Token, u8>| match entry {
+    ///             CacheEntry::Loaded(token) => Some(Duration::from_secs(token.expires_in)),
+    ///             CacheEntry::Loading(_) => None,
+    ///         });
+    ///     let cache = LoadingCache::with_backing(backing, move |_: String| {
+    ///         async move {
+    ///             Ok(Token { expires_in: 3600 })
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn ttl_from_value<T>(mut self, ttl_from_value: T) -> TtlCacheBacking<K, V, B, C>
+        where T: Fn(&V) -> Option<Duration> + Send + Sync + 'static {
+        self.ttl_from_value = Some(Box::new(ttl_from_value));
+        self
+    }
+
+    /// Replaces the default TTL with one depending on the key, so different classes of keys
+    /// can have different lifetimes within one backing
+    ///
+    /// A `TtlMeta` and the TTL derived by `ttl_from_value` still take precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use cache_loader_async::backing::TtlCacheBacking;
+    /// use cache_loader_async::cache_api::LoadingCache;
+    ///
+    /// async fn example() {
+    ///     let backing = TtlCacheBacking::new(Duration::from_secs(60))
+    ///         .ttl_per_key(|key: &String| if key.starts_with("config:") {
+    ///             Duration::from_secs(3600)
+    ///         } else {
+    ///             Duration::from_secs(60)
+    ///         });
+    ///     let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+    ///         async move {
+    ///             Ok(key.to_lowercase())
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn ttl_per_key<T>(mut self, ttl_per_key: T) -> TtlCacheBacking<K, V, B, C>
+        where T: Fn(&K) -> Duration + Send + Sync + 'static {
+        self.ttl_per_key = Some(Box::new(ttl_per_key));
+        self
+    }
+
+    /// Retains expired values for the given duration, so `LoadingCache::get_allow_stale` can
+    /// return them while their key is reloaded
+    ///
+    /// Retained values are neither returned by other operations, nor do they count as existing.
+    /// A value replaced by a load stays available until the load completes; if the load fails,
+    /// or the key is removed while loading, the stale value is kept for the rest of its
+    /// retention.
+    pub fn stale_retention(mut self, retention: Duration) -> TtlCacheBacking<K, V, B, C> {
+        self.stale_retention = retention;
+        self
+    }
+
+    /// Limits how many expired entries an operation removes in `ExpirationMode::Eager`
+    ///
+    /// Without a limit, the first operation after a long idle period removes every entry which
+    /// expired in the meantime. With a limit, the remaining entries are removed by the following
+    /// operations or by `remove_expired`, e.g. called by `CacheOptions::expiry_sweep`, which
+    /// isn't limited. Expired entries are never returned, regardless whether they were removed.
+    pub fn purge_batch_size(mut self, purge_batch_size: usize) -> TtlCacheBacking<K, V, B, C> {
+        self.purge_batch_size = Some(purge_batch_size);
+        self
+    }
+
+    fn default_ttl(&self, key: &K) -> Duration {
+        self.ttl_per_key.as_ref()
+            .map_or(self.ttl, |ttl_per_key| ttl_per_key(key))
+    }
+
+    /// Removes expired entries before an operation on the key, depending on the `ExpirationMode`
+    fn expire(&mut self, key: &K, now: C::Time) -> Result<(), BackingError> {
+        match self.expiration_mode {
+            ExpirationMode::Eager => self.remove_old(self.purge_batch_size, now),
+            ExpirationMode::Lazy => {
+                if self.is_purgeable(key, now)? {
+                    self.expirations += 1;
+                    if let Some(value) = self.remove_key(key)? {
+                        self.notify_expired(key.clone(), value);
+                    }
+                }
+                Ok(())
+            }
+            ExpirationMode::Background => Ok(()),
+        }
+    }
+
+    /// Extends the expiry of a read key by the `access_ttl`, moving it back in the expiry queue
+    fn touch(&mut self, key: &K, now: C::Time) -> Result<(), BackingError> {
+        let expiry = match self.access_ttl {
+            Some(access_ttl) => now.add_duration(access_ttl),
+            None => return Ok(()),
+        };
+        let extended = match self.map.get_mut(key)? {
+            Some((_, stored)) if now < *stored && *stored < expiry => {
+                *stored = expiry;
+                true
             }
-            self.map.remove(&entry.key)?;
+            _ => false,
+        };
+        if extended {
+            self.insert_expiry(key.clone(), expiry);
         }
         Ok(())
     }
 
-    fn replace(&mut self, key: K, value: V, expiry: Instant) -> Result<Option<V>, BackingError> {
-        let entry = self.map.set(key.clone(), (value, expiry), None)?;
-        let res = self.cleanup_expiry(entry, &key);
-        match self.expiry_queue.binary_search_by_key(&expiry, |entry| entry.expiry) {
-            Ok(found) => {
-                self.expiry_queue.insert(found + 1, (key, expiry).into());
+    fn is_expired(&mut self, key: &K, now: C::Time) -> Result<bool, BackingError> {
+        Ok(self.map.get(key)?
+            .is_some_and(|(_, expiry)| !now.lt(expiry)))
+    }
+
+    /// Whether the key has expired and isn't retained as stale value anymore
+    fn is_purgeable(&mut self, key: &K, now: C::Time) -> Result<bool, BackingError> {
+        let retention = self.stale_retention;
+        Ok(self.map.get(key)?
+            .is_some_and(|(_, expiry)| !now.lt(&expiry.add_duration(retention))))
+    }
+
+    /// Returns the expiry of the key, if it has expired but is still retained as stale value
+    fn retained_expiry(&mut self, key: &K, now: C::Time) -> Result<Option<C::Time>, BackingError> {
+        let retention = self.stale_retention;
+        Ok(self.map.get(key)?
+            .map(|(_, expiry)| *expiry)
+            .filter(|expiry| !now.lt(expiry) && now.lt(&expiry.add_duration(retention))))
+    }
+
+    /// Removes up to `limit` expired entries, all of them without a limit
+    fn remove_old(&mut self, limit: Option<usize>, now: C::Time) -> Result<(), BackingError> {
+        let mut remaining = limit.unwrap_or(usize::MAX);
+        while let Some(entry) = self.expiry_queue.first_entry() {
+            if remaining == 0 {
+                break;
             }
-            Err(idx) => {
-                self.expiry_queue.insert(idx, (key, expiry).into());
+            remaining -= 1;
+            let (expiry, _) = *entry.key();
+            if now.lt(&expiry.add_duration(self.stale_retention)) {
+                break;
+            }
+            let key = entry.remove();
+            self.expirations += 1;
+            self.expiry_index.remove(&key);
+            if let Some((value, _)) = self.map.remove(&key)? {
+                self.notify_expired(key, value);
             }
         }
+        Ok(())
+    }
+
+    fn notify_expired(&self, key: K, value: V) {
+        if let Some(listener) = &self.removal_listener {
+            listener(key, value, RemovalCause::Expired);
+        }
+    }
+
+    fn replace(&mut self, key: K, value: V, expiry: C::Time) -> Result<Option<V>, BackingError> {
+        let entry = self.map.set(key.clone(), (value, expiry), None)?;
+        let res = self.cleanup_expiry(entry, &key);
+        self.insert_expiry(key, expiry);
         res
     }
 
+    /// Returns the expiries of all entries of the nested backing
+    fn stored_expiries(&mut self) -> Result<Vec<(K, C::Time)>, BackingError>
+        where K: 'static {
+        let expiries = Arc::new(Mutex::new(Vec::new()));
+        let collector = expiries.clone();
+        // remove_if is the only way to visit every entry of the nested backing, nothing is removed
+        self.map.remove_if(Box::new(move |(key, (_, expiry))| {
+            collector.lock().unwrap().push((key.clone(), *expiry));
+            false
+        }))?;
+        let expiries = std::mem::take(&mut *expiries.lock().unwrap());
+        Ok(expiries)
+    }
+
+    fn insert_expiry(&mut self, key: K, expiry: C::Time) {
+        self.expiry_sequence += 1;
+        let handle = (expiry, self.expiry_sequence);
+        if let Some(previous) = self.expiry_index.insert(key.clone(), handle) {
+            self.expiry_queue.remove(&previous);
+        }
+        self.expiry_queue.insert(handle, key);
+    }
+
     fn remove_key(&mut self, key: &K) -> Result<Option<V>, BackingError> {
         let entry = self.map.remove(key)?;
         self.cleanup_expiry(entry, key)
     }
 
-    fn cleanup_expiry(&mut self, entry: Option<(V, Instant)>, key: &K) -> Result<Option<V>, BackingError> {
-        if let Some((value, old_expiry)) = entry {
-            match self.expiry_queue.binary_search_by_key(&old_expiry, |entry| entry.expiry) {
-                Ok(found) => {
-                    let index = self.expiry_index_on_key_eq(found, &old_expiry, key);
-                    if let Some(index) = index {
-                        self.expiry_queue.remove(index);
-                    } else {
-                        return Err(TtlError::ExpiryKeyNotFound.into());
-                    }
-                }
-                Err(_) => {
-                    return Err(TtlError::ExpiryNotFound.into());
-                }
-            }
+    fn cleanup_expiry(&mut self, entry: Option<(V, C::Time)>, key: &K) -> Result<Option<V>, BackingError> {
+        if let Some((value, _)) = entry {
+            let handle = self.expiry_index.remove(key)
+                .ok_or(TtlError::ExpiryNotFound)?;
+            self.expiry_queue.remove(&handle)
+                .ok_or(TtlError::ExpiryKeyNotFound)?;
             Ok(Some(value))
         } else {
             Ok(None)
         }
     }
+}
+
+pub type BackingErrorHook = Box<dyn Fn(&BackingError) + Send + Sync>;
+
+/// Decides how a `RecoveringBacking` reacts to errors of the backing it wraps
+#[derive(Debug, Copy, Clone, Default)]
+pub enum BackingErrorPolicy {
+    /// Fail the operation with the error, which is the behaviour of an unwrapped backing
+    #[default]
+    Fail,
+    /// Retry the failed operation up to the given amount of times
+    Retry(usize),
+    /// Remove the key the operation failed on and retry once
+    DropKey,
+    /// Call `CacheBacking::repair` on the wrapped backing and retry once
+    Rebuild,
+}
+
+/// A backing wrapper which applies a `BackingErrorPolicy` when the wrapped backing fails,
+/// optionally reporting every error to a hook.
+///
+/// Lookups (`get`/`get_mut`) are executed once more after they succeeded, as the reference of a
+/// successful attempt can't be returned from within the retry loop.
+pub struct RecoveringBacking<B> {
+    backing: B,
+    policy: BackingErrorPolicy,
+    hook: Option<BackingErrorHook>,
+}
 
-    fn expiry_index_on_key_eq(&self, idx: usize, expiry: &Instant, key: &K) -> Option<usize> {
-        let entry = self.expiry_queue.get(idx).unwrap();
-        if entry.key.eq(key) {
-            return Some(idx);
+impl<B> RecoveringBacking<B> {
+    pub fn new(backing: B, policy: BackingErrorPolicy) -> RecoveringBacking<B> {
+        RecoveringBacking {
+            backing,
+            policy,
+            hook: None,
         }
+    }
 
-        let mut offset = 0;
-        while idx - offset > 0 {
-            offset += 1;
-            let entry = self.expiry_queue.get(idx - offset).unwrap();
-            if !entry.expiry.eq(expiry) {
-                break;
-            }
-            if entry.key.eq(key) {
-                return Some(idx - offset);
-            }
+    /// Registers a hook which is called with every error of the wrapped backing, including the
+    /// ones which were recovered from
+    pub fn on_error<H: Fn(&BackingError) + Send + Sync + 'static>(mut self, hook: H) -> RecoveringBacking<B> {
+        self.hook = Some(Box::new(hook));
+        self
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.backing
+    }
+
+    fn max_attempts(&self) -> usize {
+        match self.policy {
+            BackingErrorPolicy::Fail => 0,
+            BackingErrorPolicy::Retry(attempts) => attempts,
+            BackingErrorPolicy::DropKey | BackingErrorPolicy::Rebuild => 1,
         }
-        offset = 0;
-        while idx + offset < self.expiry_queue.len() {
-            offset += 1;
-            let entry = self.expiry_queue.get(idx + offset).unwrap();
-            if !entry.expiry.eq(expiry) {
-                break;
+    }
+
+    fn with_recovery<K, V, T, O>(&mut self, key: Option<&K>, mut operation: O) -> Result<T, BackingError>
+        where K: Eq + Hash + Sized + Clone + Send,
+              V: Sized + Clone + Send,
+              B: CacheBacking<K, V>,
+              O: FnMut(&mut B) -> Result<T, BackingError> {
+        let mut attempt = 0;
+        loop {
+            let error = match operation(&mut self.backing) {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+            if let Some(hook) = &self.hook {
+                hook(&error);
             }
-            if entry.key.eq(key) {
-                return Some(idx + offset);
+            if attempt >= self.max_attempts() {
+                return Err(error);
+            }
+            attempt += 1;
+            match self.policy {
+                BackingErrorPolicy::DropKey => {
+                    match key {
+                        // the removal might run into the same error, the retry will tell
+                        Some(key) => { self.backing.remove(key).ok(); }
+                        None => return Err(error),
+                    }
+                }
+                BackingErrorPolicy::Rebuild => self.backing.repair()?,
+                BackingErrorPolicy::Fail | BackingErrorPolicy::Retry(_) => {}
             }
         }
-        None
     }
 }
 
-pub struct HashMapBacking<K, V> {
-    map: HashMap<K, V>,
+impl<
+    K: Eq + Hash + Sized + Clone + Send + 'static,
+    V: Sized + Clone + Send + 'static,
+    B: CacheBacking<K, V>
+> CacheBacking<K, V> for RecoveringBacking<B> {
+    type Meta = B::Meta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
+        self.with_recovery(Some(key), |backing| backing.get_mut(key).map(|_| ()))?;
+        self.backing.get_mut(key)
+    }
+
+    fn write_back(&mut self, key: &K) -> Result<(), BackingError> {
+        self.with_recovery(Some(key), |backing| backing.write_back(key))
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        self.with_recovery(Some(key), |backing| backing.get(key).map(|_| ()))?;
+        self.backing.get(key)
+    }
+
+    fn set(&mut self, key: K, value: V, meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
+        self.with_recovery(Some(&key), |backing| backing.set(key.clone(), value.clone(), meta.clone()))
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        self.with_recovery(Some(key), |backing| backing.remove(key))
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        self.with_recovery(Some(key), |backing| backing.contains_key(key))
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
+        let predicate = Arc::new(predicate);
+        self.with_recovery(None, |backing| {
+            let predicate = predicate.clone();
+            backing.remove_if(Box::new(move |entry| predicate(entry)))
+        })
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.with_recovery(None, |backing| backing.clear())
+    }
+
+    fn peek(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        self.with_recovery(Some(key), |backing| backing.peek(key).map(|_| ()))?;
+        self.backing.peek(key)
+    }
+
+    fn evict(&mut self, count: usize) -> Result<Vec<(K, V)>, BackingError> {
+        self.with_recovery(None, |backing| backing.evict(count))
+    }
+
+    fn entries_by_recency(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.with_recovery(None, |backing| backing.entries_by_recency())
+    }
+
+    fn entries(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.with_recovery(None, |backing| backing.entries())
+    }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, V, B::Meta>, BackingError> {
+        self.with_recovery(None, |backing| backing.export(cursor, count))
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        self.backing.repair()
+    }
+
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        self.backing.verify()
+    }
+
+    fn remove_expired(&mut self) -> Result<(), BackingError> {
+        self.with_recovery(None, |backing| backing.remove_expired())
+    }
+
+    fn drain_expired(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.with_recovery(None, |backing| backing.drain_expired())
+    }
+
+    fn get_stale(&mut self, key: &K, max_staleness: std::time::Duration) -> Result<Option<(V, std::time::Duration)>, BackingError> {
+        self.with_recovery(Some(key), |backing| backing.get_stale(key, max_staleness))
+    }
+
+    fn time_to_live(&mut self, key: &K) -> Result<Option<std::time::Duration>, BackingError> {
+        self.with_recovery(Some(key), |backing| backing.time_to_live(key))
+    }
+
+    fn next_expiry(&mut self) -> Result<Option<std::time::Duration>, BackingError> {
+        self.with_recovery(None, |backing| backing.next_expiry())
+    }
+
+    fn set_removal_listener(&mut self, listener: RemovalListener<K, V>) {
+        self.backing.set_removal_listener(listener)
+    }
+
+    fn stats(&self) -> BackingStats {
+        self.backing.stats()
+    }
+
+    fn maintenance(&mut self) -> Option<&mut dyn Maintenance> {
+        self.backing.maintenance()
+    }
+}
+
+/// A backing storing the entries in a `HashMap`, without a limit
+///
+/// The keys are hashed with the `RandomState` of the standard library, which resists HashDoS.
+/// Hot caches with trusted keys can use a faster hasher like `ahash` or `fxhash` with
+/// `HashMapBacking::with_hasher`.
+pub struct HashMapBacking<K, V, S = RandomState> {
+    map: HashMap<K, V, S>,
 }
 
 impl<
     K: Eq + Hash + Sized + Clone + Send,
-    V: Sized + Clone + Send
-> CacheBacking<K, V> for HashMapBacking<K, V> {
+    V: Sized + Clone + Send,
+    S: BuildHasher,
+> CacheBacking<K, V> for HashMapBacking<K, V, S> {
     type Meta = NoMeta;
 
     fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
@@ -365,7 +1815,7 @@ impl<
         Ok(self.map.contains_key(key))
     }
 
-    fn remove_if(&mut self, predicate: Box<dyn Fn((&K, &V)) -> bool + Send + Sync>) -> Result<Vec<(K, V)>, BackingError> {
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
         let removed = self.map.iter()
             .filter(|(k, v)| predicate((k, v)))
             .map(|(k, v)| (k.clone(), v.clone()))
@@ -381,18 +1831,750 @@ impl<
         self.map.clear();
         Ok(())
     }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, V, NoMeta>, BackingError> {
+        let entries = self.map.iter()
+            .map(|(key, value)| (key.clone(), value.clone(), None));
+        Ok(ExportChunk::from_position(entries, cursor, count))
+    }
+
+    fn entries(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        Ok(self.map.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    fn stats(&self) -> BackingStats {
+        BackingStats {
+            occupancy: Some(Occupancy {
+                entries: self.map.len(),
+                weight: self.map.len(),
+            }),
+            ..BackingStats::default()
+        }
+    }
+}
+
+impl<K, V, S: Default> Default for HashMapBacking<K, V, S> {
+    fn default() -> Self {
+        HashMapBacking {
+            map: Default::default()
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher + Default> FromIterator<(K, V)> for HashMapBacking<K, V, S> {
+    fn from_iter<I: IntoIterator<Item=(K, V)>>(entries: I) -> Self {
+        HashMapBacking::construct(entries.into_iter().collect())
+    }
 }
 
 impl<K, V> HashMapBacking<K, V> {
     pub fn new() -> HashMapBacking<K, V> {
+        Default::default()
+    }
+}
+
+impl<K, V, S> HashMapBacking<K, V, S> {
+    /// Creates an empty backing hashing the keys with the given hasher
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// use cache_loader_async::backing::HashMapBacking;
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     // e.g. `ahash::RandomState::new()` or `fxhash::FxBuildHasher::default()`
+    ///     let backing = HashMapBacking::with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+    ///     let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::with_backing(backing, move |key: u64| {
+    ///         async move { Ok(key * 2) }
+    ///     });
+    ///     assert_eq!(cache.get(2).await.unwrap(), 4);
+    /// }
+    /// ```
+    pub fn with_hasher(hash_builder: S) -> HashMapBacking<K, V, S> {
         HashMapBacking {
-            map: Default::default()
+            map: HashMap::with_hasher(hash_builder)
         }
     }
 
-    pub fn construct(map: HashMap<K, V>) -> HashMapBacking<K, V> {
+    pub fn construct(map: HashMap<K, V, S>) -> HashMapBacking<K, V, S> {
         HashMapBacking {
             map
         }
     }
-}
\ No newline at end of file
+}
+/// The order in which a `BoundedHashMapBacking` evicts entries once it's full
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Evicts a randomly chosen entry
+    Random,
+    /// Evicts the key which was set first, setting a new value for a stored key keeps its position
+    Fifo,
+    /// Evicts the key whose value was set longest ago, setting a new value for a stored key moves
+    /// it to the back
+    OldestInsert,
+}
+
+/// A `HashMapBacking` holding at most `max_entries` entries, evicting one according to its
+/// `EvictionPolicy` when a new key is set into a full backing
+///
+/// Unlike `LruCacheBacking`, reads don't change which entry is evicted next, so it doesn't require
+/// the `lru-cache` feature and reads don't need exclusive access to the order.
+pub struct BoundedHashMapBacking<K, V, S = RandomState> {
+    // every value is stored with its position in the eviction order
+    map: HashMap<K, (V, u64), S>,
+    // the next entry to evict comes first
+    order: BTreeMap<u64, K>,
+    next_position: u64,
+    max_entries: usize,
+    policy: EvictionPolicy,
+    rng: XorShift,
+    removal_listener: Option<RemovalListener<K, V>>,
+}
+
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+    S: BuildHasher,
+> CacheBacking<K, V> for BoundedHashMapBacking<K, V, S> {
+    type Meta = NoMeta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
+        Ok(self.map.get_mut(key).map(|(value, _)| value))
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        Ok(self.map.get(key).map(|(value, _)| value))
+    }
+
+    fn set(&mut self, key: K, value: V, _meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
+        if self.max_entries == 0 {
+            return Ok(None);
+        }
+        let stored = self.map.get(&key).map(|(_, position)| *position);
+        let position = match stored {
+            Some(position) if self.policy != EvictionPolicy::OldestInsert => position,
+            stored => {
+                if let Some(position) = stored {
+                    self.order.remove(&position);
+                } else if self.map.len() >= self.max_entries {
+                    self.evict(self.map.len() + 1 - self.max_entries)?;
+                }
+                let position = self.next_position();
+                self.order.insert(position, key.clone());
+                position
+            }
+        };
+        Ok(self.map.insert(key, (value, position)).map(|(replaced, _)| replaced))
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        Ok(self.map.remove(key).map(|(value, position)| {
+            self.order.remove(&position);
+            value
+        }))
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        Ok(self.map.contains_key(key))
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
+        let keys = self.map.iter()
+            .filter(|(key, (value, _))| predicate((key, value)))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<K>>();
+        Ok(keys.into_iter()
+            .filter_map(|key| {
+                let (value, position) = self.map.remove(&key)?;
+                self.order.remove(&position);
+                Some((key, value))
+            })
+            .collect())
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.map.clear();
+        self.order.clear();
+        Ok(())
+    }
+
+    fn evict(&mut self, count: usize) -> Result<Vec<(K, V)>, BackingError> {
+        let mut evicted = Vec::new();
+        while evicted.len() < count {
+            let key = match self.order.pop_first() {
+                Some((_, key)) => key,
+                None => break,
+            };
+            let (value, _) = self.map.remove(&key).expect("Evicted key is not stored");
+            if let Some(listener) = &self.removal_listener {
+                listener(key.clone(), value.clone(), RemovalCause::Evicted);
+            }
+            evicted.push((key, value));
+        }
+        Ok(evicted)
+    }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, V, NoMeta>, BackingError> {
+        // in eviction order, so setting the entries in order restores it for FIFO and oldest insert
+        let entries = self.order.values()
+            .map(|key| (key.clone(), self.map[key].0.clone(), None));
+        Ok(ExportChunk::from_position(entries, cursor, count))
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        self.order = self.map.iter()
+            .map(|(key, (_, position))| (*position, key.clone()))
+            .collect();
+        Ok(())
+    }
+
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        let mut inconsistencies = Vec::new();
+        let unordered = self.map.iter()
+            .filter(|(key, (_, position))| self.order.get(position) != Some(*key))
+            .count();
+        if unordered > 0 || self.order.len() != self.map.len() {
+            inconsistencies.push(Inconsistency::new("BoundedHashMapBacking",
+                format!("{} keys are missing from the eviction order, which holds {} keys for {} entries",
+                        unordered, self.order.len(), self.map.len())));
+        }
+        Ok(inconsistencies)
+    }
+
+    fn set_removal_listener(&mut self, listener: RemovalListener<K, V>) {
+        self.removal_listener = Some(listener);
+    }
+
+    fn stats(&self) -> BackingStats {
+        BackingStats {
+            occupancy: Some(Occupancy {
+                entries: self.map.len(),
+                weight: self.map.len(),
+            }),
+            ..BackingStats::default()
+        }
+    }
+}
+
+impl<K, V> BoundedHashMapBacking<K, V> {
+    pub fn new(max_entries: usize, policy: EvictionPolicy) -> BoundedHashMapBacking<K, V> {
+        Self::with_hasher(max_entries, policy, RandomState::new())
+    }
+}
+
+impl<K, V, S> BoundedHashMapBacking<K, V, S> {
+    /// Creates a backing holding at most `max_entries` entries, hashing the keys with the given
+    /// hasher, see `HashMapBacking::with_hasher`
+    pub fn with_hasher(max_entries: usize, policy: EvictionPolicy, hash_builder: S) -> BoundedHashMapBacking<K, V, S> {
+        BoundedHashMapBacking {
+            map: HashMap::with_hasher(hash_builder),
+            order: BTreeMap::new(),
+            next_position: 0,
+            max_entries,
+            policy,
+            rng: XorShift::new(RandomState::new().build_hasher().finish()),
+            removal_listener: None,
+        }
+    }
+
+    /// Seeds the random choice of `EvictionPolicy::Random`, to evict the same entries on every run
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = XorShift::new(seed);
+        self
+    }
+
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    pub fn policy(&self) -> EvictionPolicy {
+        self.policy
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes the entry which is evicted next, skipping `keep`, and reports it to the removal
+    /// listener
+    fn evict_except(&mut self, keep: &K) -> Option<(K, V)>
+        where K: Eq + Hash + Clone,
+              V: Clone,
+              S: BuildHasher {
+        let position = self.order.iter()
+            .find(|(_, key)| *key != keep)
+            .map(|(position, _)| *position)?;
+        let key = self.order.remove(&position).expect("Evicted position is not ordered");
+        let (value, _) = self.map.remove(&key).expect("Evicted key is not stored");
+        if let Some(listener) = &self.removal_listener {
+            listener(key.clone(), value.clone(), RemovalCause::Evicted);
+        }
+        Some((key, value))
+    }
+
+    fn next_position(&mut self) -> u64 {
+        match self.policy {
+            // evicting the first of randomly ordered positions evicts a random entry
+            EvictionPolicy::Random => loop {
+                let position = self.rng.next_u64();
+                if !self.order.contains_key(&position) {
+                    return position;
+                }
+            },
+            EvictionPolicy::Fifo | EvictionPolicy::OldestInsert => {
+                self.next_position += 1;
+                self.next_position
+            }
+        }
+    }
+}
+
+/// Computes the weight of an entry of a `WeightedCacheBacking`
+pub type WeightFn<K, V> = Box<dyn Fn(&K, &V) -> u64 + Send + Sync>;
+
+/// A backing limiting the total weight of its entries instead of their number, e.g. the bytes of
+/// cached blobs of different sizes
+///
+/// Entries are evicted in the order of an `EvictionPolicy` until the entries fit into the limit
+/// again. The entry which was set last is never evicted for its weight, so an entry heavier than
+/// the limit stays until the next entry is set. The weight is computed when the entry is set,
+/// changes through `get_mut` don't update it. Use `LruCacheBacking::max_weight` of the
+/// `lru-cache` feature to evict the least recently used entries instead.
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::backing::WeightedCacheBacking;
+/// use cache_loader_async::cache_api::{CacheEntry, LoadingCache};
+///
+/// async fn example() {
+///     // holds up to 1 MiB of blobs
+///     let backing = WeightedCacheBacking::new(1 << 20, |_: &String, entry: &CacheEntry<Vec<u8>, u8>| match entry {
+///         CacheEntry::Loaded(blob) => blob.len() as u64,
+///         CacheEntry::Loading(_) => 0,
+///     });
+///     let cache = LoadingCache::with_backing(backing, move |key: String| {
+///         async move {
+///             Ok(key.into_bytes().repeat(1000))
+///         }
+///     });
+/// }
+/// ```
+pub struct WeightedCacheBacking<K, V> {
+    backing: BoundedHashMapBacking<K, V>,
+    weights: HashMap<K, u64>,
+    weigher: WeightFn<K, V>,
+    max_weight: u64,
+    weight: u64,
+}
+
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send
+> CacheBacking<K, V> for WeightedCacheBacking<K, V> {
+    type Meta = NoMeta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
+        self.backing.get_mut(key)
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        self.backing.get(key)
+    }
+
+    fn set(&mut self, key: K, value: V, _meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
+        let weight = (self.weigher)(&key, &value);
+        let replaced = self.backing.set(key.clone(), value, None)?;
+        self.weight += weight;
+        if let Some(replaced_weight) = self.weights.insert(key.clone(), weight) {
+            self.weight -= replaced_weight;
+        }
+        while self.weight > self.max_weight {
+            match self.backing.evict_except(&key) {
+                Some((evicted, _)) => self.forget(&evicted),
+                None => break,
+            }
+        }
+        Ok(replaced)
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        let removed = self.backing.remove(key)?;
+        self.forget(key);
+        Ok(removed)
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        self.backing.contains_key(key)
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
+        let removed = self.backing.remove_if(predicate)?;
+        for (key, _) in removed.iter() {
+            self.forget(key);
+        }
+        Ok(removed)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.backing.clear()?;
+        self.weights.clear();
+        self.weight = 0;
+        Ok(())
+    }
+
+    fn evict(&mut self, count: usize) -> Result<Vec<(K, V)>, BackingError> {
+        let evicted = self.backing.evict(count)?;
+        for (key, _) in evicted.iter() {
+            self.forget(key);
+        }
+        Ok(evicted)
+    }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, V, NoMeta>, BackingError> {
+        self.backing.export(cursor, count)
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        self.backing.repair()?;
+        let weigher = &self.weigher;
+        self.weights = self.backing.map.iter()
+            .map(|(key, (value, _))| (key.clone(), weigher(key, value)))
+            .collect();
+        self.weight = self.weights.values().sum();
+        Ok(())
+    }
+
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        let mut inconsistencies = self.backing.verify()?;
+        let unweighed = self.backing.map.keys()
+            .filter(|key| !self.weights.contains_key(*key))
+            .count();
+        if unweighed > 0 || self.weights.len() != self.backing.len() {
+            inconsistencies.push(Inconsistency::new("WeightedCacheBacking",
+                format!("{} keys have no weight, {} weights are tracked for {} entries",
+                        unweighed, self.weights.len(), self.backing.len())));
+        }
+        let stored_weight = self.weights.values().sum::<u64>();
+        if stored_weight != self.weight {
+            inconsistencies.push(Inconsistency::new("WeightedCacheBacking",
+                format!("The tracked weight is {}, the entries weigh {}", self.weight, stored_weight)));
+        }
+        Ok(inconsistencies)
+    }
+
+    fn set_removal_listener(&mut self, listener: RemovalListener<K, V>) {
+        self.backing.set_removal_listener(listener)
+    }
+
+    fn stats(&self) -> BackingStats {
+        BackingStats {
+            occupancy: Some(Occupancy {
+                entries: self.backing.len(),
+                weight: self.weight.min(usize::MAX as u64) as usize,
+            }),
+            ..BackingStats::default()
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> WeightedCacheBacking<K, V> {
+    /// Creates a backing holding entries up to a total weight of `max_weight`, evicting the ones
+    /// set longest ago first
+    pub fn new<W>(max_weight: u64, weigher: W) -> WeightedCacheBacking<K, V>
+        where W: Fn(&K, &V) -> u64 + Send + Sync + 'static {
+        Self::with_policy(max_weight, weigher, EvictionPolicy::OldestInsert)
+    }
+
+    /// Creates a backing holding entries up to a total weight of `max_weight`, evicting them in
+    /// the order of `policy`
+    pub fn with_policy<W>(max_weight: u64, weigher: W, policy: EvictionPolicy) -> WeightedCacheBacking<K, V>
+        where W: Fn(&K, &V) -> u64 + Send + Sync + 'static {
+        WeightedCacheBacking {
+            backing: BoundedHashMapBacking::new(usize::MAX, policy),
+            weights: HashMap::new(),
+            weigher: Box::new(weigher),
+            max_weight,
+            weight: 0,
+        }
+    }
+
+    /// Returns the total weight of the entries
+    pub fn weight(&self) -> u64 {
+        self.weight
+    }
+
+    pub fn max_weight(&self) -> u64 {
+        self.max_weight
+    }
+
+    pub fn len(&self) -> usize {
+        self.backing.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.backing.is_empty()
+    }
+
+    fn forget(&mut self, key: &K) {
+        if let Some(weight) = self.weights.remove(key) {
+            self.weight -= weight;
+        }
+    }
+}
+
+/// The meta of a `TieredBacking`, passed to the set of each tier
+#[derive(Debug, Clone)]
+pub struct TieredMeta<M1, M2> {
+    pub l1: Option<M1>,
+    pub l2: Option<M2>,
+}
+
+impl<M1: From<std::time::Duration>, M2: From<std::time::Duration>> From<std::time::Duration> for TieredMeta<M1, M2> {
+    fn from(ttl: std::time::Duration) -> Self {
+        Self {
+            l1: Some(M1::from(ttl)),
+            l2: Some(M2::from(ttl)),
+        }
+    }
+}
+
+/// A backing layering a fast backing `L1` over a slower one `L2`, e.g. a small in-memory LRU
+/// over a `SledBacking` or `RedisBacking`
+///
+/// Lookups check `L1` first and promote values found in `L2` into `L1`. Sets write through to
+/// both tiers, removals remove the key from both. `L2` holds all entries, so `L1` may evict
+/// entries on its own without them being reported as removed, while evictions and expiries of
+/// `L2` are reported and remove the key from `L1` as well. Values mutated by
+/// `LoadingCache::update_mut` are mutated in `L2` and promoted again on the next lookup. Give
+/// `L1` a TTL not longer than the one of `L2`, or it serves entries `L2` expired until they
+/// are swept by `CacheBacking::remove_expired`.
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::backing::{BoundedHashMapBacking, EvictionPolicy, HashMapBacking, TieredBacking};
+/// use cache_loader_async::cache_api::LoadingCache;
+///
+/// async fn example() {
+///     // keeps up to 100 hot entries in front of the full map
+///     let backing = TieredBacking::new(BoundedHashMapBacking::new(100, EvictionPolicy::Fifo), HashMapBacking::new());
+///     let cache = LoadingCache::with_backing(backing, move |key: String| {
+///         async move {
+///             Ok::<_, u8>(key.to_lowercase())
+///         }
+///     });
+/// }
+/// ```
+pub struct TieredBacking<L1, L2> {
+    l1: L1,
+    l2: L2,
+}
+
+impl<L1, L2> TieredBacking<L1, L2> {
+    pub fn new(l1: L1, l2: L2) -> TieredBacking<L1, L2> {
+        TieredBacking { l1, l2 }
+    }
+
+    /// Returns the fast tier
+    pub fn l1(&self) -> &L1 {
+        &self.l1
+    }
+
+    /// Returns the slow tier, which holds all entries
+    pub fn l2(&self) -> &L2 {
+        &self.l2
+    }
+
+    pub fn l1_mut(&mut self) -> &mut L1 {
+        &mut self.l1
+    }
+
+    pub fn l2_mut(&mut self) -> &mut L2 {
+        &mut self.l2
+    }
+
+    /// Copies the value of the key from `L2` into `L1` unless `L1` holds it already, returns
+    /// whether the key was found in either tier
+    fn promote<K, V>(&mut self, key: &K) -> Result<bool, BackingError>
+        where K: Eq + Hash + Sized + Clone + Send,
+              V: Sized + Clone + Send,
+              L1: CacheBacking<K, V>,
+              L2: CacheBacking<K, V> {
+        if self.l1.contains_key(key)? {
+            return Ok(true);
+        }
+        match self.l2.get(key)?.cloned() {
+            Some(value) => {
+                self.l1.set(key.clone(), value, None)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Removes entries `L2` removed on its own from `L1` as well
+    fn forget<K, V>(&mut self, removed: &[(K, V)]) -> Result<(), BackingError>
+        where K: Eq + Hash + Sized + Clone + Send,
+              V: Sized + Clone + Send,
+              L1: CacheBacking<K, V> {
+        for (key, _) in removed {
+            self.l1.remove(key)?;
+        }
+        Ok(())
+    }
+}
+
+impl<
+    K: Eq + Hash + Sized + Clone + Send + 'static,
+    V: Sized + Clone + Send + 'static,
+    L1: CacheBacking<K, V>,
+    L2: CacheBacking<K, V>
+> CacheBacking<K, V> for TieredBacking<L1, L2> {
+    type Meta = TieredMeta<L1::Meta, L2::Meta>;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
+        if self.l2.contains_key(key)? {
+            // the mutation must reach L2, the copy of L1 would become stale
+            self.l1.remove(key)?;
+            return self.l2.get_mut(key);
+        }
+        self.l1.get_mut(key)
+    }
+
+    fn write_back(&mut self, key: &K) -> Result<(), BackingError> {
+        self.l2.write_back(key)?;
+        self.l1.write_back(key)
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        // L1 may evict the promoted value right away, e.g. with a capacity of zero
+        if self.promote(key)? && self.l1.contains_key(key)? {
+            return self.l1.get(key);
+        }
+        self.l2.get(key)
+    }
+
+    fn set(&mut self, key: K, value: V, meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
+        let (l1_meta, l2_meta) = match meta {
+            Some(meta) => (meta.l1, meta.l2),
+            None => (None, None),
+        };
+        let replaced = self.l2.set(key.clone(), value.clone(), l2_meta)?;
+        let cached = self.l1.set(key, value, l1_meta)?;
+        Ok(replaced.or(cached))
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        let cached = self.l1.remove(key)?;
+        let removed = self.l2.remove(key)?;
+        Ok(removed.or(cached))
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        Ok(self.l1.contains_key(key)? || self.l2.contains_key(key)?)
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
+        let predicate = Arc::new(predicate);
+        let l1_predicate = predicate.clone();
+        let cached = self.l1.remove_if(Box::new(move |entry| l1_predicate(entry)))?;
+        let mut removed = self.l2.remove_if(Box::new(move |entry| predicate(entry)))?;
+        let removed_keys = removed.iter()
+            .map(|(key, _)| key.clone())
+            .collect::<std::collections::HashSet<_>>();
+        removed.extend(cached.into_iter().filter(|(key, _)| !removed_keys.contains(key)));
+        Ok(removed)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.l1.clear()?;
+        self.l2.clear()
+    }
+
+    fn peek(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        if self.l1.contains_key(key)? {
+            return self.l1.peek(key);
+        }
+        self.l2.peek(key)
+    }
+
+    fn evict(&mut self, count: usize) -> Result<Vec<(K, V)>, BackingError> {
+        let evicted = self.l2.evict(count)?;
+        self.forget(&evicted)?;
+        Ok(evicted)
+    }
+
+    fn entries_by_recency(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.l2.entries_by_recency()
+    }
+
+    fn entries(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.l2.entries()
+    }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, V, Self::Meta>, BackingError> {
+        let chunk = self.l2.export(cursor, count)?;
+        Ok(ExportChunk {
+            entries: chunk.entries.into_iter()
+                .map(|(key, value, meta)| (key, value, Some(TieredMeta { l1: None, l2: meta })))
+                .collect(),
+            next: chunk.next,
+        })
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        self.l1.repair()?;
+        self.l2.repair()
+    }
+
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        let mut inconsistencies = self.l1.verify()?;
+        inconsistencies.extend(self.l2.verify()?);
+        Ok(inconsistencies)
+    }
+
+    fn remove_expired(&mut self) -> Result<(), BackingError> {
+        self.drain_expired().map(|_| ())
+    }
+
+    fn drain_expired(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.l1.remove_expired()?;
+        let expired = self.l2.drain_expired()?;
+        self.forget(&expired)?;
+        Ok(expired)
+    }
+
+    fn get_stale(&mut self, key: &K, max_staleness: std::time::Duration) -> Result<Option<(V, std::time::Duration)>, BackingError> {
+        self.l2.get_stale(key, max_staleness)
+    }
+
+    fn time_to_live(&mut self, key: &K) -> Result<Option<std::time::Duration>, BackingError> {
+        self.l2.time_to_live(key)
+    }
+
+    fn next_expiry(&mut self) -> Result<Option<std::time::Duration>, BackingError> {
+        Ok(self.l1.next_expiry()?.into_iter().chain(self.l2.next_expiry()?).min())
+    }
+
+    fn set_removal_listener(&mut self, listener: RemovalListener<K, V>) {
+        // entries L1 evicts are still held by L2, so only the removals of L2 are reported
+        self.l2.set_removal_listener(listener)
+    }
+
+    fn stats(&self) -> BackingStats {
+        self.l2.stats()
+    }
+
+    fn maintenance(&mut self) -> Option<&mut dyn Maintenance> {
+        self.l2.maintenance()
+    }
+}
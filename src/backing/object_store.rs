@@ -0,0 +1,424 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Write as _};
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use futures::Future;
+use futures::future::BoxFuture;
+use tokio::sync::{mpsc, oneshot};
+use super::{BackingError, BackingPredicate, CacheBacking, ExportChunk, Inconsistency, Maintenance, RemovalListener};
+use crate::cache_api::CacheEntry;
+use crate::codec::ValueCodec;
+use crate::stats::BackingStats;
+
+/// A bucket of an object store like S3 or GCS, implemented on top of the client of the store
+///
+/// The futures are awaited by the tasks of the cache and must not borrow the store.
+pub trait ObjectStore: Send + Sync + 'static {
+    /// Returns the bytes of the object, `None` if there's no object with the name
+    fn get(&self, name: &str) -> BoxFuture<'static, io::Result<Option<Vec<u8>>>>;
+    /// Creates or replaces the object
+    fn put(&self, name: &str, bytes: Vec<u8>) -> BoxFuture<'static, io::Result<()>>;
+    /// Deletes the object, succeeds if there's no object with the name
+    fn delete(&self, name: &str) -> BoxFuture<'static, io::Result<()>>;
+}
+
+/// A backing keeping the values read recently in a local backing `B` and all values in an
+/// `ObjectStore`, for very large values which are rarely read, like ML artifacts or rendered
+/// reports
+///
+/// Objects are named by the `prefix` followed by the hex digits of the key encoded with the
+/// `ValueCodec`. Every operation of the cache runs on the local backing, which bounds the
+/// values held in memory or on disk, e.g. an `LruCacheBacking`:
+///
+/// * Values set in the cache are uploaded in the background, in the order they were set. Values
+///   mutated by `LoadingCache::update_mut` are uploaded again.
+/// * Values aren't fetched by the actor. The loader of the cache is wrapped with
+///   `ColdTier::loader` instead, which fetches the object of a key the local backing doesn't
+///   hold asynchronously within the load and only calls the loader if there's none. The
+///   fetched value is set in the local backing like any loaded value, which promotes it, but
+///   isn't uploaded again.
+///
+/// Values of the store don't expire, entries evicted or expired by the local backing are
+/// fetched again. Removed keys are deleted from the store, `LoadingCache::clear` only deletes
+/// the objects of keys set through this backing and `LoadingCache::remove_if` only the objects
+/// of keys the local backing holds, so give every cache its own prefix. Keys removed while
+/// they are loading keep their object.
+///
+/// The backing doesn't list objects, `LoadingCache::keys`, `LoadingCache::entries`,
+/// `LoadingCache::export` and the statistics cover the entries of the local backing, like the
+/// lookups of the cache do. As the `L2` of a `TieredBacking`, its local backing is the tier
+/// holding all entries.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cache_loader_async::backing::{HashMapBacking, ObjectStore, ObjectStoreBacking};
+/// use cache_loader_async::cache_api::LoadingCache;
+/// use cache_loader_async::codec::JsonCodec;
+///
+/// async fn example<S: ObjectStore>(bucket: S) {
+///     let backing = ObjectStoreBacking::new(HashMapBacking::new(), bucket, "reports/", JsonCodec);
+///     let loader = backing.cold_tier().loader(|id: u64| async move {
+///         Ok::<_, u8>(format!("report-{}", id))
+///     });
+///     let cache = LoadingCache::with_backing(backing, loader);
+///     // a cache on the same bucket after a restart fetches the report instead of rendering it
+///     cache.get(1).await.unwrap();
+/// }
+/// ```
+pub struct ObjectStoreBacking<B, K, V, C, S> {
+    backing: B,
+    tier: ColdTier<K, V, C, S>,
+    // the keys set through this backing, whose objects are deleted by `clear`
+    stored: HashSet<K>,
+    // numbers the queued writes, so a write only settles its own pending value
+    sequence: u64,
+}
+
+impl<
+    B,
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    C: ValueCodec<K> + ValueCodec<V> + 'static,
+    S: ObjectStore,
+> ObjectStoreBacking<B, K, V, C, S> {
+    /// Keeps the values read recently in `backing` and stores all values in objects named with
+    /// the `prefix`, spawns the task uploading them
+    pub fn new(backing: B, store: S, prefix: &str, codec: C) -> Self {
+        let (writes, rx) = mpsc::unbounded_channel();
+        let shared = Arc::new(Shared {
+            store,
+            prefix: prefix.to_owned(),
+            codec,
+            pending: Mutex::new(HashMap::new()),
+            fetched: Mutex::new(HashMap::new()),
+        });
+        tokio::spawn(Shared::write(shared.clone(), rx));
+        Self {
+            backing,
+            tier: ColdTier { shared, writes },
+            stored: HashSet::new(),
+            sequence: 0,
+        }
+    }
+
+    /// Returns a handle of the tier, which wraps the loader of the cache and waits for uploads
+    pub fn cold_tier(&self) -> ColdTier<K, V, C, S> {
+        self.tier.clone()
+    }
+
+    /// Returns the local backing
+    pub fn backing(&self) -> &B {
+        &self.backing
+    }
+
+    pub fn backing_mut(&mut self) -> &mut B {
+        &mut self.backing
+    }
+
+    /// Queues the upload of the value or the deletion of the object of the key
+    fn queue(&mut self, key: &K, value: Option<(V, Vec<u8>)>) -> Result<(), BackingError> {
+        let name = self.tier.shared.name(key)?;
+        self.sequence += 1;
+        let (value, bytes) = value.unzip();
+        self.tier.shared.pending.lock().unwrap().insert(key.clone(), (self.sequence, value));
+        // the task only ends with the runtime
+        self.tier.writes.send(Write::Store(key.clone(), self.sequence, name, bytes)).ok();
+        Ok(())
+    }
+
+    /// Deletes the objects of the removed keys, entries of loads which didn't complete have none
+    fn delete<E: Debug>(&mut self, removed: &[(K, CacheEntry<V, E>)]) -> Result<(), BackingError> {
+        for (key, entry) in removed {
+            if let CacheEntry::Loaded(_) = entry {
+                self.stored.remove(key);
+                self.queue(key, None)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<
+    B: CacheBacking<K, CacheEntry<V, E>>,
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V> + 'static,
+    S: ObjectStore,
+> CacheBacking<K, CacheEntry<V, E>> for ObjectStoreBacking<B, K, V, C, S> {
+    type Meta = B::Meta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut CacheEntry<V, E>>, BackingError> {
+        self.backing.get_mut(key)
+    }
+
+    fn write_back(&mut self, key: &K) -> Result<(), BackingError> {
+        self.backing.write_back(key)?;
+        // the value was mutated in place, upload it again
+        if let Some(CacheEntry::Loaded(value)) = self.backing.peek(key)? {
+            let value = value.clone();
+            let bytes = ValueCodec::<V>::encode(&self.tier.shared.codec, &value)?;
+            self.queue(key, Some((value, bytes)))?;
+        }
+        Ok(())
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&CacheEntry<V, E>>, BackingError> {
+        self.backing.get(key)
+    }
+
+    fn set(&mut self, key: K, value: CacheEntry<V, E>, meta: Option<Self::Meta>) -> Result<Option<CacheEntry<V, E>>, BackingError> {
+        if let CacheEntry::Loaded(value) = &value {
+            let bytes = ValueCodec::<V>::encode(&self.tier.shared.codec, value)?;
+            let fetched = self.tier.shared.fetched.lock().unwrap().remove(&key);
+            // a value the loader fetched from the store is stored already
+            if fetched != Some(digest(&bytes)) {
+                self.queue(&key, Some((value.clone(), bytes)))?;
+            }
+            self.stored.insert(key.clone());
+        }
+        self.backing.set(key, value, meta)
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<CacheEntry<V, E>>, BackingError> {
+        let removed = self.backing.remove(key)?;
+        self.tier.shared.fetched.lock().unwrap().remove(key);
+        self.stored.remove(key);
+        // the cache removes the entries of failed or cancelled loads, which keep their object,
+        // other objects may have been stored by a previous run
+        if !matches!(removed, Some(CacheEntry::Loading(_))) {
+            self.queue(key, None)?;
+        }
+        Ok(removed)
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        self.backing.contains_key(key)
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, CacheEntry<V, E>>) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        let removed = self.backing.remove_if(predicate)?;
+        self.delete(&removed)?;
+        Ok(removed)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.backing.clear()?;
+        self.tier.shared.fetched.lock().unwrap().clear();
+        for key in std::mem::take(&mut self.stored) {
+            self.queue(&key, None)?;
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self, key: &K) -> Result<Option<&CacheEntry<V, E>>, BackingError> {
+        self.backing.peek(key)
+    }
+
+    fn evict(&mut self, count: usize) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        // evicted values are still stored as objects
+        self.backing.evict(count)
+    }
+
+    fn entries_by_recency(&mut self) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        self.backing.entries_by_recency()
+    }
+
+    fn entries(&mut self) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        self.backing.entries()
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        self.backing.repair()
+    }
+
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        self.backing.verify()
+    }
+
+    fn remove_expired(&mut self) -> Result<(), BackingError> {
+        self.backing.remove_expired()
+    }
+
+    fn drain_expired(&mut self) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        self.backing.drain_expired()
+    }
+
+    fn get_stale(&mut self, key: &K, max_staleness: Duration) -> Result<Option<(CacheEntry<V, E>, Duration)>, BackingError> {
+        self.backing.get_stale(key, max_staleness)
+    }
+
+    fn time_to_live(&mut self, key: &K) -> Result<Option<Duration>, BackingError> {
+        self.backing.time_to_live(key)
+    }
+
+    fn next_expiry(&mut self) -> Result<Option<Duration>, BackingError> {
+        self.backing.next_expiry()
+    }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, CacheEntry<V, E>, Self::Meta>, BackingError> {
+        self.backing.export(cursor, count)
+    }
+
+    fn set_removal_listener(&mut self, listener: RemovalListener<K, CacheEntry<V, E>>) {
+        self.backing.set_removal_listener(listener)
+    }
+
+    fn stats(&self) -> BackingStats {
+        self.backing.stats()
+    }
+
+    fn maintenance(&mut self) -> Option<&mut dyn Maintenance> {
+        self.backing.maintenance()
+    }
+}
+
+/// A handle of an `ObjectStoreBacking`, see `ObjectStoreBacking::cold_tier`
+pub struct ColdTier<K, V, C, S> {
+    shared: Arc<Shared<K, V, C, S>>,
+    writes: mpsc::UnboundedSender<Write<K>>,
+}
+
+impl<K, V, C, S> Clone for ColdTier<K, V, C, S> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            writes: self.writes.clone(),
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    C: ValueCodec<K> + ValueCodec<V> + 'static,
+    S: ObjectStore,
+> ColdTier<K, V, C, S> {
+    /// Wraps the loader of the cache, the returned loader fetches the object of the key and only
+    /// calls `loader` if there's none or the fetch failed
+    pub fn loader<E, T, F>(&self, loader: T) -> impl Fn(K) -> BoxFuture<'static, Result<V, E>> + Send + Sync + 'static
+        where E: Send + 'static,
+              F: Future<Output=Result<V, E>> + Send + 'static,
+              T: Fn(K) -> F + Send + Sync + 'static {
+        let tier = self.clone();
+        let loader = Arc::new(loader);
+        move |key: K| {
+            let tier = tier.clone();
+            let loader = loader.clone();
+            Box::pin(async move {
+                match tier.fetch(&key).await {
+                    Some(value) => Ok(value),
+                    None => loader(key).await,
+                }
+            })
+        }
+    }
+
+    /// Waits until the values set and the keys removed so far reached the store, returns the
+    /// error of the last write which failed since the previous call. Failed values are still
+    /// returned by the wrapped loader until the key is set or removed again.
+    pub async fn sync(&self) -> io::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        let stopped = || io::Error::other("the object store task stopped");
+        self.writes.send(Write::Sync(tx)).map_err(|_| stopped())?;
+        rx.await.map_err(|_| stopped())?
+    }
+
+    /// Returns the value of the key, from a write which didn't reach the store yet or fetched
+    /// from the store
+    async fn fetch(&self, key: &K) -> Option<V> {
+        if let Some((_, value)) = self.shared.pending.lock().unwrap().get(key) {
+            return value.clone();
+        }
+        let fetched = async {
+            let name = self.shared.name(key).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+            let bytes = match self.shared.store.get(&name).await? {
+                Some(bytes) => bytes,
+                None => return Ok(None),
+            };
+            let value = ValueCodec::<V>::decode(&self.shared.codec, &bytes)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            self.shared.fetched.lock().unwrap().insert(key.clone(), digest(&bytes));
+            Ok::<_, io::Error>(Some(value))
+        };
+        fetched.await
+            .inspect_err(|_err| {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_err, "object store fetch failed, loading the value");
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// An operation of the upload task
+enum Write<K> {
+    /// Uploads the bytes to the object, or deletes it without bytes, and settles the pending
+    /// value of the key with the number
+    Store(K, u64, String, Option<Vec<u8>>),
+    /// Completes once the previous writes are done, with the last error since the previous sync
+    Sync(oneshot::Sender<io::Result<()>>),
+}
+
+struct Shared<K, V, C, S> {
+    store: S,
+    prefix: String,
+    codec: C,
+    // the latest write of every key which didn't reach the store yet, `None` for deletions
+    pending: Mutex<HashMap<K, (u64, Option<V>)>>,
+    // digests of the objects fetched by loads which weren't set yet
+    fetched: Mutex<HashMap<K, u64>>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+    C: ValueCodec<K> + ValueCodec<V> + 'static,
+    S: ObjectStore,
+> Shared<K, V, C, S> {
+    fn name(&self, key: &K) -> Result<String, BackingError> {
+        let mut name = self.prefix.clone();
+        for byte in ValueCodec::<K>::encode(&self.codec, key)? {
+            write!(name, "{:02x}", byte).unwrap();
+        }
+        Ok(name)
+    }
+
+    /// Runs the writes in order until every handle of the tier is gone
+    async fn write(shared: Arc<Self>, mut rx: mpsc::UnboundedReceiver<Write<K>>) {
+        let mut failed = None;
+        while let Some(write) = rx.recv().await {
+            match write {
+                Write::Store(key, sequence, name, bytes) => {
+                    let result = match bytes {
+                        Some(bytes) => shared.store.put(&name, bytes).await,
+                        None => shared.store.delete(&name).await,
+                    };
+                    if let Err(err) = result {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(error = %err, object = %name, "object store write failed");
+                        // the pending value stays, the store doesn't hold it
+                        failed = Some(err);
+                        continue;
+                    }
+                    let mut pending = shared.pending.lock().unwrap();
+                    if matches!(pending.get(&key), Some((latest, _)) if *latest == sequence) {
+                        pending.remove(&key);
+                    }
+                }
+                Write::Sync(tx) => {
+                    tx.send(failed.take().map_or(Ok(()), Err)).ok();
+                }
+            }
+        }
+    }
+}
+
+fn digest(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,363 @@
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use super::{BackingError, BackingPredicate, CacheBacking, ExportChunk, Inconsistency, Maintenance, RemovalListener};
+use crate::stats::BackingStats;
+
+/// An operation executed on a `RecordingBacking`, with its arguments and result
+///
+/// Values are cloned when the operation is recorded, `GetMut` records the value before it is
+/// mutated.
+#[derive(Debug, Clone)]
+pub enum BackingOperation<K, V, M> {
+    Get { key: K, result: Result<Option<V>, BackingError> },
+    GetMut { key: K, result: Result<Option<V>, BackingError> },
+    Set { key: K, value: V, meta: Option<M>, result: Result<Option<V>, BackingError> },
+    Remove { key: K, result: Result<Option<V>, BackingError> },
+    ContainsKey { key: K, result: Result<bool, BackingError> },
+    RemoveIf { result: Result<Vec<(K, V)>, BackingError> },
+    Clear { result: Result<(), BackingError> },
+    Repair { result: Result<(), BackingError> },
+    Peek { key: K, result: Result<Option<V>, BackingError> },
+    Evict { count: usize, result: Result<Vec<(K, V)>, BackingError> },
+    RemoveExpired { result: Result<(), BackingError> },
+    DrainExpired { result: Result<Vec<(K, V)>, BackingError> },
+    GetStale { key: K, max_staleness: Duration, result: Result<Option<(V, Duration)>, BackingError> },
+}
+
+struct LogState<K, V, M> {
+    operations: VecDeque<BackingOperation<K, V, M>>,
+    capacity: Option<usize>,
+}
+
+/// The log of a `RecordingBacking`
+///
+/// Clones share the same log, so it can still be inspected once the backing has been moved into
+/// a cache. Recording can be toggled at runtime, to only record while an incident is analyzed.
+pub struct OperationLog<K, V, M> {
+    state: Arc<Mutex<LogState<K, V, M>>>,
+    enabled: Arc<AtomicBool>,
+}
+
+impl<K, V, M> Clone for OperationLog<K, V, M> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            enabled: self.enabled.clone(),
+        }
+    }
+}
+
+impl<K: Clone, V: Clone, M: Clone> Default for OperationLog<K, V, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone, V: Clone, M: Clone> OperationLog<K, V, M> {
+    /// Creates an enabled, unbounded log
+    pub fn new() -> OperationLog<K, V, M> {
+        Self::with_capacity(None)
+    }
+
+    /// Creates an enabled log which only keeps the latest `capacity` operations
+    pub fn bounded(capacity: usize) -> OperationLog<K, V, M> {
+        Self::with_capacity(Some(capacity))
+    }
+
+    fn with_capacity(capacity: Option<usize>) -> OperationLog<K, V, M> {
+        OperationLog {
+            state: Arc::new(Mutex::new(LogState {
+                operations: VecDeque::new(),
+                capacity,
+            })),
+            enabled: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Starts or stops recording, already recorded operations are kept
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns a copy of the recorded operations, oldest first
+    pub fn operations(&self) -> Vec<BackingOperation<K, V, M>> {
+        self.state.lock().unwrap().operations.iter().cloned().collect()
+    }
+
+    /// Removes and returns the recorded operations, oldest first
+    pub fn take(&self) -> Vec<BackingOperation<K, V, M>> {
+        self.state.lock().unwrap().operations.drain(..).collect()
+    }
+
+    pub fn clear(&self) {
+        self.state.lock().unwrap().operations.clear();
+    }
+
+    fn record(&self, operation: BackingOperation<K, V, M>) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(capacity) = state.capacity {
+            if capacity == 0 {
+                return;
+            }
+            while state.operations.len() >= capacity {
+                state.operations.pop_front();
+            }
+        }
+        state.operations.push_back(operation);
+    }
+}
+
+/// A backing wrapper which records every operation with its arguments and result into an
+/// `OperationLog`
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::backing::{BackingOperation, CacheBacking, HashMapBacking, RecordingBacking};
+///
+/// let mut backing = RecordingBacking::new(HashMapBacking::<String, u32>::new());
+/// let log = backing.log().clone();
+///
+/// backing.set("foo".to_owned(), 32, None).unwrap();
+/// backing.get(&"foo".to_owned()).unwrap();
+///
+/// assert!(matches!(log.operations()[1], BackingOperation::Get { result: Ok(Some(32)), .. }));
+/// ```
+pub struct RecordingBacking<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+    B: CacheBacking<K, V>,
+> {
+    backing: B,
+    log: OperationLog<K, V, B::Meta>,
+}
+
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+    B: CacheBacking<K, V>,
+> RecordingBacking<K, V, B> {
+    pub fn new(backing: B) -> RecordingBacking<K, V, B> {
+        Self::with_log(backing, OperationLog::new())
+    }
+
+    /// Creates a backing recording into an existing log, e.g. a bounded or disabled one
+    pub fn with_log(backing: B, log: OperationLog<K, V, B::Meta>) -> RecordingBacking<K, V, B> {
+        RecordingBacking {
+            backing,
+            log,
+        }
+    }
+
+    pub fn log(&self) -> &OperationLog<K, V, B::Meta> {
+        &self.log
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.backing
+    }
+}
+
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+    B: CacheBacking<K, V>,
+> CacheBacking<K, V> for RecordingBacking<K, V, B> {
+    type Meta = B::Meta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
+        let result = self.backing.get_mut(key);
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::GetMut {
+                key: key.clone(),
+                result: cloned(&result),
+            });
+        }
+        result
+    }
+
+    fn write_back(&mut self, key: &K) -> Result<(), BackingError> {
+        self.backing.write_back(key)
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        let result = self.backing.get(key);
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::Get {
+                key: key.clone(),
+                result: result.clone().map(|value| value.cloned()),
+            });
+        }
+        result
+    }
+
+    fn set(&mut self, key: K, value: V, meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
+        if !self.log.is_enabled() {
+            return self.backing.set(key, value, meta);
+        }
+        let result = self.backing.set(key.clone(), value.clone(), meta.clone());
+        self.log.record(BackingOperation::Set {
+            key,
+            value,
+            meta,
+            result: result.clone(),
+        });
+        result
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        let result = self.backing.remove(key);
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::Remove {
+                key: key.clone(),
+                result: result.clone(),
+            });
+        }
+        result
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        let result = self.backing.contains_key(key);
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::ContainsKey {
+                key: key.clone(),
+                result: result.clone(),
+            });
+        }
+        result
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
+        let result = self.backing.remove_if(predicate);
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::RemoveIf {
+                result: result.clone(),
+            });
+        }
+        result
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        let result = self.backing.clear();
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::Clear {
+                result: result.clone(),
+            });
+        }
+        result
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        let result = self.backing.repair();
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::Repair {
+                result: result.clone(),
+            });
+        }
+        result
+    }
+
+    fn peek(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        let result = self.backing.peek(key);
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::Peek {
+                key: key.clone(),
+                result: result.clone().map(|value| value.cloned()),
+            });
+        }
+        result
+    }
+
+    fn evict(&mut self, count: usize) -> Result<Vec<(K, V)>, BackingError> {
+        let result = self.backing.evict(count);
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::Evict {
+                count,
+                result: result.clone(),
+            });
+        }
+        result
+    }
+
+    fn entries_by_recency(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.backing.entries_by_recency()
+    }
+
+    fn entries(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.backing.entries()
+    }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, V, B::Meta>, BackingError> {
+        self.backing.export(cursor, count)
+    }
+
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        self.backing.verify()
+    }
+
+    fn remove_expired(&mut self) -> Result<(), BackingError> {
+        let result = self.backing.remove_expired();
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::RemoveExpired {
+                result: result.clone(),
+            });
+        }
+        result
+    }
+
+    fn drain_expired(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        let result = self.backing.drain_expired();
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::DrainExpired {
+                result: result.clone(),
+            });
+        }
+        result
+    }
+
+    fn get_stale(&mut self, key: &K, max_staleness: Duration) -> Result<Option<(V, Duration)>, BackingError> {
+        let result = self.backing.get_stale(key, max_staleness);
+        if self.log.is_enabled() {
+            self.log.record(BackingOperation::GetStale {
+                key: key.clone(),
+                max_staleness,
+                result: result.clone(),
+            });
+        }
+        result
+    }
+
+    fn time_to_live(&mut self, key: &K) -> Result<Option<Duration>, BackingError> {
+        self.backing.time_to_live(key)
+    }
+
+    fn next_expiry(&mut self) -> Result<Option<Duration>, BackingError> {
+        self.backing.next_expiry()
+    }
+
+    fn set_removal_listener(&mut self, listener: RemovalListener<K, V>) {
+        self.backing.set_removal_listener(listener)
+    }
+
+    fn stats(&self) -> BackingStats {
+        self.backing.stats()
+    }
+
+    fn maintenance(&mut self) -> Option<&mut dyn Maintenance> {
+        self.backing.maintenance()
+    }
+}
+
+fn cloned<V: Clone>(result: &Result<Option<&mut V>, BackingError>) -> Result<Option<V>, BackingError> {
+    match result {
+        Ok(value) => Ok(value.as_deref().cloned()),
+        Err(err) => Err(err.clone()),
+    }
+}
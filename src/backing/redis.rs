@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::time::UNIX_EPOCH;
+use tokio::time::Duration;
+use ::redis::{Cmd, Connection, ConnectionLike, IntoConnectionInfo, RedisError};
+use super::{BackingError, BackingPredicate, CacheBacking, TtlMeta};
+use crate::cache_api::CacheEntry;
+use crate::codec::ValueCodec;
+
+const BACKING: &str = "RedisBacking";
+/// How many keys a `SCAN` of `remove_if` and `clear` asks for at once
+const SCAN_COUNT: usize = 256;
+
+/// A backing storing its values in Redis, so caches of several processes share the loaded
+/// values
+///
+/// Keys are stored as the `prefix` followed by the key encoded with the `ValueCodec`, values
+/// as their encoding. The TTL of `TtlMeta`, e.g. passed by `LoadingCache::set_with_ttl`, is
+/// mapped to the expiry of the Redis key, values set without one expire after the TTL of
+/// `RedisBacking::ttl` or never. `LoadingCache::clear` and `LoadingCache::remove_if` only
+/// touch keys starting with the prefix.
+///
+/// `CacheBacking` is synchronous, so every operation of the cache blocks its actor until Redis
+/// answered. Use a connection with read and write timeouts, failures are returned as
+/// `BackingError::Unavailable` or `BackingError::Other`. Loading entries stay local to the
+/// process like with the `SharedMemoryBacking`, and values mutated by `LoadingCache::update_mut`
+/// are written back to Redis by the same operation. Requires Redis 6.2 or later.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cache_loader_async::backing::RedisBacking;
+/// use cache_loader_async::cache_api::LoadingCache;
+/// use cache_loader_async::codec::JsonCodec;
+/// use std::time::Duration;
+///
+/// async fn example() -> redis::RedisResult<()> {
+///     let backing = RedisBacking::open("redis://127.0.0.1/", "users:", JsonCodec)?
+///         .ttl(Duration::from_secs(300));
+///     let cache: LoadingCache<u64, String, u8, _> = LoadingCache::with_backing(backing, |id: u64| async move {
+///         Ok(format!("user-{}", id))
+///     });
+///     // other processes using the same prefix read the loaded value without loading it
+///     cache.get(1).await.unwrap();
+///     Ok(())
+/// }
+/// ```
+pub struct RedisBacking<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+    R: ConnectionLike + Send = Connection,
+> {
+    connection: R,
+    prefix: Vec<u8>,
+    codec: C,
+    ttl: Option<Duration>,
+    // loading entries, which can't be shared with other processes
+    local: HashMap<K, CacheEntry<V, E>>,
+    // the last value read from Redis, returned as reference
+    read: Option<(K, CacheEntry<V, E>)>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+> RedisBacking<K, V, E, C> {
+    /// Connects to the Redis server at `info`, e.g. `redis://127.0.0.1/`, storing the keys of
+    /// the cache with the `prefix`
+    pub fn open<T: IntoConnectionInfo>(info: T, prefix: &str, codec: C) -> ::redis::RedisResult<Self> {
+        let connection = ::redis::Client::open(info)?.get_connection()?;
+        Ok(Self::with_connection(connection, prefix, codec))
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+    R: ConnectionLike + Send,
+> RedisBacking<K, V, E, C, R> {
+    /// Uses an established connection, e.g. one with timeouts or to a Redis cluster
+    pub fn with_connection(connection: R, prefix: &str, codec: C) -> Self {
+        Self {
+            connection,
+            prefix: prefix.as_bytes().to_vec(),
+            codec,
+            ttl: None,
+            local: HashMap::new(),
+            read: None,
+        }
+    }
+
+    /// Expires values set without a `TtlMeta` after `ttl`, instead of keeping them until they
+    /// are removed or evicted by Redis
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Returns the connection, e.g. to issue other commands on it
+    pub fn connection(&mut self) -> &mut R {
+        &mut self.connection
+    }
+
+    fn query<T: ::redis::FromRedisValue>(&mut self, cmd: &Cmd) -> Result<T, BackingError> {
+        cmd.query(&mut self.connection).map_err(redis_error)
+    }
+
+    fn encode_key(&self, key: &K) -> Result<Vec<u8>, BackingError> {
+        let mut encoded = self.prefix.clone();
+        encoded.extend(ValueCodec::<K>::encode(&self.codec, key)?);
+        Ok(encoded)
+    }
+
+    fn decode_value(&self, bytes: Option<Vec<u8>>) -> Result<Option<V>, BackingError> {
+        Ok(bytes.map(|bytes| ValueCodec::<V>::decode(&self.codec, &bytes)).transpose()?)
+    }
+
+    /// Reads the value of the key from Redis into `read`
+    fn refresh(&mut self, key: &K) -> Result<(), BackingError> {
+        let bytes = self.query(::redis::cmd("GET").arg(self.encode_key(key)?))?;
+        self.read = self.decode_value(bytes)?
+            .map(|value| (key.clone(), CacheEntry::Loaded(value)));
+        Ok(())
+    }
+
+    fn store(&mut self, key: &K, value: &V, meta: Option<TtlMeta>) -> Result<Option<V>, BackingError> {
+        let mut cmd = ::redis::cmd("SET");
+        cmd.arg(self.encode_key(key)?)
+            .arg(ValueCodec::<V>::encode(&self.codec, value)?)
+            .arg("GET");
+        match (meta, self.ttl) {
+            (Some(TtlMeta { expires_at: Some(expires_at), .. }), _) => {
+                let millis = expires_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+                cmd.arg("PXAT").arg(millis.max(1) as u64);
+            }
+            (Some(TtlMeta { ttl, .. }), _) | (None, Some(ttl)) => {
+                // Redis rejects a TTL of zero, the value expires right away instead
+                cmd.arg("PX").arg(ttl.as_millis().max(1) as u64);
+            }
+            (None, None) => {}
+        }
+        let replaced = self.query(&cmd)?;
+        self.decode_value(replaced)
+    }
+
+    fn remove_shared(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        if matches!(&self.read, Some((read_key, _)) if read_key == key) {
+            self.read = None;
+        }
+        let removed = self.query(::redis::cmd("GETDEL").arg(self.encode_key(key)?))?;
+        self.decode_value(removed)
+    }
+
+    /// Returns all keys in Redis starting with the prefix
+    fn scan(&mut self) -> Result<Vec<Vec<u8>>, BackingError> {
+        let mut pattern = Vec::with_capacity(self.prefix.len() + 1);
+        for byte in &self.prefix {
+            if matches!(byte, b'*' | b'?' | b'[' | b']' | b'\\') {
+                pattern.push(b'\\');
+            }
+            pattern.push(*byte);
+        }
+        pattern.push(b'*');
+        let mut keys = Vec::new();
+        let mut cursor = 0u64;
+        loop {
+            let mut cmd = ::redis::cmd("SCAN");
+            cmd.arg(cursor).arg("MATCH").arg(&pattern).arg("COUNT").arg(SCAN_COUNT);
+            let (next, batch): (u64, Vec<Vec<u8>>) = self.query(&cmd)?;
+            keys.extend(batch);
+            if next == 0 {
+                return Ok(keys);
+            }
+            cursor = next;
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+    R: ConnectionLike + Send,
+> CacheBacking<K, CacheEntry<V, E>> for RedisBacking<K, V, E, C, R> {
+    type Meta = TtlMeta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut CacheEntry<V, E>>, BackingError> {
+        if self.local.contains_key(key) {
+            return Ok(self.local.get_mut(key));
+        }
+        self.refresh(key)?;
+        Ok(self.read.as_mut().map(|(_, entry)| entry))
+    }
+
+    fn write_back(&mut self, key: &K) -> Result<(), BackingError> {
+        match self.read.take() {
+            Some((read_key, CacheEntry::Loaded(value))) if read_key == *key => {
+                let mut cmd = ::redis::cmd("SET");
+                cmd.arg(self.encode_key(&read_key)?)
+                    .arg(ValueCodec::<V>::encode(&self.codec, &value)?)
+                    // a value which expired meanwhile isn't stored again
+                    .arg("XX")
+                    .arg("KEEPTTL");
+                self.query::<()>(&cmd)?;
+            }
+            read => self.read = read,
+        }
+        Ok(())
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&CacheEntry<V, E>>, BackingError> {
+        if self.local.contains_key(key) {
+            return Ok(self.local.get(key));
+        }
+        self.refresh(key)?;
+        Ok(self.read.as_ref().map(|(_, entry)| entry))
+    }
+
+    fn set(&mut self, key: K, value: CacheEntry<V, E>, meta: Option<Self::Meta>) -> Result<Option<CacheEntry<V, E>>, BackingError> {
+        if matches!(&self.read, Some((read_key, _)) if *read_key == key) {
+            self.read = None;
+        }
+        let local = self.local.remove(&key);
+        let replaced = match value {
+            CacheEntry::Loaded(value) => self.store(&key, &value, meta)?,
+            loading => {
+                // the value in Redis stays visible to the other processes while it's reloaded
+                let bytes = self.query(::redis::cmd("GET").arg(self.encode_key(&key)?))?;
+                let replaced = self.decode_value(bytes)?;
+                self.local.insert(key, loading);
+                replaced
+            }
+        };
+        Ok(local.or(replaced.map(CacheEntry::Loaded)))
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<CacheEntry<V, E>>, BackingError> {
+        let local = self.local.remove(key);
+        let removed = self.remove_shared(key)?;
+        Ok(local.or(removed.map(CacheEntry::Loaded)))
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        if self.local.contains_key(key) {
+            return Ok(true);
+        }
+        let key_bytes = self.encode_key(key)?;
+        self.query(::redis::cmd("EXISTS").arg(key_bytes))
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, CacheEntry<V, E>>) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        self.read = None;
+        let mut removed = Vec::new();
+        for key_bytes in self.scan()? {
+            let key = ValueCodec::<K>::decode(&self.codec, &key_bytes[self.prefix.len()..])?;
+            let bytes = self.query(::redis::cmd("GET").arg(&key_bytes))?;
+            // keys expiring after the scan are skipped
+            if let Some(value) = self.decode_value(bytes)? {
+                let entry = CacheEntry::Loaded(value);
+                if predicate((&key, &entry)) && self.query::<u64>(::redis::cmd("DEL").arg(&key_bytes))? > 0 {
+                    removed.push((key, entry));
+                }
+            }
+        }
+        let local_keys = self.local.iter()
+            .filter(|(key, entry)| predicate((key, entry)))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in local_keys {
+            if let Some(entry) = self.local.remove(&key) {
+                removed.push((key, entry));
+            }
+        }
+        Ok(removed)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.read = None;
+        self.local.clear();
+        let keys = self.scan()?;
+        for batch in keys.chunks(SCAN_COUNT) {
+            self.query::<()>(::redis::cmd("DEL").arg(batch))?;
+        }
+        Ok(())
+    }
+
+    fn entries(&mut self) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        let mut entries = Vec::new();
+        for key_bytes in self.scan()? {
+            let key = ValueCodec::<K>::decode(&self.codec, &key_bytes[self.prefix.len()..])?;
+            if self.local.contains_key(&key) {
+                continue;
+            }
+            let bytes = self.query(::redis::cmd("GET").arg(&key_bytes))?;
+            // keys expiring after the scan are skipped
+            if let Some(value) = self.decode_value(bytes)? {
+                entries.push((key, CacheEntry::Loaded(value)));
+            }
+        }
+        // local entries take precedence, like in `get`
+        entries.extend(self.local.iter().map(|(key, entry)| (key.clone(), entry.clone())));
+        Ok(entries)
+    }
+}
+
+/// Errors of the connection may go away on a retry, errors of the commands won't
+fn redis_error(error: RedisError) -> BackingError {
+    if error.is_timeout() || error.is_connection_dropped() || error.is_connection_refusal() {
+        BackingError::unavailable(BACKING, error.to_string())
+    } else {
+        BackingError::other(BACKING, error)
+    }
+}
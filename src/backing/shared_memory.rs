@@ -0,0 +1,451 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::OpenOptions;
+use std::hash::Hash;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+use memmap2::MmapMut;
+use super::{BackingError, BackingPredicate, CacheBacking, NoMeta};
+use crate::cache_api::CacheEntry;
+use crate::codec::ValueCodec;
+
+const MAGIC: u64 = 0x6361_6368_655f_7368;
+// magic, slot count and slot size, padded to keep the slots aligned
+const HEADER_SIZE: usize = 64;
+// sequence, state, key length and value length
+const SLOT_HEADER_SIZE: usize = 24;
+const EMPTY: u32 = 0;
+const USED: u32 = 1;
+const REMOVED: u32 = 2;
+/// How many slots starting at the home slot of a key are searched for it
+const MAX_PROBES: usize = 8;
+/// How often a slot which is being written is polled before it's skipped
+const MAX_SPINS: usize = 10_000;
+
+enum Slot {
+    Empty,
+    Removed,
+    Used { sequence: u64, key: Vec<u8>, value: Vec<u8> },
+}
+
+/// A backing sharing its values with other processes on the same host through a memory-mapped
+/// file, e.g. in `/dev/shm`
+///
+/// The file is split into a fixed number of slots of a fixed size, a key is stored in one of
+/// the `8` slots following the slot its hash points to, or replaces the entry of that slot if
+/// all of them are taken. Reads don't take locks, they retry while another process writes the
+/// slot. Keys and values are encoded with the `ValueCodec`, values which don't fit into a slot
+/// are only cached by this process.
+///
+/// Loading entries stay local to the process, so every process loads a key at most once at a
+/// time, but processes missing the same key at the same time load it each. Values mutated by
+/// `LoadingCache::update_mut` are written back to the map by the same operation. A process
+/// dying while it writes a slot leaves the slot locked, it's skipped until the file is
+/// recreated.
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::backing::SharedMemoryBacking;
+/// use cache_loader_async::cache_api::LoadingCache;
+/// use cache_loader_async::codec::JsonCodec;
+///
+/// async fn example() -> std::io::Result<()> {
+///     let backing = SharedMemoryBacking::open("/dev/shm/users", 4096, 256, JsonCodec)?;
+///     let cache: LoadingCache<u64, String, u8, _> = LoadingCache::with_backing(backing, |id: u64| async move {
+///         Ok(format!("user-{}", id))
+///     });
+///     // other processes opening /dev/shm/users read the loaded value without loading it
+///     cache.get(1).await.unwrap();
+///     Ok(())
+/// }
+/// ```
+pub struct SharedMemoryBacking<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+> {
+    map: MmapMut,
+    slots: usize,
+    slot_size: usize,
+    codec: C,
+    // loading entries and values which don't fit into a slot
+    local: HashMap<K, CacheEntry<V, E>>,
+    // the last value read from the map with the sequence of its slot, returned as reference
+    read: Option<(K, u64, CacheEntry<V, E>)>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+> SharedMemoryBacking<K, V, E, C> {
+    /// Opens the map at `path`, creating it with `slots` slots of `slot_size` bytes if the file
+    /// doesn't contain one yet
+    ///
+    /// A slot holds the encoded key and value as well as a header of 24 bytes. Once the map has
+    /// been created, other processes open it with its geometry regardless of their arguments.
+    pub fn open<P: AsRef<Path>>(path: P, slots: usize, slot_size: usize, codec: C) -> io::Result<Self> {
+        let slots = slots.max(1);
+        // slots start with an atomic sequence, so their size is a multiple of its alignment
+        let slot_size = slot_size.max(SLOT_HEADER_SIZE + 8).div_ceil(8) * 8;
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let size = (HEADER_SIZE + slots * slot_size) as u64;
+        if file.metadata()?.len() < size {
+            file.set_len(size)?;
+        }
+        // Safety: the file is only accessed through atomics and the seqlocks of its slots
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        let mut backing = Self {
+            map,
+            slots,
+            slot_size,
+            codec,
+            local: HashMap::new(),
+            read: None,
+        };
+        if backing.header(0).load(Ordering::Acquire) == MAGIC {
+            backing.slots = backing.header(1).load(Ordering::Relaxed) as usize;
+            backing.slot_size = backing.header(2).load(Ordering::Relaxed) as usize;
+            if backing.map.len() < HEADER_SIZE + backing.slots * backing.slot_size {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "The shared map is truncated"));
+            }
+        } else {
+            backing.header(1).store(slots as u64, Ordering::Relaxed);
+            backing.header(2).store(slot_size as u64, Ordering::Relaxed);
+            backing.header(0).store(MAGIC, Ordering::Release);
+        }
+        Ok(backing)
+    }
+
+    /// Returns the number of slots of the map
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    fn header(&self, index: usize) -> &AtomicU64 {
+        // Safety: the header is within the map, which is page aligned
+        unsafe { &*(self.map.as_ptr().add(index * 8) as *const AtomicU64) }
+    }
+
+    fn slot_ptr(&self, index: usize) -> *mut u8 {
+        // Safety: the map holds `slots` slots after the header
+        unsafe { (self.map.as_ptr() as *mut u8).add(HEADER_SIZE + index * self.slot_size) }
+    }
+
+    fn sequence(&self, index: usize) -> &AtomicU64 {
+        // Safety: slots are aligned to 8 bytes and start with their sequence
+        unsafe { &*(self.slot_ptr(index) as *const AtomicU64) }
+    }
+
+    fn field(&self, index: usize, field: usize) -> &AtomicU32 {
+        // Safety: the state and lengths follow the sequence of the slot
+        unsafe { &*(self.slot_ptr(index).add(8 + field * 4) as *const AtomicU32) }
+    }
+
+    fn capacity(&self) -> usize {
+        self.slot_size - SLOT_HEADER_SIZE
+    }
+
+    /// Reads a slot, `None` if another process kept writing it
+    fn read_slot(&self, index: usize) -> Option<Slot> {
+        let sequence = self.sequence(index);
+        for _ in 0..MAX_SPINS {
+            let before = sequence.load(Ordering::Acquire);
+            if before & 1 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let slot = match self.field(index, 0).load(Ordering::Relaxed) {
+                USED => {
+                    let key_len = self.field(index, 1).load(Ordering::Relaxed) as usize;
+                    let value_len = self.field(index, 2).load(Ordering::Relaxed) as usize;
+                    if key_len + value_len > self.capacity() {
+                        // torn read of a slot which is written meanwhile
+                        continue;
+                    }
+                    let mut bytes = vec![0; key_len + value_len];
+                    // Safety: the bytes are within the slot, a concurrent write is detected by
+                    // the sequence and the copy discarded
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(self.slot_ptr(index).add(SLOT_HEADER_SIZE), bytes.as_mut_ptr(), bytes.len());
+                    }
+                    let value = bytes.split_off(key_len);
+                    Slot::Used { sequence: before, key: bytes, value }
+                }
+                REMOVED => Slot::Removed,
+                _ => Slot::Empty,
+            };
+            fence(Ordering::Acquire);
+            if sequence.load(Ordering::Relaxed) == before {
+                return Some(slot);
+            }
+        }
+        None
+    }
+
+    /// Writes an entry into a slot or clears it, `false` if another process kept writing it
+    fn write_slot(&self, index: usize, state: u32, key: &[u8], value: &[u8]) -> bool {
+        let sequence = self.sequence(index);
+        for _ in 0..MAX_SPINS {
+            let current = sequence.load(Ordering::Relaxed);
+            if current & 1 == 0 && sequence.compare_exchange_weak(current, current + 1, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                fence(Ordering::Release);
+                self.field(index, 0).store(state, Ordering::Relaxed);
+                self.field(index, 1).store(key.len() as u32, Ordering::Relaxed);
+                self.field(index, 2).store(value.len() as u32, Ordering::Relaxed);
+                // Safety: the caller checked the capacity, the slot is locked by its sequence
+                unsafe {
+                    let data = self.slot_ptr(index).add(SLOT_HEADER_SIZE);
+                    std::ptr::copy_nonoverlapping(key.as_ptr(), data, key.len());
+                    std::ptr::copy_nonoverlapping(value.as_ptr(), data.add(key.len()), value.len());
+                }
+                sequence.store(current + 2, Ordering::Release);
+                return true;
+            }
+            std::hint::spin_loop();
+        }
+        false
+    }
+
+    fn probe(&self, key: &[u8]) -> impl Iterator<Item = usize> {
+        let slots = self.slots;
+        let home = (fnv1a(key) % slots as u64) as usize;
+        (0..MAX_PROBES.min(slots)).map(move |offset| (home + offset) % slots)
+    }
+
+    /// Returns the sequence and value of the key
+    fn find(&self, key: &[u8]) -> Option<(u64, Vec<u8>)> {
+        for index in self.probe(key) {
+            match self.read_slot(index) {
+                Some(Slot::Empty) => return None,
+                Some(Slot::Used { sequence, key: slot_key, value }) if slot_key == key => return Some((sequence, value)),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Stores the value of the key, returning the replaced one, or `Err` with the value if the
+    /// map has no slot for it
+    fn insert(&self, key: &[u8], value: Vec<u8>) -> Result<Option<Vec<u8>>, Vec<u8>> {
+        if key.len() + value.len() > self.capacity() {
+            return Err(value);
+        }
+        let (mut existing, mut free) = (None, None);
+        for index in self.probe(key) {
+            match self.read_slot(index) {
+                Some(Slot::Used { key: slot_key, value, .. }) if slot_key == key => {
+                    existing = Some((index, value));
+                    break;
+                }
+                Some(Slot::Empty) => {
+                    free = free.or(Some(index));
+                    break;
+                }
+                Some(Slot::Removed) => free = free.or(Some(index)),
+                _ => {}
+            }
+        }
+        let (index, replaced) = match (existing, free) {
+            (Some((index, replaced)), _) => (index, Some(replaced)),
+            (None, Some(index)) => (index, None),
+            // every slot of the key is taken, the entry of its home slot is evicted
+            (None, None) => (self.probe(key).next().unwrap_or_default(), None),
+        };
+        if self.write_slot(index, USED, key, &value) {
+            Ok(replaced)
+        } else {
+            Err(value)
+        }
+    }
+
+    /// Removes the key from all slots it was stored in, returning its value
+    fn remove_key(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let mut removed = None;
+        for index in self.probe(key) {
+            match self.read_slot(index) {
+                Some(Slot::Empty) => break,
+                Some(Slot::Used { key: slot_key, value, .. })
+                    if slot_key == key && self.write_slot(index, REMOVED, &[], &[]) => {
+                    removed = removed.or(Some(value));
+                }
+                _ => {}
+            }
+        }
+        removed
+    }
+
+    fn encode_key(&self, key: &K) -> Result<Vec<u8>, BackingError> {
+        Ok(ValueCodec::<K>::encode(&self.codec, key)?)
+    }
+
+    fn decode_value(&self, bytes: &[u8]) -> Result<V, BackingError> {
+        Ok(ValueCodec::<V>::decode(&self.codec, bytes)?)
+    }
+
+    /// Reads the value of the key from the map into `read`, unless it is read already
+    fn refresh(&mut self, key: &K) -> Result<(), BackingError> {
+        let key_bytes = self.encode_key(key)?;
+        match self.find(&key_bytes) {
+            Some((sequence, value)) => {
+                let fresh = matches!(&self.read, Some((read_key, read_sequence, _))
+                    if read_key == key && *read_sequence == sequence);
+                if !fresh {
+                    let value = self.decode_value(&value)?;
+                    self.read = Some((key.clone(), sequence, CacheEntry::Loaded(value)));
+                }
+            }
+            None => self.read = None,
+        }
+        Ok(())
+    }
+
+    fn store(&mut self, key: K, value: V) -> Result<Option<V>, BackingError> {
+        let key_bytes = self.encode_key(&key)?;
+        let value_bytes = ValueCodec::<V>::encode(&self.codec, &value)?;
+        match self.insert(&key_bytes, value_bytes) {
+            Ok(replaced) => replaced.map(|replaced| self.decode_value(&replaced)).transpose(),
+            Err(_) => {
+                // a stale value of the map mustn't remain visible to the other processes
+                let replaced = self.remove_key(&key_bytes);
+                self.local.insert(key, CacheEntry::Loaded(value));
+                replaced.map(|replaced| self.decode_value(&replaced)).transpose()
+            }
+        }
+    }
+
+    fn remove_shared(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        if matches!(&self.read, Some((read_key, _, _)) if read_key == key) {
+            self.read = None;
+        }
+        let key_bytes = self.encode_key(key)?;
+        self.remove_key(&key_bytes)
+            .map(|removed| self.decode_value(&removed))
+            .transpose()
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+> CacheBacking<K, CacheEntry<V, E>> for SharedMemoryBacking<K, V, E, C> {
+    type Meta = NoMeta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut CacheEntry<V, E>>, BackingError> {
+        if self.local.contains_key(key) {
+            return Ok(self.local.get_mut(key));
+        }
+        self.refresh(key)?;
+        Ok(self.read.as_mut().map(|(_, _, entry)| entry))
+    }
+
+    fn write_back(&mut self, key: &K) -> Result<(), BackingError> {
+        match self.read.take() {
+            Some((read_key, _, CacheEntry::Loaded(value))) if read_key == *key => {
+                self.store(read_key, value)?;
+            }
+            read => self.read = read,
+        }
+        Ok(())
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&CacheEntry<V, E>>, BackingError> {
+        if self.local.contains_key(key) {
+            return Ok(self.local.get(key));
+        }
+        self.refresh(key)?;
+        Ok(self.read.as_ref().map(|(_, _, entry)| entry))
+    }
+
+    fn set(&mut self, key: K, value: CacheEntry<V, E>, _meta: Option<Self::Meta>) -> Result<Option<CacheEntry<V, E>>, BackingError> {
+        let local = self.local.remove(&key);
+        let replaced = match value {
+            CacheEntry::Loaded(value) => self.store(key, value)?,
+            loading => {
+                // the value of the map stays visible to the other processes while it's reloaded
+                let key_bytes = self.encode_key(&key)?;
+                let replaced = self.find(&key_bytes)
+                    .map(|(_, replaced)| self.decode_value(&replaced))
+                    .transpose()?;
+                self.local.insert(key, loading);
+                replaced
+            }
+        };
+        Ok(local.or(replaced.map(CacheEntry::Loaded)))
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<CacheEntry<V, E>>, BackingError> {
+        let local = self.local.remove(key);
+        let removed = self.remove_shared(key)?;
+        Ok(local.or(removed.map(CacheEntry::Loaded)))
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        if self.local.contains_key(key) {
+            return Ok(true);
+        }
+        let key_bytes = self.encode_key(key)?;
+        Ok(self.find(&key_bytes).is_some())
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, CacheEntry<V, E>>) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        self.read = None;
+        let mut removed = Vec::new();
+        for index in 0..self.slots {
+            if let Some(Slot::Used { key, value, .. }) = self.read_slot(index) {
+                let decoded_key = ValueCodec::<K>::decode(&self.codec, &key)?;
+                let entry = CacheEntry::Loaded(self.decode_value(&value)?);
+                if predicate((&decoded_key, &entry)) && self.write_slot(index, REMOVED, &[], &[]) {
+                    removed.push((decoded_key, entry));
+                }
+            }
+        }
+        let local_keys = self.local.iter()
+            .filter(|(key, entry)| predicate((key, entry)))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in local_keys {
+            if let Some(entry) = self.local.remove(&key) {
+                removed.push((key, entry));
+            }
+        }
+        Ok(removed)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.read = None;
+        self.local.clear();
+        for index in 0..self.slots {
+            self.write_slot(index, EMPTY, &[], &[]);
+        }
+        Ok(())
+    }
+
+    fn entries(&mut self) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        let mut entries = Vec::new();
+        for index in 0..self.slots {
+            if let Some(Slot::Used { key, value, .. }) = self.read_slot(index) {
+                let key = ValueCodec::<K>::decode(&self.codec, &key)?;
+                if !self.local.contains_key(&key) {
+                    entries.push((key, CacheEntry::Loaded(self.decode_value(&value)?)));
+                }
+            }
+        }
+        // local entries take precedence, like in `get`
+        entries.extend(self.local.iter().map(|(key, entry)| (key.clone(), entry.clone())));
+        Ok(entries)
+    }
+}
+
+/// FNV-1a, which hashes keys the same in every process and build
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::path::Path;
+use ::sled::{Db, Tree};
+use super::{BackingError, BackingPredicate, CacheBacking, Maintenance, NoMeta};
+use crate::cache_api::CacheEntry;
+use crate::codec::ValueCodec;
+use crate::stats::{BackingStats, MaintenanceReport, Occupancy};
+
+const BACKING: &str = "SledBacking";
+
+/// A backing persisting its values in a sled tree on disk, so they survive restarts of the
+/// process
+///
+/// Keys and values are stored encoded with the `ValueCodec`, a cache opened on the tree of a
+/// previous run serves its values without loading them. Several caches can share a database
+/// with a tree each, see `SledBacking::with_tree`.
+///
+/// Writes are flushed to disk by sled in the background, and when the cache runs its
+/// `Maintenance`, e.g. with `CacheOptions::maintenance`. Loading entries stay in memory, and
+/// values mutated by `LoadingCache::update_mut` are written back to the tree by the same
+/// operation. sled locks its database, so other processes can't open it at the same time.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cache_loader_async::backing::SledBacking;
+/// use cache_loader_async::cache_api::LoadingCache;
+/// use cache_loader_async::codec::JsonCodec;
+///
+/// async fn example() -> sled::Result<()> {
+///     let backing = SledBacking::open("/var/cache/users", JsonCodec)?;
+///     let cache: LoadingCache<u64, String, u8, _> = LoadingCache::with_backing(backing, |id: u64| async move {
+///         Ok(format!("user-{}", id))
+///     });
+///     // a cache opened on the same path after a restart returns the value without loading it
+///     cache.get(1).await.unwrap();
+///     Ok(())
+/// }
+/// ```
+pub struct SledBacking<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+> {
+    db: Db,
+    tree: Tree,
+    codec: C,
+    // loading entries, which aren't persisted
+    local: HashMap<K, CacheEntry<V, E>>,
+    // the last value read from the tree, returned as reference
+    read: Option<(K, CacheEntry<V, E>)>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+> SledBacking<K, V, E, C> {
+    /// Opens the database at `path`, creating it if it doesn't exist, and stores the entries in
+    /// its default tree
+    pub fn open<P: AsRef<Path>>(path: P, codec: C) -> ::sled::Result<Self> {
+        let db = ::sled::open(path)?;
+        let tree = Tree::clone(&db);
+        Ok(Self::new(db, tree, codec))
+    }
+
+    /// Stores the entries in the tree `name` of an opened database, so several caches can share
+    /// it
+    pub fn with_tree(db: Db, name: &str, codec: C) -> ::sled::Result<Self> {
+        let tree = db.open_tree(name)?;
+        Ok(Self::new(db, tree, codec))
+    }
+
+    fn new(db: Db, tree: Tree, codec: C) -> Self {
+        Self {
+            db,
+            tree,
+            codec,
+            local: HashMap::new(),
+            read: None,
+        }
+    }
+
+    /// Returns the tree storing the entries
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    fn encode_key(&self, key: &K) -> Result<Vec<u8>, BackingError> {
+        Ok(ValueCodec::<K>::encode(&self.codec, key)?)
+    }
+
+    fn decode_value(&self, bytes: Option<::sled::IVec>) -> Result<Option<V>, BackingError> {
+        Ok(bytes.map(|bytes| ValueCodec::<V>::decode(&self.codec, &bytes)).transpose()?)
+    }
+
+    /// Reads the value of the key from the tree into `read`
+    fn refresh(&mut self, key: &K) -> Result<(), BackingError> {
+        let bytes = self.tree.get(self.encode_key(key)?).map_err(sled_error)?;
+        self.read = self.decode_value(bytes)?
+            .map(|value| (key.clone(), CacheEntry::Loaded(value)));
+        Ok(())
+    }
+
+    fn store(&mut self, key: &K, value: &V) -> Result<Option<V>, BackingError> {
+        let value_bytes = ValueCodec::<V>::encode(&self.codec, value)?;
+        let replaced = self.tree.insert(self.encode_key(key)?, value_bytes).map_err(sled_error)?;
+        self.decode_value(replaced)
+    }
+
+    fn remove_stored(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        if matches!(&self.read, Some((read_key, _)) if read_key == key) {
+            self.read = None;
+        }
+        let removed = self.tree.remove(self.encode_key(key)?).map_err(sled_error)?;
+        self.decode_value(removed)
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+> CacheBacking<K, CacheEntry<V, E>> for SledBacking<K, V, E, C> {
+    type Meta = NoMeta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut CacheEntry<V, E>>, BackingError> {
+        if self.local.contains_key(key) {
+            return Ok(self.local.get_mut(key));
+        }
+        self.refresh(key)?;
+        Ok(self.read.as_mut().map(|(_, entry)| entry))
+    }
+
+    fn write_back(&mut self, key: &K) -> Result<(), BackingError> {
+        match self.read.take() {
+            Some((read_key, CacheEntry::Loaded(value))) if read_key == *key => {
+                self.store(&read_key, &value)?;
+            }
+            read => self.read = read,
+        }
+        Ok(())
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&CacheEntry<V, E>>, BackingError> {
+        if self.local.contains_key(key) {
+            return Ok(self.local.get(key));
+        }
+        self.refresh(key)?;
+        Ok(self.read.as_ref().map(|(_, entry)| entry))
+    }
+
+    fn set(&mut self, key: K, value: CacheEntry<V, E>, _meta: Option<Self::Meta>) -> Result<Option<CacheEntry<V, E>>, BackingError> {
+        if matches!(&self.read, Some((read_key, _)) if *read_key == key) {
+            self.read = None;
+        }
+        let local = self.local.remove(&key);
+        let replaced = match value {
+            CacheEntry::Loaded(value) => self.store(&key, &value)?,
+            loading => {
+                // the stored value is replaced once the reload completes, it stays persisted if
+                // the process stops meanwhile
+                let bytes = self.tree.get(self.encode_key(&key)?).map_err(sled_error)?;
+                let replaced = self.decode_value(bytes)?;
+                self.local.insert(key, loading);
+                replaced
+            }
+        };
+        Ok(local.or(replaced.map(CacheEntry::Loaded)))
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<CacheEntry<V, E>>, BackingError> {
+        let local = self.local.remove(key);
+        let removed = self.remove_stored(key)?;
+        Ok(local.or(removed.map(CacheEntry::Loaded)))
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        if self.local.contains_key(key) {
+            return Ok(true);
+        }
+        self.tree.contains_key(self.encode_key(key)?).map_err(sled_error)
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, CacheEntry<V, E>>) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        self.read = None;
+        let mut removed = Vec::new();
+        for item in self.tree.iter() {
+            let (key_bytes, value) = item.map_err(sled_error)?;
+            let key = ValueCodec::<K>::decode(&self.codec, &key_bytes)?;
+            let entry = CacheEntry::Loaded(ValueCodec::<V>::decode(&self.codec, &value)?);
+            if predicate((&key, &entry)) && self.tree.remove(&key_bytes).map_err(sled_error)?.is_some() {
+                removed.push((key, entry));
+            }
+        }
+        let local_keys = self.local.iter()
+            .filter(|(key, entry)| predicate((key, entry)))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in local_keys {
+            if let Some(entry) = self.local.remove(&key) {
+                removed.push((key, entry));
+            }
+        }
+        Ok(removed)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.read = None;
+        self.local.clear();
+        self.tree.clear().map_err(sled_error)
+    }
+
+    fn entries(&mut self) -> Result<Vec<(K, CacheEntry<V, E>)>, BackingError> {
+        let mut entries = Vec::new();
+        for item in self.tree.iter() {
+            let (key, value) = item.map_err(sled_error)?;
+            let key = ValueCodec::<K>::decode(&self.codec, &key)?;
+            if !self.local.contains_key(&key) {
+                entries.push((key, CacheEntry::Loaded(ValueCodec::<V>::decode(&self.codec, &value)?)));
+            }
+        }
+        // local entries take precedence, like in `get`
+        entries.extend(self.local.iter().map(|(key, entry)| (key.clone(), entry.clone())));
+        Ok(entries)
+    }
+
+    fn stats(&self) -> BackingStats {
+        let entries = self.tree.len() + self.local.len();
+        BackingStats {
+            occupancy: Some(Occupancy {
+                entries,
+                weight: entries,
+            }),
+            ..BackingStats::default()
+        }
+    }
+
+    fn maintenance(&mut self) -> Option<&mut dyn Maintenance> {
+        Some(self)
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+> Maintenance for SledBacking<K, V, E, C> {
+    /// Writes the pending changes of the tree to disk, sled reclaims the space of removed
+    /// entries on its own
+    fn compact(&mut self) -> Result<(), BackingError> {
+        self.tree.flush().map_err(sled_error)?;
+        Ok(())
+    }
+
+    fn vacuum(&mut self) -> Result<(), BackingError> {
+        Ok(())
+    }
+
+    fn report(&self) -> MaintenanceReport {
+        MaintenanceReport {
+            disk_size: self.db.size_on_disk().unwrap_or_default(),
+            reclaimable: 0,
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Send,
+    E: Clone + Send + Debug,
+    C: ValueCodec<K> + ValueCodec<V>,
+> Drop for SledBacking<K, V, E, C> {
+    fn drop(&mut self) {
+        // the pending writes of the tree reach the disk, errors have no caller left
+        let _ = self.tree.flush();
+    }
+}
+
+fn sled_error(error: ::sled::Error) -> BackingError {
+    match error {
+        ::sled::Error::Io(error) => BackingError::io(BACKING, error),
+        error => BackingError::other(BACKING, error),
+    }
+}
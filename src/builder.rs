@@ -0,0 +1,362 @@
+//! A fluent builder for `LoadingCache`, see `LoadingCache::builder`
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::time::Duration;
+use futures::Future;
+use crate::backing::{CacheBacking, HashMapBacking, RemovalCause};
+#[cfg(feature = "lru-cache")]
+use crate::backing::LruCacheBacking;
+#[cfg(feature = "ttl-cache")]
+use crate::backing::TtlCacheBacking;
+use crate::cache_api::{CacheEntry, DataWithMeta, LoadingCache};
+use crate::options::CacheOptions;
+#[cfg(feature = "ttl-cache")]
+use tokio::time::Instant;
+
+/// Creates the backing a `CacheBuilder` builds the cache with
+///
+/// The builder collects the options of the backing in a spec, the backing is only created by
+/// `CacheBuilder::build`, once the value type it stores is known.
+pub trait BackingSpec<K, V>
+    where K: Eq + Hash + Sized + Clone + Send,
+          V: Sized + Clone + Send {
+    type Backing: CacheBacking<K, V>;
+
+    fn into_backing(self) -> Self::Backing;
+}
+
+/// Builds a `HashMapBacking`, the spec of a new `CacheBuilder`
+#[derive(Debug, Copy, Clone, Default)]
+pub struct HashMapSpec;
+
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send
+> BackingSpec<K, V> for HashMapSpec {
+    type Backing = HashMapBacking<K, V>;
+
+    fn into_backing(self) -> HashMapBacking<K, V> {
+        HashMapBacking::new()
+    }
+}
+
+/// Builds an `LruCacheBacking` holding up to `max_capacity` entries, see
+/// `CacheBuilder::max_capacity`
+#[cfg(feature = "lru-cache")]
+#[derive(Debug, Copy, Clone)]
+pub struct CapacitySpec {
+    max_capacity: usize,
+}
+
+#[cfg(feature = "lru-cache")]
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send
+> BackingSpec<K, V> for CapacitySpec {
+    type Backing = LruCacheBacking<K, V>;
+
+    fn into_backing(self) -> LruCacheBacking<K, V> {
+        LruCacheBacking::new(self.max_capacity)
+    }
+}
+
+/// Builds a `TtlCacheBacking` around the backing of the nested spec, see
+/// `CacheBuilder::time_to_live` and `CacheBuilder::time_to_idle`
+#[cfg(feature = "ttl-cache")]
+#[derive(Debug, Copy, Clone)]
+pub struct ExpirySpec<S> {
+    nested: S,
+    time_to_live: Option<Duration>,
+    time_to_idle: Option<Duration>,
+}
+
+#[cfg(feature = "ttl-cache")]
+impl<
+    K: Eq + Hash + Sized + Clone + Send + 'static,
+    V: Sized + Clone + Send + 'static,
+    S: BackingSpec<K, (V, Instant)>,
+> BackingSpec<K, V> for ExpirySpec<S> {
+    type Backing = TtlCacheBacking<K, V, S::Backing>;
+
+    fn into_backing(self) -> TtlCacheBacking<K, V, S::Backing> {
+        // the spec is only created with one of them
+        let ttl = self.time_to_live.or(self.time_to_idle).unwrap_or_default();
+        let backing = TtlCacheBacking::with_backing(ttl, self.nested.into_backing());
+        match self.time_to_idle {
+            Some(time_to_idle) => backing.access_ttl(time_to_idle),
+            None => backing,
+        }
+    }
+}
+
+/// The specs whose backing can be wrapped into a `TtlCacheBacking`, see `ExpirySpec`
+#[cfg(feature = "ttl-cache")]
+pub trait Expirable: Sized {
+    type Nested;
+
+    fn into_expiry(self) -> ExpirySpec<Self::Nested>;
+}
+
+#[cfg(feature = "ttl-cache")]
+impl Expirable for HashMapSpec {
+    type Nested = HashMapSpec;
+
+    fn into_expiry(self) -> ExpirySpec<HashMapSpec> {
+        ExpirySpec { nested: self, time_to_live: None, time_to_idle: None }
+    }
+}
+
+#[cfg(all(feature = "ttl-cache", feature = "lru-cache"))]
+impl Expirable for CapacitySpec {
+    type Nested = CapacitySpec;
+
+    fn into_expiry(self) -> ExpirySpec<CapacitySpec> {
+        ExpirySpec { nested: self, time_to_live: None, time_to_idle: None }
+    }
+}
+
+#[cfg(feature = "ttl-cache")]
+impl<S> Expirable for ExpirySpec<S> {
+    type Nested = S;
+
+    fn into_expiry(self) -> ExpirySpec<S> {
+        self
+    }
+}
+
+/// Builds the backing passed to `CacheBuilder::backing`
+#[derive(Debug, Clone)]
+pub struct CustomSpec<B>(B);
+
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+    B: CacheBacking<K, V>,
+> BackingSpec<K, V> for CustomSpec<B> {
+    type Backing = B;
+
+    fn into_backing(self) -> B {
+        self.0
+    }
+}
+
+/// A `CacheBuilder` without a loader function yet
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NoLoader;
+
+/// A loader function returning values, see `CacheBuilder::loader`
+pub struct Loader<T>(T);
+
+/// A loader function returning values with meta, see `CacheBuilder::meta_loader`
+pub struct MetaLoader<T>(T);
+
+/// Collects the backing, the loader function and the options of a `LoadingCache`, created by
+/// `LoadingCache::builder`
+///
+/// The backing is a `HashMapBacking` unless `max_capacity`, `time_to_live`, `time_to_idle` or
+/// `backing` choose another one. `build` is available once a loader function is set.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(all(feature = "lru-cache", feature = "ttl-cache"))]
+/// # mod example {
+/// use std::time::Duration;
+/// use cache_loader_async::cache_api::LoadingCache;
+/// async fn example() {
+///     let cache = LoadingCache::builder()
+///         .max_capacity(10_000)
+///         .time_to_live(Duration::from_secs(300))
+///         .channel_size(1024)
+///         .loader(move |key: String| {
+///             async move {
+///                 Ok::<_, u8>(key.to_lowercase())
+///             }
+///         })
+///         .build();
+///
+///     assert_eq!(cache.get("FOO".to_owned()).await.unwrap(), "foo");
+/// }
+/// # }
+/// ```
+pub struct CacheBuilder<K, V, E, S = HashMapSpec, L = NoLoader> {
+    spec: S,
+    loader: L,
+    options: CacheOptions,
+    phantom: Types<K, V, E>,
+}
+
+// the builder doesn't own any values of its types, so it's `Send` and `Sync` regardless of them
+type Types<K, V, E> = PhantomData<fn() -> (K, V, E)>;
+
+impl<K, V, E> CacheBuilder<K, V, E> {
+    pub fn new() -> CacheBuilder<K, V, E> {
+        CacheBuilder {
+            spec: HashMapSpec,
+            loader: NoLoader,
+            options: CacheOptions::new(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<K, V, E> Default for CacheBuilder<K, V, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, E, S, L> CacheBuilder<K, V, E, S, L> {
+    fn with_spec<T>(self, spec: T) -> CacheBuilder<K, V, E, T, L> {
+        CacheBuilder {
+            spec,
+            loader: self.loader,
+            options: self.options,
+            phantom: PhantomData,
+        }
+    }
+
+    fn with_loader<T>(self, loader: T) -> CacheBuilder<K, V, E, S, T> {
+        CacheBuilder {
+            spec: self.spec,
+            loader,
+            options: self.options,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Builds the cache with the given backing, replacing the one chosen by `max_capacity`,
+    /// `time_to_live` or `time_to_idle`
+    pub fn backing<B>(self, backing: B) -> CacheBuilder<K, V, E, CustomSpec<B>, L> {
+        self.with_spec(CustomSpec(backing))
+    }
+
+    /// Sets the loader function, see `LoadingCache::new`
+    pub fn loader<T, F>(self, loader: T) -> CacheBuilder<K, V, E, S, Loader<T>>
+        where F: Future<Output=Result<V, E>> + Sized + Send + 'static,
+              T: Fn(K) -> F + Send + 'static {
+        self.with_loader(Loader(loader))
+    }
+
+    /// Sets a loader function which returns the meta of the values along with them, see
+    /// `LoadingCache::with_meta_loader`
+    pub fn meta_loader<T>(self, loader: T) -> CacheBuilder<K, V, E, S, MetaLoader<T>> {
+        self.with_loader(MetaLoader(loader))
+    }
+
+    /// Sets how many operations the queue of the actor holds, see
+    /// `CacheOptions::channel_capacity`
+    pub fn channel_size(mut self, capacity: usize) -> CacheBuilder<K, V, E, S, L> {
+        self.options = self.options.channel_capacity(capacity);
+        self
+    }
+
+    /// Cancels loads which take longer than `timeout`, see `CacheOptions::load_timeout`
+    pub fn load_timeout(mut self, timeout: Duration) -> CacheBuilder<K, V, E, S, L> {
+        self.options = self.options.load_timeout(timeout);
+        self
+    }
+
+    /// Registers a listener which is called with every removed entry, see
+    /// `CacheOptions::removal_listener`
+    pub fn listener<R>(mut self, listener: R) -> CacheBuilder<K, V, E, S, L>
+        where K: 'static,
+              V: 'static,
+              R: Fn(K, V, RemovalCause) + Send + Sync + 'static {
+        self.options = self.options.removal_listener(listener);
+        self
+    }
+
+    /// Applies the other `CacheOptions`, e.g. `CacheOptions::name`
+    pub fn options<O>(mut self, configure: O) -> CacheBuilder<K, V, E, S, L>
+        where O: FnOnce(CacheOptions) -> CacheOptions {
+        self.options = configure(self.options);
+        self
+    }
+}
+
+#[cfg(feature = "lru-cache")]
+impl<K, V, E, L> CacheBuilder<K, V, E, HashMapSpec, L> {
+    /// Holds up to `max_capacity` entries in an `LruCacheBacking`, evicting the least recently
+    /// used ones
+    pub fn max_capacity(self, max_capacity: usize) -> CacheBuilder<K, V, E, CapacitySpec, L> {
+        self.with_spec(CapacitySpec { max_capacity })
+    }
+}
+
+#[cfg(all(feature = "lru-cache", feature = "ttl-cache"))]
+impl<K, V, E, L> CacheBuilder<K, V, E, ExpirySpec<HashMapSpec>, L> {
+    /// Holds up to `max_capacity` entries in an `LruCacheBacking` within the `TtlCacheBacking`,
+    /// evicting the least recently used ones
+    pub fn max_capacity(self, max_capacity: usize) -> CacheBuilder<K, V, E, ExpirySpec<CapacitySpec>, L> {
+        let spec = ExpirySpec {
+            nested: CapacitySpec { max_capacity },
+            time_to_live: self.spec.time_to_live,
+            time_to_idle: self.spec.time_to_idle,
+        };
+        self.with_spec(spec)
+    }
+}
+
+#[cfg(feature = "ttl-cache")]
+impl<K, V, E, S: Expirable, L> CacheBuilder<K, V, E, S, L> {
+    /// Expires entries `ttl` after they were written, in a `TtlCacheBacking`
+    pub fn time_to_live(self, ttl: Duration) -> CacheBuilder<K, V, E, ExpirySpec<S::Nested>, L> {
+        let mut spec = self.spec.into_expiry();
+        spec.time_to_live = Some(ttl);
+        CacheBuilder {
+            spec,
+            loader: self.loader,
+            options: self.options,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Expires entries which haven't been read for `tti`, in a `TtlCacheBacking`
+    ///
+    /// Entries expire `time_to_live` after they were written, or `tti` without one, and every
+    /// read extends their expiry to `tti` after the read, see `TtlCacheBacking::access_ttl`.
+    pub fn time_to_idle(self, tti: Duration) -> CacheBuilder<K, V, E, ExpirySpec<S::Nested>, L> {
+        let mut spec = self.spec.into_expiry();
+        spec.time_to_idle = Some(tti);
+        CacheBuilder {
+            spec,
+            loader: self.loader,
+            options: self.options,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    S: BackingSpec<K, CacheEntry<V, E>>,
+    T,
+> CacheBuilder<K, V, E, S, Loader<T>>
+    where S::Backing: Send + 'static {
+    /// Creates the cache, see `LoadingCache::with_options`
+    pub fn build<F>(self) -> LoadingCache<K, V, E, S::Backing>
+        where F: Future<Output=Result<V, E>> + Sized + Send + 'static,
+              T: Fn(K) -> F + Send + 'static {
+        LoadingCache::with_options(self.spec.into_backing(), self.loader.0, self.options)
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    S: BackingSpec<K, CacheEntry<V, E>>,
+    T,
+> CacheBuilder<K, V, E, S, MetaLoader<T>>
+    where S::Backing: Send + 'static {
+    /// Creates the cache, see `LoadingCache::with_meta_loader_and_options`
+    pub fn build<F>(self) -> LoadingCache<K, V, E, S::Backing>
+        where F: Future<Output=Result<DataWithMeta<K, V, E, S::Backing>, E>> + Sized + Send + 'static,
+              T: Fn(K) -> F + Send + 'static {
+        LoadingCache::with_meta_loader_and_options(self.spec.into_backing(), self.loader.0, self.options)
+    }
+}
@@ -1,10 +1,38 @@
-use tokio::task::JoinHandle;
+use tokio::sync::mpsc::error::TrySendError;
 use std::hash::Hash;
-use futures::Future;
+use futures::{Future, Stream, StreamExt, TryStreamExt};
+use futures::future::BoxFuture;
 use thiserror::Error;
-use crate::internal_cache::{CacheAction, InternalCacheStore, CacheMessage};
-use crate::backing::{BackingError, CacheBacking, HashMapBacking};
+use crate::internal_cache::{BatchLoader, InternalCacheStore, CacheMessage, CacheSender, ParkSlot};
+#[cfg(not(feature = "unstable"))]
+use crate::internal_cache::CacheAction;
+#[cfg(feature = "unstable")]
+pub use crate::internal_cache::CacheAction;
+use crate::mapped::MappedLoadingCache;
+use crate::read_only::ReadOnlyLoadingCache;
+use crate::fallback::FallbackLoadingCache;
+use crate::key_guard::KeyGuard;
+use crate::args::{self, ArgsConflict, ArgsLoader, ArgsLoadingCache, RunningArgs};
+use crate::write_window::WriteWindow;
+use crate::write_through::WriteThrough;
+use crate::write_behind::WriteBehind;
+use crate::write_ahead_log::WriteAheadLog;
+use crate::codec::ValueCodec;
+use crate::options::{Backpressure, CacheOptions, ExecutionMode, DEFAULT_CHANNEL_CAPACITY};
+use crate::builder::CacheBuilder;
+use crate::http_meta::{FromHttpMeta, Validated};
+use crate::backing::{BackingError, CacheBacking, ExportedEntry, HashMapBacking, Inconsistency};
+use crate::stats::{ActorMetrics, ActorStats, BackingStats, CacheMetrics, CacheStats, MaintenanceReport};
+use crate::response::{ResponsePool, ResponseReceiver};
+use crate::queue::{queue, QueueSender};
 use std::fmt::{Debug};
+use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::Notify;
+use tokio::task::{AbortHandle, JoinHandle};
 
 #[derive(Error, Debug)]
 pub enum CacheLoadingError<E: Debug> {
@@ -17,6 +45,23 @@ pub enum CacheLoadingError<E: Debug> {
     // todo better handling here? eventually return loadingerror if possible
     #[error("An error occurred when loading the entity from the loader function")]
     LoadingError(E),
+    /// The storer of `LoadingCache::with_storer` failed to write the value, which wasn't cached
+    #[error("An error occurred when writing the entity with the storer function")]
+    StoreError(E),
+    #[error("The request queue of the cache is full")]
+    Busy(),
+    #[error("The operation was cancelled by the caller")]
+    Cancelled(),
+    #[error("The loader function did not complete within {configured:?} (elapsed: {elapsed:?})")]
+    LoadTimeout {
+        elapsed: Duration,
+        configured: Duration,
+    },
+    /// A loader waited for a load which in turn waits for the loader, either loading its own key
+    /// or through a cycle of loaders depending on each other. Carries the hashes of the keys of the
+    /// cycle, starting with the key whose loader waited, hashed like the keys in the task names.
+    #[error("The loader of a key waited for a load depending on itself (cycle of {} keys)", .0.len())]
+    RecursiveLoad(Vec<u64>),
 }
 
 #[derive(Error, Debug)]
@@ -48,6 +93,13 @@ impl<E: Debug> CacheLoadingError<E> {
         }
     }
 
+    pub fn as_store_error(&self) -> Option<&E> {
+        match self {
+            CacheLoadingError::StoreError(error) => Some(error),
+            _ => None
+        }
+    }
+
     pub fn as_communication_error(&self) -> Option<&CacheCommunicationError> {
         match self {
             CacheLoadingError::CommunicationError(error) => Some(error),
@@ -61,36 +113,289 @@ impl<E: Debug> CacheLoadingError<E> {
             _ => None
         }
     }
+
+    pub fn as_backing_error(&self) -> Option<&BackingError> {
+        match self {
+            CacheLoadingError::BackingError(error) => Some(error),
+            _ => None
+        }
+    }
+
+    pub fn into_backing_error(self) -> Option<BackingError> {
+        match self {
+            CacheLoadingError::BackingError(error) => Some(error),
+            _ => None
+        }
+    }
+
+    /// Returns whether the load was aborted because the loader exceeded its configured timeout,
+    /// as opposed to the loader function failing by itself
+    pub fn is_load_timeout(&self) -> bool {
+        matches!(self, CacheLoadingError::LoadTimeout { .. })
+    }
+
+    /// Returns whether the caller stopped waiting for the operation, see `LoadingCache::get_until`
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, CacheLoadingError::Cancelled())
+    }
+
+    /// Returns whether a loader waited for a load depending on itself, see
+    /// `CacheLoadingError::RecursiveLoad`
+    pub fn is_recursive_load(&self) -> bool {
+        matches!(self, CacheLoadingError::RecursiveLoad(_))
+    }
+
+    /// Converts the loader or storer error of this error with the given function, leaving every
+    /// other variant untouched
+    pub fn map_loading_error<F: Debug, M: FnOnce(E) -> F>(self, mapper: M) -> CacheLoadingError<F> {
+        match self {
+            CacheLoadingError::BackingError(error) => CacheLoadingError::BackingError(error),
+            CacheLoadingError::CommunicationError(error) => CacheLoadingError::CommunicationError(error),
+            CacheLoadingError::NoData() => CacheLoadingError::NoData(),
+            CacheLoadingError::Busy() => CacheLoadingError::Busy(),
+            CacheLoadingError::Cancelled() => CacheLoadingError::Cancelled(),
+            CacheLoadingError::LoadingError(error) => CacheLoadingError::LoadingError(mapper(error)),
+            CacheLoadingError::StoreError(error) => CacheLoadingError::StoreError(mapper(error)),
+            CacheLoadingError::LoadTimeout { elapsed, configured } => CacheLoadingError::LoadTimeout { elapsed, configured },
+            CacheLoadingError::RecursiveLoad(keys) => CacheLoadingError::RecursiveLoad(keys),
+        }
+    }
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ResultMeta<V> {
     pub result: V,
     pub cached: bool,
 }
 
+/// A cached value with information about its entry, returned by
+/// `LoadingCache::get_if_present_with_meta`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryMeta<V> {
+    pub value: V,
+    /// How long ago the value was set or loaded, `None` unless `CacheOptions::track_entries` is
+    /// enabled
+    pub age: Option<Duration>,
+    /// How often the value was read from the cache, including this read, `None` unless
+    /// `CacheOptions::track_entries` is enabled
+    pub hits: Option<u64>,
+    /// How long the value remains before it expires, `None` for backings without expiry, see
+    /// `CacheBacking::time_to_live`
+    pub ttl_remaining: Option<Duration>,
+}
+
+/// What `LoadingCache::swap_with` does when the key is loading
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum SwapPolicy {
+    /// Waits for the load and replaces the loaded value, which is returned. If the load fails,
+    /// the value is set without a previous one.
+    #[default]
+    Defer,
+    /// Replaces the load right away, the callers waiting for it receive the swapped value and no
+    /// previous value is returned. The loader keeps running, but its result is discarded.
+    Replace,
+}
+
+/// What `LoadingCache::set` and `LoadingCache::update` do when the storer of
+/// `LoadingCache::with_storer` fails
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum StoreFailure {
+    /// The value isn't cached and the caller receives `CacheLoadingError::StoreError`
+    #[default]
+    Reject,
+    /// The value is cached regardless, the error is only reported as a `tracing` event with the
+    /// `tracing` feature enabled
+    Continue,
+}
+
+/// A value returned by `LoadingCache::get_allow_stale`, which might have expired already
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaybeStale<V> {
+    pub value: V,
+    /// How long the value has been expired, `None` if it is fresh
+    pub staleness: Option<Duration>,
+}
+
+impl<V> MaybeStale<V> {
+    pub fn is_stale(&self) -> bool {
+        self.staleness.is_some()
+    }
+}
+
+/// The state of a key returned by `LoadingCache::lookup`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Lookup<V> {
+    /// The value is cached
+    Hit(V),
+    /// The key is neither cached nor loading
+    Miss,
+    /// Another operation is loading the value, which isn't awaited
+    Loading,
+}
+
+impl<V> Lookup<V> {
+    /// Returns the cached value, `None` for missing and loading keys
+    pub fn value(self) -> Option<V> {
+        match self {
+            Lookup::Hit(value) => Some(value),
+            Lookup::Miss | Lookup::Loading => None,
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(self, Lookup::Loading)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum CacheEntry<V, E: Debug> {
     Loaded(V),
     Loading(tokio::sync::broadcast::Sender<Result<V, E>>),
 }
 
-#[derive(Debug)]
+/// A load which hasn't completed yet, carried by `CacheResult::Loading`
+///
+/// Loads usually run in their own task, which is awaited by this future. In
+/// `ExecutionMode::Inline`, the future drives the load itself. Operations waiting for a load
+/// started by another operation get a future which waits for its result.
+pub struct LoadFuture<V, E: Debug> {
+    future: BoxFuture<'static, Result<V, CacheLoadingError<E>>>,
+    // the task running the load, if it runs in its own task
+    task: Option<AbortHandle>,
+    // cancels the loader within its task, which unblocks the key before the task completes
+    cancel: Option<Arc<Notify>>,
+    finished: bool,
+}
+
+impl<V: Send + 'static, E: Debug + Send + 'static> LoadFuture<V, E> {
+    /// Creates a future which is driven by its caller
+    pub(crate) fn new<F>(future: F) -> Self
+        where F: Future<Output=Result<V, CacheLoadingError<E>>> + Send + 'static {
+        Self {
+            future: Box::pin(future),
+            task: None,
+            cancel: None,
+            finished: false,
+        }
+    }
+
+    /// Creates a future awaiting the task, which is aborted by `LoadFuture::abort` unless it
+    /// can be cancelled through `cancel`
+    pub(crate) fn task(handle: JoinHandle<Result<V, CacheLoadingError<E>>>, cancel: Option<Arc<Notify>>) -> Self {
+        let task = handle.abort_handle();
+        Self {
+            future: Box::pin(async move {
+                handle.await.unwrap_or_else(|err| {
+                    Err(CacheLoadingError::CommunicationError(CacheCommunicationError::FutureJoinError(err)))
+                })
+            }),
+            task: Some(task),
+            cancel,
+            finished: false,
+        }
+    }
+}
+
+impl<V, E: Debug> LoadFuture<V, E> {
+    /// Aborts the load running in its own task, the key is unblocked and every operation waiting
+    /// for the load fails
+    ///
+    /// This has no effect on futures waiting for a load started by another operation and on
+    /// loads of `ExecutionMode::Inline`, which are cancelled by dropping their future.
+    pub fn abort(&self) {
+        match (&self.cancel, &self.task) {
+            (Some(cancel), _) => cancel.notify_one(),
+            (None, Some(task)) => task.abort(),
+            (None, None) => {}
+        }
+    }
+
+    /// Returns whether the load completed, either because this future returned its result or
+    /// because the task running it finished
+    pub fn is_finished(&self) -> bool {
+        self.finished || self.task.as_ref().is_some_and(|task| task.is_finished())
+    }
+}
+
+impl<V, E: Debug> Future for LoadFuture<V, E> {
+    type Output = Result<V, CacheLoadingError<E>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = self.future.as_mut().poll(cx);
+        if result.is_ready() {
+            self.finished = true;
+        }
+        result
+    }
+}
+
 pub enum CacheResult<V, E: Debug> {
     Error(BackingError),
     Found(V),
-    Loading(JoinHandle<Result<V, CacheLoadingError<E>>>),
+    /// An expired value and how long it has been expired, returned by `CacheAction::GetAllowStale`
+    Stale(V, Duration),
+    Loading(LoadFuture<V, E>),
     None,
+    /// The results of a `CacheAction::Pipeline`, in the order of its actions
+    Pipeline(Vec<CacheResult<V, E>>),
+}
+
+impl<V: Debug, E: Debug> Debug for CacheResult<V, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheResult::Error(err) => f.debug_tuple("Error").field(err).finish(),
+            CacheResult::Found(value) => f.debug_tuple("Found").field(value).finish(),
+            CacheResult::Stale(value, staleness) => f.debug_tuple("Stale").field(value).field(staleness).finish(),
+            CacheResult::Loading(_) => f.write_str("Loading"),
+            CacheResult::None => f.write_str("None"),
+            CacheResult::Pipeline(results) => f.debug_tuple("Pipeline").field(results).finish(),
+        }
+    }
 }
 
-#[derive(Debug)]
 pub struct LoadingCache<
     K: Clone + Eq + Hash + Send,
     V: Clone + Sized + Send,
     E: Debug + Clone + Send,
     B: CacheBacking<K, CacheEntry<V, E>>
 > {
-    tx: tokio::sync::mpsc::Sender<CacheMessage<K, V, E, B>>,
+    tx: CacheSender<K, V, E, B>,
+    metrics: Arc<ActorMetrics>,
+    stats: Option<Arc<CacheMetrics>>,
+    responses: Arc<ResponsePool<CacheResult<V, E>>>,
+    name: Option<Arc<str>>,
+    writes: Option<Arc<WriteWindow<K, V, E>>>,
+    // the actor only holds a weak reference, so it ends once every handle is gone
+    park: Option<Arc<ParkSlot>>,
+    batch_loader: Option<BatchLoader<K, V, E>>,
+    write_through: Option<WriteThrough<K, V, E>>,
+    write_behind: Option<Arc<WriteBehind<K, V, E>>>,
+    backpressure: Backpressure,
+}
+
+impl<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> Debug for LoadingCache<K, V, E, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadingCache")
+            .field("tx", &self.tx)
+            .field("metrics", &self.metrics)
+            .field("stats", &self.stats)
+            .field("name", &self.name)
+            .field("park", &self.park)
+            .field("batch_loader", &self.batch_loader.is_some())
+            .field("write_through", &self.write_through.is_some())
+            .field("write_behind", &self.write_behind.is_some())
+            .field("backpressure", &self.backpressure)
+            .finish_non_exhaustive()
+    }
 }
 
 // Funnily enough we need to impl Clone ourselves, because it cannot derive Clone for B
@@ -104,7 +409,17 @@ impl<
 > Clone for LoadingCache<K, V, E, B> {
     fn clone(&self) -> Self {
         Self {
-            tx: self.tx.clone()
+            tx: self.tx.clone(),
+            metrics: self.metrics.clone(),
+            stats: self.stats.clone(),
+            responses: self.responses.clone(),
+            name: self.name.clone(),
+            writes: self.writes.clone(),
+            park: self.park.clone(),
+            batch_loader: self.batch_loader.clone(),
+            write_through: self.write_through.clone(),
+            write_behind: self.write_behind.clone(),
+            backpressure: self.backpressure,
         }
     }
 }
@@ -174,6 +489,24 @@ impl<
     }
 }
 
+/// An entry which can be set by `LoadingCache::extend_from_stream`, either `(K, V)` or
+/// `(K, V, Meta)`
+pub trait IntoEntry<K, V, M> {
+    fn into_entry(self) -> ExportedEntry<K, V, M>;
+}
+
+impl<K, V, M> IntoEntry<K, V, M> for (K, V) {
+    fn into_entry(self) -> ExportedEntry<K, V, M> {
+        (self.0, self.1, None)
+    }
+}
+
+impl<K, V, M> IntoEntry<K, V, M> for (K, V, M) {
+    fn into_entry(self) -> ExportedEntry<K, V, M> {
+        (self.0, self.1, Some(self.2))
+    }
+}
+
 impl<
     K: Eq + Hash + Clone + Send + 'static,
     V: Clone + Sized + Send + 'static,
@@ -220,6 +553,69 @@ impl<
               T: Fn(K) -> F + Send + 'static {
         LoadingCache::with_backing(HashMapBacking::new(), loader)
     }
+
+    /// Returns a `CacheBuilder` choosing the backing, the loader function and the options of a
+    /// cache step by step, instead of picking one of the constructors
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use cache_loader_async::backing::RemovalCause;
+    /// async fn example() {
+    ///     let cache = LoadingCache::builder()
+    ///         .channel_size(256)
+    ///         .listener(|key: String, _: usize, cause: RemovalCause| println!("{} removed: {:?}", key, cause))
+    ///         .options(|options| options.name("lengths"))
+    ///         .loader(move |key: String| {
+    ///             async move {
+    ///                 Ok::<_, u8>(key.len())
+    ///             }
+    ///         })
+    ///         .build();
+    ///
+    ///     assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+    /// }
+    /// ```
+    pub fn builder() -> CacheBuilder<K, V, E> {
+        CacheBuilder::new()
+    }
+
+    /// Creates a new instance of a LoadingCache with the default `HashMapBacking`, which
+    /// already contains the given entries
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - The entries the cache should contain
+    /// * `loader` - A function which returns a Future<Output=Result<V, E>>
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let cache = LoadingCache::with_entries(
+    ///         vec![("foo".to_owned(), 32), ("bar".to_owned(), 64)],
+    ///         move |key: String| {
+    ///             async move {
+    ///                 key.parse::<u32>().map_err(|_| 1)
+    ///             }
+    ///         }
+    ///     );
+    ///
+    ///     assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), Some(32));
+    ///     assert_eq!(cache.get("16".to_owned()).await.unwrap(), 16);
+    /// }
+    /// ```
+    pub fn with_entries<I, T, F>(entries: I, loader: T) -> LoadingCache<K, V, E, HashMapBacking<K, CacheEntry<V, E>>>
+        where I: IntoIterator<Item=(K, V)>,
+              F: Future<Output=Result<V, E>> + Sized + Send + 'static,
+              T: Fn(K) -> F + Send + 'static {
+        let backing = entries.into_iter()
+            .map(|(key, value)| (key, CacheEntry::Loaded(value)))
+            .collect();
+        LoadingCache::with_backing(backing, loader)
+    }
 }
 
 
@@ -273,13 +669,50 @@ impl<
     pub fn with_backing<T, F>(backing: B, loader: T) -> LoadingCache<K, V, E, B>
         where F: Future<Output=Result<V, E>> + Sized + Send + 'static,
               T: Fn(K) -> F + Send + 'static {
-        let loader = loader;
-        LoadingCache::with_meta_loader(backing, move |key| {
+        LoadingCache::with_options(backing, loader, CacheOptions::default())
+    }
+
+    /// Creates a new instance of a LoadingCache with a custom `CacheBacking` and `CacheOptions`
+    ///
+    /// # Arguments
+    ///
+    /// * `backing` - The custom backing which the cache should use
+    /// * `loader` - A function which returns a Future<Output=Result<V, E>>
+    /// * `options` - The options which should be applied to the cache
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use cache_loader_async::backing::HashMapBacking;
+    /// use cache_loader_async::options::CacheOptions;
+    /// use std::time::Duration;
+    /// async fn example() {
+    ///     let cache = LoadingCache::with_options(
+    ///         HashMapBacking::new(),
+    ///         move |key: String| {
+    ///             async move {
+    ///                 key.parse::<u32>().map_err(|_| 1)
+    ///             }
+    ///         },
+    ///         CacheOptions::new()
+    ///             .loading_watchdog(Duration::from_secs(10), Some(Duration::from_secs(60))),
+    ///     );
+    ///
+    ///     let result = cache.get("32".to_owned()).await.unwrap();
+    ///
+    ///     assert_eq!(result, 32);
+    /// }
+    /// ```
+    pub fn with_options<T, F>(backing: B, loader: T, options: CacheOptions) -> LoadingCache<K, V, E, B>
+        where F: Future<Output=Result<V, E>> + Sized + Send + 'static,
+              T: Fn(K) -> F + Send + 'static {
+        LoadingCache::with_meta_loader_and_options(backing, move |key| {
             let future = loader(key);
             async move {
                 future.await.with_meta(None)
             }
-        })
+        }, options)
     }
 
     /// Creates a new instance of a LoadingCache with a custom `CacheBacking` and an optional
@@ -328,14 +761,193 @@ impl<
     pub fn with_meta_loader<T, F>(backing: B, loader: T) -> LoadingCache<K, V, E, B>
         where F: Future<Output=Result<DataWithMeta<K, V, E, B>, E>> + Sized + Send + 'static,
               T: Fn(K) -> F + Send + 'static {
-        let (tx, rx) = tokio::sync::mpsc::channel(128);
-        let store = InternalCacheStore::new(backing, tx.clone(), loader);
-        store.run(rx); // we're discarding the handle, we never do unsafe stuff, so it can't error, right?
+        LoadingCache::with_meta_loader_and_options(backing, loader, CacheOptions::default())
+    }
+
+    /// Creates a new instance of a LoadingCache with a conditional loader, following HTTP caching
+    /// semantics.
+    ///
+    /// The loader receives the previously loaded value of the key together with its `HttpMeta`
+    /// validators, if there is one, and may answer with `Validated::not_modified` to keep the
+    /// previous value instead of fully reloading it. The `HttpMeta` is converted into the meta
+    /// of the backing with `FromHttpMeta`, e.g. the `max_age` becomes the ttl of a
+    /// `TtlCacheBacking`.
+    ///
+    /// The previous values and validators are kept for every key which has been loaded once,
+    /// independent of the backing, so the cache should only be used with a bounded set of keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `backing` - The custom backing which the cache should use
+    /// * `loader` - A function which returns a Future<Output=Result<Validated<V>, E>>
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use cache_loader_async::backing::HashMapBacking;
+    /// use cache_loader_async::http_meta::{HttpMeta, Validated};
+    /// async fn example() {
+    ///     let cache = LoadingCache::with_conditional_loader(
+    ///         HashMapBacking::new(),
+    ///         move |key: String, previous: Option<Validated<String>>| {
+    ///             async move {
+    ///                 match previous {
+    ///                     // the origin answered with 304 Not Modified
+    ///                     Some(previous) if previous.meta.etag.as_deref() == Some("v1") =>
+    ///                         Ok(previous.not_modified(HttpMeta::new())),
+    ///                     _ => Ok::<_, u8>(Validated::modified(key, HttpMeta::new().with_etag("v1"))),
+    ///                 }
+    ///             }
+    ///         }
+    ///     );
+    ///
+    ///     let result = cache.get("foo".to_owned()).await.unwrap();
+    ///
+    ///     assert_eq!(result, "foo");
+    /// }
+    /// ```
+    pub fn with_conditional_loader<T, F>(backing: B, loader: T) -> LoadingCache<K, V, E, B>
+        where F: Future<Output=Result<Validated<V>, E>> + Sized + Send + 'static,
+              T: Fn(K, Option<Validated<V>>) -> F + Send + 'static,
+              B::Meta: FromHttpMeta {
+        let validated: Arc<Mutex<HashMap<K, Validated<V>>>> = Default::default();
+        LoadingCache::with_meta_loader(backing, move |key: K| {
+            let previous = validated.lock().unwrap().get(&key).cloned();
+            let future = loader(key.clone(), previous);
+            let validated = validated.clone();
+            async move {
+                let result = future.await?;
+                let meta = B::Meta::from_http_meta(&result.meta);
+                let value = result.value.clone();
+                validated.lock().unwrap().insert(key, result);
+                Ok(DataWithMeta::new(value, meta))
+            }
+        })
+    }
+
+    /// Creates a new instance of a LoadingCache with a custom `CacheBacking` and a loader which
+    /// takes per-call arguments besides the key, loaded by `ArgsLoadingCache::get_with_args`
+    ///
+    /// Loads are deduplicated by key, so concurrent calls with different arguments can't all pass
+    /// them to the loader, `conflict` decides what these calls do. The `get` of the underlying
+    /// `ArgsLoadingCache::cache` loads with `A::default()`, a call with other arguments treats
+    /// such a load like one started with differing arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `backing` - The custom backing which the cache should use
+    /// * `loader` - A function which returns a Future<Output=Result<V, E>> for a key and arguments
+    /// * `conflict` - What a call does whose arguments differ from the ones of the running load
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use cache_loader_async::backing::HashMapBacking;
+    /// use cache_loader_async::args::ArgsConflict;
+    /// async fn example() {
+    ///     let cache = LoadingCache::with_args_loader(
+    ///         HashMapBacking::new(),
+    ///         move |key: String, locale: String| {
+    ///             async move {
+    ///                 Ok::<_, u8>(format!("{}.{}", key, locale))
+    ///             }
+    ///         },
+    ///         ArgsConflict::Join,
+    ///     );
+    ///
+    ///     let result = cache.get_with_args("greeting".to_owned(), "de".to_owned()).await.unwrap();
+    ///
+    ///     assert_eq!(result, "greeting.de");
+    /// }
+    /// ```
+    pub fn with_args_loader<A, T, F>(backing: B, loader: T, conflict: ArgsConflict) -> ArgsLoadingCache<K, V, E, A, B>
+        where F: Future<Output=Result<V, E>> + Sized + Send + 'static,
+              T: Fn(K, A) -> F + Send + Sync + 'static,
+              A: Default + Clone + PartialEq + Send + 'static {
+        let loader: ArgsLoader<K, V, E, A> = Arc::new(move |key, args| Box::pin(loader(key, args)));
+        let running = RunningArgs::<K, A>::default();
+        let cache = LoadingCache::with_backing(backing, args::default_loader(loader.clone(), running.clone()));
+        ArgsLoadingCache::new(cache, loader, conflict, running)
+    }
+
+    /// Creates a new instance of a LoadingCache with a custom `CacheBacking`, a `Meta` loader and
+    /// `CacheOptions`
+    ///
+    /// # Arguments
+    ///
+    /// * `backing` - The custom backing which the cache should use
+    /// * `loader` - A function which returns a Future<Output=Result<MetaWithData<K, V, E, B>, E>>
+    /// * `options` - The options which should be applied to the cache
+    pub fn with_meta_loader_and_options<T, F>(backing: B, loader: T, options: CacheOptions) -> LoadingCache<K, V, E, B>
+        where F: Future<Output=Result<DataWithMeta<K, V, E, B>, E>> + Sized + Send + 'static,
+              T: Fn(K) -> F + Send + 'static {
+        let name = options.name.as_deref().map(Arc::from);
+        let backpressure = options.backpressure;
+        let writes = options.write_coalescing.map(|window| Arc::new(WriteWindow::new(window)));
+        if options.execution_mode == ExecutionMode::Inline {
+            let (store, stats) = InternalCacheStore::inline(backing, loader, options);
+            return LoadingCache {
+                tx: CacheSender::Inline(store),
+                metrics: Default::default(),
+                stats,
+                // the callers execute their operations themselves, there is no queue to size it by
+                responses: Arc::new(ResponsePool::new(DEFAULT_CHANNEL_CAPACITY)),
+                name,
+                writes,
+                park: None,
+                batch_loader: None,
+                write_through: None,
+                write_behind: None,
+                backpressure,
+            };
+        }
+        let capacity = match backpressure {
+            Backpressure::Unbounded => None,
+            _ => Some(options.channel_capacity.unwrap_or(DEFAULT_CHANNEL_CAPACITY)),
+        };
+        let (tx, rx) = queue(capacity);
+        let (completion_tx, completion_rx) = queue(capacity);
+        let park = options.idle_park.map(|_| Arc::new(ParkSlot::default()));
+        let sender = |tx: QueueSender<_>| match &park {
+            Some(park) => CacheSender::Parking(tx, Arc::downgrade(park)),
+            None => CacheSender::Channel(tx),
+        };
+        let store = InternalCacheStore::new(backing, sender(tx.clone()), sender(completion_tx), loader, options);
+        let metrics = store.metrics();
+        let stats = store.stats();
+        store.run(rx, completion_rx); // we're discarding the handle, we never do unsafe stuff, so it can't error, right?
         LoadingCache {
-            tx
+            tx: sender(tx),
+            metrics,
+            stats,
+            // an unbounded queue would keep the slots of a burst of operations forever
+            responses: Arc::new(ResponsePool::new(capacity.unwrap_or(DEFAULT_CHANNEL_CAPACITY))),
+            name,
+            writes,
+            park,
+            batch_loader: None,
+            write_through: None,
+            write_behind: None,
+            backpressure,
         }
     }
 
+    /// Returns the name of the cache, see `CacheOptions::name`
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns whether the actor of the cache is parked, see `CacheOptions::idle_park`
+    pub fn is_parked(&self) -> bool {
+        self.park.as_ref().is_some_and(|park| park.is_parked())
+    }
+
+    pub(crate) fn write_window(&self) -> Option<&WriteWindow<K, V, E>> {
+        self.writes.as_deref()
+    }
+
     /// Retrieves or loads the value for specified key from either cache or loader function
     ///
     /// # Arguments
@@ -353,8 +965,12 @@ impl<
             .map(|meta| meta.result)
     }
 
-    /// Retrieves or loads the value for specified key from either cache or loader function with
-    /// meta information, i.e. if the key was loaded from cache or from the loader function
+    /// Retrieves or loads the value for specified key like `get`, but doesn't mark a cached value
+    /// as recently used
+    ///
+    /// Background jobs touching many keys, e.g. consistency checkers, can use this to keep the
+    /// working set of an `LruCacheBacking` in place. Loaded values are still inserted as the most
+    /// recently used entry.
     ///
     /// # Arguments
     ///
@@ -363,42 +979,406 @@ impl<
     /// # Return Value
     ///
     /// Returns a Result with:
-    /// Ok - Value of type ResultMeta<V>
+    /// Ok - Value of type V
     /// Err - Error of type CacheLoadingError
-    pub async fn get_with_meta(&self, key: K) -> Result<ResultMeta<V>, CacheLoadingError<E>> {
-        self.send_cache_action(CacheAction::Get(key)).await
+    pub async fn get_no_promote(&self, key: K) -> Result<V, CacheLoadingError<E>> {
+        self.send_cache_action(CacheAction::GetNoPromote(key)).await
             .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
+            .map(|meta| meta.result)
     }
 
-    /// Sets the value for specified key and bypasses eventual currently ongoing loads
-    /// If a key has been set programmatically, eventual concurrent loads will not change
-    /// the value of the key.
+    /// Retrieves or loads the value for specified key like `get`, but stops waiting once the
+    /// `cancelled` future completes
+    ///
+    /// This allows abandoning a request whose caller went away, e.g. an HTTP client which
+    /// disconnected, or which exceeded a deadline. A load which was started by the request keeps
+    /// running, so other callers waiting for the key still receive its value.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key which should be set
-    /// * `value` - The value which should be set
-    /// * `meta` - The meta which should be attached to the key
+    /// * `key` - The key which should be loaded
+    /// * `cancelled` - A future completing when the caller stops waiting, e.g.
+    ///   `CancellationToken::cancelled` of tokio-util or `tokio::time::sleep_until`
     ///
     /// # Return Value
     ///
     /// Returns a Result with:
-    /// Ok - Previous value of type V wrapped in an Option depending whether there was a previous
-    ///      value
-    /// Err - Error of type CacheLoadingError
-    pub async fn set_with_meta(&self, key: K, value: V, meta: Option<B::Meta>) -> Result<Option<V>, CacheLoadingError<E>> {
-        self.send_cache_action(CacheAction::Set(key, value, meta)).await
-            .map(|opt_meta| opt_meta.map(|meta| meta.result))
+    /// Ok - Value of type V
+    /// Err - Error of type CacheLoadingError, `CacheLoadingError::Cancelled` if `cancelled`
+    ///       completed first
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use std::time::Duration;
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     let deadline = tokio::time::Instant::now() + Duration::from_millis(100);
+    ///     match cache.get_until("foo".to_owned(), tokio::time::sleep_until(deadline)).await {
+    ///         Ok(value) => assert_eq!(value, 3),
+    ///         Err(error) => assert!(error.is_cancelled()),
+    ///     }
+    /// }
+    /// ```
+    pub async fn get_until<C>(&self, key: K, cancelled: C) -> Result<V, CacheLoadingError<E>>
+        where C: Future<Output=()> {
+        Self::until(self.get(key), cancelled).await
     }
 
-    /// Sets the value for specified key and bypasses eventual currently ongoing loads
-    /// If a key has been set programmatically, eventual concurrent loads will not change
-    /// the value of the key.
+    /// Retrieves or loads the value for specified key from either cache or loader function with
+    /// meta information, i.e. if the key was loaded from cache or from the loader function
     ///
     /// # Arguments
     ///
-    /// * `key` - The key which should be set
-    /// * `value` - The value which should be set
+    /// * `key` - The key which should be loaded
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type ResultMeta<V>
+    /// Err - Error of type CacheLoadingError
+    pub async fn get_with_meta(&self, key: K) -> Result<ResultMeta<V>, CacheLoadingError<E>> {
+        self.send_cache_action(CacheAction::Get(key)).await
+            .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
+    }
+
+    /// Retrieves or loads the value for specified key like `get`, a value loaded by this call
+    /// expires after `ttl` instead of the TTL the loader returned or the default TTL of the backing
+    ///
+    /// A cached value and a value loaded by a running load of another caller keep their TTL.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be loaded
+    /// * `ttl` - How long a loaded value should be kept
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V
+    /// Err - Error of type CacheLoadingError
+    pub async fn get_with_ttl(&self, key: K, ttl: Duration) -> Result<V, CacheLoadingError<E>>
+        where B::Meta: From<Duration> {
+        self.send_cache_action(CacheAction::GetWithLoadMeta(key, B::Meta::from(ttl))).await
+            .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
+            .map(|meta| meta.result)
+    }
+
+    /// Sets a batch loader, which loads the keys missing in a `get_all` call at once instead of
+    /// calling the loader function for each of them
+    ///
+    /// The batch loader receives the keys which are neither cached nor loading and returns the
+    /// values it found. Keys it doesn't return aren't cached and are left out of the result of
+    /// `get_all`, other callers waiting for such a key receive a `TokioBroadcastRecvError`. If the
+    /// batch loader fails, the loads of all its keys fail with its error. Only this handle and
+    /// the handles cloned from it afterwards use the batch loader.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_loader` - A function which returns a Future<Output=Result<HashMap<K, V>, E>>
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use std::collections::HashMap;
+    /// async fn example() {
+    ///     let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::new(move |key: u64| {
+    ///         async move { Ok(key * 2) }
+    ///     }).with_batch_loader(move |keys: Vec<u64>| {
+    ///         async move {
+    ///             // e.g. a single `SELECT ... WHERE id IN (...)`
+    ///             Ok(keys.into_iter().map(|key| (key, key * 2)).collect::<HashMap<_, _>>())
+    ///         }
+    ///     });
+    ///
+    ///     let values = cache.get_all(vec![1, 2, 3]).await.unwrap();
+    ///     assert_eq!(values[&3], 6);
+    /// }
+    /// ```
+    pub fn with_batch_loader<T, F>(mut self, batch_loader: T) -> LoadingCache<K, V, E, B>
+        where F: Future<Output=Result<HashMap<K, V>, E>> + Send + 'static,
+              T: Fn(Vec<K>) -> F + Send + Sync + 'static {
+        self.batch_loader = Some(Arc::new(move |keys| Box::pin(batch_loader(keys))));
+        self
+    }
+
+    /// Sets a storer, which writes every value of `set` and `update` to the underlying store
+    /// before it's cached, so the cache acts as a write-through layer e.g. in front of a database
+    ///
+    /// The key is locked while its value is stored, see `lock_key`, so the store receives the
+    /// writes of a key in the same order as the cache, and other operations on the key wait for
+    /// the store. If the storer fails, `failure` decides whether the write is rejected with
+    /// `CacheLoadingError::StoreError` or the value is cached regardless. Writes through a storer
+    /// aren't coalesced by `CacheOptions::write_coalescing`, and values written by other
+    /// operations like `set_with_meta` or `update_mut` aren't stored. Only this handle and the
+    /// handles cloned from it afterwards use the storer.
+    ///
+    /// # Arguments
+    ///
+    /// * `storer` - A function which returns a Future<Output=Result<(), E>>
+    /// * `failure` - What `set` and `update` do when the storer fails
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::{LoadingCache, StoreFailure};
+    /// async fn example() {
+    ///     let cache: LoadingCache<u64, String, u8, _> = LoadingCache::new(move |key: u64| {
+    ///         async move { Ok(format!("user {}", key)) }
+    ///     }).with_storer(move |key: u64, name: String| {
+    ///         async move {
+    ///             // e.g. an `UPDATE users SET name = ... WHERE id = ...`
+    ///             println!("storing {} for {}", name, key);
+    ///             Ok(())
+    ///         }
+    ///     }, StoreFailure::Reject);
+    ///
+    ///     cache.set(1, "alice".to_owned()).await.unwrap();
+    ///     assert_eq!(cache.get(1).await.unwrap(), "alice");
+    /// }
+    /// ```
+    pub fn with_storer<T, F>(mut self, storer: T, failure: StoreFailure) -> LoadingCache<K, V, E, B>
+        where F: Future<Output=Result<(), E>> + Send + 'static,
+              T: Fn(K, V) -> F + Send + Sync + 'static {
+        self.write_through = Some(WriteThrough::new(Arc::new(move |key, value| Box::pin(storer(key, value))), failure));
+        self
+    }
+
+    /// Sets a write-behind sink, which receives the values of `set`, `update` and `update_mut`
+    /// in batches after they were cached
+    ///
+    /// Written keys are marked as dirty, only their latest value is written. The dirty entries
+    /// are written every `interval` and as soon as `batch_size` keys are dirty, in batches of up
+    /// to `batch_size` entries, `flush` writes them right away. Batches are written one at a time,
+    /// so the sink receives the writes of a key in order. If the sink fails, the entries of the
+    /// batch stay dirty and are written again with the next flush, background flushes only
+    /// report the error as a `tracing` event with the `tracing` feature enabled. Once every
+    /// handle of the cache is gone, the remaining dirty entries are written in a single batch.
+    /// `remove`, `remove_if` and `clear` drop the dirty entries of the keys they remove, the sink
+    /// isn't told about removals. Unlike `with_storer`, writes don't wait for the sink, so a crash
+    /// loses the dirty entries unless they are persisted with `with_write_ahead_log`. Only this
+    /// handle and the handles cloned from it afterwards mark entries as dirty.
+    /// Requires a tokio runtime with the time driver enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - A function which returns a Future<Output=Result<(), E>>
+    /// * `interval` - How often the dirty entries are written
+    /// * `batch_size` - How many entries the sink receives at most at once
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::new(move |_: u64| {
+    ///         async move { Ok(0) }
+    ///     }).with_write_behind(move |counters: Vec<(u64, u64)>| {
+    ///         async move {
+    ///             // e.g. a single multi-row `INSERT ... ON CONFLICT DO UPDATE`
+    ///             println!("writing {} counters", counters.len());
+    ///             Ok(())
+    ///         }
+    ///     }, Duration::from_secs(5), 500);
+    ///
+    ///     cache.update_mut(1, |count| *count += 1).await.unwrap();
+    ///     cache.flush().await.unwrap();
+    /// }
+    /// ```
+    pub fn with_write_behind<T, F>(mut self, sink: T, interval: Duration, batch_size: usize) -> LoadingCache<K, V, E, B>
+        where F: Future<Output=Result<(), E>> + Send + 'static,
+              T: Fn(Vec<(K, V)>) -> F + Send + Sync + 'static {
+        let sink = Arc::new(move |entries| Box::pin(sink(entries)) as BoxFuture<'static, Result<(), E>>);
+        self.write_behind = Some(WriteBehind::spawn(sink, interval, batch_size));
+        self
+    }
+
+    /// Persists the dirty entries of the write-behind in a write-ahead log at `path`, so they
+    /// are still written to the sink after a crash, and replays the entries a previous run left
+    /// in the log
+    ///
+    /// Every dirty entry is appended to the log before the `set`, `update` or `update_mut` which
+    /// marked it returns, and every flush compacts the log to the entries which are still dirty.
+    /// The replayed entries are marked as dirty, unless their key was written meanwhile, and
+    /// flushed right away. Appends aren't synced to disk, so the log survives a crash of the
+    /// process, but a crash of the host may lose the entries marked since the last flush. The
+    /// entries of a batch written right before a crash are written again, so the sink should be
+    /// idempotent. Failures to write the log don't fail the operations of the cache, they are only
+    /// reported as `tracing` event with the `tracing` feature enabled. The log can't be shared by
+    /// several caches.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file of the log, created if it doesn't exist. Flushes write the compacted
+    ///   log to a temporary file next to it, named like the log with a `.tmp` suffix, and rename
+    ///   it over the log.
+    /// * `codec` - The codec encoding the keys and values in the log
+    ///
+    /// # Return Value
+    ///
+    /// Returns the cache, or the error reading or writing the log, `io::ErrorKind::InvalidInput`
+    /// if the cache has no write-behind, see `with_write_behind`
+    pub fn with_write_ahead_log<P, C>(self, path: P, codec: C) -> std::io::Result<LoadingCache<K, V, E, B>>
+        where P: AsRef<std::path::Path>,
+              C: ValueCodec<K> + ValueCodec<V> + 'static {
+        let behind = self.write_behind.as_ref().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "The write-ahead log requires a write-behind sink")
+        })?;
+        let (log, replayed) = WriteAheadLog::open(path, codec)?;
+        behind.attach_log(log, replayed)?;
+        Ok(self)
+    }
+
+    fn mark_dirty(&self, key: K, value: V) {
+        if let Some(behind) = &self.write_behind {
+            behind.mark(key, value);
+        }
+    }
+
+    /// Drops the dirty entries of removed keys, the sink isn't told about removals
+    fn forget_dirty<P: Fn(&K, &V) -> bool>(&self, predicate: P) {
+        if let Some(behind) = &self.write_behind {
+            behind.forget_if(predicate);
+        }
+    }
+
+    /// Retrieves or loads the values of several keys in a single operation
+    ///
+    /// Missing keys are loaded by the batch loader with one call if there is one, see
+    /// `with_batch_loader`, otherwise by the loader function. Keys which are already loading are
+    /// awaited like by `get`.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - The keys which should be loaded
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - The values of the keys, without the keys the batch loader didn't return
+    /// Err - The first error of type CacheLoadingError of a key
+    pub async fn get_all(&self, keys: Vec<K>) -> Result<HashMap<K, V>, CacheLoadingError<E>> {
+        let (tx, rx) = self.responses.channel();
+        let message = CacheMessage::new(CacheAction::GetAll(keys.clone(), self.batch_loader.clone()), tx);
+        self.send_message(message).await?;
+        let results = match rx.await {
+            Ok(CacheResult::Pipeline(results)) => results,
+            Ok(_) => return Err(CacheLoadingError::CommunicationError(CacheCommunicationError::LookupLoop())),
+            Err(err) => return Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioOneshotRecvError(err))),
+        };
+        let mut values = HashMap::with_capacity(keys.len());
+        for (key, result) in keys.into_iter().zip(results) {
+            match Self::resolve_cache_result(result).await {
+                Ok(Some(meta)) => {
+                    values.insert(key, meta.result);
+                }
+                Ok(None) | Err(CacheLoadingError::NoData()) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(values)
+    }
+
+    /// Retrieves the value for specified key like `get`, but falls back to an expired value
+    /// instead of waiting for the load
+    ///
+    /// If the key has expired no longer than `max_staleness` ago and the backing still retains
+    /// its value, the stale value is returned right away while the key is reloaded in the
+    /// background. This keeps serving values when the loader is down or slow. Backings only retain
+    /// expired values if configured to, see `TtlCacheBacking::stale_retention`. In
+    /// `ExecutionMode::Inline` there is no background to run the load in, so the load is awaited
+    /// like in `get`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be loaded
+    /// * `max_staleness` - How long ago the key may have expired to return its stale value
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type MaybeStale<V>, flagged stale if it has expired
+    /// Err - Error of type CacheLoadingError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "ttl-cache")]
+    /// # mod example {
+    /// use std::time::Duration;
+    /// use cache_loader_async::backing::TtlCacheBacking;
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let backing = TtlCacheBacking::new(Duration::from_secs(30))
+    ///         .stale_retention(Duration::from_secs(600));
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     let result = cache.get_allow_stale("foo".to_owned(), Duration::from_secs(300)).await.unwrap();
+    ///     if result.is_stale() {
+    ///         println!("serving a value which expired {:?} ago", result.staleness.unwrap());
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn get_allow_stale(&self, key: K, max_staleness: Duration) -> Result<MaybeStale<V>, CacheLoadingError<E>> {
+        let (tx, rx) = self.responses.channel();
+        self.send_message(CacheMessage::new(CacheAction::GetAllowStale(key, max_staleness), tx)).await?;
+        match rx.await {
+            Ok(CacheResult::Stale(value, staleness)) => Ok(MaybeStale {
+                value,
+                staleness: Some(staleness),
+            }),
+            Ok(result) => Self::resolve_cache_result(result).await
+                .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
+                .map(|meta| MaybeStale {
+                    value: meta.result,
+                    staleness: None,
+                }),
+            Err(err) => Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioOneshotRecvError(err))),
+        }
+    }
+
+    /// Sets the value for specified key and bypasses eventual currently ongoing loads
+    /// If a key has been set programmatically, eventual concurrent loads will not change
+    /// the value of the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be set
+    /// * `value` - The value which should be set
+    /// * `meta` - The meta which should be attached to the key
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Previous value of type V wrapped in an Option depending whether there was a previous
+    ///      value
+    /// Err - Error of type CacheLoadingError
+    pub async fn set_with_meta(&self, key: K, value: V, meta: Option<B::Meta>) -> Result<Option<V>, CacheLoadingError<E>> {
+        self.send_cache_action(CacheAction::Set(key, value, meta)).await
+            .map(|opt_meta| opt_meta.map(|meta| meta.result))
+    }
+
+    /// Sets the value for specified key and bypasses eventual currently ongoing loads
+    /// If a key has been set programmatically, eventual concurrent loads will not change
+    /// the value of the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be set
+    /// * `value` - The value which should be set
     ///
     /// # Return Value
     ///
@@ -407,7 +1387,249 @@ impl<
     ///      value
     /// Err - Error of type CacheLoadingError
     pub async fn set(&self, key: K, value: V) -> Result<Option<V>, CacheLoadingError<E>> {
-        self.set_with_meta(key, value, None).await
+        let dirty = self.write_behind.as_ref().map(|_| (key.clone(), value.clone()));
+        let previous = if let Some(through) = &self.write_through {
+            self.set_through(through, key, value).await?
+        } else if let Some(writes) = self.write_window() {
+            writes.set(self, key, value).await?
+        } else {
+            self.set_with_meta(key, value, None).await?
+        };
+        if let Some((key, value)) = dirty {
+            self.mark_dirty(key, value);
+        }
+        Ok(previous)
+    }
+
+    /// Sets the value for specified key like `set`, expiring it after `ttl` instead of the
+    /// default TTL of the backing
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be set
+    /// * `value` - The value which should be set
+    /// * `ttl` - How long the value should be kept
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Previous value of type V wrapped in an Option depending whether there was a previous
+    ///      value
+    /// Err - Error of type CacheLoadingError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "ttl-cache")]
+    /// # mod example {
+    /// use std::time::Duration;
+    /// use cache_loader_async::backing::TtlCacheBacking;
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+    ///         TtlCacheBacking::new(Duration::from_secs(30)), move |key: String| {
+    ///             async move {
+    ///                 Ok(key.len())
+    ///             }
+    ///         });
+    ///
+    ///     cache.set_with_ttl("session".to_owned(), 1, Duration::from_secs(5)).await.unwrap();
+    ///     let value = cache.get_with_ttl("config".to_owned(), Duration::from_secs(3600)).await.unwrap();
+    ///     assert_eq!(value, 6);
+    /// }
+    /// # }
+    /// ```
+    pub async fn set_with_ttl(&self, key: K, value: V, ttl: Duration) -> Result<Option<V>, CacheLoadingError<E>>
+        where B::Meta: From<Duration> {
+        self.set_with_meta(key, value, Some(B::Meta::from(ttl))).await
+    }
+
+    /// Sets the value for the specified key and returns the previous value, waiting for a running
+    /// load of the key to replace the loaded value
+    ///
+    /// Unlike `set`, which replaces a running load without returning a previous value, this
+    /// never loses the value it replaces. See `swap_with` for the other `SwapPolicy`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be set
+    /// * `value` - The value which should be set
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Previous value of type V wrapped in an Option depending whether there was a previous
+    ///      value
+    /// Err - Error of type CacheLoadingError
+    pub async fn swap(&self, key: K, value: V) -> Result<Option<V>, CacheLoadingError<E>> {
+        self.swap_with(key, value, SwapPolicy::Defer).await
+    }
+
+    /// Sets the value for the specified key and returns the previous value, the `SwapPolicy`
+    /// defines what happens to a running load of the key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be set
+    /// * `value` - The value which should be set
+    /// * `policy` - Whether to wait for a running load or to replace it
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Previous value of type V wrapped in an Option depending whether there was a previous
+    ///      value, always `None` if a load was replaced
+    /// Err - Error of type CacheLoadingError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::{LoadingCache, SwapPolicy};
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     let load = cache.get("foo".to_owned());
+    ///     let swap = cache.swap_with("foo".to_owned(), 7, SwapPolicy::Defer);
+    ///     let (loaded, previous) = tokio::join!(load, swap);
+    ///     assert_eq!(loaded.unwrap(), 3);
+    ///     assert_eq!(previous.unwrap(), Some(3));
+    /// }
+    /// ```
+    pub async fn swap_with(&self, key: K, value: V, policy: SwapPolicy) -> Result<Option<V>, CacheLoadingError<E>> {
+        self.send_cache_action(CacheAction::Swap(key, value, policy)).await
+            .map(|opt_meta| opt_meta.map(|meta| meta.result))
+    }
+
+    /// Retrieves the value for the specified key from the cache or loads it with the given future
+    /// instead of the loader function, still deduplicating concurrent loads of the key
+    ///
+    /// The future is only awaited if the key is neither cached nor loading, otherwise it's dropped
+    /// and the cached value or the result of the running load is returned. Other callers of the
+    /// key, including `get`, join the load of the future while it runs.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be loaded
+    /// * `load` - A Future<Output=Result<V, E>> loading the value of this key
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V
+    /// Err - Error of type CacheLoadingError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     // e.g. a value which is already at hand from another request
+    ///     let value = cache.get_or_insert_with("foo".to_owned(), async { Ok(42) }).await.unwrap();
+    ///     assert_eq!(value, 42);
+    ///     assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 42);
+    /// }
+    /// ```
+    pub async fn get_or_insert_with<F>(&self, key: K, load: F) -> Result<V, CacheLoadingError<E>>
+        where F: Future<Output=Result<V, E>> + Send + 'static {
+        let load = Box::pin(async move {
+            load.await.map(|data| Some(DataWithMeta::new(data, None)))
+        });
+        self.send_cache_action(CacheAction::GetWith(key, load)).await
+            .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
+            .map(|meta| meta.result)
+    }
+
+    /// Calls the loader for the caller even if the key is cached or a load of it is running, for
+    /// paths which must not share the result of another caller's load
+    ///
+    /// Other callers never join this load. If `update` is set, the loaded value is set afterwards
+    /// like by `set`, replacing a cached value and a running load of the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be loaded
+    /// * `update` - Whether the loaded value should be set
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V
+    /// Err - Error of type CacheLoadingError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// async fn example() {
+    ///     let loads = Arc::new(AtomicUsize::new(0));
+    ///     let counter = loads.clone();
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |_key: String| {
+    ///         let load = counter.fetch_add(1, Ordering::SeqCst);
+    ///         async move { Ok(load) }
+    ///     });
+    ///
+    ///     assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 0);
+    ///     assert_eq!(cache.load_independent("foo".to_owned(), false).await.unwrap(), 1);
+    ///     assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 0);
+    /// }
+    /// ```
+    pub async fn load_independent(&self, key: K, update: bool) -> Result<V, CacheLoadingError<E>> {
+        self.send_cache_action(CacheAction::LoadIndependent(key, update)).await
+            .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
+            .map(|meta| meta.result)
+    }
+
+    /// Same as `get`, but fails immediately with `CacheLoadingError::Busy` instead of waiting when
+    /// the request queue of the cache is full
+    ///
+    /// This is meant for latency-sensitive callers which rather fall back to the source than
+    /// queue behind a backlog of requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be loaded
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V
+    /// Err - Error of type CacheLoadingError, `CacheLoadingError::Busy` if the queue is full
+    pub async fn try_get(&self, key: K) -> Result<V, CacheLoadingError<E>> {
+        self.try_send_cache_action(CacheAction::Get(key)).await
+            .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
+            .map(|meta| meta.result)
+    }
+
+    /// Same as `set`, but fails immediately with `CacheLoadingError::Busy` instead of waiting when
+    /// the request queue of the cache is full
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be set
+    /// * `value` - The value which should be set
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Previous value of type V wrapped in an Option depending whether there was a previous
+    ///      value
+    /// Err - Error of type CacheLoadingError, `CacheLoadingError::Busy` if the queue is full
+    pub async fn try_set(&self, key: K, value: V) -> Result<Option<V>, CacheLoadingError<E>> {
+        self.try_send_cache_action(CacheAction::Set(key, value, None)).await
+            .map(|opt_meta| opt_meta.map(|meta| meta.result))
     }
 
     /// Loads the value for the specified key from the cache and returns None if not present
@@ -426,53 +1648,333 @@ impl<
             .map(|opt_meta| opt_meta.map(|meta| meta.result))
     }
 
-    /// Checks whether a specific value is mapped for the given key
+    /// Loads the value for the specified key from the cache like `get_if_present`, together with
+    /// its age, its hits and its remaining TTL
+    ///
+    /// This saves observability wrappers a second round trip to the actor per lookup. A value
+    /// which is present was always cached, keys which aren't cached or still loading return
+    /// `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be loaded
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type Option<EntryMeta<V>>
+    /// Err - Error of type CacheLoadingError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use cache_loader_async::backing::HashMapBacking;
+    /// use cache_loader_async::options::CacheOptions;
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_options(HashMapBacking::new(), move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     }, CacheOptions::new().track_entries(true));
+    ///
+    ///     cache.get("foo".to_owned()).await.unwrap();
+    ///     let meta = cache.get_if_present_with_meta("foo".to_owned()).await.unwrap().unwrap();
+    ///     assert_eq!(meta.value, 3);
+    ///     assert_eq!(meta.hits, Some(1));
+    /// }
+    /// ```
+    pub async fn get_if_present_with_meta(&self, key: K) -> Result<Option<EntryMeta<V>>, CacheLoadingError<E>> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.send_cache_action(CacheAction::GetIfPresentWithMeta(key, Box::new(move |meta| {
+            let _ = tx.send(meta);
+        }))).await?;
+        rx.await
+            .map_err(|err| CacheLoadingError::CommunicationError(CacheCommunicationError::TokioOneshotRecvError(err)))
+    }
+
+    /// Looks up the key without loading it or waiting for its load, telling keys which aren't
+    /// cached apart from keys another operation is loading
+    ///
+    /// Unlike `get_if_present`, which returns `None` for both, callers can skip starting work of
+    /// their own for a key which is about to be cached. `try_get` loads the key and only avoids
+    /// waiting for a full request queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be looked up
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type Lookup<V>
+    /// Err - Error of type CacheLoadingError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::{LoadingCache, Lookup};
+    /// use cache_loader_async::backing::HashMapBacking;
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     assert_eq!(cache.lookup("foo".to_owned()).await.unwrap(), Lookup::Miss);
+    ///     cache.get("foo".to_owned()).await.unwrap();
+    ///     assert_eq!(cache.lookup("foo".to_owned()).await.unwrap(), Lookup::Hit(3));
+    /// }
+    /// ```
+    pub async fn lookup(&self, key: K) -> Result<Lookup<V>, CacheLoadingError<E>> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.send_cache_action(CacheAction::Lookup(key, Box::new(move |lookup| {
+            let _ = tx.send(lookup);
+        }))).await?;
+        rx.await
+            .map_err(|err| CacheLoadingError::CommunicationError(CacheCommunicationError::TokioOneshotRecvError(err)))
+    }
+
+    /// Loads the value for the specified key from the cache like `get_if_present`, but without
+    /// marking it as recently used
+    ///
+    /// Reading the cache for health checks or metrics this way doesn't change which entries an
+    /// `LruCacheBacking` evicts next.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be loaded
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type Option<V>
+    /// Err - Error of type CacheLoadingError
+    pub async fn peek_if_present(&self, key: K) -> Result<Option<V>, CacheLoadingError<E>> {
+        self.send_cache_action(CacheAction::PeekIfPresent(key)).await
+            .map(|opt_meta| opt_meta.map(|meta| meta.result))
+    }
+
+    /// Checks whether a specific value is mapped for the given key
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be checked
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - bool
+    /// Err - Error of type CacheLoadingError
+    pub async fn exists(&self, key: K) -> Result<bool, CacheLoadingError<E>> {
+        self.get_if_present(key).await
+            .map(|result| result.is_some())
+    }
+
+    /// Removes a specific key-value mapping from the cache and returns the previous result
+    /// if there was any or None
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be evicted
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type Option<V>
+    /// Err - Error of type CacheLoadingError
+    pub async fn remove(&self, key: K) -> Result<Option<V>, CacheLoadingError<E>> {
+        let removed = self.send_cache_action(CacheAction::Remove(key.clone())).await
+            .map(|opt_meta| opt_meta.map(|meta| meta.result))?;
+        self.forget_dirty(|dirty_key, _| *dirty_key == key);
+        Ok(removed)
+    }
+
+    /// Removes all entries which match the specified predicate
+    ///
+    /// # Arguments
+    ///
+    /// * `predicate` - The predicate to test all entries against
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Nothing, the removed values are discarded
+    /// Err - Error of type CacheLoadingError -> the values were not discarded
+    pub async fn remove_if<P: Fn((&K, Option<&V>)) -> bool + Send + Sync + 'static>(&self, predicate: P) -> Result<(), CacheLoadingError<E>> {
+        let predicate = Arc::new(predicate);
+        let removing = predicate.clone();
+        self.send_cache_action(CacheAction::RemoveIf(Box::new(move |entry| removing(entry)))).await?;
+        self.forget_dirty(|key, value| predicate((key, Some(value))));
+        Ok(())
+    }
+
+    /// Removes up to `count` entries in the order the backing would evict them and returns the
+    /// loaded ones
+    ///
+    /// An `LruCacheBacking` removes its least recently used entries, which allows shedding cache
+    /// weight on memory pressure, see `CacheBacking::evict`. Backings without an eviction order
+    /// remove nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The maximum number of entries to remove
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - The removed keys and values, excluding keys which were still loading
+    /// Err - Error of type CacheLoadingError
+    pub async fn evict(&self, count: usize) -> Result<Vec<(K, V)>, CacheLoadingError<E>> {
+        let evicted = self.inspect_backing(move |backing| backing.evict(count)).await?
+            .map_err(CacheLoadingError::BackingError)?;
+        Ok(Self::loaded_entries(evicted))
+    }
+
+    /// Returns copies of the loaded entries, from the most to the least recently used one
+    ///
+    /// Reading the entries doesn't change their recency, so this can be used to debug the
+    /// eviction of an `LruCacheBacking` or to export its hot set. Reverse the entries to start
+    /// with the one evicted next. Backings without a recency order return nothing, see
+    /// `CacheBacking::entries_by_recency`.
+    pub async fn entries_by_recency(&self) -> Result<Vec<(K, V)>, CacheLoadingError<E>> {
+        let entries = self.inspect_backing(|backing| backing.entries_by_recency()).await?
+            .map_err(CacheLoadingError::BackingError)?;
+        Ok(Self::loaded_entries(entries))
+    }
+
+    /// Returns a snapshot of the keys of the loaded values, keys which are still loading are left
+    /// out
+    ///
+    /// The keys are copied by the actor of the cache in one operation, in no particular order.
+    /// Use `export` to walk large caches in chunks instead. Backings which can't be iterated
+    /// return nothing, see `CacheBacking::entries`.
+    pub async fn keys(&self) -> Result<Vec<K>, CacheLoadingError<E>> {
+        Ok(self.entries().await?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect())
+    }
+
+    /// Returns a snapshot of copies of the loaded entries like `keys`, keys which are still
+    /// loading are left out
+    pub async fn entries(&self) -> Result<Vec<(K, V)>, CacheLoadingError<E>> {
+        let entries = self.inspect_backing(|backing| backing.entries()).await?
+            .map_err(CacheLoadingError::BackingError)?;
+        Ok(Self::loaded_entries(entries))
+    }
+
+    /// Streams copies of the loaded entries with their meta, fetching `chunk_size` entries from
+    /// the backing at a time
+    ///
+    /// The cache is never copied as a whole and stays usable during the export, entries which
+    /// are set or removed meanwhile may be left out or exported twice. Together with `import`,
+    /// this migrates a cache into another one, e.g. of another process after serializing the
+    /// entries. Backings which can't be iterated export nothing, see `CacheBacking::export`.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key which should be checked
+    /// * `chunk_size` - How many entries are fetched from the backing per operation
     ///
-    /// # Return Value
+    /// # Examples
     ///
-    /// Returns a Result with:
-    /// Ok - bool
-    /// Err - Error of type CacheLoadingError
-    pub async fn exists(&self, key: K) -> Result<bool, CacheLoadingError<E>> {
-        self.get_if_present(key).await
-            .map(|result| result.is_some())
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let loader = |key: String| async move { Ok::<_, u8>(key.len()) };
+    ///     let source: LoadingCache<String, usize, u8, _> = LoadingCache::new(loader);
+    ///     let target: LoadingCache<String, usize, u8, _> = LoadingCache::new(loader);
+    ///     source.set("foo".to_owned(), 5).await.unwrap();
+    ///
+    ///     assert_eq!(target.import(source.export(100)).await.unwrap(), 1);
+    ///     assert_eq!(target.get_if_present("foo".to_owned()).await.unwrap(), Some(5));
+    /// }
+    /// ```
+    pub fn export(&self, chunk_size: usize) -> impl Stream<Item = Result<ExportedEntry<K, V, B::Meta>, CacheLoadingError<E>>>
+        where B::Meta: 'static {
+        let chunk_size = chunk_size.max(1);
+        let chunks = futures::stream::try_unfold((self.clone(), Some(0)), move |(cache, cursor)| async move {
+            let cursor = match cursor {
+                Some(cursor) => cursor,
+                None => return Ok(None),
+            };
+            let chunk = cache.inspect_backing(move |backing| backing.export(cursor, chunk_size)).await?
+                .map_err(CacheLoadingError::BackingError)?;
+            let entries = chunk.entries.into_iter()
+                .filter_map(|(key, entry, meta)| match entry {
+                    CacheEntry::Loaded(value) => Some(Ok((key, value, meta))),
+                    CacheEntry::Loading(_) => None,
+                })
+                .collect::<Vec<_>>();
+            Ok(Some((futures::stream::iter(entries), (cache, chunk.next))))
+        });
+        chunks.try_flatten()
     }
 
-    /// Removes a specific key-value mapping from the cache and returns the previous result
-    /// if there was any or None
-    ///
-    /// # Arguments
+    /// Sets the entries of the stream with their meta, e.g. those exported by `export`, and
+    /// returns how many entries were set
     ///
-    /// * `key` - The key which should be evicted
+    /// The entries are set one after another, so the stream is never collected. The first error,
+    /// of the stream or of setting an entry, stops the import and is returned, the entries before
+    /// it stay set.
     ///
-    /// # Return Value
+    /// # Arguments
     ///
-    /// Returns a Result with:
-    /// Ok - Value of type Option<V>
-    /// Err - Error of type CacheLoadingError
-    pub async fn remove(&self, key: K) -> Result<Option<V>, CacheLoadingError<E>> {
-        self.send_cache_action(CacheAction::Remove(key)).await
-            .map(|opt_meta| opt_meta.map(|meta| meta.result))
+    /// * `entries` - The entries which should be set
+    pub async fn import<S>(&self, entries: S) -> Result<usize, CacheLoadingError<E>>
+        where S: Stream<Item = Result<ExportedEntry<K, V, B::Meta>, CacheLoadingError<E>>> {
+        futures::pin_mut!(entries);
+        let mut imported = 0;
+        while let Some(entry) = entries.next().await {
+            let (key, value, meta) = entry?;
+            self.set_with_meta(key, value, meta).await?;
+            imported += 1;
+        }
+        Ok(imported)
     }
 
-    /// Removes all entries which match the specified predicate
+    /// Sets the entries of the stream in batches of up to `batch_size` entries and returns how
+    /// many entries were set, e.g. to warm up the cache from a database cursor or a file reader
+    ///
+    /// Every batch is sent to the cache as a single operation, the next entries are only taken
+    /// from the stream once the batch was set, so a fast stream can't flood the cache. Entries of
+    /// a locked key are set once it's released. The first error stops the extension and is
+    /// returned, the batches before it stay set.
     ///
     /// # Arguments
     ///
-    /// * `predicate` - The predicate to test all entries against
+    /// * `entries` - The entries which should be set, `(K, V)` or `(K, V, Meta)` tuples
+    /// * `batch_size` - The maximum amount of entries set by one operation
     ///
-    /// # Return Value
+    /// # Examples
     ///
-    /// Returns a Result with:
-    /// Ok - Nothing, the removed values are discarded
-    /// Err - Error of type CacheLoadingError -> the values were not discarded
-    pub async fn remove_if<P: Fn((&K, Option<&V>)) -> bool + Send + Sync + 'static>(&self, predicate: P) -> Result<(), CacheLoadingError<E>> {
-        self.send_cache_action(CacheAction::RemoveIf(Box::new(predicate))).await
-            .map(|_| ())
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     let entries = futures::stream::iter(vec![("foo".to_owned(), 1), ("bar".to_owned(), 2)]);
+    ///     assert_eq!(cache.extend_from_stream(entries, 100).await.unwrap(), 2);
+    ///     assert_eq!(cache.get_if_present("bar".to_owned()).await.unwrap(), Some(2));
+    /// }
+    /// ```
+    pub async fn extend_from_stream<S, T>(&self, entries: S, batch_size: usize) -> Result<usize, CacheLoadingError<E>>
+        where S: Stream<Item = T>,
+              T: IntoEntry<K, V, B::Meta> {
+        let batches = entries.map(IntoEntry::into_entry).chunks(batch_size.max(1));
+        futures::pin_mut!(batches);
+        let mut extended = 0;
+        while let Some(batch) = batches.next().await {
+            let len = batch.len();
+            self.send_cache_action(CacheAction::SetBatch(batch)).await?;
+            extended += len;
+        }
+        Ok(extended)
     }
 
     /// Removes all entries from the underlying backing
@@ -483,7 +1985,28 @@ impl<
     /// Ok - Nothing, the removed values are discarded
     /// Err - Error of type CacheLoadingError -> the values were not discarded
     pub async fn clear(&self) -> Result<(), CacheLoadingError<E>> {
-        self.send_cache_action(CacheAction::Clear()).await
+        self.send_cache_action(CacheAction::Clear()).await?;
+        self.forget_dirty(|_, _| true);
+        Ok(())
+    }
+
+    /// Shuts the cache down, ending the task which operates the cache
+    ///
+    /// Operations sent before the shutdown are still processed, the running loads are cancelled
+    /// afterwards, so their callers receive `CacheLoadingError::Cancelled` and the callers waiting
+    /// for them a `TokioBroadcastRecvError`. Once the task ended, the operations of every handle
+    /// of the cache fail with a `CommunicationError`.
+    ///
+    /// In `ExecutionMode::Inline` there's no task, the running loads are cancelled and the cache
+    /// stays usable.
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - The task ended
+    /// Err - Error of type CacheLoadingError -> the cache was already shut down
+    pub async fn shutdown(&self) -> Result<(), CacheLoadingError<E>> {
+        self.send_cache_action(CacheAction::Shutdown()).await
             .map(|_| ())
     }
 
@@ -499,7 +2022,7 @@ impl<
     ///
     /// * `key` - The key which should be updated
     /// * `update_fn` - A `FnOnce(V) -> V` which has the current value as parameter and should
-    ///                 return the updated value
+    ///   return the updated value
     ///
     /// # Return Value
     ///
@@ -508,9 +2031,45 @@ impl<
     /// Err - Error of type CacheLoadingError
     pub async fn update<U>(&self, key: K, update_fn: U) -> Result<V, CacheLoadingError<E>>
         where U: FnOnce(V) -> V + Send + 'static {
-        self.send_cache_action(CacheAction::Update(key, None, Box::new(update_fn), true)).await
-            .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
-            .map(|meta| meta.result)
+        let dirty_key = self.write_behind.as_ref().map(|_| key.clone());
+        let value = if let Some(through) = &self.write_through {
+            self.update_through(through, key, update_fn).await?
+        } else if let Some(writes) = self.write_window() {
+            writes.update(self, key, Box::new(update_fn)).await?
+        } else {
+            self.send_cache_action(CacheAction::Update(key, None, Box::new(update_fn), true)).await
+                .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
+                .map(|meta| meta.result)?
+        };
+        if let Some(key) = dirty_key {
+            self.mark_dirty(key, value.clone());
+        }
+        Ok(value)
+    }
+
+    /// Updates a key on the cache like `update`, but stops waiting once the `cancelled` future
+    /// completes, see `get_until`
+    ///
+    /// The update is applied regardless whether the caller stopped waiting, if the cache already
+    /// received it.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be updated
+    /// * `update_fn` - A `FnOnce(V) -> V` which has the current value as parameter and should
+    ///   return the updated value
+    /// * `cancelled` - A future completing when the caller stops waiting
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V which is the previously mapped value
+    /// Err - Error of type CacheLoadingError, `CacheLoadingError::Cancelled` if `cancelled`
+    ///       completed first
+    pub async fn update_until<U, C>(&self, key: K, update_fn: U, cancelled: C) -> Result<V, CacheLoadingError<E>>
+        where U: FnOnce(V) -> V + Send + 'static,
+              C: Future<Output=()> {
+        Self::until(self.update(key, update_fn), cancelled).await
     }
 
     /// Updates a key on the cache with the given update function and returns the updated value if
@@ -522,7 +2081,7 @@ impl<
     ///
     /// * `key` - The key which should be updated
     /// * `update_fn` - A `FnOnce(V) -> V` which has the current value as parameter and should
-    ///                 return the updated value
+    ///   return the updated value
     ///
     /// # Return Value
     ///
@@ -546,8 +2105,8 @@ impl<
     /// # Arguments
     ///
     /// * `key` - The key which should be updated
-    /// * `update_fn` - A `FnMut(&mut V) -> ()` which has the current value as parameter and should
-    ///                 update it accordingly
+    /// * `update_fn` - A `FnMut(&mut V)` which has the current value as parameter and should
+    ///   update it accordingly
     ///
     /// # Return Value
     ///
@@ -555,10 +2114,15 @@ impl<
     /// Ok - Value of type V
     /// Err - Error of type CacheLoadingError
     pub async fn update_mut<U>(&self, key: K, update_fn: U) -> Result<V, CacheLoadingError<E>>
-        where U: FnMut(&mut V) -> () + Send + 'static {
-        self.send_cache_action(CacheAction::UpdateMut(key, Box::new(update_fn), true)).await
+        where U: FnMut(&mut V) + Send + 'static {
+        let dirty_key = self.write_behind.as_ref().map(|_| key.clone());
+        let value = self.send_cache_action(CacheAction::UpdateMut(key, Box::new(update_fn), true)).await
             .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
-            .map(|meta| meta.result)
+            .map(|meta| meta.result)?;
+        if let Some(key) = dirty_key {
+            self.mark_dirty(key, value.clone());
+        }
+        Ok(value)
     }
 
     /// Updates a key on the cache with the given update function and returns the updated value if
@@ -570,8 +2134,8 @@ impl<
     /// # Arguments
     ///
     /// * `key` - The key which should be updated
-    /// * `update_fn` - A `FnMut(&mut V) -> ()` which has the current value as parameter and should
-    ///                 update it accordingly
+    /// * `update_fn` - A `FnMut(&mut V)` which has the current value as parameter and should
+    ///   update it accordingly
     ///
     /// # Return Value
     ///
@@ -579,54 +2143,455 @@ impl<
     /// Ok - Optional value of type V
     /// Err - Error of type CacheLoadingError
     pub async fn update_mut_if_exists<U>(&self, key: K, update_fn: U) -> Result<Option<V>, CacheLoadingError<E>>
-        where U: FnMut(&mut V) -> () + Send + 'static {
+        where U: FnMut(&mut V) + Send + 'static {
         self.send_cache_action(CacheAction::UpdateMut(key, Box::new(update_fn), false)).await
             .map(|opt| opt.map(|meta| meta.result))
     }
 
-    async fn send_cache_action(&self, action: CacheAction<K, V, E, B>) -> Result<Option<ResultMeta<V>>, CacheLoadingError<E>> {
-        let (tx, rx) = tokio::sync::oneshot::channel();
-        match self.tx.send(CacheMessage {
-            action,
-            response: tx,
-        }).await {
-            Ok(_) => {
-                match rx.await {
-                    Ok(result) => {
-                        match result {
-                            CacheResult::Found(value) => {
-                                Ok(Some(ResultMeta {
-                                    result: value,
-                                    cached: true,
-                                }))
-                            }
-                            CacheResult::Loading(handle) => {
-                                match handle.await {
-                                    Ok(load_result) => {
-                                        load_result.map(|v| Some(ResultMeta {
-                                            result: v,
-                                            cached: false,
-                                        }))
-                                    }
-                                    Err(err) => {
-                                        Err(CacheLoadingError::CommunicationError(CacheCommunicationError::FutureJoinError(err)))
-                                    }
-                                }
-                            }
-                            CacheResult::None => { Ok(None) }
-                            CacheResult::Error(err) => {
-                                Err(CacheLoadingError::BackingError(err))
-                            }
-                        }
-                    }
-                    Err(err) => {
-                        Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioOneshotRecvError(err)))
-                    }
+    /// Creates a handle to this cache which converts loader errors with the given function
+    ///
+    /// This allows exposing a cache without leaking the internal loader error type, as every
+    /// method of the returned handle yields a `CacheLoadingError<F>` instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapper` - A `Fn(E) -> F` which converts the loader error
+    ///
+    /// # Return Value
+    ///
+    /// Returns a `MappedLoadingCache` operating on the same underlying cache
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let cache = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             key.parse::<u32>().map_err(|_| 1u8)
+    ///         }
+    ///     });
+    ///
+    ///     let mapped = cache.map_err(|code| format!("loader failed with code {}", code));
+    ///     let error = mapped.get("foo".to_owned()).await.unwrap_err();
+    ///
+    ///     assert_eq!(error.into_loading_error().unwrap(), "loader failed with code 1");
+    /// }
+    /// ```
+    pub fn map_err<F, M>(&self, mapper: M) -> MappedLoadingCache<K, V, E, F, B>
+        where F: Debug,
+              M: Fn(E) -> F + Send + Sync + 'static {
+        MappedLoadingCache::new(self.clone(), mapper)
+    }
+
+    /// Creates a handle to this cache which can only read and load values
+    ///
+    /// The handle exposes `get`, `get_if_present`, `exists` and the statistics of the cache, so
+    /// it can be handed to plugins or request handlers which mustn't set, update or remove
+    /// entries.
+    ///
+    /// # Return Value
+    ///
+    /// Returns a `ReadOnlyLoadingCache` operating on the same underlying cache
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     let read_only = cache.read_only();
+    ///     assert_eq!(read_only.get("foo".to_owned()).await.unwrap(), 3);
+    ///     assert!(read_only.exists("foo".to_owned()).await.unwrap());
+    /// }
+    /// ```
+    pub fn read_only(&self) -> ReadOnlyLoadingCache<K, V, E, B> {
+        ReadOnlyLoadingCache::new(self.clone())
+    }
+
+    /// Returns a handle which reads this cache and falls back to the `fallback` cache on misses,
+    /// setting the values found there in this cache
+    ///
+    /// This expresses a local cache in front of a shared one without a custom backing. Only the
+    /// loader of the fallback cache is called by the handle, sets and removals of the handle
+    /// apply to both caches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let shared: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///     let local: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     let cache = local.with_fallback(shared.clone());
+    ///     assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+    ///     assert_eq!(local.get_if_present("foo".to_owned()).await.unwrap(), Some(3));
+    ///     assert_eq!(shared.get_if_present("foo".to_owned()).await.unwrap(), Some(3));
+    /// }
+    /// ```
+    pub fn with_fallback<F>(&self, fallback: LoadingCache<K, V, E, F>) -> FallbackLoadingCache<K, V, E, B, F>
+        where F: CacheBacking<K, CacheEntry<V, E>> + Send + 'static {
+        FallbackLoadingCache::new(self.clone(), fallback)
+    }
+
+    /// Locks the key until the returned guard is dropped, so work spanning other systems can be
+    /// serialized per key, e.g. a read-modify-write of a value which is also written to a database
+    ///
+    /// While the key is locked, every other operation on it, including loads, sets and other
+    /// locks, waits for the guard to be dropped and is executed afterwards in the order it was
+    /// sent. Operations on other keys aren't affected. A running load of the key completes
+    /// before the key is locked. Operations on the key by the holder of the guard would wait for
+    /// the guard themselves, the value is set through `KeyGuard::set` instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be locked
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - The `KeyGuard` of the key holding its cached value, if there is one
+    /// Err - Error of type CacheLoadingError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, u64, u8, _> = LoadingCache::new(move |_: String| {
+    ///         async move {
+    ///             Ok(0)
+    ///         }
+    ///     });
+    ///
+    ///     let mut guard = cache.lock_key("balance".to_owned()).await.unwrap();
+    ///     let balance = guard.value().copied().unwrap_or_default();
+    ///     // e.g. write the new balance to a database before other operations see it
+    ///     guard.set(balance + 10);
+    ///     drop(guard);
+    ///
+    ///     assert_eq!(cache.get("balance".to_owned()).await.unwrap(), 10);
+    /// }
+    /// ```
+    pub async fn lock_key(&self, key: K) -> Result<KeyGuard<K, V, E, B>, CacheLoadingError<E>> {
+        let (tx, rx) = self.responses.channel();
+        let message = CacheMessage::new(CacheAction::Lock(key.clone()), tx);
+        // the guard exists before the lock, so a caller which stops waiting still releases it
+        let mut guard = KeyGuard::new(self.tx.clone(), key, message.id);
+        self.send_message(message).await?;
+        let value = Self::receive_cache_result(rx).await?;
+        guard.acquired(value.map(|meta| meta.result));
+        Ok(guard)
+    }
+
+    /// Returns the statistics reported by the backing, see `CacheBacking::stats`
+    pub async fn backing_stats(&self) -> Result<BackingStats, CacheLoadingError<E>> {
+        self.inspect_backing(|backing| backing.stats()).await
+    }
+
+    /// Compacts and vacuums the storage of the backing right away, see `Maintenance`
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - The report of the storage after the maintenance, `None` for in-memory backings
+    /// Err - Error of type CacheLoadingError::BackingError, if the maintenance failed
+    pub async fn maintain(&self) -> Result<Option<MaintenanceReport>, CacheLoadingError<E>> {
+        self.inspect_backing(|backing| {
+            match backing.maintenance() {
+                Some(maintenance) => {
+                    maintenance.compact()?;
+                    maintenance.vacuum()?;
+                    Ok(Some(maintenance.report()))
                 }
+                None => Ok(None),
+            }
+        }).await?
+            .map_err(CacheLoadingError::BackingError)
+    }
+
+    /// Returns the size of the storage of the backing, `None` for in-memory backings, see
+    /// `Maintenance::report`
+    pub async fn maintenance_report(&self) -> Result<Option<MaintenanceReport>, CacheLoadingError<E>> {
+        self.inspect_backing(|backing| backing.maintenance().map(|maintenance| maintenance.report())).await
+    }
+
+    /// Cross-checks the internal invariants of the backing and returns those which are violated,
+    /// see `CacheBacking::verify`
+    ///
+    /// # Arguments
+    ///
+    /// * `repair` - Whether to restore the invariants with `CacheBacking::repair` if any are
+    ///   violated, the returned inconsistencies are those found before the repair
+    pub async fn verify(&self, repair: bool) -> Result<Vec<Inconsistency>, CacheLoadingError<E>> {
+        self.inspect_backing(move |backing| {
+            let inconsistencies = backing.verify()?;
+            if repair && !inconsistencies.is_empty() {
+                backing.repair()?;
             }
-            Err(_) => {
+            Ok(inconsistencies)
+        }).await?
+            .map_err(CacheLoadingError::BackingError)
+    }
+
+    /// Returns how long operations waited in the queue of the actor and how long the actor took
+    /// to execute them
+    ///
+    /// This tells whether latency comes from a saturated actor, slow backing operations or the
+    /// loader, whose time isn't included. The statistics are read without sending an operation,
+    /// so they are available while the actor is busy. Operations executed in
+    /// `ExecutionMode::Inline` aren't recorded.
+    pub fn actor_stats(&self) -> ActorStats {
+        self.metrics.snapshot()
+    }
+
+    /// Returns a snapshot of the hits, misses, loads and evictions of the cache, `None` unless
+    /// `CacheOptions::record_stats` is enabled
+    ///
+    /// The statistics are read without sending an operation, so they are available while the
+    /// actor is busy.
+    pub fn stats(&self) -> Option<CacheStats> {
+        self.stats.as_ref().map(|stats| stats.snapshot())
+    }
+
+    /// Removes all expired entries from the backing and returns the loaded ones
+    ///
+    /// This allows handing expired entries to another system, e.g. writing expired sessions to an
+    /// audit log, see `CacheBacking::drain_expired`. Backings without expiry return nothing.
+    pub async fn drain_expired(&self) -> Result<Vec<(K, V)>, CacheLoadingError<E>> {
+        let drained = self.inspect_backing(|backing| backing.drain_expired()).await?
+            .map_err(CacheLoadingError::BackingError)?;
+        Ok(Self::loaded_entries(drained))
+    }
+
+    fn loaded_entries(entries: Vec<(K, CacheEntry<V, E>)>) -> Vec<(K, V)> {
+        entries.into_iter()
+            .filter_map(|(key, entry)| match entry {
+                CacheEntry::Loaded(value) => Some((key, value)),
+                CacheEntry::Loading(_) => None,
+            })
+            .collect()
+    }
+
+    /// Waits until the cache processed all operations which were sent before this call, and
+    /// writes the dirty entries of `with_write_behind` to its sink
+    ///
+    /// Loads which are still running are not awaited, only the operations in the queue of the
+    /// cache. If the sink fails, the remaining dirty entries are kept for the next flush and
+    /// its error is returned as `CacheLoadingError::StoreError`.
+    pub async fn flush(&self) -> Result<(), CacheLoadingError<E>> {
+        self.inspect_backing(|_| ()).await?;
+        match &self.write_behind {
+            Some(behind) => behind.flush().await.map_err(CacheLoadingError::StoreError),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes all expired entries from the backing right away
+    ///
+    /// This makes the expiry observable without waiting for the next access to the backing, see
+    /// `CacheBacking::remove_expired`. Only available with the `test-util` feature.
+    #[cfg(any(test, feature = "test-util"))]
+    pub async fn sweep_expired(&self) -> Result<(), CacheLoadingError<E>> {
+        self.inspect_backing(|backing| backing.remove_expired()).await?
+            .map_err(CacheLoadingError::BackingError)
+    }
+
+    /// Returns a copy of the raw `CacheEntry` of the key, which tells whether the key is loaded or
+    /// still loading. Only available with the `test-util` feature.
+    #[cfg(any(test, feature = "test-util"))]
+    pub async fn entry_state(&self, key: K) -> Result<Option<CacheEntry<V, E>>, CacheLoadingError<E>> {
+        self.inspect_backing(move |backing| backing.get(&key).map(|entry| entry.cloned())).await?
+            .map_err(CacheLoadingError::BackingError)
+    }
+
+    async fn inspect_backing<R, F>(&self, inspect: F) -> Result<R, CacheLoadingError<E>>
+        where R: Send + 'static,
+              F: FnOnce(&mut B) -> R + Send + 'static {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.send_cache_action(CacheAction::WithBacking(Box::new(move |backing| {
+            let _ = tx.send(inspect(backing));
+        }))).await?;
+        rx.await
+            .map_err(|err| CacheLoadingError::CommunicationError(CacheCommunicationError::TokioOneshotRecvError(err)))
+    }
+
+    /// Sends a raw `CacheAction` to the cache and returns the unprocessed `CacheResult`
+    ///
+    /// This is an escape hatch for operations which aren't covered by the other methods. The
+    /// actions are an implementation detail of the cache, so this API is only available with the
+    /// `unstable` feature and may change in any release. Internal actions, like
+    /// `CacheAction::SetAndUnblock`, must be used with care as they can break the loading of keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The action which should be executed by the cache
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - The `CacheResult` of the action, a `CacheResult::Loading` has to be awaited by the
+    ///      caller
+    /// Err - Error of type CacheLoadingError::CommunicationError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::{CacheAction, CacheResult, LoadingCache};
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     match cache.send_action(CacheAction::Get("foo".to_owned())).await.unwrap() {
+    ///         CacheResult::Loading(handle) => assert_eq!(handle.await.unwrap(), 3),
+    ///         _ => panic!("the key hasn't been loaded yet"),
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "unstable")]
+    pub async fn send_action(&self, action: CacheAction<K, V, E, B>) -> Result<CacheResult<V, E>, CacheLoadingError<E>> {
+        let (tx, rx) = self.responses.channel();
+        self.send_message(CacheMessage::new(action, tx)).await?;
+        rx.await
+            .map_err(|err| CacheLoadingError::CommunicationError(CacheCommunicationError::TokioOneshotRecvError(err)))
+    }
+
+    /// Sends several raw `CacheAction`s to the cache, which are executed one after another without
+    /// other operations in between, and returns their unprocessed `CacheResult`s
+    ///
+    /// Callers which always perform the same sequence of operations save a round trip to the actor
+    /// per action. Like `send_action`, this API is only available with the `unstable` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `actions` - The actions which should be executed by the cache, in order
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - The `CacheResult`s of the actions in their order, a `CacheResult::Loading` has to be
+    ///      awaited by the caller
+    /// Err - Error of type CacheLoadingError::CommunicationError
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::{CacheAction, CacheResult, LoadingCache};
+    /// async fn example() {
+    ///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+    ///         async move {
+    ///             Ok(key.len())
+    ///         }
+    ///     });
+    ///
+    ///     let results = cache.send_actions(vec![
+    ///         CacheAction::GetIfPresent("foo".to_owned()),
+    ///         CacheAction::Set("foo".to_owned(), 5, None),
+    ///         CacheAction::GetIfPresent("foo".to_owned()),
+    ///     ]).await.unwrap();
+    ///     assert!(matches!(results[..], [CacheResult::None, CacheResult::None, CacheResult::Found(5)]));
+    /// }
+    /// ```
+    #[cfg(feature = "unstable")]
+    pub async fn send_actions(&self, actions: Vec<CacheAction<K, V, E, B>>) -> Result<Vec<CacheResult<V, E>>, CacheLoadingError<E>> {
+        match self.send_action(CacheAction::Pipeline(actions)).await? {
+            CacheResult::Pipeline(results) => Ok(results),
+            _ => Err(CacheLoadingError::CommunicationError(CacheCommunicationError::LookupLoop())),
+        }
+    }
+
+    async fn until<T, O, C>(operation: O, cancelled: C) -> Result<T, CacheLoadingError<E>>
+        where O: Future<Output=Result<T, CacheLoadingError<E>>>,
+              C: Future<Output=()> {
+        tokio::select! {
+            result = operation => result,
+            _ = cancelled => Err(CacheLoadingError::Cancelled()),
+        }
+    }
+
+    pub(crate) async fn send_cache_action(&self, action: CacheAction<K, V, E, B>) -> Result<Option<ResultMeta<V>>, CacheLoadingError<E>> {
+        let (tx, rx) = self.responses.channel();
+        self.send_message(CacheMessage::new(action, tx)).await?;
+        // only once the operation is queued, so the operations of a caller keep their order
+        #[cfg(feature = "chaos")]
+        crate::chaos::point().await;
+        Self::receive_cache_result(rx).await
+    }
+
+    /// Sends the message to the actor, a full queue is handled according to the `Backpressure`
+    /// of the cache
+    async fn send_message(&self, message: CacheMessage<K, V, E, B>) -> Result<(), CacheLoadingError<E>> {
+        let sent = match self.backpressure {
+            Backpressure::Fail => match self.tx.try_send(message) {
+                Err(TrySendError::Full(_)) => return Err(CacheLoadingError::Busy()),
+                sent => sent.is_ok(),
+            },
+            Backpressure::Wait | Backpressure::Unbounded => self.tx.send(message).await.is_ok(),
+        };
+        if sent {
+            Ok(())
+        } else {
+            Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioMpscSendError()))
+        }
+    }
+
+    async fn try_send_cache_action(&self, action: CacheAction<K, V, E, B>) -> Result<Option<ResultMeta<V>>, CacheLoadingError<E>> {
+        let (tx, rx) = self.responses.channel();
+        match self.tx.try_send(CacheMessage::new(action, tx)) {
+            Ok(_) => Self::receive_cache_result(rx).await,
+            Err(TrySendError::Full(_)) => Err(CacheLoadingError::Busy()),
+            Err(TrySendError::Closed(_)) => {
                 Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioMpscSendError()))
             }
         }
     }
+
+    async fn receive_cache_result(rx: ResponseReceiver<CacheResult<V, E>>) -> Result<Option<ResultMeta<V>>, CacheLoadingError<E>> {
+        match rx.await {
+            Ok(result) => Self::resolve_cache_result(result).await,
+            Err(err) => {
+                Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioOneshotRecvError(err)))
+            }
+        }
+    }
+
+    async fn resolve_cache_result(result: CacheResult<V, E>) -> Result<Option<ResultMeta<V>>, CacheLoadingError<E>> {
+        match result {
+            CacheResult::Found(value) | CacheResult::Stale(value, _) => {
+                Ok(Some(ResultMeta {
+                    result: value,
+                    cached: true,
+                }))
+            }
+            CacheResult::Loading(handle) => {
+                handle.await.map(|v| Some(ResultMeta {
+                    result: v,
+                    cached: false,
+                }))
+            }
+            CacheResult::None => { Ok(None) }
+            CacheResult::Error(err) => {
+                Err(CacheLoadingError::BackingError(err))
+            }
+            // only answers pipelines, which are resolved by their caller
+            CacheResult::Pipeline(_) => Err(CacheLoadingError::CommunicationError(CacheCommunicationError::LookupLoop())),
+        }
+    }
 }
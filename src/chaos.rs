@@ -0,0 +1,65 @@
+use std::sync::{Mutex, OnceLock};
+use crate::rng::XorShift;
+
+// the sequence deciding what happens at the chaos points, `None` while they are off
+static RNG: OnceLock<Mutex<Option<XorShift>>> = OnceLock::new();
+
+/// The environment variable which turns on the chaos points with a seed, to reproduce a failing
+/// run
+pub const SEED_VARIABLE: &str = "CACHE_LOADER_CHAOS_SEED";
+
+/// Turns on the chaos points, or restarts the sequence deciding what happens at them, with the
+/// seed
+///
+/// The chaos points are off until `CACHE_LOADER_CHAOS_SEED` is set or this is called, so the
+/// `chaos` feature can be enabled along with all other features, e.g. by `--all-features`,
+/// without affecting the cache. The sequence is shared by all caches of the process, so a seed
+/// only reproduces a run if the scheduling of the tasks is deterministic as well, e.g. on a
+/// `current_thread` runtime.
+pub fn reseed(seed: u64) {
+    *rng().lock().unwrap() = Some(XorShift::new(seed));
+}
+
+fn rng() -> &'static Mutex<Option<XorShift>> {
+    RNG.get_or_init(|| {
+        let seed = std::env::var(SEED_VARIABLE).ok()
+            .and_then(|seed| seed.parse().ok());
+        Mutex::new(seed.map(XorShift::new))
+    })
+}
+
+/// Returns the next number of the sequence, `None` while the chaos points are off
+fn draw() -> Option<u64> {
+    rng().lock().unwrap().as_mut().map(XorShift::next_u64)
+}
+
+/// A point where other tasks may run first
+///
+/// Half of the time nothing happens, otherwise the task yields up to eight times. Yielding
+/// doesn't advance a paused clock, but on a running clock the task continues only once the
+/// tasks which ran meanwhile yield back.
+pub(crate) async fn point() {
+    let sample = match draw() {
+        Some(sample) if !sample.is_multiple_of(2) => sample,
+        _ => return,
+    };
+    for _ in 0..=(sample >> 1) % 8 {
+        tokio::task::yield_now().await;
+    }
+}
+
+/// Whether a message should take a path which reorders it, e.g. a load completion sent through
+/// the queue of the operations instead of the lane ahead of them, one in four times
+pub(crate) fn reorder() -> bool {
+    draw().is_some_and(|sample| sample.is_multiple_of(4))
+}
+
+/// Shuffles messages which were queued concurrently, like the batch the actor received
+pub(crate) fn shuffle<T>(messages: &mut [T]) {
+    for index in (1..messages.len()).rev() {
+        match draw() {
+            Some(sample) => messages.swap(index, (sample % (index as u64 + 1)) as usize),
+            None => return,
+        }
+    }
+}
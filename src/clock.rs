@@ -0,0 +1,181 @@
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{Duration, Instant, MissedTickBehavior};
+
+/// A source of the current time, used by the `TtlCacheBacking` to expire entries
+pub trait Clock: Send {
+    /// The points in time returned by the clock, which the backing stores as expiries
+    type Time: ExpiryTime;
+
+    fn now(&self) -> Self::Time;
+}
+
+/// A point in time entries can expire at, implemented for the monotonic `Instant` and the
+/// wall-clock `SystemTime`
+pub trait ExpiryTime: Copy + Ord + Debug + Send + Sync + 'static {
+    /// Returns the point in time `duration` after this one
+    fn add_duration(self, duration: Duration) -> Self;
+
+    /// Returns the time elapsed since `earlier`, or zero if `earlier` is later than this point
+    fn saturating_duration_since(self, earlier: Self) -> Duration;
+}
+
+impl ExpiryTime for Instant {
+    fn add_duration(self, duration: Duration) -> Self {
+        self + duration
+    }
+
+    fn saturating_duration_since(self, earlier: Self) -> Duration {
+        Instant::saturating_duration_since(&self, earlier)
+    }
+}
+
+impl ExpiryTime for SystemTime {
+    fn add_duration(self, duration: Duration) -> Self {
+        self + duration
+    }
+
+    fn saturating_duration_since(self, earlier: Self) -> Duration {
+        self.duration_since(earlier).unwrap_or(Duration::ZERO)
+    }
+}
+
+/// The default `Clock`, based on `tokio::time::Instant`
+///
+/// It follows the time of the tokio runtime, so expiry can be tested with `tokio::time::pause`
+/// and `tokio::time::advance`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    type Time = Instant;
+
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A `Clock` which only moves when it is advanced manually
+///
+/// Clones share the same time, so a clone can be kept to control the clock of a backing.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManualClock {
+    /// Creates a clock starting at the current time
+    pub fn new() -> ManualClock {
+        Self::starting_at(Instant::now())
+    }
+
+    pub fn starting_at(now: Instant) -> ManualClock {
+        ManualClock {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+
+    pub fn set(&self, now: Instant) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Clock for ManualClock {
+    type Time = Instant;
+
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// A `Clock` which reads the time only once per `resolution`, in a background task
+///
+/// Reading this clock is a single atomic load, which avoids the overhead of reading the time in
+/// the hot path of caches with a high throughput. The time lags behind by up to the resolution,
+/// so entries may expire that much later. Clones share the same time. Must be created within a
+/// tokio runtime, the task ends once all clones are dropped.
+#[derive(Debug, Clone)]
+pub struct CoarseClock {
+    start: Instant,
+    elapsed_nanos: Arc<AtomicU64>,
+}
+
+impl CoarseClock {
+    pub fn new(resolution: Duration) -> CoarseClock {
+        let start = Instant::now();
+        let elapsed_nanos = Arc::new(AtomicU64::new(0));
+        let updated = Arc::downgrade(&elapsed_nanos);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(resolution);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match updated.upgrade() {
+                    Some(elapsed_nanos) => {
+                        elapsed_nanos.store(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                    }
+                    None => break,
+                }
+            }
+        });
+        CoarseClock {
+            start,
+            elapsed_nanos,
+        }
+    }
+}
+
+impl Clock for CoarseClock {
+    type Time = Instant;
+
+    fn now(&self) -> Instant {
+        self.start + Duration::from_nanos(self.elapsed_nanos.load(Ordering::Relaxed))
+    }
+}
+
+/// A `Clock` based on the wall-clock time, `std::time::SystemTime`
+///
+/// Unlike `Instant`s, wall-clock times mean the same after a restart and in other processes, so
+/// the expiries stored in a persistent or shared backing stay valid. The system time can be
+/// adjusted though: when it jumps forward, entries expire early. When it jumps backwards, the
+/// clock holds the latest time it returned until the system time catches up, so expired entries
+/// never come back and no entry lives longer than its TTL. Clones share the latest time.
+#[derive(Debug, Clone, Default)]
+pub struct SystemClock {
+    latest_nanos: Arc<AtomicU64>,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        Self::default()
+    }
+
+    /// Returns `now`, or the latest time returned before if the system time went backwards
+    pub(crate) fn observe(&self, now: SystemTime) -> SystemTime {
+        let nanos = now.duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_nanos() as u64)
+            .unwrap_or(0);
+        let latest = self.latest_nanos.fetch_max(nanos, Ordering::Relaxed).max(nanos);
+        UNIX_EPOCH + Duration::from_nanos(latest)
+    }
+}
+
+impl Clock for SystemClock {
+    type Time = SystemTime;
+
+    fn now(&self) -> SystemTime {
+        self.observe(SystemTime::now())
+    }
+}
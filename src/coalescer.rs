@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use futures::Future;
+use tokio::sync::broadcast;
+use crate::cache_api::{CacheCommunicationError, CacheLoadingError};
+
+type InFlight<K, V, E> = Arc<Mutex<HashMap<K, broadcast::Sender<Result<V, E>>>>>;
+
+/// Deduplicates concurrent calls for the same key without storing their results
+///
+/// This is the single-flight behaviour of `LoadingCache` on its own: while a call for a key is
+/// running, further calls for that key wait for its result instead of starting another call. Once
+/// the call finished, the next call for the key starts a new one.
+///
+/// Calls are spawned as tasks, so they complete even if the caller which started them is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::coalescer::Coalescer;
+/// async fn example() {
+///     let coalescer: Coalescer<String, usize, u8> = Coalescer::new();
+///
+///     let (first, second) = tokio::join!(
+///         coalescer.call("foo".to_owned(), || async move { Ok(3) }),
+///         coalescer.call("foo".to_owned(), || async move { Ok(4) }), // not called
+///     );
+///
+///     assert_eq!(first.unwrap(), 3);
+///     assert_eq!(second.unwrap(), 3);
+/// }
+/// ```
+pub struct Coalescer<K, V, E> {
+    in_flight: InFlight<K, V, E>,
+}
+
+impl<K, V, E> Clone for Coalescer<K, V, E> {
+    fn clone(&self) -> Self {
+        Self {
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+    E: Clone + Send + Debug + 'static,
+> Default for Coalescer<K, V, E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+    E: Clone + Send + Debug + 'static,
+> Coalescer<K, V, E> {
+    pub fn new() -> Coalescer<K, V, E> {
+        Coalescer {
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Calls `call_fn` for the key, unless a call for the key is already running, in which case
+    /// its result is awaited instead
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which identifies identical calls
+    /// * `call_fn` - A function which returns a Future<Output=Result<V, E>>, only invoked if no
+    ///   call for the key is running
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V
+    /// Err - Error of type CacheLoadingError::LoadingError with the error of the call or
+    ///       CacheLoadingError::CommunicationError if the call panicked
+    pub async fn call<F, Fut>(&self, key: K, call_fn: F) -> Result<V, CacheLoadingError<E>>
+        where F: FnOnce() -> Fut,
+              Fut: Future<Output=Result<V, E>> + Send + 'static {
+        let mut rx = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(&key) {
+                Some(tx) => tx.subscribe(),
+                None => {
+                    let (tx, rx) = broadcast::channel(1);
+                    in_flight.insert(key.clone(), tx.clone());
+                    let guard = InFlightGuard {
+                        key,
+                        in_flight: self.in_flight.clone(),
+                    };
+                    let future = call_fn();
+                    tokio::spawn(async move {
+                        let result = future.await;
+                        // calls starting from now on must not subscribe anymore
+                        drop(guard);
+                        tx.send(result).ok();
+                    });
+                    rx
+                }
+            }
+        };
+        match rx.recv().await {
+            Ok(result) => result.map_err(CacheLoadingError::LoadingError),
+            Err(err) => Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioBroadcastRecvError(err))),
+        }
+    }
+
+    /// Returns the amount of keys with a running call
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
+    }
+}
+
+/// Removes the key of a call once it completed or panicked
+struct InFlightGuard<K: Eq + Hash, V, E> {
+    key: K,
+    in_flight: InFlight<K, V, E>,
+}
+
+impl<K: Eq + Hash, V, E> Drop for InFlightGuard<K, V, E> {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.in_flight.lock() {
+            in_flight.remove(&self.key);
+        }
+    }
+}
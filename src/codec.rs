@@ -0,0 +1,216 @@
+use thiserror::Error;
+
+/// Converts values from and to bytes, used by backings which persist values or store them
+/// remotely.
+///
+/// Implementations based on serde are available with the `json-codec`, `bincode-codec` and
+/// `msgpack-codec` features.
+pub trait ValueCodec<V>: Send + Sync {
+    fn encode(&self, value: &V) -> Result<Vec<u8>, CodecError>;
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError>;
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum CodecError {
+    #[error("Failed to encode value: {0}")]
+    Encode(String),
+    #[error("Failed to decode value: {0}")]
+    Decode(String),
+    #[error("Failed to transform value: {0}")]
+    Transform(String),
+}
+
+/// Transforms the encoded bytes of values before a backing stores them and reverts it when
+/// they are read, e.g. to encrypt cached personal data at rest with a key of the application.
+///
+/// Backings taking a `ValueCodec` apply it through a `TransformedCodec`, so they don't need to
+/// support it themselves.
+pub trait EntryTransform: Send + Sync {
+    fn encrypt(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CodecError>;
+    fn decrypt(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError>;
+}
+
+/// A `ValueCodec` applying an `EntryTransform` to the bytes of another codec
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TransformedCodec<C, T> {
+    codec: C,
+    transform: T,
+}
+
+impl<C, T: EntryTransform> TransformedCodec<C, T> {
+    pub fn new(codec: C, transform: T) -> Self {
+        Self { codec, transform }
+    }
+}
+
+impl<V, C: ValueCodec<V>, T: EntryTransform> ValueCodec<V> for TransformedCodec<C, T> {
+    fn encode(&self, value: &V) -> Result<Vec<u8>, CodecError> {
+        self.transform.encrypt(self.codec.encode(value)?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError> {
+        self.codec.decode(&self.transform.decrypt(bytes)?)
+    }
+}
+
+/// Encodes values as JSON using `serde_json`
+#[cfg(feature = "json-codec")]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct JsonCodec;
+
+#[cfg(feature = "json-codec")]
+impl<V: serde::Serialize + serde::de::DeserializeOwned> ValueCodec<V> for JsonCodec {
+    fn encode(&self, value: &V) -> Result<Vec<u8>, CodecError> {
+        serde_json::to_vec(value)
+            .map_err(|err| CodecError::Encode(err.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError> {
+        serde_json::from_slice(bytes)
+            .map_err(|err| CodecError::Decode(err.to_string()))
+    }
+}
+
+/// Encodes values with `bincode`
+#[cfg(feature = "bincode-codec")]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "bincode-codec")]
+impl<V: serde::Serialize + serde::de::DeserializeOwned> ValueCodec<V> for BincodeCodec {
+    fn encode(&self, value: &V) -> Result<Vec<u8>, CodecError> {
+        bincode::serialize(value)
+            .map_err(|err| CodecError::Encode(err.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError> {
+        bincode::deserialize(bytes)
+            .map_err(|err| CodecError::Decode(err.to_string()))
+    }
+}
+
+/// Encodes values as MessagePack using `rmp-serde`
+///
+/// Structs are encoded as maps, so fields can be added to a value type without invalidating
+/// already stored values.
+#[cfg(feature = "msgpack-codec")]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MessagePackCodec;
+
+#[cfg(feature = "msgpack-codec")]
+impl<V: serde::Serialize + serde::de::DeserializeOwned> ValueCodec<V> for MessagePackCodec {
+    fn encode(&self, value: &V) -> Result<Vec<u8>, CodecError> {
+        rmp_serde::to_vec_named(value)
+            .map_err(|err| CodecError::Encode(err.to_string()))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError> {
+        rmp_serde::from_slice(bytes)
+            .map_err(|err| CodecError::Decode(err.to_string()))
+    }
+}
+
+/// The algorithm a `CompressedCodec` compresses large values with
+#[cfg(any(feature = "lz4-compression", feature = "zstd-compression"))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    /// Fast compression with `lz4_flex` (feature `lz4-compression`)
+    #[cfg(feature = "lz4-compression")]
+    Lz4,
+    /// Compression with `zstd` at the given level, trading CPU for smaller values than lz4
+    /// (feature `zstd-compression`)
+    #[cfg(feature = "zstd-compression")]
+    Zstd { level: i32 },
+}
+
+#[cfg(any(feature = "lz4-compression", feature = "zstd-compression"))]
+const UNCOMPRESSED: u8 = 0;
+#[cfg(feature = "lz4-compression")]
+const LZ4: u8 = 1;
+#[cfg(feature = "zstd-compression")]
+const ZSTD: u8 = 2;
+
+/// A `ValueCodec` compressing the bytes of another codec once they exceed a size threshold,
+/// so persistent and remote backings fit more large values like JSON or HTML payloads.
+///
+/// The in-memory backings hand out references to their values, so they keep them decoded.
+/// Encoded values start with a byte naming their compression, so values stored with another
+/// compression or threshold can still be decoded. Wrap it in a `TransformedCodec` to encrypt
+/// the compressed bytes.
+#[cfg(any(feature = "lz4-compression", feature = "zstd-compression"))]
+#[derive(Debug, Copy, Clone)]
+pub struct CompressedCodec<C> {
+    codec: C,
+    compression: Compression,
+    threshold: usize,
+}
+
+#[cfg(any(feature = "lz4-compression", feature = "zstd-compression"))]
+impl<C> CompressedCodec<C> {
+    /// Compresses encoded values of at least 1 KiB
+    pub fn new(codec: C, compression: Compression) -> Self {
+        Self {
+            codec,
+            compression,
+            threshold: 1024,
+        }
+    }
+
+    /// Sets the encoded size from which values are compressed
+    pub fn threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+        match self.compression {
+            #[cfg(feature = "lz4-compression")]
+            Compression::Lz4 => {
+                let mut compressed = vec![LZ4];
+                compressed.extend(lz4_flex::compress_prepend_size(bytes));
+                Ok(compressed)
+            }
+            #[cfg(feature = "zstd-compression")]
+            Compression::Zstd { level } => {
+                let mut compressed = vec![ZSTD];
+                compressed.extend(zstd::bulk::compress(bytes, level)
+                    .map_err(|err| CodecError::Encode(err.to_string()))?);
+                Ok(compressed)
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "lz4-compression", feature = "zstd-compression"))]
+impl<V, C: ValueCodec<V>> ValueCodec<V> for CompressedCodec<C> {
+    fn encode(&self, value: &V) -> Result<Vec<u8>, CodecError> {
+        let bytes = self.codec.encode(value)?;
+        if bytes.len() >= self.threshold {
+            return self.compress(&bytes);
+        }
+        let mut encoded = Vec::with_capacity(bytes.len() + 1);
+        encoded.push(UNCOMPRESSED);
+        encoded.extend(bytes);
+        Ok(encoded)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<V, CodecError> {
+        match bytes.split_first() {
+            Some((&UNCOMPRESSED, bytes)) => self.codec.decode(bytes),
+            #[cfg(feature = "lz4-compression")]
+            Some((&LZ4, bytes)) => {
+                let bytes = lz4_flex::decompress_size_prepended(bytes)
+                    .map_err(|err| CodecError::Decode(err.to_string()))?;
+                self.codec.decode(&bytes)
+            }
+            #[cfg(feature = "zstd-compression")]
+            Some((&ZSTD, bytes)) => {
+                let bytes = zstd::stream::decode_all(bytes)
+                    .map_err(|err| CodecError::Decode(err.to_string()))?;
+                self.codec.decode(&bytes)
+            }
+            Some((compression, _)) => Err(CodecError::Decode(format!("Unknown compression {}", compression))),
+            None => Err(CodecError::Decode("Missing compression header".to_owned())),
+        }
+    }
+}
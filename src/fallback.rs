@@ -0,0 +1,142 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, CacheLoadingError, LoadingCache};
+
+/// A handle reading a `LoadingCache` and falling back to a second cache on misses, e.g. a small
+/// local cache in front of a cache shared by several services
+///
+/// Created by `LoadingCache::with_fallback`, see there for details.
+pub struct FallbackLoadingCache<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>,
+    F: CacheBacking<K, CacheEntry<V, E>>
+> {
+    front: LoadingCache<K, V, E, B>,
+    fallback: LoadingCache<K, V, E, F>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+    F: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> Clone for FallbackLoadingCache<K, V, E, B, F> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+    F: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> FallbackLoadingCache<K, V, E, B, F> {
+    pub(crate) fn new(front: LoadingCache<K, V, E, B>, fallback: LoadingCache<K, V, E, F>) -> Self {
+        Self {
+            front,
+            fallback,
+        }
+    }
+
+    /// The cache which is checked first
+    pub fn front(&self) -> &LoadingCache<K, V, E, B> {
+        &self.front
+    }
+
+    /// The cache which is checked if the front cache misses
+    pub fn fallback(&self) -> &LoadingCache<K, V, E, F> {
+        &self.fallback
+    }
+
+    /// Retrieves the value from the front cache, or gets it from the fallback cache, which loads
+    /// it on a miss, and sets it in the front cache
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be loaded
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V
+    /// Err - Error of type CacheLoadingError
+    pub async fn get(&self, key: K) -> Result<V, CacheLoadingError<E>> {
+        if let Some(value) = self.front.get_if_present(key.clone()).await? {
+            return Ok(value);
+        }
+        let value = self.fallback.get(key.clone()).await?;
+        self.front.set(key, value.clone()).await?;
+        Ok(value)
+    }
+
+    /// Retrieves the value from the front cache, or from the fallback cache without loading it,
+    /// setting a value of the fallback cache in the front cache
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be retrieved
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V wrapped in an Option depending whether either cache has the key
+    /// Err - Error of type CacheLoadingError
+    pub async fn get_if_present(&self, key: K) -> Result<Option<V>, CacheLoadingError<E>> {
+        if let Some(value) = self.front.get_if_present(key.clone()).await? {
+            return Ok(Some(value));
+        }
+        match self.fallback.get_if_present(key.clone()).await? {
+            Some(value) => {
+                self.front.set(key, value.clone()).await?;
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Sets the value in both caches, returning the previous value of the fallback cache
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be set
+    /// * `value` - The value which should be set
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Previous value of type V of the fallback cache wrapped in an Option depending whether
+    ///      there was a previous value
+    /// Err - Error of type CacheLoadingError
+    pub async fn set(&self, key: K, value: V) -> Result<Option<V>, CacheLoadingError<E>> {
+        let previous = self.fallback.set(key.clone(), value.clone()).await?;
+        self.front.set(key, value).await?;
+        Ok(previous)
+    }
+
+    /// Removes the key from both caches, returning the value of the fallback cache
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key which should be removed
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - Value of type V of the fallback cache wrapped in an Option depending whether there was
+    ///      a value
+    /// Err - Error of type CacheLoadingError
+    pub async fn remove(&self, key: K) -> Result<Option<V>, CacheLoadingError<E>> {
+        let removed = self.fallback.remove(key.clone()).await?;
+        self.front.remove(key).await?;
+        Ok(removed)
+    }
+}
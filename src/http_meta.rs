@@ -0,0 +1,102 @@
+use std::time::{Duration, SystemTime};
+use crate::backing::NoMeta;
+#[cfg(feature = "ttl-cache")]
+use crate::backing::TtlMeta;
+#[cfg(feature = "lru-cache")]
+use crate::backing::LruMeta;
+
+/// HTTP caching validators and freshness information of a value
+///
+/// Used by caches created with `LoadingCache::with_conditional_loader`, whose loader can
+/// revalidate a previously loaded value instead of fully reloading it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HttpMeta {
+    /// The `ETag` of the value
+    pub etag: Option<String>,
+    /// The `Last-Modified` time of the value
+    pub last_modified: Option<SystemTime>,
+    /// The `max-age` of the value, after which it should be revalidated
+    pub max_age: Option<Duration>,
+}
+
+impl HttpMeta {
+    pub fn new() -> HttpMeta {
+        Default::default()
+    }
+
+    pub fn with_etag<S: Into<String>>(mut self, etag: S) -> HttpMeta {
+        self.etag = Some(etag.into());
+        self
+    }
+
+    pub fn with_last_modified(mut self, last_modified: SystemTime) -> HttpMeta {
+        self.last_modified = Some(last_modified);
+        self
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> HttpMeta {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+/// A value together with the `HttpMeta` it has been loaded with
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Validated<V> {
+    pub value: V,
+    pub meta: HttpMeta,
+}
+
+impl<V> Validated<V> {
+    /// A newly loaded (modified) value
+    pub fn modified(value: V, meta: HttpMeta) -> Validated<V> {
+        Validated {
+            value,
+            meta,
+        }
+    }
+
+    /// Keeps the previous value, as the origin reported it as not modified
+    ///
+    /// Validators missing in `meta` are kept from the previous meta, the `max_age` is always
+    /// replaced.
+    pub fn not_modified(self, meta: HttpMeta) -> Validated<V> {
+        Validated {
+            value: self.value,
+            meta: HttpMeta {
+                etag: meta.etag.or(self.meta.etag),
+                last_modified: meta.last_modified.or(self.meta.last_modified),
+                max_age: meta.max_age,
+            },
+        }
+    }
+}
+
+/// Converts the `HttpMeta` of a loaded value into the meta of a `CacheBacking`
+pub trait FromHttpMeta: Sized {
+    fn from_http_meta(meta: &HttpMeta) -> Option<Self>;
+}
+
+impl FromHttpMeta for NoMeta {
+    fn from_http_meta(_: &HttpMeta) -> Option<Self> {
+        None
+    }
+}
+
+/// Uses the `max_age` as ttl, falling back to the default ttl of the backing
+#[cfg(feature = "ttl-cache")]
+impl FromHttpMeta for TtlMeta {
+    fn from_http_meta(meta: &HttpMeta) -> Option<Self> {
+        meta.max_age.map(TtlMeta::from)
+    }
+}
+
+/// Falls back to the weigher of the backing and leaves the entry unprotected
+#[cfg(feature = "lru-cache")]
+impl FromHttpMeta for LruMeta {
+    fn from_http_meta(_: &HttpMeta) -> Option<Self> {
+        None
+    }
+}
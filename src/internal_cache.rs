@@ -1,9 +1,110 @@
 use std::hash::Hash;
-use futures::Future;
-use tokio::task::JoinHandle;
-use crate::cache_api::{CacheResult, CacheLoadingError, CacheEntry, CacheCommunicationError, DataWithMeta};
-use crate::backing::CacheBacking;
+use futures::{Future, FutureExt, TryFutureExt};
+use futures::future::{BoxFuture, Either};
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio::sync::mpsc::error::TrySendError;
+use crate::cache_api::{CacheResult, CacheLoadingError, CacheEntry, CacheCommunicationError, DataWithMeta, EntryMeta, LoadFuture, Lookup, SwapPolicy};
+use crate::backing::{BackingPredicate, CacheBacking, ExportedEntry, RemovalCause, RemovalListener};
+use std::any::Any;
 use std::fmt::Debug;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use tokio::sync::Notify;
+use tokio::sync::oneshot::error::TryRecvError;
+use tokio::time::{Duration, Instant, Interval};
+use crate::options::{CacheOptions, ExecutionMode, OccupancyEvent, OccupancyLevel};
+use crate::stats::{ActorMetrics, CacheMetrics};
+use crate::response::{self, ResponseSender};
+use crate::queue::{QueueReceiver, QueueSender};
+use std::sync::{Arc, Mutex, Weak};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub(crate) type CachePredicate<K, V> = Box<dyn Fn((&K, Option<&V>)) -> bool + Send + Sync + 'static>;
+
+/// A load supplied by the caller instead of the loader function of the cache, which may find
+/// no value for the key
+pub(crate) type SuppliedLoad<K, V, E, B> = BoxFuture<'static, Result<Option<DataWithMeta<K, V, E, B>>, E>>;
+
+/// Loads several keys at once, see `LoadingCache::with_batch_loader`
+pub(crate) type BatchLoader<K, V, E> = Arc<dyn Fn(Vec<K>) -> BoxFuture<'static, Result<HashMap<K, V>, E>> + Send + Sync + 'static>;
+
+/// The hash identifying a key in task names and `CacheLoadingError::RecursiveLoad`
+pub(crate) fn key_hash<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Names a task of a cache for tokio-console, identifying the cache by its name and loads by the
+/// hash of their key
+pub(crate) fn task_name<K: Hash>(cache: Option<&str>, task: &str, key: Option<&K>) -> String {
+    let cache = cache.unwrap_or("unnamed");
+    match key {
+        Some(key) => format!("cache-loader-async:{}:{}:{:016x}", cache, task, key_hash(key)),
+        None => format!("cache-loader-async:{}:{}", cache, task),
+    }
+}
+
+/// Spawns a task, which is named if the `task-names` feature is enabled and the crate is built
+/// with `--cfg tokio_unstable`
+fn spawn_task<N, Fut>(name: N, future: Fut) -> JoinHandle<Fut::Output>
+    where N: FnOnce() -> String,
+          Fut: Future + Send + 'static,
+          Fut::Output: Send + 'static {
+    #[cfg(all(tokio_unstable, feature = "task-names"))]
+    {
+        tokio::task::Builder::new()
+            .name(&name())
+            .spawn(future)
+            .expect("Failed to spawn cache task")
+    }
+    #[cfg(not(all(tokio_unstable, feature = "task-names")))]
+    {
+        let _ = name;
+        tokio::spawn(future)
+    }
+}
+
+/// Identifies an operation sent to a cache, the loads it starts and their completions carry the
+/// same id, so the operation can be followed across tasks
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct OperationId(u64);
+
+impl OperationId {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        OperationId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl std::fmt::Display for OperationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "op-{}", self.0)
+    }
+}
+
+/// Identifies a store, so loads of one cache aren't mistaken for loads of another one with the
+/// same key type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct StoreId(u64);
+
+impl StoreId {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        StoreId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// The load a loader is running for, set while the loader is polled
+struct RunningLoad {
+    store: StoreId,
+    key: Box<dyn Any + Send>,
+}
+
+tokio::task_local! {
+    static RUNNING_LOAD: RunningLoad;
+}
 
 macro_rules! unwrap_backing {
     ($expr:expr) => {
@@ -14,23 +115,149 @@ macro_rules! unwrap_backing {
     }
 }
 
-pub(crate) enum CacheAction<
+/// An action executed by the actor of a cache
+///
+/// Exposed through `LoadingCache::send_action` with the `unstable` feature, the variants may change
+/// in any release.
+pub enum CacheAction<
     K: Clone + Eq + Hash + Send,
     V: Clone + Sized + Send,
     E: Debug + Clone + Send,
     B: CacheBacking<K, CacheEntry<V, E>>
 > {
     GetIfPresent(K),
+    /// Answers the closure with the value of the key and its `EntryMeta`
+    GetIfPresentWithMeta(K, Box<dyn FnOnce(Option<EntryMeta<V>>) + Send + 'static>),
+    /// Answers the closure with whether the key is cached, missing or loading
+    Lookup(K, Box<dyn FnOnce(Lookup<V>) + Send + 'static>),
+    PeekIfPresent(K),
     Get(K),
+    GetNoPromote(K),
+    GetAllowStale(K, Duration),
+    GetWith(K, SuppliedLoad<K, V, E, B>),
+    /// Gets the key like `Get`, a value loaded for it is set with the meta instead of the meta
+    /// returned by the loader
+    GetWithLoadMeta(K, B::Meta),
+    /// Gets the keys, loading the missing ones with a single call of the batch loader if there is
+    /// one, answered with a `CacheResult::Pipeline` in the order of the keys
+    GetAll(Vec<K>, Option<BatchLoader<K, V, E>>),
+    /// Calls the loader for the caller regardless of a cached value or a running load, setting
+    /// the loaded value if the flag is set
+    LoadIndependent(K, bool),
     Set(K, V, Option<B::Meta>),
+    /// Sets the entries in order, the entries of locked keys once their key is released
+    SetBatch(Vec<ExportedEntry<K, V, B::Meta>>),
+    /// Sets the value and answers with the previous one, a running load is handled according to
+    /// the `SwapPolicy`
+    Swap(K, V, SwapPolicy),
     Update(K, Option<B::Meta>, Box<dyn FnOnce(V) -> V + Send + 'static>, bool),
-    UpdateMut(K, Box<dyn FnMut(&mut V) -> () + Send + 'static>, bool),
+    UpdateMut(K, Box<dyn FnMut(&mut V) + Send + 'static>, bool),
     Remove(K),
-    RemoveIf(Box<dyn Fn((&K, Option<&V>)) -> bool + Send + Sync + 'static>),
+    RemoveIf(CachePredicate<K, V>),
     Clear(),
+    /// Executes the actions in order without other operations in between, answered with a
+    /// `CacheResult::Pipeline`
+    #[cfg(feature = "unstable")]
+    Pipeline(Vec<CacheAction<K, V, E, B>>),
     // Internal use
+    /// Locks the key for the operation, answered with its value once other operations on the
+    /// key are held back
+    Lock(K),
     SetAndUnblock(K, V, Option<B::Meta>),
     Unblock(K),
+    /// Releases the lock of the operation on the key, setting the value if there is one
+    Unlock(K, OperationId, Option<V>),
+    WithBacking(Box<dyn FnOnce(&mut B) + Send + 'static>),
+    /// Sets the value reloaded by a refresh, unless the key was written since the value the
+    /// refresh replaces was stored at the given time
+    Refreshed(K, Instant, Option<DataWithMeta<K, V, E, B>>),
+    /// Cancels the running loads, the actor processes the queued operations first and ends
+    /// before it answers
+    Shutdown(),
+}
+
+impl<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> CacheAction<K, V, E, B> {
+    /// Whether the action only produces a result for its caller, so it can be skipped once the
+    /// caller stopped waiting for it
+    fn is_read(&self) -> bool {
+        matches!(self,
+            CacheAction::GetIfPresent(_)
+            | CacheAction::GetIfPresentWithMeta(..)
+            | CacheAction::Lookup(..)
+            | CacheAction::PeekIfPresent(_)
+            | CacheAction::Get(_)
+            | CacheAction::GetNoPromote(_)
+            | CacheAction::GetAllowStale(..)
+            | CacheAction::GetWith(..)
+            | CacheAction::GetWithLoadMeta(..)
+            | CacheAction::GetAll(..)
+            | CacheAction::LoadIndependent(..)
+            // a lock whose caller is gone would never be released
+            | CacheAction::Lock(_))
+    }
+
+    /// The key the action operates on, actions on a locked key wait for its release
+    fn key(&self) -> Option<&K> {
+        match self {
+            CacheAction::GetIfPresent(key)
+            | CacheAction::GetIfPresentWithMeta(key, _)
+            | CacheAction::Lookup(key, _)
+            | CacheAction::PeekIfPresent(key)
+            | CacheAction::Get(key)
+            | CacheAction::GetNoPromote(key)
+            | CacheAction::GetAllowStale(key, _)
+            | CacheAction::GetWith(key, _)
+            | CacheAction::GetWithLoadMeta(key, _)
+            | CacheAction::LoadIndependent(key, _)
+            | CacheAction::Set(key, ..)
+            | CacheAction::Swap(key, ..)
+            | CacheAction::Update(key, ..)
+            | CacheAction::UpdateMut(key, ..)
+            | CacheAction::Remove(key)
+            | CacheAction::Refreshed(key, ..)
+            | CacheAction::Lock(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    fn name(&self) -> &'static str {
+        match self {
+            CacheAction::GetIfPresent(_) => "get_if_present",
+            CacheAction::GetIfPresentWithMeta(..) => "get_if_present_with_meta",
+            CacheAction::Lookup(..) => "lookup",
+            CacheAction::PeekIfPresent(_) => "peek_if_present",
+            CacheAction::Get(_) => "get",
+            CacheAction::GetNoPromote(_) => "get_no_promote",
+            CacheAction::GetAllowStale(..) => "get_allow_stale",
+            CacheAction::GetWith(..) => "get_with",
+            CacheAction::GetWithLoadMeta(..) => "get_with_load_meta",
+            CacheAction::GetAll(..) => "get_all",
+            CacheAction::LoadIndependent(..) => "load_independent",
+            CacheAction::Set(..) => "set",
+            CacheAction::SetBatch(_) => "set_batch",
+            CacheAction::Swap(..) => "swap",
+            CacheAction::Update(..) => "update",
+            CacheAction::UpdateMut(..) => "update_mut",
+            CacheAction::Remove(_) => "remove",
+            CacheAction::RemoveIf(_) => "remove_if",
+            CacheAction::Clear() => "clear",
+            #[cfg(feature = "unstable")]
+            CacheAction::Pipeline(_) => "pipeline",
+            CacheAction::Lock(_) => "lock",
+            CacheAction::SetAndUnblock(..) => "set_and_unblock",
+            CacheAction::Unblock(_) => "unblock",
+            CacheAction::Unlock(..) => "unlock",
+            CacheAction::WithBacking(_) => "with_backing",
+            CacheAction::Refreshed(..) => "refreshed",
+            CacheAction::Shutdown() => "shutdown",
+        }
+    }
 }
 
 pub(crate) struct CacheMessage<
@@ -40,7 +267,208 @@ pub(crate) struct CacheMessage<
     B: CacheBacking<K, CacheEntry<V, E>>
 > {
     pub(crate) action: CacheAction<K, V, E, B>,
-    pub(crate) response: tokio::sync::oneshot::Sender<CacheResult<V, E>>,
+    pub(crate) response: ResponseSender<CacheResult<V, E>>,
+    pub(crate) id: OperationId,
+    // when the message was sent, to measure how long it waited in the queue
+    pub(crate) sent: Instant,
+    // the load whose loader sent the message, to detect loads waiting for themselves
+    pub(crate) running_load: Option<(StoreId, K)>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> CacheMessage<K, V, E, B> {
+
+    /// Creates a message belonging to an existing operation, like the completion of a load
+    pub(crate) fn with_id(
+        action: CacheAction<K, V, E, B>,
+        response: ResponseSender<CacheResult<V, E>>,
+        id: OperationId,
+    ) -> Self {
+        Self {
+            action,
+            response,
+            id,
+            sent: Instant::now(),
+            running_load: None,
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> CacheMessage<K, V, E, B> {
+    /// Creates a message for a new operation, which belongs to the load of the loader sending it
+    pub(crate) fn new(action: CacheAction<K, V, E, B>, response: ResponseSender<CacheResult<V, E>>) -> Self {
+        let mut message = Self::with_id(action, response, OperationId::next());
+        message.running_load = RUNNING_LOAD.try_with(|load| {
+            load.key.downcast_ref::<K>().map(|key| (load.store, key.clone()))
+        }).ok().flatten();
+        message
+    }
+}
+
+/// The result of a message and the sender it's answered through
+pub(crate) type Response<V, E> = (ResponseSender<CacheResult<V, E>>, CacheResult<V, E>);
+
+/// Executes actions directly on a store, used by the inline execution mode
+pub(crate) trait CacheExecutor<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+>: Send {
+    /// Executes the message, `None` if it waits for the lock of its key
+    fn execute(&mut self, message: CacheMessage<K, V, E, B>) -> Option<Response<V, E>>;
+}
+
+pub(crate) type InlineStore<K, V, E, B> = Arc<Mutex<dyn CacheExecutor<K, V, E, B>>>;
+
+/// Holds the actor of a cache while it's parked by `CacheOptions::idle_park`, as a function
+/// spawning it again
+#[derive(Default)]
+pub(crate) struct ParkSlot {
+    parked: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
+
+impl ParkSlot {
+    pub(crate) fn is_parked(&self) -> bool {
+        self.parked.lock().unwrap().is_some()
+    }
+
+    /// Spawns the parked actor again, has no effect while it's running
+    fn wake(&self) {
+        let resume = self.parked.lock().unwrap().take();
+        if let Some(resume) = resume {
+            resume();
+        }
+    }
+}
+
+impl Debug for ParkSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParkSlot")
+            .field("parked", &self.is_parked())
+            .finish()
+    }
+}
+
+/// Delivers messages to a store, either through the channel of its actor or by executing them
+/// inline on the caller
+pub(crate) enum CacheSender<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> {
+    Channel(QueueSender<CacheMessage<K, V, E, B>>),
+    Inline(InlineStore<K, V, E, B>),
+    // the channel of an actor which parks when idle, every message wakes it up
+    Parking(QueueSender<CacheMessage<K, V, E, B>>, Weak<ParkSlot>),
+    // held by the store itself and its loads, so the store is dropped with the last cache handle
+    WeakInline(Weak<Mutex<dyn CacheExecutor<K, V, E, B>>>),
+}
+
+impl<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> Clone for CacheSender<K, V, E, B> {
+    fn clone(&self) -> Self {
+        match self {
+            CacheSender::Channel(tx) => CacheSender::Channel(tx.clone()),
+            CacheSender::Inline(store) => CacheSender::Inline(store.clone()),
+            CacheSender::Parking(tx, slot) => CacheSender::Parking(tx.clone(), slot.clone()),
+            CacheSender::WeakInline(store) => CacheSender::WeakInline(store.clone()),
+        }
+    }
+}
+
+impl<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> Debug for CacheSender<K, V, E, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheSender::Channel(tx) => f.debug_tuple("Channel").field(tx).finish(),
+            CacheSender::Inline(_) => f.write_str("Inline"),
+            CacheSender::Parking(tx, _) => f.debug_tuple("Parking").field(tx).finish(),
+            CacheSender::WeakInline(_) => f.write_str("WeakInline"),
+        }
+    }
+}
+
+impl<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> CacheSender<K, V, E, B> {
+    /// Sends the message, the message is returned if the store is gone
+    pub(crate) async fn send(&self, message: CacheMessage<K, V, E, B>) -> Result<(), CacheMessage<K, V, E, B>> {
+        match self {
+            CacheSender::Channel(tx) => tx.send(message).await,
+            CacheSender::Parking(tx, slot) => {
+                tx.send(message).await?;
+                Self::wake(slot);
+                Ok(())
+            }
+            _ => self.execute_inline(message),
+        }
+    }
+
+    /// Sends the message if the queue of the actor has capacity, inline execution never waits
+    pub(crate) fn try_send(&self, message: CacheMessage<K, V, E, B>) -> Result<(), TrySendError<CacheMessage<K, V, E, B>>> {
+        match self {
+            CacheSender::Channel(tx) => tx.try_send(message),
+            CacheSender::Parking(tx, slot) => {
+                tx.try_send(message)?;
+                Self::wake(slot);
+                Ok(())
+            }
+            _ => self.execute_inline(message).map_err(TrySendError::Closed),
+        }
+    }
+
+    /// The message is sent before the actor is woken up, an actor parking meanwhile sees it in
+    /// its queue and keeps running
+    fn wake(slot: &Weak<ParkSlot>) {
+        if let Some(slot) = slot.upgrade() {
+            slot.wake();
+        }
+    }
+
+    fn execute_inline(&self, message: CacheMessage<K, V, E, B>) -> Result<(), CacheMessage<K, V, E, B>> {
+        let store = match self {
+            CacheSender::Channel(_) | CacheSender::Parking(..) => None,
+            CacheSender::Inline(store) => Some(store.clone()),
+            CacheSender::WeakInline(store) => store.upgrade(),
+        };
+        let store = match store {
+            Some(store) => store,
+            None => return Err(message),
+        };
+        // a poisoned store is treated like an actor which died
+        let mut store = match store.lock() {
+            Ok(store) => store,
+            Err(_) => return Err(message),
+        };
+        let executed = store.execute(message);
+        std::mem::drop(store);
+        if let Some((response, result)) = executed {
+            response.send(result).ok();
+        }
+        Ok(())
+    }
 }
 
 pub(crate) struct InternalCacheStore<
@@ -50,9 +478,118 @@ pub(crate) struct InternalCacheStore<
     E: Debug + Clone + Send,
     B: CacheBacking<K, CacheEntry<V, E>>
 > {
-    tx: tokio::sync::mpsc::Sender<CacheMessage<K, V, E, B>>,
+    tx: CacheSender<K, V, E, B>,
+    // loads report their completion through a separate lane, which is processed first
+    completion_tx: CacheSender<K, V, E, B>,
     data: B,
     loader: T,
+    options: CacheOptions,
+    loading: HashMap<K, LoadTracker>,
+    // values of swaps which replaced a running load, the loader answers its caller with them
+    swapped: HashMap<K, V>,
+    locks: HashMap<K, KeyLock<K, V, E, B>>,
+    last_sweep: Instant,
+    last_expiry_sweep: Instant,
+    last_maintenance: Instant,
+    metrics: Arc<ActorMetrics>,
+    // the `CacheStats`, with `CacheOptions::record_stats`
+    stats: Option<Arc<CacheMetrics>>,
+    // when the entries were set and how often they were read, see `CacheOptions::track_entries`
+    entries: HashMap<K, EntryAccess>,
+    // the keys which are reloaded in the background, see `CacheOptions::refresh_after_write`
+    refreshing: HashSet<K>,
+    // the number of records at which records of keys the backing dropped are removed
+    prune_entries_at: usize,
+    // the last level of every `OccupancyWatch` of the options
+    occupancy_levels: Vec<OccupancyLevel>,
+    // the operation which is handled, loads are started on its behalf
+    operation: OperationId,
+    id: StoreId,
+    // the key whose loader sent the handled operation
+    running_load: Option<K>,
+    // the loads the loader of a loading key waits for, to detect cycles between loads
+    dependencies: HashMap<K, Vec<K>>,
+    // cancels every running load on shutdown
+    closing: Arc<Notify>,
+    removal_listener: Option<RemovalListener<K, V>>,
+}
+
+/// Tracks a running load for the loading watchdog
+struct LoadTracker {
+    started: Instant,
+    // the loader task holds the sender, it's dropped without a message once the task is gone
+    alive: tokio::sync::oneshot::Receiver<()>,
+    // the loader task still holds a broadcast sender, it must be gone for the waiters to wake up,
+    // inline loads have no task which could be aborted
+    abort: Option<AbortHandle>,
+}
+
+/// The record of an entry kept with `CacheOptions::track_entries`
+struct EntryAccess {
+    stored: Instant,
+    hits: u64,
+}
+
+/// Records are pruned once there are at least this many
+const MIN_PRUNE_ENTRIES: usize = 1024;
+
+/// A key locked by `LoadingCache::lock_key` and the operations waiting for it
+struct KeyLock<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> {
+    // the operation holding the lock, `None` while the lock waits for the running load of the key
+    holder: Option<OperationId>,
+    waiting: VecDeque<CacheMessage<K, V, E, B>>,
+}
+
+/// Unblocks the key of an inline load which is dropped before it completed, nothing else would
+/// drive the load to completion
+struct UnblockOnDrop<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+>{
+    tx: CacheSender<K, V, E, B>,
+    // taken once the load completed
+    key: Option<K>,
+    operation: OperationId,
+}
+
+impl<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> UnblockOnDrop<K, V, E, B> {
+    fn disarm(mut self) {
+        self.key = None;
+    }
+}
+
+impl<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> Drop for UnblockOnDrop<K, V, E, B> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            // inline execution never waits, the store is gone if it fails
+            let (response, _) = response::channel();
+            self.tx.try_send(CacheMessage::with_id(CacheAction::Unblock(key), response, self.operation)).ok();
+        }
+    }
+}
+
+impl LoadTracker {
+    fn is_stuck(&mut self, now: Instant, max_loading_age: Option<Duration>) -> bool {
+        matches!(self.alive.try_recv(), Err(TryRecvError::Closed))
+            || max_loading_age.is_some_and(|age| now.duration_since(self.started) >= age)
+    }
 }
 
 impl<
@@ -65,56 +602,668 @@ impl<
 > InternalCacheStore<K, V, T, E, B>
 {
     pub fn new(
-        backing: B,
-        tx: tokio::sync::mpsc::Sender<CacheMessage<K, V, E, B>>,
+        mut backing: B,
+        tx: CacheSender<K, V, E, B>,
+        completion_tx: CacheSender<K, V, E, B>,
         loader: T,
+        options: CacheOptions,
     ) -> Self {
+        let occupancy_levels = vec![OccupancyLevel::Normal; options.occupancy_watches.len()];
+        let removal_listener = options.removal_listener.as_ref()
+            .map(|listener| listener.downcast::<K, V>());
+        let stats = options.record_stats.then(|| Arc::new(CacheMetrics::default()));
+        if removal_listener.is_some() || stats.is_some() {
+            let listener = removal_listener.clone();
+            let recorder = stats.clone();
+            backing.set_removal_listener(Arc::new(move |key, entry, cause| {
+                if let Some(recorder) = &recorder {
+                    recorder.record_removal(cause);
+                }
+                // keys which were still loading have no value to report
+                if let (Some(listener), CacheEntry::Loaded(value)) = (&listener, entry) {
+                    listener(key, value, cause);
+                }
+            }));
+        }
         Self {
             tx,
+            completion_tx,
             data: backing,
             loader,
+            options,
+            loading: HashMap::new(),
+            swapped: HashMap::new(),
+            locks: HashMap::new(),
+            entries: HashMap::new(),
+            refreshing: HashSet::new(),
+            prune_entries_at: MIN_PRUNE_ENTRIES,
+            last_sweep: Instant::now(),
+            last_expiry_sweep: Instant::now(),
+            last_maintenance: Instant::now(),
+            metrics: Default::default(),
+            stats,
+            occupancy_levels,
+            operation: OperationId(0),
+            id: StoreId::next(),
+            running_load: None,
+            dependencies: HashMap::new(),
+            closing: Arc::new(Notify::new()),
+            removal_listener,
         }
     }
 
-    pub(crate) fn run(mut self, mut rx: tokio::sync::mpsc::Receiver<CacheMessage<K, V, E, B>>) -> JoinHandle<()> {
-        tokio::spawn(async move {
+    pub(crate) fn metrics(&self) -> Arc<ActorMetrics> {
+        self.metrics.clone()
+    }
+
+    pub(crate) fn stats(&self) -> Option<Arc<CacheMetrics>> {
+        self.stats.clone()
+    }
+
+    /// Creates a store for `ExecutionMode::Inline`, which holds a weak reference to itself to
+    /// send the results of its loads, and returns it with its `CacheMetrics`
+    pub(crate) fn inline(backing: B, loader: T, options: CacheOptions) -> (InlineStore<K, V, E, B>, Option<Arc<CacheMetrics>>) {
+        let store = Arc::new_cyclic(|store: &Weak<Mutex<Self>>| {
+            let store: Weak<Mutex<dyn CacheExecutor<K, V, E, B>>> = store.clone();
+            let tx = CacheSender::WeakInline(store);
+            Mutex::new(Self::new(backing, tx.clone(), tx, loader, options))
+        });
+        let stats = store.lock().unwrap().stats();
+        (store, stats)
+    }
+
+    /// Runs the actor, which processes the messages of the completion lane ahead of the queued
+    /// operations, so completed loads don't wait behind a backlog of reads
+    pub(crate) fn run(
+        mut self,
+        mut rx: QueueReceiver<CacheMessage<K, V, E, B>>,
+        mut completion_rx: QueueReceiver<CacheMessage<K, V, E, B>>,
+    ) -> JoinHandle<()> {
+        let name = task_name::<K>(self.options.name.as_deref(), "actor", None);
+        spawn_task(move || name, async move {
+            let mut watchdog = self.options.loading_watchdog
+                .map(|watchdog| tokio::time::interval(watchdog.interval));
+            let mut expiry_sweep = self.options.expiry_sweep
+                .map(tokio::time::interval);
+            let mut maintenance = self.options.maintenance
+                .map(tokio::time::interval);
+            let batch_size = self.options.batch_size.unwrap_or(1);
+            let mut batch = Vec::with_capacity(batch_size);
+            let mut last_message = Instant::now();
             loop {
-                if let Some(message) = rx.recv().await {
-                    let result = match message.action {
-                        CacheAction::GetIfPresent(key) => self.get_if_present(key),
-                        CacheAction::Get(key) => self.get(key),
-                        CacheAction::Set(key, value, meta) => self.set(key, value, false, meta),
-                        CacheAction::Update(key, meta, update_fn, load) => self.update(key, update_fn, load, meta),
-                        CacheAction::UpdateMut(key, update_mut_fn, load) => self.update_mut(key, update_mut_fn, load),
-                        CacheAction::Remove(key) => self.remove(key),
-                        CacheAction::RemoveIf(predicate) => self.remove_if(predicate),
-                        CacheAction::Clear() => self.clear(),
-                        CacheAction::SetAndUnblock(key, value, meta) => self.set(key, value, true, meta),
-                        CacheAction::Unblock(key) => self.unblock(key),
+                let mut idle = false;
+                let mut shutdown = None;
+                tokio::select! {
+                    biased;
+                    message = completion_rx.recv() => {
+                        last_message = Instant::now();
+                        if let Some((response, result)) = message.and_then(|message| self.process(message)) {
+                            response.send(result).ok();
+                        }
+                    }
+                    received = rx.recv_many(&mut batch, batch_size) => {
+                        last_message = Instant::now();
+                        // the responses are sent once the whole batch was executed
+                        let mut responses = Vec::with_capacity(received);
+                        #[cfg(feature = "chaos")]
+                        crate::chaos::shuffle(&mut batch);
+                        for message in batch.drain(..) {
+                            #[cfg(feature = "chaos")]
+                            crate::chaos::point().await;
+                            // reads of callers which stopped waiting would only start pointless loads
+                            if message.action.is_read() && message.response.is_closed() {
+                                continue;
+                            }
+                            if let CacheAction::Shutdown() = message.action {
+                                shutdown = Some(message.response);
+                                continue;
+                            }
+                            responses.extend(self.process(message));
+                        }
+                        for (response, result) in responses {
+                            response.send(result).ok();
+                        }
+                    }
+                    _ = Self::tick(&mut watchdog) => self.unblock_stuck_loads(),
+                    _ = Self::tick(&mut expiry_sweep) => self.remove_expired(),
+                    _ = Self::tick(&mut maintenance) => self.maintain(),
+                    _ = Self::idle(self.options.idle_park, last_message) => idle = true,
+                }
+                if let Some(response) = shutdown {
+                    // operations sent before the shutdown are still processed, later ones fail
+                    completion_rx.close();
+                    rx.close();
+                    self.drain(&mut completion_rx).await;
+                    self.drain(&mut rx).await;
+                    self.closing.notify_waiters();
+                    response.send(CacheResult::None).ok();
+                    return;
+                }
+                // a parked actor doesn't sweep, so it's woken up once the first entry expired
+                let mut sweep_after = None;
+                if idle && self.options.expiry_sweep.is_some() {
+                    match self.data.next_expiry() {
+                        Ok(next_expiry) => sweep_after = next_expiry,
+                        // without a deadline the entries left to expire would stay in memory
+                        Err(_) => {
+                            last_message = Instant::now();
+                            idle = false;
+                        }
+                    }
+                }
+                if idle {
+                    let slot = match &self.tx {
+                        CacheSender::Parking(_, slot) => slot.upgrade(),
+                        _ => None,
+                    };
+                    // every handle of the cache is gone, nobody could send another operation
+                    let slot = match slot {
+                        Some(slot) => slot,
+                        None => return,
                     };
-                    message.response.send(result).ok();
+                    let mut parked = slot.parked.lock().unwrap();
+                    // senders wake the actor after sending, so a message sent before it's parked
+                    // has to be processed by this run
+                    if rx.is_empty() && completion_rx.is_empty() {
+                        let runtime = tokio::runtime::Handle::current();
+                        *parked = Some(Box::new(move || {
+                            let _runtime = runtime.enter();
+                            self.run(rx, completion_rx);
+                        }));
+                        if let Some(sweep_after) = sweep_after {
+                            let slot = Arc::downgrade(&slot);
+                            tokio::spawn(async move {
+                                tokio::time::sleep(sweep_after).await;
+                                // the spawned actor sweeps right away
+                                if let Some(slot) = slot.upgrade() {
+                                    slot.wake();
+                                }
+                            });
+                        }
+                        return;
+                    }
+                    last_message = Instant::now();
                 }
             }
         })
     }
 
-    fn unblock(&mut self, key: K) -> CacheResult<V, E>{
-        if let Some(entry) = unwrap_backing!(self.data.get(&key)) {
-            if let CacheEntry::Loading(_) = entry {
-                if let Some(entry) = unwrap_backing!(self.data.remove(&key)) {
-                    if let CacheEntry::Loading(waiter) = entry {
-                        std::mem::drop(waiter) // dropping the sender closes the channel
-                    }
+    /// Processes the messages left in the closed queue
+    async fn drain(&mut self, rx: &mut QueueReceiver<CacheMessage<K, V, E, B>>) {
+        while let Some(message) = rx.recv().await {
+            if let Some((response, result)) = self.process(message) {
+                response.send(result).ok();
+            }
+        }
+    }
+
+    async fn idle(idle_park: Option<Duration>, last_message: Instant) {
+        match idle_park {
+            Some(idle_park) => tokio::time::sleep_until(last_message + idle_park).await,
+            None => futures::future::pending().await,
+        }
+    }
+
+    /// Handles the message of the actor and records its timings, `None` if the message waits for
+    /// the lock of its key
+    fn process(&mut self, message: CacheMessage<K, V, E, B>) -> Option<Response<V, E>> {
+        let message = self.defer(message)?;
+        self.running_load = message.running_load
+            .and_then(|(store, key)| if store == self.id { Some(key) } else { None });
+        let started = Instant::now();
+        let result = self.handle_operation(message.id, message.action);
+        if !self.is_inline() {
+            self.metrics.record(started.duration_since(message.sent), started.elapsed());
+        }
+        Some((message.response, result))
+    }
+
+    /// Holds back the message while its key is locked, locks and deferred swaps also wait for the
+    /// running load of their key
+    fn defer(&mut self, message: CacheMessage<K, V, E, B>) -> Option<CacheMessage<K, V, E, B>> {
+        let key = match message.action.key() {
+            Some(key) => key,
+            None => return Some(message),
+        };
+        if let Some(lock) = self.locks.get_mut(key) {
+            lock.waiting.push_back(message);
+            return None;
+        }
+        if matches!(message.action, CacheAction::Lock(_) | CacheAction::Swap(_, _, SwapPolicy::Defer))
+            && matches!(self.data.peek(key), Ok(Some(CacheEntry::Loading(_)))) {
+            let key = key.clone();
+            self.locks.insert(key, KeyLock {
+                holder: None,
+                waiting: VecDeque::from([message]),
+            });
+            return None;
+        }
+        Some(message)
+    }
+
+    fn lock(&mut self, key: K) -> CacheResult<V, E> {
+        let result = self.get_if_present(key.clone());
+        if !matches!(result, CacheResult::Error(_)) {
+            self.locks.insert(key, KeyLock {
+                holder: Some(self.operation),
+                waiting: VecDeque::new(),
+            });
+        }
+        result
+    }
+
+    fn unlock(&mut self, key: K, holder: OperationId, value: Option<V>) -> CacheResult<V, E> {
+        if self.locks.get(&key).and_then(|lock| lock.holder) != Some(holder) {
+            // the lock was never acquired, its caller stopped waiting
+            return CacheResult::None;
+        }
+        // the lock is released even if the value can't be set, so the key doesn't stay blocked
+        let result = match value {
+            Some(value) => self.set(key.clone(), value, false, None),
+            None => CacheResult::None,
+        };
+        self.release(&key);
+        result
+    }
+
+    /// Executes the operations held back by the lock of the key, until one of them locks it again
+    fn release(&mut self, key: &K) {
+        if let Some(lock) = self.locks.remove(key) {
+            for message in lock.waiting {
+                if message.action.is_read() && message.response.is_closed() {
+                    continue;
+                }
+                if let Some((response, result)) = self.process(message) {
+                    response.send(result).ok();
                 }
             }
         }
+    }
+
+    /// Executes the locks and operations which waited for the load of the key
+    fn release_load(&mut self, key: &K) {
+        if self.locks.get(key).is_some_and(|lock| lock.holder.is_none()) {
+            self.release(key);
+        }
+    }
+
+    /// Handles the action on behalf of the operation, which is traced in a span with its id
+    fn handle_operation(&mut self, operation: OperationId, action: CacheAction<K, V, E, B>) -> CacheResult<V, E> {
+        self.operation = operation;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            "cache_operation",
+            cache = self.options.name.as_deref().unwrap_or("unnamed"),
+            operation = %operation,
+            action = action.name(),
+        ).entered();
+        let result = self.handle(action);
+        self.check_occupancy();
+        #[cfg(debug_assertions)]
+        if self.options.verify_backing {
+            self.assert_consistent();
+        }
+        result
+    }
+
+    #[cfg(debug_assertions)]
+    fn assert_consistent(&mut self) {
+        match self.data.verify() {
+            Ok(inconsistencies) => assert!(inconsistencies.is_empty(),
+                "Operation {} left the backing inconsistent: {:?}", self.operation, inconsistencies),
+            Err(err) => panic!("Operation {} failed to verify the backing: {}", self.operation, err),
+        }
+    }
+
+    fn handle(&mut self, action: CacheAction<K, V, E, B>) -> CacheResult<V, E> {
+        // reads which find a cached value count as hits of its entry
+        let read = match &action {
+            CacheAction::GetIfPresent(key)
+            | CacheAction::Lookup(key, _)
+            | CacheAction::PeekIfPresent(key)
+            | CacheAction::Get(key)
+            | CacheAction::GetNoPromote(key)
+            | CacheAction::GetAllowStale(key, _)
+            | CacheAction::GetWith(key, _)
+            | CacheAction::GetWithLoadMeta(key, _) if self.tracks_entries() => Some(key.clone()),
+            _ => None,
+        };
+        let counted = self.stats.is_some() && matches!(action,
+            CacheAction::GetIfPresent(_)
+            | CacheAction::Lookup(..)
+            | CacheAction::PeekIfPresent(_)
+            | CacheAction::Get(_)
+            | CacheAction::GetNoPromote(_)
+            | CacheAction::GetAllowStale(..)
+            | CacheAction::GetWith(..)
+            | CacheAction::GetWithLoadMeta(..)
+            | CacheAction::GetAll(..));
+        let result = self.dispatch(action);
+        if counted {
+            self.record_read(&result);
+        }
+        if let (Some(key), CacheResult::Found(_)) = (read, &result) {
+            if let Some(access) = self.entries.get_mut(&key) {
+                access.hits += 1;
+            }
+            self.refresh_if_due(key);
+        }
+        result
+    }
+
+    /// Counts the result of a read as hit or miss, failed reads aren't counted
+    fn record_read(&self, result: &CacheResult<V, E>) {
+        let stats = match &self.stats {
+            Some(stats) => stats,
+            None => return,
+        };
+        match result {
+            CacheResult::Found(_) | CacheResult::Stale(..) => stats.record_read(true),
+            CacheResult::Loading(_) | CacheResult::None => stats.record_read(false),
+            CacheResult::Pipeline(results) => results.iter().for_each(|result| self.record_read(result)),
+            CacheResult::Error(_) => {}
+        }
+    }
+
+    /// Whether the actor keeps a record per key, which refreshes need to know the age of a value
+    fn tracks_entries(&self) -> bool {
+        self.options.track_entries || self.options.refresh_after_write.is_some()
+    }
+
+    /// Reloads the value of the key in the background if it's older than
+    /// `CacheOptions::refresh_after_write`, the current value is served meanwhile
+    fn refresh_if_due(&mut self, key: K) {
+        let refresh_after_write = match self.options.refresh_after_write {
+            // inline loads are driven by their caller, nobody would drive a refresh
+            Some(refresh_after_write) if !self.is_inline() => refresh_after_write,
+            _ => return,
+        };
+        let stored = match self.entries.get(&key) {
+            Some(access) if access.stored.elapsed() >= refresh_after_write => access.stored,
+            _ => return,
+        };
+        if !self.refreshing.insert(key.clone()) {
+            return;
+        }
+        let loader = (self.loader)(key.clone());
+        let tx = self.completion_tx.clone();
+        let closing = self.closing.clone().notified_owned();
+        let operation = self.operation;
+        let name = task_name(self.options.name.as_deref(), "refresh", Some(&key));
+        let stats = self.stats.clone();
+        spawn_task(move || name, async move {
+            let started = Instant::now();
+            let result = tokio::select! {
+                result = loader => Some(result),
+                _ = closing => None,
+            };
+            if let (Some(stats), Some(result)) = (&stats, &result) {
+                stats.record_load(result.is_ok(), started.elapsed());
+            }
+            // a failed refresh keeps the current value, the next read past the interval retries
+            let value = result.and_then(Result::ok);
+            let (response, _) = response::channel();
+            tx.send(CacheMessage::with_id(CacheAction::Refreshed(key, stored, value), response, operation)).await.ok();
+        });
+    }
+
+    fn refreshed(&mut self, key: K, stored: Instant, value: Option<DataWithMeta<K, V, E, B>>) -> CacheResult<V, E> {
+        self.refreshing.remove(&key);
+        // a value which was written, removed or evicted meanwhile isn't replaced
+        let unchanged = self.entries.get(&key).is_some_and(|access| access.stored == stored)
+            && matches!(self.data.peek(&key), Ok(Some(CacheEntry::Loaded(_))));
+        match value {
+            Some(value) if unchanged => self.set(key, value.data, false, value.meta),
+            _ => CacheResult::None,
+        }
+    }
+
+    fn dispatch(&mut self, action: CacheAction<K, V, E, B>) -> CacheResult<V, E> {
+        match action {
+            CacheAction::GetIfPresent(key) => self.get_if_present(key),
+            CacheAction::GetIfPresentWithMeta(key, respond) => self.get_if_present_with_meta(key, respond),
+            CacheAction::Lookup(key, respond) => self.lookup(key, respond),
+            CacheAction::PeekIfPresent(key) => self.peek_if_present(key),
+            CacheAction::Get(key) => self.get(key),
+            CacheAction::GetNoPromote(key) => self.get_no_promote(key),
+            CacheAction::GetAllowStale(key, max_staleness) => self.get_allow_stale(key, max_staleness),
+            CacheAction::GetWith(key, load) => self.get_with(key, Some(load)),
+            CacheAction::GetWithLoadMeta(key, meta) => {
+                let load = (self.loader)(key.clone())
+                    .map_ok(move |data| Some(DataWithMeta::new(data.data, Some(meta))));
+                self.get_with(key, Some(Box::pin(load)))
+            }
+            CacheAction::GetAll(keys, batch_loader) => self.get_all(keys, batch_loader),
+            CacheAction::LoadIndependent(key, update) => self.load_independent(key, update),
+            CacheAction::Set(key, value, meta) => self.set(key, value, false, meta),
+            CacheAction::SetBatch(entries) => self.set_batch(entries),
+            CacheAction::Swap(key, value, _) => self.swap(key, value),
+            CacheAction::Update(key, meta, update_fn, load) => self.update(key, update_fn, load, meta),
+            CacheAction::UpdateMut(key, update_mut_fn, load) => self.update_mut(key, update_mut_fn, load),
+            CacheAction::Remove(key) => self.remove(key),
+            CacheAction::RemoveIf(predicate) => self.remove_if(predicate),
+            CacheAction::Clear() => self.clear(),
+            #[cfg(feature = "unstable")]
+            CacheAction::Pipeline(actions) => {
+                CacheResult::Pipeline(actions.into_iter().map(|action| self.handle(action)).collect())
+            }
+            CacheAction::Lock(key) => self.lock(key),
+            CacheAction::SetAndUnblock(key, value, meta) => {
+                self.loading.remove(&key);
+                self.forget_dependencies(&key);
+                let result = match self.swapped.remove(&key) {
+                    Some(swapped) => CacheResult::Found(swapped),
+                    None => self.set(key.clone(), value, true, meta),
+                };
+                self.release_load(&key);
+                result
+            }
+            CacheAction::Unblock(key) => {
+                self.loading.remove(&key);
+                self.unblock(key)
+            }
+            CacheAction::Unlock(key, holder, value) => self.unlock(key, holder, value),
+            CacheAction::WithBacking(inspect) => {
+                inspect(&mut self.data);
+                CacheResult::None
+            }
+            CacheAction::Refreshed(key, stored, value) => self.refreshed(key, stored, value),
+            CacheAction::Shutdown() => {
+                self.closing.notify_waiters();
+                CacheResult::None
+            }
+        }
+    }
+
+    fn is_inline(&self) -> bool {
+        matches!(self.options.execution_mode, ExecutionMode::Inline)
+    }
+
+    /// Runs the future in a task, or returns it to be driven by the caller in inline mode
+    fn pending<Fut>(&self, future: Fut) -> LoadFuture<V, E>
+        where Fut: Future<Output=Result<V, CacheLoadingError<E>>> + Send + 'static {
+        if self.is_inline() {
+            LoadFuture::new(future)
+        } else {
+            LoadFuture::task(tokio::spawn(future), None)
+        }
+    }
+
+    /// There is no actor loop in inline mode, so the watchdog, the expiry sweep and the
+    /// maintenance run while executing actions
+    fn sweep_if_due(&mut self) {
+        if let Some(watchdog) = self.options.loading_watchdog {
+            let now = Instant::now();
+            if now.duration_since(self.last_sweep) >= watchdog.interval {
+                self.last_sweep = now;
+                self.unblock_stuck_loads();
+            }
+        }
+        if let Some(interval) = self.options.expiry_sweep {
+            let now = Instant::now();
+            if now.duration_since(self.last_expiry_sweep) >= interval {
+                self.last_expiry_sweep = now;
+                self.remove_expired();
+            }
+        }
+        if let Some(interval) = self.options.maintenance {
+            let now = Instant::now();
+            if now.duration_since(self.last_maintenance) >= interval {
+                self.last_maintenance = now;
+                self.maintain();
+            }
+        }
+    }
+
+    fn maintain(&mut self) {
+        if let Some(maintenance) = self.data.maintenance() {
+            // there is no caller to report the errors to, the next run tries again
+            maintenance.compact().ok();
+            maintenance.vacuum().ok();
+        }
+    }
+
+    fn remove_expired(&mut self) {
+        // there is no caller to report the error to, the next sweep tries again
+        self.data.remove_expired().ok();
+        self.check_occupancy();
+    }
+
+    /// Notifies the listeners of the thresholds the occupancy of the backing crossed
+    fn check_occupancy(&mut self) {
+        if self.occupancy_levels.is_empty() {
+            return;
+        }
+        let occupancy = match self.data.stats().occupancy {
+            Some(occupancy) => occupancy,
+            None => return,
+        };
+        for (watch, previous) in self.options.occupancy_watches.iter().zip(self.occupancy_levels.iter_mut()) {
+            let level = watch.thresholds.level(&occupancy);
+            if level != *previous {
+                (watch.listener)(OccupancyEvent {
+                    thresholds: watch.thresholds,
+                    previous: *previous,
+                    level,
+                    occupancy,
+                });
+                *previous = level;
+            }
+        }
+    }
+
+    async fn tick(interval: &mut Option<Interval>) {
+        match interval {
+            Some(interval) => { interval.tick().await; }
+            None => futures::future::pending().await,
+        }
+    }
+
+    fn unblock_stuck_loads(&mut self) {
+        let max_loading_age = self.options.loading_watchdog
+            .and_then(|watchdog| watchdog.max_loading_age);
+        let now = Instant::now();
+        let stuck = self.loading.iter_mut()
+            .filter_map(|(key, tracker)| {
+                if tracker.is_stuck(now, max_loading_age) {
+                    Some(key.clone())
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<K>>();
+        for key in stuck {
+            if let Some(abort) = self.loading.remove(&key).and_then(|tracker| tracker.abort) {
+                abort.abort();
+            }
+            self.unblock(key);
+        }
+    }
+
+    fn unblock_if_stuck(&mut self, key: &K) {
+        let max_loading_age = self.options.loading_watchdog
+            .and_then(|watchdog| watchdog.max_loading_age);
+        let stuck = self.loading.get_mut(key)
+            .is_some_and(|tracker| tracker.is_stuck(Instant::now(), max_loading_age));
+        if stuck {
+            if let Some(abort) = self.loading.remove(key).and_then(|tracker| tracker.abort) {
+                abort.abort();
+            }
+            self.unblock(key.clone());
+        }
+    }
+
+    fn unblock(&mut self, key: K) -> CacheResult<V, E>{
+        self.forget_dependencies(&key);
+        let swapped = self.swapped.remove(&key);
+        let result = self.remove_loading(&key);
+        // the locks waiting for the load are released even if the backing fails
+        self.release_load(&key);
+        match (result, swapped) {
+            (CacheResult::None, Some(swapped)) => CacheResult::Found(swapped),
+            (result, _) => result,
+        }
+    }
+
+    fn remove_loading(&mut self, key: &K) -> CacheResult<V, E> {
+        if let Some(CacheEntry::Loading(_)) = unwrap_backing!(self.data.get(key)) {
+            if let Some(CacheEntry::Loading(waiter)) = unwrap_backing!(self.data.remove(key)) {
+                std::mem::drop(waiter) // dropping the sender closes the channel
+            }
+        }
+        CacheResult::None
+    }
+
+    /// Records that the value of the key was set, with `CacheOptions::track_entries` or
+    /// `CacheOptions::refresh_after_write`
+    fn track_stored(&mut self, key: &K) {
+        if !self.tracks_entries() {
+            return;
+        }
+        self.entries.insert(key.clone(), EntryAccess {
+            stored: Instant::now(),
+            hits: 0,
+        });
+        if self.entries.len() >= self.prune_entries_at {
+            let data = &mut self.data;
+            // records of keys the backing evicted or expired on its own
+            self.entries.retain(|key, _| data.contains_key(key).unwrap_or(true));
+            self.prune_entries_at = (self.entries.len() * 2).max(MIN_PRUNE_ENTRIES);
+        }
+    }
+
+    fn get_if_present_with_meta(&mut self, key: K, respond: Box<dyn FnOnce(Option<EntryMeta<V>>) + Send + 'static>) -> CacheResult<V, E> {
+        let value = match unwrap_backing!(self.data.get(&key)) {
+            Some(CacheEntry::Loaded(value)) => value.clone(),
+            _ => {
+                respond(None);
+                return CacheResult::None;
+            }
+        };
+        let ttl_remaining = unwrap_backing!(self.data.time_to_live(&key));
+        let access = self.entries.get_mut(&key).map(|access| {
+            access.hits += 1;
+            (access.stored.elapsed(), access.hits)
+        });
+        respond(Some(EntryMeta {
+            value,
+            age: access.map(|(age, _)| age),
+            hits: access.map(|(_, hits)| hits),
+            ttl_remaining,
+        }));
         CacheResult::None
     }
 
     fn remove(&mut self, key: K) -> CacheResult<V, E> {
+        self.entries.remove(&key);
+        self.loading.remove(&key);
+        self.swapped.remove(&key);
         if let Some(entry) = unwrap_backing!(self.data.remove(&key)) {
             match entry {
-                CacheEntry::Loaded(data) => CacheResult::Found(data),
+                CacheEntry::Loaded(data) => {
+                    self.notify_removal(key, data.clone(), RemovalCause::Explicit);
+                    CacheResult::Found(data)
+                }
                 CacheEntry::Loading(_) => CacheResult::None
             }
         } else {
@@ -122,13 +1271,27 @@ impl<
         }
     }
 
-    fn remove_if(&mut self, predicate: Box<dyn Fn((&K, Option<&V>)) -> bool + Send + Sync + 'static>) -> CacheResult<V, E> {
-        unwrap_backing!(self.data.remove_if(self.to_predicate(predicate)));
+    fn remove_if(&mut self, predicate: CachePredicate<K, V>) -> CacheResult<V, E> {
+        let removed = unwrap_backing!(self.data.remove_if(self.to_predicate(predicate)));
+        for (key, entry) in removed {
+            self.entries.remove(&key);
+            self.loading.remove(&key);
+            self.swapped.remove(&key);
+            if let CacheEntry::Loaded(value) = entry {
+                self.notify_removal(key, value, RemovalCause::Explicit);
+            }
+        }
         CacheResult::None
     }
 
-    fn to_predicate(&self, predicate: Box<dyn Fn((&K, Option<&V>)) -> bool + Send + Sync + 'static>)
-                    -> Box<dyn Fn((&K, &CacheEntry<V, E>)) -> bool + Send + Sync + 'static> {
+    fn notify_removal(&self, key: K, value: V, cause: RemovalCause) {
+        if let Some(listener) = &self.removal_listener {
+            listener(key, value, cause);
+        }
+    }
+
+    fn to_predicate(&self, predicate: CachePredicate<K, V>)
+                    -> BackingPredicate<K, CacheEntry<V, E>> {
         Box::new(move |(key, value)| {
             match value {
                 CacheEntry::Loaded(value) => {
@@ -142,28 +1305,42 @@ impl<
     }
 
     fn clear(&mut self) -> CacheResult<V, E> {
+        // backings can't return the entries they clear, so they are removed one by one first
+        let removed = match self.removal_listener {
+            Some(_) => unwrap_backing!(self.data.remove_if(Box::new(|_| true))),
+            None => Vec::new(),
+        };
         unwrap_backing!(self.data.clear());
+        for (key, entry) in removed {
+            if let CacheEntry::Loaded(value) = entry {
+                self.notify_removal(key, value, RemovalCause::Cleared);
+            }
+        }
+        self.entries.clear();
+        self.loading.clear();
+        self.swapped.clear();
+        self.dependencies.clear();
         CacheResult::None
     }
 
-    fn update_mut(&mut self, key: K, mut update_mut_fn: Box<dyn FnMut(&mut V) -> () + Send + 'static>, load: bool) -> CacheResult<V, E> {
+    fn update_mut(&mut self, key: K, mut update_mut_fn: Box<dyn FnMut(&mut V) + Send + 'static>, load: bool) -> CacheResult<V, E> {
         match unwrap_backing!(self.data.get_mut(&key)) {
             Some(entry) => {
                 match entry {
                     CacheEntry::Loaded(data) => {
                         update_mut_fn(data);
-                        CacheResult::Found(data.clone())
+                        let data = data.clone();
+                        unwrap_backing!(self.data.write_back(&key));
+                        self.track_stored(&key);
+                        CacheResult::Found(data)
                     }
                     CacheEntry::Loading(waiter) => {
                         let mut rx = waiter.subscribe();
                         let cache_tx = self.tx.clone();
-                        CacheResult::Loading(tokio::spawn(async move {
+                        CacheResult::Loading(self.pending(async move {
                             rx.recv().await.ok(); // result confirmed
-                            let (response_tx, response_rx) = tokio::sync::oneshot::channel();
-                            cache_tx.send(CacheMessage {
-                                action: CacheAction::UpdateMut(key, update_mut_fn, load),
-                                response: response_tx,
-                            }).await.ok();
+                            let (response_tx, response_rx) = response::channel();
+                            cache_tx.send(CacheMessage::new(CacheAction::UpdateMut(key, update_mut_fn, load), response_tx)).await.ok();
                             match response_rx.await.unwrap() {
                                 CacheResult::Found(data) => Ok(data),
                                 _ => Err(CacheLoadingError::NoData())
@@ -178,13 +1355,10 @@ impl<
                     match result {
                         CacheResult::Loading(waiter) => {
                             let cache_tx = self.tx.clone();
-                            CacheResult::Loading(tokio::spawn(async move {
+                            CacheResult::Loading(self.pending(async move {
                                 waiter.await.ok(); // result confirmed
-                                let (response_tx, response_rx) = tokio::sync::oneshot::channel();
-                                cache_tx.send(CacheMessage {
-                                    action: CacheAction::UpdateMut(key, update_mut_fn, load),
-                                    response: response_tx,
-                                }).await.ok();
+                                let (response_tx, response_rx) = response::channel();
+                                cache_tx.send(CacheMessage::new(CacheAction::UpdateMut(key, update_mut_fn, load), response_tx)).await.ok();
                                 match response_rx.await.unwrap() {
                                     CacheResult::Found(data) => Ok(data),
                                     _ => Err(CacheLoadingError::NoData())
@@ -210,26 +1384,24 @@ impl<
         match data {
             CacheResult::Found(data) => {
                 let updated_data = update_fn(data);
+                self.track_stored(&key);
                 unwrap_backing!(self.data.set(key, CacheEntry::Loaded(updated_data.clone()), meta));
                 CacheResult::Found(updated_data)
             }
             CacheResult::Loading(handle) => {
                 let tx = self.tx.clone();
-                CacheResult::Loading(tokio::spawn(async move {
+                CacheResult::Loading(self.pending(async move {
                     handle.await.ok(); // set stupidly await the load to be done
                     // we let the set logic take place which is called from within the future
                     // and we're invoking a second update on the (now cached) data
-                    let (response_tx, rx) = tokio::sync::oneshot::channel();
-                    tx.send(CacheMessage {
-                        action: CacheAction::Update(key, meta, update_fn, load),
-                        response: response_tx,
-                    }).await.ok();
+                    let (response_tx, rx) = response::channel();
+                    tx.send(CacheMessage::new(CacheAction::Update(key, meta, update_fn, load), response_tx)).await.ok();
                     match rx.await {
                         Ok(result) => {
                             match result {
-                                CacheResult::Found(data) => Ok(data),
+                                CacheResult::Found(data) | CacheResult::Stale(data, _) => Ok(data),
                                 CacheResult::Loading(_) => Err(CacheLoadingError::CommunicationError(CacheCommunicationError::LookupLoop())),
-                                CacheResult::None => Err(CacheLoadingError::CommunicationError(CacheCommunicationError::LookupLoop())),
+                                CacheResult::None | CacheResult::Pipeline(_) => Err(CacheLoadingError::CommunicationError(CacheCommunicationError::LookupLoop())),
                                 CacheResult::Error(err) => Err(CacheLoadingError::BackingError(err)),
                             }
                         }
@@ -252,6 +1424,7 @@ impl<
                 return CacheResult::None; // abort mission, we already have an updated entry!
             }
         }
+        self.track_stored(&key);
         unwrap_backing!(self.data.set(key, CacheEntry::Loaded(value), meta))
             .and_then(|entry| {
                 match entry {
@@ -263,6 +1436,104 @@ impl<
             .unwrap_or(CacheResult::None)
     }
 
+    fn set_batch(&mut self, entries: Vec<ExportedEntry<K, V, B::Meta>>) -> CacheResult<V, E> {
+        for (key, value, meta) in entries {
+            if let Some(lock) = self.locks.get_mut(&key) {
+                // nobody waits for the response of the held back set
+                let (response, _) = response::channel();
+                lock.waiting.push_back(CacheMessage::with_id(CacheAction::Set(key, value, meta), response, self.operation));
+                continue;
+            }
+            if let CacheResult::Error(err) = self.set(key, value, false, meta) {
+                return CacheResult::Error(err);
+            }
+        }
+        CacheResult::None
+    }
+
+    /// Replaces the value, deferred swaps of a loading key only get here once the load completed
+    /// or within a pipeline, which can't wait for it
+    fn swap(&mut self, key: K, value: V) -> CacheResult<V, E> {
+        let waiter = match unwrap_backing!(self.data.get(&key)) {
+            Some(CacheEntry::Loading(waiter)) => waiter.clone(),
+            _ => return self.set(key, value, false, None),
+        };
+        // the load is replaced, its waiters receive the swapped value once it's cached and the
+        // loader discards its result
+        if let CacheResult::Error(err) = self.set(key.clone(), value.clone(), false, None) {
+            return CacheResult::Error(err);
+        }
+        waiter.send(Ok(value.clone())).ok();
+        self.swapped.insert(key.clone(), value);
+        self.release_load(&key);
+        CacheResult::None
+    }
+
+    /// Loads the key without marking it as loading, so neither the caller joins a running load
+    /// nor other callers join this one
+    fn load_independent(&mut self, key: K, update: bool) -> CacheResult<V, E> {
+        let loader = (self.loader)(key.clone());
+        let tx = self.tx.clone();
+        let operation = self.operation;
+        let load = async move {
+            let value = loader.await.map_err(CacheLoadingError::LoadingError)?;
+            if update {
+                // the value is set like by `LoadingCache::set`, after the operations queued so far
+                let (response_tx, rx) = response::channel();
+                tx.send(CacheMessage::with_id(CacheAction::Set(key, value.data.clone(), value.meta), response_tx, operation)).await.ok();
+                if let Ok(CacheResult::Error(err)) = rx.await {
+                    return Err(CacheLoadingError::BackingError(err));
+                }
+            }
+            Ok(value.data)
+        };
+        #[cfg(feature = "tracing")]
+        let load = tracing::Instrument::instrument(load, tracing::trace_span!("cache_load", operation = %operation));
+        CacheResult::Loading(self.pending(load))
+    }
+
+    /// Records that the loader sending the operation waits for the load of the key, returns the
+    /// keys of the cycle if the load of the key in turn waits for the loader
+    fn wait_for_load(&mut self, key: &K) -> Option<Vec<K>> {
+        let waiting = self.running_load.clone()?;
+        if let Some(path) = self.dependency_path(key, &waiting, &mut HashSet::new()) {
+            let mut cycle = vec![waiting];
+            cycle.extend(path);
+            return Some(cycle);
+        }
+        self.dependencies.entry(waiting).or_default().push(key.clone());
+        None
+    }
+
+    /// The loads from `from` up to `to`, excluding `to`, the loader of each one waiting for the
+    /// next one
+    fn dependency_path(&self, from: &K, to: &K, visited: &mut HashSet<K>) -> Option<Vec<K>> {
+        if from == to {
+            return Some(Vec::new());
+        }
+        if !visited.insert(from.clone()) {
+            return None;
+        }
+        for next in self.dependencies.get(from).into_iter().flatten() {
+            if let Some(mut path) = self.dependency_path(next, to, visited) {
+                path.insert(0, from.clone());
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    /// Forgets what the load of the key waited for and which loads waited for it, once it completed
+    fn forget_dependencies(&mut self, key: &K) {
+        if self.dependencies.is_empty() {
+            return;
+        }
+        self.dependencies.remove(key);
+        for dependencies in self.dependencies.values_mut() {
+            dependencies.retain(|dependency| dependency != key);
+        }
+    }
+
     fn get_if_present(&mut self, key: K) -> CacheResult<V, E> {
         if let Some(entry) = unwrap_backing!(self.data.get(&key)) {
             match entry {
@@ -274,7 +1545,114 @@ impl<
         }
     }
 
+    /// Answers with the state of the key, the result counts as hit or miss like `get_if_present`
+    fn lookup(&mut self, key: K, respond: Box<dyn FnOnce(Lookup<V>) + Send + 'static>) -> CacheResult<V, E> {
+        match unwrap_backing!(self.data.get(&key)) {
+            Some(CacheEntry::Loaded(value)) => {
+                let value = value.clone();
+                respond(Lookup::Hit(value.clone()));
+                CacheResult::Found(value)
+            }
+            Some(CacheEntry::Loading(_)) => {
+                respond(Lookup::Loading);
+                CacheResult::None
+            }
+            None => {
+                respond(Lookup::Miss);
+                CacheResult::None
+            }
+        }
+    }
+
+    fn peek_if_present(&mut self, key: K) -> CacheResult<V, E> {
+        match unwrap_backing!(self.data.peek(&key)) {
+            Some(CacheEntry::Loaded(data)) => CacheResult::Found(data.clone()),
+            _ => CacheResult::None,
+        }
+    }
+
     fn get(&mut self, key: K) -> CacheResult<V, E> {
+        self.get_with(key, None)
+    }
+
+    fn get_no_promote(&mut self, key: K) -> CacheResult<V, E> {
+        match unwrap_backing!(self.data.peek(&key)) {
+            Some(CacheEntry::Loaded(data)) => CacheResult::Found(data.clone()),
+            _ => self.get(key),
+        }
+    }
+
+    fn get_allow_stale(&mut self, key: K, max_staleness: Duration) -> CacheResult<V, E> {
+        // the stale value has to be read first, starting the load replaces it
+        let stale = match unwrap_backing!(self.data.get_stale(&key, max_staleness)) {
+            Some((CacheEntry::Loaded(value), staleness)) => Some((value, staleness)),
+            _ => None,
+        };
+        match (self.get(key), stale) {
+            // the load continues in its task, inline loads would be dropped with their future
+            (CacheResult::Loading(_), Some((value, staleness))) if !self.is_inline() => {
+                CacheResult::Stale(value, staleness)
+            }
+            (result, _) => result,
+        }
+    }
+
+    fn get_all(&mut self, keys: Vec<K>, batch_loader: Option<BatchLoader<K, V, E>>) -> CacheResult<V, E> {
+        // the keys of the batch are collected before any of its loads is polled
+        let missing = Arc::new(Mutex::new(Vec::new()));
+        let batch = batch_loader.map(|batch_loader| {
+            let missing = missing.clone();
+            async move {
+                let keys = std::mem::take(&mut *missing.lock().unwrap());
+                // shared by the loads of the keys, which take their value out of it
+                Arc::new(Mutex::new(batch_loader(keys).await))
+            }.boxed().shared()
+        });
+        let mut results = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(lock) = self.locks.get_mut(&key) {
+                // the key is read once it's released, like a `get` held back by the lock
+                let (response, rx) = response::channel();
+                lock.waiting.push_back(CacheMessage::with_id(CacheAction::Get(key), response, self.operation));
+                results.push(CacheResult::Loading(LoadFuture::new(async move {
+                    match rx.await {
+                        Ok(CacheResult::Found(value)) | Ok(CacheResult::Stale(value, _)) => Ok(value),
+                        Ok(CacheResult::Loading(load)) => load.await,
+                        Ok(CacheResult::Error(err)) => Err(CacheLoadingError::BackingError(err)),
+                        Ok(CacheResult::None) | Ok(CacheResult::Pipeline(_)) => Err(CacheLoadingError::NoData()),
+                        Err(err) => Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioOneshotRecvError(err))),
+                    }
+                })));
+                continue;
+            }
+            let batch = match &batch {
+                Some(batch) => batch.clone(),
+                None => {
+                    results.push(self.get(key));
+                    continue;
+                }
+            };
+            if matches!(self.data.peek(&key), Ok(None)) {
+                missing.lock().unwrap().push(key.clone());
+            }
+            let batch_key = key.clone();
+            let load = async move {
+                let values = batch.await;
+                let mut values = values.lock().unwrap();
+                match &mut *values {
+                    Ok(values) => Ok(values.remove(&batch_key).map(|value| DataWithMeta::new(value, None))),
+                    Err(err) => Err(err.clone()),
+                }
+            };
+            results.push(self.get_with(key, Some(Box::pin(load))));
+        }
+        CacheResult::Pipeline(results)
+    }
+
+    fn get_with(&mut self, key: K, load: Option<SuppliedLoad<K, V, E, B>>) -> CacheResult<V, E> {
+        if self.options.loading_watchdog.is_some() {
+            self.unblock_if_stuck(&key);
+        }
         if let Some(entry) = unwrap_backing!(self.data.get(&key)) {
             match entry {
                 CacheEntry::Loaded(value) => {
@@ -282,8 +1660,34 @@ impl<
                 }
                 CacheEntry::Loading(waiter) => {
                     let waiter = waiter.clone();
-                    CacheResult::Loading(tokio::spawn(async move {
-                        match waiter.subscribe().recv().await {
+                    // waiting for a load which waits for the caller would never complete
+                    if let Some(cycle) = self.wait_for_load(&key) {
+                        let keys = cycle.iter().map(key_hash).collect();
+                        return CacheResult::Loading(LoadFuture::new(async move {
+                            Err(CacheLoadingError::RecursiveLoad(keys))
+                        }));
+                    }
+                    // subscribe right away, a late subscription could miss the result and
+                    // holding a sender would keep the channel open when the load is unblocked
+                    let mut rx = waiter.subscribe();
+                    // waiters time out together with the load they wait for
+                    let timeout = self.options.load_timeout
+                        .and_then(|configured| self.loading.get(&key).map(|tracker| (tracker.started, configured)));
+                    // waiting doesn't need a task, so a caller which stops waiting unsubscribes
+                    let wait = async move {
+                        let received = match timeout {
+                            Some((started, configured)) => {
+                                match tokio::time::timeout_at(started + configured, rx.recv()).await {
+                                    Ok(Ok(result)) => Ok(result),
+                                    // the load closes the channel once it timed out, which may
+                                    // happen before the timer of the waiter fired
+                                    Ok(Err(err)) if started.elapsed() < configured => Err(err),
+                                    _ => return Err(CacheLoadingError::LoadTimeout { elapsed: started.elapsed(), configured }),
+                                }
+                            }
+                            None => rx.recv().await,
+                        };
+                        match received {
                             Ok(result) => {
                                 match result {
                                     Ok(data) => {
@@ -296,45 +1700,164 @@ impl<
                             }
                             Err(err) => Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioBroadcastRecvError(err)))
                         }
-                    }))
+                    };
+                    #[cfg(feature = "tracing")]
+                    let wait = tracing::Instrument::instrument(wait, tracing::trace_span!("cache_wait", operation = %self.operation));
+                    CacheResult::Loading(LoadFuture::new(wait))
                 }
             }
         } else {
             let (tx, _) = tokio::sync::broadcast::channel(1);
             let inner_tx = tx.clone();
-            let cache_tx = self.tx.clone();
-            let loader = (self.loader)(key.clone());
+            let cache_tx = self.completion_tx.clone();
+            // the completion may also reach the actor after operations queued meanwhile
+            #[cfg(feature = "chaos")]
+            let cache_tx = if crate::chaos::reorder() { self.tx.clone() } else { cache_tx };
+            let loader = match load {
+                Some(load) => Either::Right(load),
+                None => Either::Left((self.loader)(key.clone()).map_ok(Some)),
+            };
             let inner_key = key.clone();
-            let join_handle = tokio::spawn(async move {
-                match loader.await {
-                    Ok(value) => {
+            // the completion is sent on behalf of the operation which started the load
+            let operation = self.operation;
+            let (alive_tx, alive_rx) = tokio::sync::oneshot::channel::<()>();
+            let cancel = Arc::new(Notify::new());
+            let cancelled = cancel.clone();
+            // registered right away, so a shutdown before the load is first polled cancels it
+            let closing = self.closing.clone().notified_owned();
+            let stats = self.stats.clone();
+            // shared with the waiters through the tracker, so they time out together with the load
+            let load_started = Instant::now();
+            let load_timeout = self.options.load_timeout;
+            let load = async move {
+                let _alive = alive_tx;
+                let started = Instant::now();
+                let timeout = match load_timeout {
+                    Some(configured) => Either::Left(tokio::time::sleep_until(load_started + configured)),
+                    None => Either::Right(futures::future::pending()),
+                };
+                let mut timed_out = false;
+                // the loader is cancelled by `LoadFuture::abort`, by a shutdown of the cache and
+                // once it exceeds the load timeout
+                let result = tokio::select! {
+                    result = loader => Some(result),
+                    _ = cancelled.notified() => None,
+                    _ = closing => None,
+                    _ = timeout => {
+                        timed_out = true;
+                        None
+                    }
+                };
+                match (&stats, &result) {
+                    (Some(stats), Some(Ok(Some(_)))) => stats.record_load(true, started.elapsed()),
+                    (Some(stats), Some(Err(_))) => stats.record_load(false, started.elapsed()),
+                    (Some(stats), None) if timed_out => stats.record_load(false, started.elapsed()),
+                    _ => {}
+                }
+                #[cfg(feature = "chaos")]
+                crate::chaos::point().await;
+                match result {
+                    Some(Ok(Some(value))) => {
                         let meta = value.meta;
                         let value = value.data;
-                        inner_tx.send(Ok(value.clone())).ok();
-                        let (tx, rx) = tokio::sync::oneshot::channel();
+                        let (tx, rx) = response::channel();
                         let send_value = value.clone();
-                        cache_tx.send(CacheMessage {
-                            action: CacheAction::SetAndUnblock(inner_key, send_value, meta),
-                            response: tx,
-                        }).await.ok();
-                        rx.await.ok(); // await cache confirmation
-                        Ok(value)
+                        cache_tx.send(CacheMessage::with_id(CacheAction::SetAndUnblock(inner_key, send_value, meta), tx, operation)).await.ok();
+                        // await cache confirmation
+                        let stored = match rx.await {
+                            Ok(CacheResult::Found(swapped)) => return Ok(swapped),
+                            Ok(CacheResult::Error(err)) => Err(err),
+                            _ => Ok(()),
+                        };
+                        #[cfg(feature = "chaos")]
+                        crate::chaos::point().await;
+                        // waiters are notified once the value is cached, so operations they send
+                        // afterwards don't find the key loading anymore
+                        inner_tx.send(Ok(value.clone())).ok();
+                        // waiters still receive the loaded value, only the load which failed to
+                        // store it reports the error of the backing
+                        stored.map(|_| value).map_err(CacheLoadingError::BackingError)
                     }
-                    Err(loading_error) => {
+                    Some(Err(loading_error)) => {
+                        let (tx, rx) = response::channel();
+                        cache_tx.send(CacheMessage::with_id(CacheAction::Unblock(inner_key), tx, operation)).await.ok();
+                        // await cache confirmation
+                        if let Ok(CacheResult::Found(swapped)) = rx.await {
+                            return Ok(swapped);
+                        }
+                        #[cfg(feature = "chaos")]
+                        crate::chaos::point().await;
                         inner_tx.send(Err(loading_error.clone())).ok();
-                        let (tx, rx) = tokio::sync::oneshot::channel();
-                        cache_tx.send(CacheMessage {
-                            action: CacheAction::Unblock(inner_key),
-                            response: tx,
-                        }).await.ok();
-                        rx.await.ok(); // await cache confirmation
                         Err(CacheLoadingError::LoadingError(loading_error))
                     }
+                    Some(Ok(None)) | None => {
+                        let (tx, rx) = response::channel();
+                        cache_tx.send(CacheMessage::with_id(CacheAction::Unblock(inner_key), tx, operation)).await.ok();
+                        rx.await.ok(); // await cache confirmation
+                        // dropping the sender fails the waiters
+                        std::mem::drop(inner_tx);
+                        // either a supplied load found no value or the loader was cancelled
+                        match (result, load_timeout) {
+                            (Some(_), _) => Err(CacheLoadingError::NoData()),
+                            (None, Some(configured)) if timed_out => Err(CacheLoadingError::LoadTimeout {
+                                elapsed: load_started.elapsed(),
+                                configured,
+                            }),
+                            (None, _) => Err(CacheLoadingError::Cancelled()),
+                        }
+                    }
                 }
-            });
+            };
+            // the operations of the loader are recognized as sent on behalf of the load
+            let load = RUNNING_LOAD.scope(RunningLoad {
+                store: self.id,
+                key: Box::new(key.clone()),
+            }, load);
+            #[cfg(feature = "tracing")]
+            let load = tracing::Instrument::instrument(load, tracing::trace_span!("cache_load", operation = %operation));
+            let (pending, abort) = if self.is_inline() {
+                let unblock = UnblockOnDrop {
+                    tx: self.completion_tx.clone(),
+                    key: Some(key.clone()),
+                    operation,
+                };
+                let load = async move {
+                    let result = load.await;
+                    unblock.disarm();
+                    result
+                };
+                (LoadFuture::new(load), None)
+            } else {
+                let name = || task_name(self.options.name.as_deref(), "load", Some(&key));
+                let join_handle = spawn_task(name, load);
+                let abort = join_handle.abort_handle();
+                (LoadFuture::task(join_handle, Some(cancel)), Some(abort))
+            };
+            if self.options.loading_watchdog.is_some() || self.options.load_timeout.is_some() {
+                self.loading.insert(key.clone(), LoadTracker {
+                    started: load_started,
+                    alive: alive_rx,
+                    abort,
+                });
+            }
+            self.wait_for_load(&key);
             // Loading state is set without any meta
             unwrap_backing!(self.data.set(key, CacheEntry::Loading(tx), None));
-            CacheResult::Loading(join_handle)
+            CacheResult::Loading(pending)
         }
     }
-}
\ No newline at end of file
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    F: Future<Output=Result<DataWithMeta<K, V, E, B>, E>> + Sized + Send + 'static,
+    T: Fn(K) -> F + Send + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static
+> CacheExecutor<K, V, E, B> for InternalCacheStore<K, V, T, E, B> {
+    fn execute(&mut self, message: CacheMessage<K, V, E, B>) -> Option<Response<V, E>> {
+        self.sweep_if_due();
+        self.process(message)
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use tokio::sync::mpsc::error::TrySendError;
+use crate::backing::CacheBacking;
+use crate::cache_api::CacheEntry;
+use crate::internal_cache::{CacheAction, CacheMessage, CacheSender, OperationId};
+use crate::response;
+
+/// The lock of a key of a `LoadingCache`, other operations on the key wait until it's dropped
+///
+/// Created by `LoadingCache::lock_key`, see there for details.
+pub struct KeyGuard<
+    K: Clone + Eq + Hash + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Debug + Clone + Send + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static
+> {
+    tx: CacheSender<K, V, E, B>,
+    key: K,
+    // the operation which locked the key, only it can unlock the key
+    holder: OperationId,
+    value: Option<V>,
+    // whether the value is written back when the key is unlocked
+    changed: bool,
+}
+
+impl<
+    K: Clone + Eq + Hash + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Debug + Clone + Send + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static
+> KeyGuard<K, V, E, B> {
+    pub(crate) fn new(tx: CacheSender<K, V, E, B>, key: K, holder: OperationId) -> Self {
+        Self {
+            tx,
+            key,
+            holder,
+            value: None,
+            changed: false,
+        }
+    }
+
+    pub(crate) fn acquired(&mut self, value: Option<V>) {
+        self.value = value;
+    }
+
+    /// Returns the locked key
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the value of the key, the cached one when it was locked or the one passed to
+    /// `KeyGuard::set`
+    pub fn value(&self) -> Option<&V> {
+        self.value.as_ref()
+    }
+
+    /// Sets the value of the key, which is written to the cache when the key is unlocked, before
+    /// the waiting operations see it
+    pub fn set(&mut self, value: V) {
+        self.value = Some(value);
+        self.changed = true;
+    }
+}
+
+impl<
+    K: Clone + Eq + Hash + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Debug + Clone + Send + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static
+> Drop for KeyGuard<K, V, E, B> {
+    fn drop(&mut self) {
+        let value = if self.changed { self.value.take() } else { None };
+        let (response, _) = response::channel();
+        let action = CacheAction::Unlock(self.key.clone(), self.holder, value);
+        let message = CacheMessage::with_id(action, response, self.holder);
+        // the key stays locked until the message is delivered, so a full queue is waited on
+        if let Err(TrySendError::Full(message)) = self.tx.try_send(message) {
+            let tx = self.tx.clone();
+            tokio::spawn(async move {
+                tx.send(message).await.ok();
+            });
+        }
+    }
+}
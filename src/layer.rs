@@ -0,0 +1,162 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use futures::future::BoxFuture;
+use tower::{Layer, Service, ServiceExt};
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, CacheLoadingError, LoadingCache};
+
+/// A `tower::Layer` which puts a `LoadingCache` in front of a service
+///
+/// Every request is mapped to a cache key with the key function. Cached responses are returned
+/// without calling the inner service, concurrent requests for the same key share a single call
+/// to the inner service. The loader function of the cache itself is only used for direct
+/// lookups on the cache.
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::cache_api::LoadingCache;
+/// use cache_loader_async::layer::CacheLayer;
+/// use tower::{service_fn, ServiceBuilder, ServiceExt};
+/// async fn example() {
+///     let cache = LoadingCache::new(move |key: String| {
+///         async move {
+///             Ok::<_, u8>(key.len())
+///         }
+///     });
+///
+///     let service = ServiceBuilder::new()
+///         .layer(CacheLayer::new(cache, |request: &String| request.to_lowercase()))
+///         .service(service_fn(|request: String| async move { Ok::<_, u8>(request.len()) }));
+///
+///     let response = service.oneshot("Foo".to_owned()).await.unwrap();
+///
+///     assert_eq!(response, 3);
+/// }
+/// ```
+pub struct CacheLayer<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>,
+    KF
+> {
+    cache: LoadingCache<K, V, E, B>,
+    key_fn: Arc<KF>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+    KF
+> CacheLayer<K, V, E, B, KF> {
+    /// Creates a new layer using the given cache and the function which derives the cache key
+    /// from a request
+    pub fn new(cache: LoadingCache<K, V, E, B>, key_fn: KF) -> CacheLayer<K, V, E, B, KF> {
+        CacheLayer {
+            cache,
+            key_fn: Arc::new(key_fn),
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+    KF
+> Clone for CacheLayer<K, V, E, B, KF> {
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            key_fn: self.key_fn.clone(),
+        }
+    }
+}
+
+impl<
+    S,
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+    KF
+> Layer<S> for CacheLayer<K, V, E, B, KF> {
+    type Service = CacheService<S, K, V, E, B, KF>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CacheService {
+            inner,
+            cache: self.cache.clone(),
+            key_fn: self.key_fn.clone(),
+        }
+    }
+}
+
+/// The service created by `CacheLayer`
+pub struct CacheService<
+    S,
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>,
+    KF
+> {
+    inner: S,
+    cache: LoadingCache<K, V, E, B>,
+    key_fn: Arc<KF>,
+}
+
+impl<
+    S: Clone,
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+    KF
+> Clone for CacheService<S, K, V, E, B, KF> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            cache: self.cache.clone(),
+            key_fn: self.key_fn.clone(),
+        }
+    }
+}
+
+impl<
+    S,
+    Request,
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+    KF
+> Service<Request> for CacheService<S, K, V, E, B, KF>
+    where S: Service<Request, Response=V, Error=E> + Clone + Send + 'static,
+          S::Future: Send + 'static,
+          Request: Send + 'static,
+          KF: Fn(&Request) -> K {
+    type Response = V;
+    type Error = CacheLoadingError<E>;
+    type Future = BoxFuture<'static, Result<V, CacheLoadingError<E>>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // the inner service is only driven to readiness when a request actually has to be loaded
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let key = (self.key_fn)(&request);
+        let cache = self.cache.clone();
+        let load = self.inner.clone().oneshot(request);
+        Box::pin(async move {
+            cache.get_or_insert_with(key, load).await
+        })
+    }
+}
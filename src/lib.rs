@@ -1,6 +1,38 @@
 mod internal_cache;
+mod response;
+mod queue;
+mod write_window;
+mod write_through;
+mod write_behind;
+mod write_ahead_log;
 pub mod cache_api;
 pub mod backing;
+pub mod mapped;
+pub mod read_only;
+pub mod fallback;
+pub mod sharded;
+pub mod key_guard;
+pub mod args;
+pub mod options;
+pub mod builder;
+pub mod codec;
+pub mod async_cache;
+pub mod http_meta;
+pub mod coalescer;
+pub mod clock;
+pub mod stats;
+pub mod registry;
+#[cfg(feature = "tower-layer")]
+pub mod layer;
+#[cfg(feature = "axum-integration")]
+pub mod axum_cache;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+#[cfg(feature = "loadgen")]
+pub mod loadgen;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+mod rng;
 
 #[cfg(test)]
 pub(crate) mod test;
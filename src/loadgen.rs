@@ -0,0 +1,191 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::time::Instant;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, LoadingCache};
+use crate::rng::XorShift;
+
+/// A synthetic mix of reads and writes driven against a `LoadingCache`
+///
+/// Keys and values are `u64`. Before the run, the keys `0..key_space` are written to the cache.
+/// Reads hit those keys, except for the `miss_ratio` share which reads keys that have never been
+/// written and have to be loaded. Writes overwrite keys of the key space. The operations are
+/// drawn from a seeded pseudo random sequence, so runs with the same workload are comparable
+/// across releases and backings.
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::backing::HashMapBacking;
+/// use cache_loader_async::loadgen::Workload;
+/// async fn example() {
+///     let report = Workload::new(10_000)
+///         .read_ratio(0.8)
+///         .miss_ratio(0.05)
+///         .concurrency(4)
+///         .run(HashMapBacking::new())
+///         .await;
+///     println!("{:.0} ops/s, {:.1}% hits", report.ops_per_sec(), report.hit_ratio() * 100.0);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Workload {
+    operations: usize,
+    key_space: u64,
+    read_ratio: f64,
+    miss_ratio: f64,
+    concurrency: usize,
+    seed: u64,
+}
+
+/// The outcome of a `Workload` run
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    pub reads: usize,
+    pub writes: usize,
+    /// Reads served from the cache
+    pub hits: usize,
+    /// Reads which had to be loaded
+    pub misses: usize,
+    /// Operations which failed, e.g. because of a backing error
+    pub errors: usize,
+    /// Duration of the run, excluding the preloading of the key space
+    pub elapsed: Duration,
+}
+
+impl LoadReport {
+    pub fn operations(&self) -> usize {
+        self.reads + self.writes
+    }
+
+    pub fn ops_per_sec(&self) -> f64 {
+        self.operations() as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Returns the share of successful reads served from the cache
+    pub fn hit_ratio(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            reads => self.hits as f64 / reads as f64,
+        }
+    }
+
+    fn merge(&mut self, other: LoadReport) {
+        self.reads += other.reads;
+        self.writes += other.writes;
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.errors += other.errors;
+    }
+}
+
+impl Workload {
+    /// Creates a workload of the given number of operations, with 90% reads, 10% of the reads
+    /// missing, a key space of 1024 keys and a single worker
+    pub fn new(operations: usize) -> Workload {
+        Workload {
+            operations,
+            key_space: 1024,
+            read_ratio: 0.9,
+            miss_ratio: 0.1,
+            concurrency: 1,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// Sets the number of keys written before the run and targeted by hits and writes
+    pub fn key_space(mut self, key_space: u64) -> Workload {
+        self.key_space = key_space.max(1);
+        self
+    }
+
+    /// Sets the share of reads (from `0.0` to `1.0`), the remaining operations are writes
+    pub fn read_ratio(mut self, read_ratio: f64) -> Workload {
+        self.read_ratio = read_ratio;
+        self
+    }
+
+    /// Sets the share of reads (from `0.0` to `1.0`) which target keys that aren't cached
+    pub fn miss_ratio(mut self, miss_ratio: f64) -> Workload {
+        self.miss_ratio = miss_ratio;
+        self
+    }
+
+    /// Sets the number of tasks sharing the operations
+    pub fn concurrency(mut self, concurrency: usize) -> Workload {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Seeds the sequence the operations are drawn from
+    pub fn seed(mut self, seed: u64) -> Workload {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs the workload against a new cache with the backing, which loads every key as its own
+    /// value
+    pub async fn run<B>(&self, backing: B) -> LoadReport
+        where B: CacheBacking<u64, CacheEntry<u64, ()>> + Send + 'static {
+        let cache = LoadingCache::with_backing(backing, |key: u64| async move { Ok(key) });
+        self.run_on(&cache).await
+    }
+
+    /// Runs the workload against an existing cache, e.g. one with options or a slow loader
+    pub async fn run_on<E, B>(&self, cache: &LoadingCache<u64, u64, E, B>) -> LoadReport
+        where E: Debug + Clone + Send + 'static,
+              B: CacheBacking<u64, CacheEntry<u64, E>> + Send + 'static {
+        let mut report = LoadReport::default();
+        for key in 0..self.key_space {
+            if cache.set(key, key).await.is_err() {
+                report.errors += 1;
+            }
+        }
+
+        let next_miss = Arc::new(AtomicU64::new(self.key_space));
+        let start = Instant::now();
+        let workers = (0..self.concurrency)
+            .map(|worker| {
+                let operations = self.operations / self.concurrency
+                    + usize::from(worker < self.operations % self.concurrency);
+                tokio::spawn(self.clone().drive(cache.clone(), worker as u64, operations, next_miss.clone()))
+            })
+            .collect::<Vec<_>>();
+        for worker in workers {
+            report.merge(worker.await.expect("Load generator worker panicked"));
+        }
+        report.elapsed = start.elapsed();
+        report
+    }
+
+    async fn drive<E, B>(self, cache: LoadingCache<u64, u64, E, B>, worker: u64, operations: usize, next_miss: Arc<AtomicU64>) -> LoadReport
+        where E: Debug + Clone + Send + 'static,
+              B: CacheBacking<u64, CacheEntry<u64, E>> + Send + 'static {
+        let mut rng = XorShift::new(self.seed.wrapping_add(worker));
+        let mut report = LoadReport::default();
+        for _ in 0..operations {
+            if rng.next_f64() < self.read_ratio {
+                report.reads += 1;
+                let key = if rng.next_f64() < self.miss_ratio {
+                    next_miss.fetch_add(1, Ordering::Relaxed)
+                } else {
+                    rng.next_u64() % self.key_space
+                };
+                match cache.get_with_meta(key).await {
+                    Ok(meta) if meta.cached => report.hits += 1,
+                    Ok(_) => report.misses += 1,
+                    Err(_) => report.errors += 1,
+                }
+            } else {
+                report.writes += 1;
+                let key = rng.next_u64() % self.key_space;
+                if cache.set(key, key).await.is_err() {
+                    report.errors += 1;
+                }
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,241 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::Arc;
+use crate::backing::CacheBacking;
+use std::time::Duration;
+use futures::Future;
+use crate::cache_api::{CacheEntry, CacheLoadingError, EntryMeta, LoadingCache, Lookup, MaybeStale, ResultMeta, SwapPolicy};
+use crate::stats::{ActorStats, CacheStats};
+
+/// A handle to a `LoadingCache` which converts the loader error `E` into `F` on every call.
+///
+/// Created by `LoadingCache::map_err`, see there for details.
+pub struct MappedLoadingCache<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    F: Debug,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> {
+    cache: LoadingCache<K, V, E, B>,
+    mapper: Arc<dyn Fn(E) -> F + Send + Sync>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    F: Debug,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> Clone for MappedLoadingCache<K, V, E, F, B> {
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+            mapper: self.mapper.clone(),
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    F: Debug,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> MappedLoadingCache<K, V, E, F, B> {
+    pub(crate) fn new<M>(cache: LoadingCache<K, V, E, B>, mapper: M) -> Self
+        where M: Fn(E) -> F + Send + Sync + 'static {
+        Self {
+            cache,
+            mapper: Arc::new(mapper),
+        }
+    }
+
+    /// Returns the underlying cache which yields the unmapped loader errors
+    pub fn inner(&self) -> &LoadingCache<K, V, E, B> {
+        &self.cache
+    }
+
+    /// See `LoadingCache::name`
+    pub fn name(&self) -> Option<&str> {
+        self.cache.name()
+    }
+
+    /// See `LoadingCache::actor_stats`
+    pub fn actor_stats(&self) -> ActorStats {
+        self.cache.actor_stats()
+    }
+
+    /// See `LoadingCache::stats`
+    pub fn stats(&self) -> Option<CacheStats> {
+        self.cache.stats()
+    }
+
+    fn map<T>(&self, result: Result<T, CacheLoadingError<E>>) -> Result<T, CacheLoadingError<F>> {
+        result.map_err(|error| error.map_loading_error(|error| (self.mapper)(error)))
+    }
+
+    /// See `LoadingCache::get`
+    pub async fn get(&self, key: K) -> Result<V, CacheLoadingError<F>> {
+        self.map(self.cache.get(key).await)
+    }
+
+    /// See `LoadingCache::get_no_promote`
+    pub async fn get_no_promote(&self, key: K) -> Result<V, CacheLoadingError<F>> {
+        self.map(self.cache.get_no_promote(key).await)
+    }
+
+    /// See `LoadingCache::get_until`
+    pub async fn get_until<C>(&self, key: K, cancelled: C) -> Result<V, CacheLoadingError<F>>
+        where C: Future<Output=()> {
+        self.map(self.cache.get_until(key, cancelled).await)
+    }
+
+    /// See `LoadingCache::get_with_meta`
+    pub async fn get_with_meta(&self, key: K) -> Result<ResultMeta<V>, CacheLoadingError<F>> {
+        self.map(self.cache.get_with_meta(key).await)
+    }
+
+    /// See `LoadingCache::get_with_ttl`
+    pub async fn get_with_ttl(&self, key: K, ttl: Duration) -> Result<V, CacheLoadingError<F>>
+        where B::Meta: From<Duration> {
+        self.map(self.cache.get_with_ttl(key, ttl).await)
+    }
+
+    /// See `LoadingCache::get_allow_stale`
+    pub async fn get_allow_stale(&self, key: K, max_staleness: Duration) -> Result<MaybeStale<V>, CacheLoadingError<F>> {
+        self.map(self.cache.get_allow_stale(key, max_staleness).await)
+    }
+
+    /// See `LoadingCache::set_with_meta`
+    pub async fn set_with_meta(&self, key: K, value: V, meta: Option<B::Meta>) -> Result<Option<V>, CacheLoadingError<F>> {
+        self.map(self.cache.set_with_meta(key, value, meta).await)
+    }
+
+    /// See `LoadingCache::set`
+    pub async fn set(&self, key: K, value: V) -> Result<Option<V>, CacheLoadingError<F>> {
+        self.map(self.cache.set(key, value).await)
+    }
+
+    /// See `LoadingCache::set_with_ttl`
+    pub async fn set_with_ttl(&self, key: K, value: V, ttl: Duration) -> Result<Option<V>, CacheLoadingError<F>>
+        where B::Meta: From<Duration> {
+        self.map(self.cache.set_with_ttl(key, value, ttl).await)
+    }
+
+    /// See `LoadingCache::swap`
+    pub async fn swap(&self, key: K, value: V) -> Result<Option<V>, CacheLoadingError<F>> {
+        self.map(self.cache.swap(key, value).await)
+    }
+
+    /// See `LoadingCache::swap_with`
+    pub async fn swap_with(&self, key: K, value: V, policy: SwapPolicy) -> Result<Option<V>, CacheLoadingError<F>> {
+        self.map(self.cache.swap_with(key, value, policy).await)
+    }
+
+    /// See `LoadingCache::load_independent`
+    pub async fn load_independent(&self, key: K, update: bool) -> Result<V, CacheLoadingError<F>> {
+        self.map(self.cache.load_independent(key, update).await)
+    }
+
+    /// See `LoadingCache::try_get`
+    pub async fn try_get(&self, key: K) -> Result<V, CacheLoadingError<F>> {
+        self.map(self.cache.try_get(key).await)
+    }
+
+    /// See `LoadingCache::try_set`
+    pub async fn try_set(&self, key: K, value: V) -> Result<Option<V>, CacheLoadingError<F>> {
+        self.map(self.cache.try_set(key, value).await)
+    }
+
+    /// See `LoadingCache::get_if_present`
+    pub async fn get_if_present(&self, key: K) -> Result<Option<V>, CacheLoadingError<F>> {
+        self.map(self.cache.get_if_present(key).await)
+    }
+
+    /// See `LoadingCache::get_if_present_with_meta`
+    pub async fn get_if_present_with_meta(&self, key: K) -> Result<Option<EntryMeta<V>>, CacheLoadingError<F>> {
+        self.map(self.cache.get_if_present_with_meta(key).await)
+    }
+
+    /// See `LoadingCache::lookup`
+    pub async fn lookup(&self, key: K) -> Result<Lookup<V>, CacheLoadingError<F>> {
+        self.map(self.cache.lookup(key).await)
+    }
+
+    /// See `LoadingCache::peek_if_present`
+    pub async fn peek_if_present(&self, key: K) -> Result<Option<V>, CacheLoadingError<F>> {
+        self.map(self.cache.peek_if_present(key).await)
+    }
+
+    /// See `LoadingCache::exists`
+    pub async fn exists(&self, key: K) -> Result<bool, CacheLoadingError<F>> {
+        self.map(self.cache.exists(key).await)
+    }
+
+    /// See `LoadingCache::remove`
+    pub async fn remove(&self, key: K) -> Result<Option<V>, CacheLoadingError<F>> {
+        self.map(self.cache.remove(key).await)
+    }
+
+    /// See `LoadingCache::remove_if`
+    pub async fn remove_if<P: Fn((&K, Option<&V>)) -> bool + Send + Sync + 'static>(&self, predicate: P) -> Result<(), CacheLoadingError<F>> {
+        self.map(self.cache.remove_if(predicate).await)
+    }
+
+    /// See `LoadingCache::evict`
+    pub async fn evict(&self, count: usize) -> Result<Vec<(K, V)>, CacheLoadingError<F>> {
+        self.map(self.cache.evict(count).await)
+    }
+
+    /// See `LoadingCache::entries_by_recency`
+    pub async fn entries_by_recency(&self) -> Result<Vec<(K, V)>, CacheLoadingError<F>> {
+        self.map(self.cache.entries_by_recency().await)
+    }
+
+    /// See `LoadingCache::keys`
+    pub async fn keys(&self) -> Result<Vec<K>, CacheLoadingError<F>> {
+        self.map(self.cache.keys().await)
+    }
+
+    /// See `LoadingCache::entries`
+    pub async fn entries(&self) -> Result<Vec<(K, V)>, CacheLoadingError<F>> {
+        self.map(self.cache.entries().await)
+    }
+
+    /// See `LoadingCache::clear`
+    pub async fn clear(&self) -> Result<(), CacheLoadingError<F>> {
+        self.map(self.cache.clear().await)
+    }
+
+    /// See `LoadingCache::update`
+    pub async fn update<U>(&self, key: K, update_fn: U) -> Result<V, CacheLoadingError<F>>
+        where U: FnOnce(V) -> V + Send + 'static {
+        self.map(self.cache.update(key, update_fn).await)
+    }
+
+    /// See `LoadingCache::update_until`
+    pub async fn update_until<U, C>(&self, key: K, update_fn: U, cancelled: C) -> Result<V, CacheLoadingError<F>>
+        where U: FnOnce(V) -> V + Send + 'static,
+              C: Future<Output=()> {
+        self.map(self.cache.update_until(key, update_fn, cancelled).await)
+    }
+
+    /// See `LoadingCache::update_if_exists`
+    pub async fn update_if_exists<U>(&self, key: K, update_fn: U) -> Result<Option<V>, CacheLoadingError<F>>
+        where U: FnOnce(V) -> V + Send + 'static {
+        self.map(self.cache.update_if_exists(key, update_fn).await)
+    }
+
+    /// See `LoadingCache::update_mut`
+    pub async fn update_mut<U>(&self, key: K, update_fn: U) -> Result<V, CacheLoadingError<F>>
+        where U: FnMut(&mut V) + Send + 'static {
+        self.map(self.cache.update_mut(key, update_fn).await)
+    }
+
+    /// See `LoadingCache::update_mut_if_exists`
+    pub async fn update_mut_if_exists<U>(&self, key: K, update_fn: U) -> Result<Option<V>, CacheLoadingError<F>>
+        where U: FnMut(&mut V) + Send + 'static {
+        self.map(self.cache.update_mut_if_exists(key, update_fn).await)
+    }
+}
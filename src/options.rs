@@ -0,0 +1,447 @@
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::backing::{RemovalCause, RemovalListener};
+use crate::stats::Occupancy;
+
+/// Options of a `LoadingCache` which are independent of the backing and the loader function.
+///
+/// Passed to `LoadingCache::with_options` or `LoadingCache::with_meta_loader_and_options`.
+#[derive(Debug, Clone, Default)]
+pub struct CacheOptions {
+    pub(crate) name: Option<String>,
+    pub(crate) execution_mode: ExecutionMode,
+    pub(crate) loading_watchdog: Option<LoadingWatchdog>,
+    pub(crate) expiry_sweep: Option<Duration>,
+    pub(crate) batch_size: Option<usize>,
+    pub(crate) maintenance: Option<Duration>,
+    pub(crate) verify_backing: bool,
+    pub(crate) occupancy_watches: Vec<OccupancyWatch>,
+    pub(crate) track_entries: bool,
+    pub(crate) write_coalescing: Option<Duration>,
+    pub(crate) idle_park: Option<Duration>,
+    pub(crate) channel_capacity: Option<usize>,
+    pub(crate) backpressure: Backpressure,
+    pub(crate) removal_listener: Option<AnyRemovalListener>,
+    pub(crate) refresh_after_write: Option<Duration>,
+    pub(crate) record_stats: bool,
+    pub(crate) load_timeout: Option<Duration>,
+}
+
+/// Configuration of the watchdog which unblocks keys stuck in the `Loading` state
+#[derive(Debug, Copy, Clone)]
+pub struct LoadingWatchdog {
+    /// How often all loading keys are checked
+    pub interval: Duration,
+    /// Loads running longer than this are considered stuck, regardless whether their task is
+    /// still alive
+    pub max_loading_age: Option<Duration>,
+}
+
+/// Soft and hard limits of the occupancy of a cache, see `CacheOptions::occupancy_thresholds`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OccupancyThresholds {
+    /// Whether the number of entries or their weight is compared with the limits
+    pub measure: OccupancyMeasure,
+    pub soft: usize,
+    pub hard: usize,
+}
+
+/// What the limits of `OccupancyThresholds` apply to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OccupancyMeasure {
+    /// `Occupancy::entries`
+    Entries,
+    /// `Occupancy::weight`
+    Weight,
+}
+
+/// Which of the `OccupancyThresholds` the occupancy of a cache reached
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OccupancyLevel {
+    /// Below the soft limit
+    Normal,
+    /// At or above the soft limit, but below the hard limit
+    Soft,
+    /// At or above the hard limit
+    Hard,
+}
+
+/// A crossed threshold, passed to the listener of `CacheOptions::occupancy_thresholds`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OccupancyEvent {
+    pub thresholds: OccupancyThresholds,
+    pub previous: OccupancyLevel,
+    pub level: OccupancyLevel,
+    pub occupancy: Occupancy,
+}
+
+impl OccupancyEvent {
+    /// Whether the occupancy rose above a threshold, otherwise it fell below one
+    pub fn is_rising(&self) -> bool {
+        self.level > self.previous
+    }
+}
+
+impl OccupancyThresholds {
+    /// Limits the number of entries
+    pub fn entries(soft: usize, hard: usize) -> Self {
+        Self {
+            measure: OccupancyMeasure::Entries,
+            soft,
+            hard,
+        }
+    }
+
+    /// Limits the total weight of the entries
+    pub fn weight(soft: usize, hard: usize) -> Self {
+        Self {
+            measure: OccupancyMeasure::Weight,
+            soft,
+            hard,
+        }
+    }
+
+    pub(crate) fn level(&self, occupancy: &Occupancy) -> OccupancyLevel {
+        let value = match self.measure {
+            OccupancyMeasure::Entries => occupancy.entries,
+            OccupancyMeasure::Weight => occupancy.weight,
+        };
+        if value >= self.hard {
+            OccupancyLevel::Hard
+        } else if value >= self.soft {
+            OccupancyLevel::Soft
+        } else {
+            OccupancyLevel::Normal
+        }
+    }
+}
+
+pub type OccupancyListener = Arc<dyn Fn(OccupancyEvent) + Send + Sync>;
+
+/// Thresholds registered by `CacheOptions::occupancy_thresholds` with their listener
+#[derive(Clone)]
+pub(crate) struct OccupancyWatch {
+    pub(crate) thresholds: OccupancyThresholds,
+    pub(crate) listener: OccupancyListener,
+}
+
+impl Debug for OccupancyWatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OccupancyWatch")
+            .field("thresholds", &self.thresholds)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The listener of `CacheOptions::removal_listener`, its key and value types are only known to
+/// the cache it's passed to
+#[derive(Clone)]
+pub(crate) struct AnyRemovalListener(Arc<dyn Any + Send + Sync>);
+
+impl AnyRemovalListener {
+    pub(crate) fn downcast<K: 'static, V: 'static>(&self) -> RemovalListener<K, V> {
+        self.0.downcast_ref::<RemovalListener<K, V>>()
+            .cloned()
+            .expect("The removal listener doesn't match the key and value types of the cache")
+    }
+}
+
+impl Debug for AnyRemovalListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnyRemovalListener").finish_non_exhaustive()
+    }
+}
+
+/// How the operations of a cache are executed
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// Operations are sent to an actor task owning the backing, loads run in their own tasks
+    #[default]
+    Actor,
+    /// Operations are executed inline on the caller, without spawning tasks or using channels
+    ///
+    /// The backing is owned by a mutex the callers lock for the duration of an operation. A load
+    /// is driven by the caller which started it, other callers of the same key wait for it. The
+    /// order of operations is the order of the calls, which makes this mode suited for
+    /// deterministic tests and single-threaded simulations, as well as for embedded uses with
+    /// little concurrency, where the actor is overhead and a background task is unwanted. It also
+    /// works without a tokio runtime, as long as no timers (e.g. of the loading watchdog) are
+    /// used. If the caller driving a load drops it, the key is unblocked, its waiters receive a
+    /// `TokioBroadcastRecvError` and the next caller loads it again.
+    Inline,
+}
+
+/// What an operation does when the queue of the actor is full, see
+/// `CacheOptions::channel_capacity`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Waits until the actor took enough operations from its queue
+    #[default]
+    Wait,
+    /// Fails right away with `CacheLoadingError::Busy`, like `LoadingCache::try_get`
+    Fail,
+    /// The queue has no capacity and holds any number of operations, so nothing ever waits. A
+    /// saturated actor lets the queue grow without bounds.
+    Unbounded,
+}
+
+/// The capacity of the queues of the actor unless `CacheOptions::channel_capacity` is set
+pub(crate) const DEFAULT_CHANNEL_CAPACITY: usize = 128;
+
+impl CacheOptions {
+    pub fn new() -> CacheOptions {
+        Default::default()
+    }
+
+    /// Names the cache, so applications with several caches can tell them apart
+    ///
+    /// The name is returned by `LoadingCache::name` to label exported statistics, and recorded
+    /// in the spans of the `tracing` feature and the error responses of the axum admin router.
+    /// With the `task-names` feature enabled and built with `--cfg tokio_unstable`, the tasks
+    /// spawned by the cache are named after it, so tokio-console can attribute them to this cache.
+    /// Loader tasks additionally carry the hash of their key.
+    pub fn name<S: Into<String>>(mut self, name: S) -> CacheOptions {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the `ExecutionMode` of the cache, defaults to `ExecutionMode::Actor`
+    pub fn execution_mode(mut self, execution_mode: ExecutionMode) -> CacheOptions {
+        self.execution_mode = execution_mode;
+        self
+    }
+
+    /// Enables the loading watchdog
+    ///
+    /// A key can remain in `Loading` state forever if its loader task is aborted (e.g. by a panic
+    /// or a runtime shutdown). The watchdog detects those loads, as well as loads exceeding
+    /// `max_loading_age`, every `interval` and whenever such a key is accessed. Stuck keys are
+    /// unblocked, waiters receive a `TokioBroadcastRecvError` and the key becomes loadable again.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - How often the loading keys should be checked
+    /// * `max_loading_age` - Optional maximum duration of a load
+    pub fn loading_watchdog(mut self, interval: Duration, max_loading_age: Option<Duration>) -> CacheOptions {
+        self.loading_watchdog = Some(LoadingWatchdog {
+            interval,
+            max_loading_age,
+        });
+        self
+    }
+
+    /// Removes the expired entries of the backing every `interval`, see
+    /// `CacheBacking::remove_expired`
+    ///
+    /// Backings which don't purge expired entries on every operation, like a `TtlCacheBacking`
+    /// with `ExpirationMode::Lazy` or `ExpirationMode::Background`, rely on this to free the
+    /// memory of entries which aren't accessed anymore. The sweep runs in the task of the actor,
+    /// also while the cache is idle, expired entries are reported to the `removal_listener`. An
+    /// actor parked by `idle_park` is woken up to sweep once the first entry expired, see
+    /// `CacheBacking::next_expiry`. In `ExecutionMode::Inline` there is no task, the sweep runs
+    /// during the next operation once it's due.
+    pub fn expiry_sweep(mut self, interval: Duration) -> CacheOptions {
+        self.expiry_sweep = Some(interval);
+        self
+    }
+
+    /// Runs the `Maintenance` of the backing every `interval`, see `CacheBacking::maintenance`
+    ///
+    /// Backings which store their entries on disk compact their storage and return its free
+    /// space, so its size stays bounded. Errors are ignored, the next run tries again. Has no
+    /// effect on in-memory backings.
+    pub fn maintenance(mut self, interval: Duration) -> CacheOptions {
+        self.maintenance = Some(interval);
+        self
+    }
+
+    /// Verifies the backing after every operation in debug builds and panics on the first
+    /// inconsistency, see `CacheBacking::verify`
+    ///
+    /// This catches bookkeeping bugs of custom or composed backings right at the operation causing
+    /// them. Verifying visits every entry, so it's meant for tests, and release builds ignore it.
+    pub fn verify_backing(mut self, verify_backing: bool) -> CacheOptions {
+        self.verify_backing = verify_backing;
+        self
+    }
+
+    /// Calls `listener` whenever the occupancy of the backing crosses the soft or hard limit of
+    /// `thresholds`, in either direction, see `OccupancyEvent::is_rising`
+    ///
+    /// This lets applications shed load or alert before the cache starts thrashing. The occupancy
+    /// is taken from `BackingStats::occupancy` after every operation, backings which don't report
+    /// it never cross a threshold. The listener is called by the actor, so it should return
+    /// quickly. It can be called several times, e.g. to watch the entries and their weight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use cache_loader_async::backing::HashMapBacking;
+    /// use cache_loader_async::options::{CacheOptions, OccupancyLevel, OccupancyThresholds};
+    /// async fn example() {
+    ///     let options = CacheOptions::new().occupancy_thresholds(OccupancyThresholds::entries(8_000, 10_000), |event| {
+    ///         if event.is_rising() && event.level == OccupancyLevel::Hard {
+    ///             eprintln!("the cache holds {} entries", event.occupancy.entries);
+    ///         }
+    ///     });
+    ///     let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::with_options(HashMapBacking::new(), |key: u64| async move {
+    ///         Ok(key * 2)
+    ///     }, options);
+    ///     cache.get(1).await.unwrap();
+    /// }
+    /// ```
+    pub fn occupancy_thresholds<L>(mut self, thresholds: OccupancyThresholds, listener: L) -> CacheOptions
+        where L: Fn(OccupancyEvent) + Send + Sync + 'static {
+        self.occupancy_watches.push(OccupancyWatch {
+            thresholds,
+            listener: Arc::new(listener),
+        });
+        self
+    }
+
+    /// Calls `listener` with every loaded entry which is removed from the cache and the
+    /// `RemovalCause`
+    ///
+    /// Entries removed by `LoadingCache::remove`, `LoadingCache::remove_if` and
+    /// `LoadingCache::clear` are reported by the cache, entries evicted or expired by the backing
+    /// are reported by the backing, see `CacheBacking::set_removal_listener`. Replaced values and
+    /// keys which were still loading aren't reported. The listener is called by the actor, so it
+    /// should return quickly and must not wait for operations of the cache.
+    ///
+    /// # Panics
+    ///
+    /// Creating the cache panics if the key and value types of the listener differ from the ones
+    /// of the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cache_loader_async::cache_api::LoadingCache;
+    /// use cache_loader_async::backing::{HashMapBacking, RemovalCause};
+    /// use cache_loader_async::options::CacheOptions;
+    /// async fn example() {
+    ///     let options = CacheOptions::new().removal_listener(|key: u64, value: u64, cause: RemovalCause| {
+    ///         println!("{} with value {} was removed: {:?}", key, value, cause);
+    ///     });
+    ///     let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::with_options(HashMapBacking::new(), |key: u64| async move {
+    ///         Ok(key * 2)
+    ///     }, options);
+    ///     cache.get(1).await.unwrap();
+    ///     cache.remove(1).await.unwrap();
+    /// }
+    /// ```
+    pub fn removal_listener<K, V, L>(mut self, listener: L) -> CacheOptions
+        where K: 'static,
+              V: 'static,
+              L: Fn(K, V, RemovalCause) + Send + Sync + 'static {
+        let listener: RemovalListener<K, V> = Arc::new(listener);
+        self.removal_listener = Some(AnyRemovalListener(Arc::new(listener)));
+        self
+    }
+
+    /// Tracks when every entry was set and how often it was read, reported by
+    /// `LoadingCache::get_if_present_with_meta`
+    ///
+    /// The actor keeps a record per key, which costs a map lookup per operation. Records of keys
+    /// the backing evicted or expired on its own are dropped once the records outgrow the entries.
+    pub fn track_entries(mut self, track_entries: bool) -> CacheOptions {
+        self.track_entries = track_entries;
+        self
+    }
+
+    /// Reloads a value in the background once it's read after being cached for `interval`,
+    /// while the current value is still returned
+    ///
+    /// Unlike an expiry, readers of hot keys never wait for their reload this way. A key is only
+    /// refreshed once at a time, the reloaded value is discarded if the key was written, removed
+    /// or evicted while refreshing. A failed refresh keeps the current value, the next read
+    /// retries. Reads through `LoadingCache::get_all` don't refresh their keys, keys which are never
+    /// read aren't refreshed at all, combine this with an expiring backing to drop them. Has no
+    /// effect in `ExecutionMode::Inline`.
+    pub fn refresh_after_write(mut self, interval: Duration) -> CacheOptions {
+        self.refresh_after_write = Some(interval);
+        self
+    }
+
+    /// Records the hits, misses, loads and evictions of the cache, returned by
+    /// `LoadingCache::stats`
+    ///
+    /// Every read and every completed load takes a lock to update the `CacheStats`, which is why
+    /// they are only recorded on request.
+    pub fn record_stats(mut self, record_stats: bool) -> CacheOptions {
+        self.record_stats = record_stats;
+        self
+    }
+
+    /// Cancels loads which don't complete within `timeout`
+    ///
+    /// The caller which started the load and all waiters receive a `CacheLoadingError::LoadTimeout`,
+    /// and the key is unblocked, so the next lookup starts a new load. The timeout starts when
+    /// the load is started, waiters joining later only wait for the rest of it.
+    pub fn load_timeout(mut self, timeout: Duration) -> CacheOptions {
+        self.load_timeout = Some(timeout);
+        self
+    }
+
+    /// Collects the `LoadingCache::set`s and `LoadingCache::update`s of a key for `window`, after
+    /// which only their combined write is sent to the actor
+    ///
+    /// The first write of a key opens its window, later writes within it replace a pending set or
+    /// are applied on top of it, so a storm of writes on a hot key reaches the actor and the
+    /// backing once per window. All writers of a window wait until it was written and receive
+    /// its result: the previous value of the combined write for `set`, the written value for
+    /// `update`. Writes are only visible once their window closed. Other operations like
+    /// `set_with_meta` or `update_mut` are not coalesced. Requires a tokio runtime with the time
+    /// driver enabled, also in `ExecutionMode::Inline`.
+    pub fn write_coalescing(mut self, window: Duration) -> CacheOptions {
+        self.write_coalescing = Some(window);
+        self
+    }
+
+    /// Ends the task of the actor once it received no operation for `idle`, the next operation
+    /// spawns it again with the same backing and state
+    ///
+    /// Applications with many rarely used caches don't keep a parked task per cache this way. The
+    /// first operation after parking pays for spawning the task. The loading watchdog and the
+    /// maintenance don't run while the actor is parked, loads which complete meanwhile wake it up.
+    /// With an `expiry_sweep`, the parked actor is spawned again once the first entry of the
+    /// backing expired, so idle entries are still swept. If every handle of the cache is gone, the
+    /// actor ends instead of parking. Has no effect in `ExecutionMode::Inline`.
+    pub fn idle_park(mut self, idle: Duration) -> CacheOptions {
+        self.idle_park = Some(idle);
+        self
+    }
+
+    /// Sets how many operations the queue of the actor holds, defaults to `128`
+    ///
+    /// Completed loads are reported through a separate queue of the same capacity. Has no effect
+    /// with `Backpressure::Unbounded` and in `ExecutionMode::Inline`.
+    pub fn channel_capacity(mut self, capacity: usize) -> CacheOptions {
+        self.channel_capacity = Some(capacity.max(1));
+        self
+    }
+
+    /// Sets what operations do when the queue of the actor is full, defaults to
+    /// `Backpressure::Wait`
+    ///
+    /// Internal messages, like completed loads and released key locks, always wait for capacity.
+    /// Has no effect in `ExecutionMode::Inline`.
+    pub fn backpressure(mut self, backpressure: Backpressure) -> CacheOptions {
+        self.backpressure = backpressure;
+        self
+    }
+
+    /// Lets the actor take up to `batch_size` queued operations at once and respond to them after
+    /// all of them were executed, defaults to `1`
+    ///
+    /// Under heavy concurrent access this saves a wakeup of the actor per operation, at the cost of
+    /// responding to the first operations of a batch a little later. Completed loads are still
+    /// processed ahead of the queued operations. Has no effect in `ExecutionMode::Inline`.
+    pub fn batch_size(mut self, batch_size: usize) -> CacheOptions {
+        self.batch_size = Some(batch_size.max(1));
+        self
+    }
+}
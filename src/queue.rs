@@ -0,0 +1,109 @@
+use std::fmt::Debug;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+
+/// The sending half of the queue of an actor, bounded unless `Backpressure::Unbounded` is used
+pub(crate) enum QueueSender<T> {
+    Bounded(mpsc::Sender<T>),
+    Unbounded(mpsc::UnboundedSender<T>),
+}
+
+/// The receiving half of the queue of an actor
+pub(crate) enum QueueReceiver<T> {
+    Bounded(mpsc::Receiver<T>),
+    Unbounded(mpsc::UnboundedReceiver<T>),
+}
+
+/// Creates a queue holding up to `capacity` messages, or any number of them without a capacity
+pub(crate) fn queue<T>(capacity: Option<usize>) -> (QueueSender<T>, QueueReceiver<T>) {
+    match capacity {
+        Some(capacity) => {
+            let (tx, rx) = mpsc::channel(capacity);
+            (QueueSender::Bounded(tx), QueueReceiver::Bounded(rx))
+        }
+        None => {
+            let (tx, rx) = mpsc::unbounded_channel();
+            (QueueSender::Unbounded(tx), QueueReceiver::Unbounded(rx))
+        }
+    }
+}
+
+impl<T> QueueSender<T> {
+    /// Sends the message, waiting for capacity of a bounded queue, the message is returned if the
+    /// receiver is gone
+    pub(crate) async fn send(&self, message: T) -> Result<(), T> {
+        match self {
+            QueueSender::Bounded(tx) => tx.send(message).await.map_err(|err| err.0),
+            QueueSender::Unbounded(tx) => tx.send(message).map_err(|err| err.0),
+        }
+    }
+
+    /// Sends the message if the queue has capacity, an unbounded queue is never full
+    pub(crate) fn try_send(&self, message: T) -> Result<(), TrySendError<T>> {
+        match self {
+            QueueSender::Bounded(tx) => tx.try_send(message),
+            QueueSender::Unbounded(tx) => tx.send(message).map_err(|err| TrySendError::Closed(err.0)),
+        }
+    }
+}
+
+impl<T> Clone for QueueSender<T> {
+    fn clone(&self) -> Self {
+        match self {
+            QueueSender::Bounded(tx) => QueueSender::Bounded(tx.clone()),
+            QueueSender::Unbounded(tx) => QueueSender::Unbounded(tx.clone()),
+        }
+    }
+}
+
+impl<T> Debug for QueueSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueueSender::Bounded(tx) => f.debug_tuple("Bounded").field(tx).finish(),
+            QueueSender::Unbounded(tx) => f.debug_tuple("Unbounded").field(tx).finish(),
+        }
+    }
+}
+
+impl<T> From<mpsc::Sender<T>> for QueueSender<T> {
+    fn from(tx: mpsc::Sender<T>) -> Self {
+        QueueSender::Bounded(tx)
+    }
+}
+
+impl<T> QueueReceiver<T> {
+    pub(crate) async fn recv(&mut self) -> Option<T> {
+        match self {
+            QueueReceiver::Bounded(rx) => rx.recv().await,
+            QueueReceiver::Unbounded(rx) => rx.recv().await,
+        }
+    }
+
+    pub(crate) async fn recv_many(&mut self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        match self {
+            QueueReceiver::Bounded(rx) => rx.recv_many(buffer, limit).await,
+            QueueReceiver::Unbounded(rx) => rx.recv_many(buffer, limit).await,
+        }
+    }
+
+    /// Closes the queue, the messages which were already sent can still be received
+    pub(crate) fn close(&mut self) {
+        match self {
+            QueueReceiver::Bounded(rx) => rx.close(),
+            QueueReceiver::Unbounded(rx) => rx.close(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        match self {
+            QueueReceiver::Bounded(rx) => rx.is_empty(),
+            QueueReceiver::Unbounded(rx) => rx.is_empty(),
+        }
+    }
+}
+
+impl<T> From<mpsc::Receiver<T>> for QueueReceiver<T> {
+    fn from(rx: mpsc::Receiver<T>) -> Self {
+        QueueReceiver::Bounded(rx)
+    }
+}
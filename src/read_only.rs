@@ -0,0 +1,89 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, CacheLoadingError, EntryMeta, LoadingCache, Lookup};
+use crate::stats::{ActorStats, BackingStats, CacheStats};
+
+/// A handle to a `LoadingCache` which can read and load values, but not set, update or remove
+/// them
+///
+/// Created by `LoadingCache::read_only`, see there for details.
+pub struct ReadOnlyLoadingCache<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> {
+    cache: LoadingCache<K, V, E, B>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> Clone for ReadOnlyLoadingCache<K, V, E, B> {
+    fn clone(&self) -> Self {
+        Self {
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> ReadOnlyLoadingCache<K, V, E, B> {
+    pub(crate) fn new(cache: LoadingCache<K, V, E, B>) -> Self {
+        Self {
+            cache,
+        }
+    }
+
+    /// See `LoadingCache::name`
+    pub fn name(&self) -> Option<&str> {
+        self.cache.name()
+    }
+
+    /// See `LoadingCache::get`
+    pub async fn get(&self, key: K) -> Result<V, CacheLoadingError<E>> {
+        self.cache.get(key).await
+    }
+
+    /// See `LoadingCache::get_if_present`
+    pub async fn get_if_present(&self, key: K) -> Result<Option<V>, CacheLoadingError<E>> {
+        self.cache.get_if_present(key).await
+    }
+
+    /// See `LoadingCache::get_if_present_with_meta`
+    pub async fn get_if_present_with_meta(&self, key: K) -> Result<Option<EntryMeta<V>>, CacheLoadingError<E>> {
+        self.cache.get_if_present_with_meta(key).await
+    }
+
+    /// See `LoadingCache::lookup`
+    pub async fn lookup(&self, key: K) -> Result<Lookup<V>, CacheLoadingError<E>> {
+        self.cache.lookup(key).await
+    }
+
+    /// See `LoadingCache::exists`
+    pub async fn exists(&self, key: K) -> Result<bool, CacheLoadingError<E>> {
+        self.cache.exists(key).await
+    }
+
+    /// See `LoadingCache::backing_stats`
+    pub async fn backing_stats(&self) -> Result<BackingStats, CacheLoadingError<E>> {
+        self.cache.backing_stats().await
+    }
+
+    /// See `LoadingCache::actor_stats`
+    pub fn actor_stats(&self) -> ActorStats {
+        self.cache.actor_stats()
+    }
+
+    /// See `LoadingCache::stats`
+    pub fn stats(&self) -> Option<CacheStats> {
+        self.cache.stats()
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use futures::future::BoxFuture;
+use futures::{FutureExt, TryStreamExt};
+use thiserror::Error;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, CacheLoadingError, LoadingCache};
+use crate::stats::{ActorStats, BackingStats};
+
+/// How many entries `CacheIntrospection::dump` fetches from the backing per operation
+const DUMP_CHUNK_SIZE: usize = 1024;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    #[error("Only named caches can be registered, see CacheOptions::name")]
+    Unnamed(),
+    #[error("A cache named {0} is already registered")]
+    Duplicate(String),
+    /// An operation of a registered cache failed, carrying the name of the cache and the
+    /// message of its `CacheLoadingError`
+    #[error("Cache {0}: {1}")]
+    Cache(String, String),
+}
+
+/// The statistics of a registered cache
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheStats {
+    pub backing: BackingStats,
+    pub actor: ActorStats,
+}
+
+/// A registered cache, whose key, value and error types are erased so caches of different types
+/// can be managed alike
+pub trait CacheIntrospection: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Returns the statistics of the backing and of the actor of the cache
+    fn stats(&self) -> BoxFuture<'_, Result<CacheStats, RegistryError>>;
+
+    /// Returns the number of entries, including keys which are still loading, `None` for backings
+    /// which don't report their `Occupancy`
+    fn len(&self) -> BoxFuture<'_, Result<Option<usize>, RegistryError>>;
+
+    /// Returns whether the cache has no entries, `None` like `len`
+    fn is_empty(&self) -> BoxFuture<'_, Result<Option<bool>, RegistryError>> {
+        let len = self.len();
+        async move {
+            Ok(len.await?.map(|len| len == 0))
+        }.boxed()
+    }
+
+    /// Removes all entries of the cache
+    fn clear(&self) -> BoxFuture<'_, Result<(), RegistryError>>;
+
+    /// Returns the loaded entries formatted with `Debug`, see `LoadingCache::export`
+    fn dump(&self) -> BoxFuture<'_, Result<Vec<(String, String)>, RegistryError>>;
+}
+
+struct RegisteredCache<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> {
+    name: String,
+    cache: LoadingCache<K, V, E, B>,
+}
+
+impl<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> RegisteredCache<K, V, E, B> {
+    fn error(&self, error: CacheLoadingError<E>) -> RegistryError {
+        RegistryError::Cache(self.name.clone(), error.to_string())
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + Sync + Debug + 'static,
+    V: Clone + Sized + Send + Sync + Debug + 'static,
+    E: Clone + Sized + Send + Sync + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> CacheIntrospection for RegisteredCache<K, V, E, B>
+    where B::Meta: 'static {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn stats(&self) -> BoxFuture<'_, Result<CacheStats, RegistryError>> {
+        async move {
+            let backing = self.cache.backing_stats().await.map_err(|err| self.error(err))?;
+            Ok(CacheStats {
+                backing,
+                actor: self.cache.actor_stats(),
+            })
+        }.boxed()
+    }
+
+    fn len(&self) -> BoxFuture<'_, Result<Option<usize>, RegistryError>> {
+        async move {
+            let stats = self.cache.backing_stats().await.map_err(|err| self.error(err))?;
+            Ok(stats.occupancy.map(|occupancy| occupancy.entries))
+        }.boxed()
+    }
+
+    fn clear(&self) -> BoxFuture<'_, Result<(), RegistryError>> {
+        async move {
+            self.cache.clear().await.map_err(|err| self.error(err))
+        }.boxed()
+    }
+
+    fn dump(&self) -> BoxFuture<'_, Result<Vec<(String, String)>, RegistryError>> {
+        async move {
+            self.cache.export(DUMP_CHUNK_SIZE)
+                .map_ok(|(key, value, _)| (format!("{:?}", key), format!("{:?}", value)))
+                .try_collect()
+                .await
+                .map_err(|err| self.error(err))
+        }.boxed()
+    }
+}
+
+/// A registry of named caches, which lets a single admin or debug endpoint enumerate and manage
+/// every cache of the process
+///
+/// Caches are only registered when asked to, by `CacheRegistry::register`. The registry holds a
+/// handle of each cache, so a registered cache lives until it's unregistered.
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::backing::HashMapBacking;
+/// use cache_loader_async::cache_api::LoadingCache;
+/// use cache_loader_async::options::CacheOptions;
+/// use cache_loader_async::registry::CacheRegistry;
+/// async fn example() {
+///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_options(
+///         HashMapBacking::new(),
+///         move |key: String| async move { Ok(key.len()) },
+///         CacheOptions::new().name("lengths"),
+///     );
+///     CacheRegistry::global().register(&cache).unwrap();
+///     cache.get("foo".to_owned()).await.unwrap();
+///
+///     for cache in CacheRegistry::global().caches() {
+///         println!("{}: {:?}", cache.name(), cache.dump().await.unwrap());
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct CacheRegistry {
+    caches: Mutex<BTreeMap<String, Arc<dyn CacheIntrospection>>>,
+}
+
+impl CacheRegistry {
+    pub const fn new() -> CacheRegistry {
+        CacheRegistry {
+            caches: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the registry shared by the whole process
+    pub fn global() -> &'static CacheRegistry {
+        static GLOBAL: CacheRegistry = CacheRegistry::new();
+        &GLOBAL
+    }
+
+    /// Registers a handle of the cache under its name
+    ///
+    /// # Return Value
+    ///
+    /// Returns a Result with:
+    /// Ok - The cache was registered
+    /// Err - `RegistryError::Unnamed` if the cache has no name, `RegistryError::Duplicate` if
+    ///       another cache is registered under its name
+    pub fn register<K, V, E, B>(&self, cache: &LoadingCache<K, V, E, B>) -> Result<(), RegistryError>
+        where K: Eq + Hash + Clone + Send + Sync + Debug + 'static,
+              V: Clone + Sized + Send + Sync + Debug + 'static,
+              E: Clone + Sized + Send + Sync + Debug + 'static,
+              B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+              B::Meta: 'static {
+        let name = cache.name().ok_or(RegistryError::Unnamed())?.to_owned();
+        let mut caches = self.caches.lock().unwrap();
+        if caches.contains_key(&name) {
+            return Err(RegistryError::Duplicate(name));
+        }
+        caches.insert(name.clone(), Arc::new(RegisteredCache {
+            name,
+            cache: cache.clone(),
+        }));
+        Ok(())
+    }
+
+    /// Removes the cache registered under the name, returns whether there was one
+    pub fn unregister(&self, name: &str) -> bool {
+        self.caches.lock().unwrap().remove(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn CacheIntrospection>> {
+        self.caches.lock().unwrap().get(name).cloned()
+    }
+
+    /// Returns the registered caches, ordered by their name
+    pub fn caches(&self) -> Vec<Arc<dyn CacheIntrospection>> {
+        self.caches.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Returns the names of the registered caches in order
+    pub fn names(&self) -> Vec<String> {
+        self.caches.lock().unwrap().keys().cloned().collect()
+    }
+}
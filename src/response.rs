@@ -0,0 +1,203 @@
+//! Single-use response slots the actor answers operations through
+//!
+//! A slot behaves like a `tokio::sync::oneshot` channel, but once both ends are done it is handed
+//! back to the `ResponsePool` it was taken from, so read heavy workloads don't allocate a channel
+//! per operation.
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use futures::FutureExt;
+use tokio::sync::oneshot::error::RecvError;
+
+struct SlotState<T> {
+    value: Option<T>,
+    // set once the sender responded or was dropped, it doesn't touch the slot afterwards
+    sender_done: bool,
+    receiver_dropped: bool,
+    waker: Option<Waker>,
+}
+
+struct Slot<T> {
+    state: Mutex<SlotState<T>>,
+}
+
+impl<T> Slot<T> {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(SlotState {
+                value: None,
+                sender_done: false,
+                receiver_dropped: false,
+                waker: None,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SlotState<T>> {
+        // the state is consistent after every update, so a poisoned lock is still usable
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Keeps the slots of finished responses for reuse
+pub(crate) struct ResponsePool<T> {
+    slots: Mutex<Vec<Arc<Slot<T>>>>,
+    max_pooled: usize,
+}
+
+impl<T> ResponsePool<T> {
+    /// Creates a pool keeping up to `max_pooled` idle slots, the capacity of the queue of the
+    /// actor, more slots are only needed while operations wait for loads
+    pub(crate) fn new(max_pooled: usize) -> Self {
+        Self {
+            slots: Mutex::new(Vec::new()),
+            max_pooled,
+        }
+    }
+
+    /// Takes an idle slot, or allocates one if there is none
+    pub(crate) fn channel(self: &Arc<Self>) -> (ResponseSender<T>, ResponseReceiver<T>) {
+        let slot = self.lock().pop().unwrap_or_else(|| Arc::new(Slot::new()));
+        pair(slot, Some(self.clone()))
+    }
+
+    /// The number of idle slots
+    pub(crate) fn pooled(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn release(&self, slot: Arc<Slot<T>>) {
+        let mut slots = self.lock();
+        if slots.len() < self.max_pooled {
+            slots.push(slot);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Arc<Slot<T>>>> {
+        self.slots.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> Debug for ResponsePool<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponsePool")
+            .field("pooled", &self.pooled())
+            .finish()
+    }
+}
+
+/// Creates a response slot which isn't returned to a pool
+pub(crate) fn channel<T>() -> (ResponseSender<T>, ResponseReceiver<T>) {
+    pair(Arc::new(Slot::new()), None)
+}
+
+fn pair<T>(slot: Arc<Slot<T>>, pool: Option<Arc<ResponsePool<T>>>) -> (ResponseSender<T>, ResponseReceiver<T>) {
+    let sender = ResponseSender {
+        slot: Some(slot.clone()),
+    };
+    let receiver = ResponseReceiver {
+        slot: Some(slot),
+        pool,
+    };
+    (sender, receiver)
+}
+
+pub(crate) struct ResponseSender<T> {
+    slot: Option<Arc<Slot<T>>>,
+}
+
+impl<T> ResponseSender<T> {
+    /// Responds with the value, which is returned if the receiver is gone
+    pub(crate) fn send(mut self, value: T) -> Result<(), T> {
+        let slot = self.slot.take().expect("Sender is only consumed by send");
+        let mut state = slot.lock();
+        state.sender_done = true;
+        if state.receiver_dropped {
+            return Err(value);
+        }
+        state.value = Some(value);
+        let waker = state.waker.take();
+        std::mem::drop(state);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Whether the receiver stopped waiting for the response
+    pub(crate) fn is_closed(&self) -> bool {
+        self.slot.as_ref().is_some_and(|slot| slot.lock().receiver_dropped)
+    }
+}
+
+impl<T> Drop for ResponseSender<T> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot.take() {
+            let mut state = slot.lock();
+            state.sender_done = true;
+            let waker = state.waker.take();
+            std::mem::drop(state);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+pub(crate) struct ResponseReceiver<T> {
+    slot: Option<Arc<Slot<T>>>,
+    pool: Option<Arc<ResponsePool<T>>>,
+}
+
+impl<T> Future for ResponseReceiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let slot = self.slot.as_ref().expect("Receiver polled after completion");
+        let mut state = slot.lock();
+        if let Some(value) = state.value.take() {
+            return Poll::Ready(Ok(value));
+        }
+        if state.sender_done {
+            return Poll::Ready(Err(closed()));
+        }
+        match &state.waker {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            _ => state.waker = Some(cx.waker().clone()),
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for ResponseReceiver<T> {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot.take() {
+            let mut state = slot.lock();
+            state.receiver_dropped = true;
+            if !state.sender_done {
+                // the sender still uses the slot, it's freed once both are gone
+                return;
+            }
+            state.value = None;
+            state.sender_done = false;
+            state.receiver_dropped = false;
+            state.waker = None;
+            std::mem::drop(state);
+            if let Some(pool) = self.pool.take() {
+                pool.release(slot);
+            }
+        }
+    }
+}
+
+/// tokio's `RecvError` can't be constructed, it's taken from a channel whose sender is gone
+fn closed() -> RecvError {
+    let (_, rx) = tokio::sync::oneshot::channel::<()>();
+    match rx.now_or_never() {
+        Some(Err(err)) => err,
+        _ => unreachable!("A oneshot channel without sender is closed"),
+    }
+}
@@ -0,0 +1,24 @@
+/// A xorshift64 pseudo random sequence, good enough to pick keys and failures reproducibly
+pub(crate) struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub(crate) fn new(seed: u64) -> XorShift {
+        // xorshift64 needs a non-zero state
+        XorShift { state: seed.max(1) }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Returns a sample from `0.0` (inclusive) to `1.0` (exclusive)
+    #[cfg(any(test, feature = "test-util", feature = "loadgen", feature = "chaos"))]
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
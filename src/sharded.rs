@@ -0,0 +1,199 @@
+use std::collections::hash_map::RandomState;
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hash};
+use std::sync::Arc;
+use futures::Future;
+use futures::future::try_join_all;
+use crate::backing::{CacheBacking, HashMapBacking};
+use crate::cache_api::{CacheEntry, CacheLoadingError, LoadingCache, Lookup};
+use crate::options::CacheOptions;
+use crate::stats::CacheStats;
+
+/// A cache split into several `LoadingCache`s, the shards, each with its own actor and backing
+///
+/// Every key is hashed to one of the shards, so operations on keys of different shards run
+/// concurrently instead of queueing up at a single actor. Operations on the same key always reach
+/// the same shard, so concurrent loads of a key are still deduplicated. Each shard gets its own
+/// backing and the same `CacheOptions`, a limit of the backing like the capacity of an
+/// `LruCacheBacking` applies per shard. Operations which aren't forwarded by this handle are
+/// available on the shard of a key, see `shard`.
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::sharded::ShardedLoadingCache;
+/// async fn example() {
+///     let cache: ShardedLoadingCache<u64, u64, u8, _> = ShardedLoadingCache::new(8, move |key: u64| {
+///         async move { Ok(key * 2) }
+///     });
+///
+///     assert_eq!(cache.get(21).await.unwrap(), 42);
+///     cache.set(1, 10).await.unwrap();
+///     assert_eq!(cache.get_if_present(1).await.unwrap(), Some(10));
+/// }
+/// ```
+pub struct ShardedLoadingCache<
+    K: Clone + Eq + Hash + Send,
+    V: Clone + Sized + Send,
+    E: Debug + Clone + Send,
+    B: CacheBacking<K, CacheEntry<V, E>>
+> {
+    shards: Arc<[LoadingCache<K, V, E, B>]>,
+    hasher: RandomState,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> Clone for ShardedLoadingCache<K, V, E, B> {
+    fn clone(&self) -> Self {
+        Self {
+            shards: self.shards.clone(),
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+> ShardedLoadingCache<K, V, E, HashMapBacking<K, CacheEntry<V, E>>> {
+    /// Creates a cache with `shards` shards, each storing its keys in a `HashMapBacking`
+    ///
+    /// # Arguments
+    ///
+    /// * `shards` - The number of shards, at least one
+    /// * `loader` - A function which returns a Future<Output=Result<V, E>>, shared by the shards
+    pub fn new<T, F>(shards: usize, loader: T) -> ShardedLoadingCache<K, V, E, HashMapBacking<K, CacheEntry<V, E>>>
+        where F: Future<Output=Result<V, E>> + Sized + Send + 'static,
+              T: Fn(K) -> F + Send + Sync + 'static {
+        ShardedLoadingCache::with_options(shards, HashMapBacking::new, loader, CacheOptions::default())
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> ShardedLoadingCache<K, V, E, B> {
+    /// Creates a cache with `shards` shards, each with a backing created by `backing` and the
+    /// given `CacheOptions`
+    ///
+    /// # Arguments
+    ///
+    /// * `shards` - The number of shards, at least one
+    /// * `backing` - A function which creates the backing of a shard
+    /// * `loader` - A function which returns a Future<Output=Result<V, E>>, shared by the shards
+    /// * `options` - The options which should be applied to every shard
+    pub fn with_options<C, T, F>(shards: usize, backing: C, loader: T, options: CacheOptions) -> ShardedLoadingCache<K, V, E, B>
+        where C: Fn() -> B,
+              F: Future<Output=Result<V, E>> + Sized + Send + 'static,
+              T: Fn(K) -> F + Send + Sync + 'static {
+        let loader = Arc::new(loader);
+        let shards = (0..shards.max(1))
+            .map(|_| {
+                let loader = loader.clone();
+                LoadingCache::with_options(backing(), move |key| loader(key), options.clone())
+            })
+            .collect();
+        ShardedLoadingCache {
+            shards,
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Returns the shards of the cache
+    pub fn shards(&self) -> &[LoadingCache<K, V, E, B>] {
+        &self.shards
+    }
+
+    /// Returns the shard which holds the key
+    pub fn shard(&self, key: &K) -> &LoadingCache<K, V, E, B> {
+        &self.shards[(self.hasher.hash_one(key) % self.shards.len() as u64) as usize]
+    }
+
+    /// See `LoadingCache::get`
+    pub async fn get(&self, key: K) -> Result<V, CacheLoadingError<E>> {
+        self.shard(&key).get(key).await
+    }
+
+    /// See `LoadingCache::get_if_present`
+    pub async fn get_if_present(&self, key: K) -> Result<Option<V>, CacheLoadingError<E>> {
+        self.shard(&key).get_if_present(key).await
+    }
+
+    /// See `LoadingCache::lookup`
+    pub async fn lookup(&self, key: K) -> Result<Lookup<V>, CacheLoadingError<E>> {
+        self.shard(&key).lookup(key).await
+    }
+
+    /// See `LoadingCache::exists`
+    pub async fn exists(&self, key: K) -> Result<bool, CacheLoadingError<E>> {
+        self.shard(&key).exists(key).await
+    }
+
+    /// See `LoadingCache::set`
+    pub async fn set(&self, key: K, value: V) -> Result<Option<V>, CacheLoadingError<E>> {
+        self.shard(&key).set(key, value).await
+    }
+
+    /// See `LoadingCache::update`
+    pub async fn update<U>(&self, key: K, update_fn: U) -> Result<V, CacheLoadingError<E>>
+        where U: FnOnce(V) -> V + Send + 'static {
+        self.shard(&key).update(key, update_fn).await
+    }
+
+    /// See `LoadingCache::update_mut`
+    pub async fn update_mut<U>(&self, key: K, update_fn: U) -> Result<V, CacheLoadingError<E>>
+        where U: FnMut(&mut V) + Send + 'static {
+        self.shard(&key).update_mut(key, update_fn).await
+    }
+
+    /// See `LoadingCache::remove`
+    pub async fn remove(&self, key: K) -> Result<Option<V>, CacheLoadingError<E>> {
+        self.shard(&key).remove(key).await
+    }
+
+    /// Returns the keys of all shards, see `LoadingCache::keys`
+    pub async fn keys(&self) -> Result<Vec<K>, CacheLoadingError<E>> {
+        let keys = try_join_all(self.shards.iter().map(|shard| shard.keys())).await?;
+        Ok(keys.into_iter().flatten().collect())
+    }
+
+    /// Returns the entries of all shards, see `LoadingCache::entries`
+    pub async fn entries(&self) -> Result<Vec<(K, V)>, CacheLoadingError<E>> {
+        let entries = try_join_all(self.shards.iter().map(|shard| shard.entries())).await?;
+        Ok(entries.into_iter().flatten().collect())
+    }
+
+    /// Clears all shards, see `LoadingCache::clear`
+    pub async fn clear(&self) -> Result<(), CacheLoadingError<E>> {
+        try_join_all(self.shards.iter().map(|shard| shard.clear())).await?;
+        Ok(())
+    }
+
+    /// Flushes all shards, see `LoadingCache::flush`
+    pub async fn flush(&self) -> Result<(), CacheLoadingError<E>> {
+        try_join_all(self.shards.iter().map(|shard| shard.flush())).await?;
+        Ok(())
+    }
+
+    /// Ends the actors of all shards, see `LoadingCache::shutdown`
+    pub async fn shutdown(&self) -> Result<(), CacheLoadingError<E>> {
+        try_join_all(self.shards.iter().map(|shard| shard.shutdown())).await?;
+        Ok(())
+    }
+
+    /// Returns the statistics of all shards added up, `None` unless `CacheOptions::record_stats`
+    /// is enabled, see `LoadingCache::stats`
+    pub fn stats(&self) -> Option<CacheStats> {
+        self.shards.iter()
+            .map(|shard| shard.stats())
+            .reduce(|total, stats| Some(total?.merge(&stats?)))
+            .flatten()
+    }
+}
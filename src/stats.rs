@@ -0,0 +1,281 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::backing::RemovalCause;
+
+/// Statistics reported by a backing, see `CacheBacking::stats`
+///
+/// Every kind of backing fills in its own section, sections of other backings stay `None`. The
+/// backing storing the entries reports their `Occupancy`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BackingStats {
+    pub ttl: Option<TtlStats>,
+    pub lru: Option<LruStats>,
+    pub occupancy: Option<Occupancy>,
+}
+
+/// How many entries a backing stores and how much they weigh
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Occupancy {
+    /// The number of entries, including keys which are still loading
+    pub entries: usize,
+    /// The total weight of the entries, equal to `entries` for backings without weights
+    pub weight: usize,
+}
+
+/// Statistics of a `TtlCacheBacking`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TtlStats {
+    /// Entries which were removed or replaced after they expired
+    pub expirations: u64,
+    /// Entries which were removed before they expired, by `remove`, `remove_if`, `evict` or
+    /// `clear`
+    pub removals: u64,
+    /// Entries in the expiry queue, including expired entries which weren't removed yet
+    pub queue_length: usize,
+}
+
+/// Statistics of an `LruCacheBacking`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LruStats {
+    /// The maximum number of entries, `usize::MAX` for unbounded backings
+    pub capacity: usize,
+    /// The current number of entries, including keys which are still loading
+    pub len: usize,
+    /// The total weight of the entries, equal to `len` unless weights are assigned, see
+    /// `LruCacheBacking::max_weight`
+    pub weight: usize,
+}
+
+impl LruStats {
+    /// Returns the share of the capacity in use, from `0.0` to `1.0`
+    ///
+    /// A backing which stays at `1.0` evicts an entry for every new key, which may be a sign
+    /// that its capacity is too small for the working set.
+    pub fn utilization(&self) -> f64 {
+        match self.capacity {
+            0 => 1.0,
+            capacity => self.len as f64 / capacity as f64,
+        }
+    }
+}
+
+/// The size of the storage of a backing which stores its entries on disk, see
+/// `Maintenance::report`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaintenanceReport {
+    /// Bytes the storage takes on disk
+    pub disk_size: u64,
+    /// Bytes of removed or replaced entries, which `compact` and `vacuum` would free
+    pub reclaimable: u64,
+}
+
+/// Timings of the operations executed by the actor of a cache, see `LoadingCache::actor_stats`
+///
+/// A high queue wait with a low processing time means the actor is saturated by the number of
+/// operations, a high processing time points to slow backing operations or update functions.
+/// The time loaders take isn't included, as loads run in their own tasks.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActorStats {
+    /// Operations executed by the actor, including the completions of loads
+    pub operations: u64,
+    /// The total time operations waited in the queue until the actor took them
+    pub queue_wait: Duration,
+    pub max_queue_wait: Duration,
+    /// The total time the actor spent executing operations
+    pub processing_time: Duration,
+    pub max_processing_time: Duration,
+}
+
+impl ActorStats {
+    pub fn mean_queue_wait(&self) -> Duration {
+        Self::mean(self.queue_wait, self.operations)
+    }
+
+    pub fn mean_processing_time(&self) -> Duration {
+        Self::mean(self.processing_time, self.operations)
+    }
+
+    fn mean(total: Duration, operations: u64) -> Duration {
+        match operations {
+            0 => Duration::ZERO,
+            operations => Duration::from_nanos((total.as_nanos() / operations as u128) as u64),
+        }
+    }
+}
+
+/// Collects the `ActorStats` of an actor, readable without sending an operation to it
+#[derive(Debug, Default)]
+pub(crate) struct ActorMetrics {
+    operations: AtomicU64,
+    queue_wait_nanos: AtomicU64,
+    max_queue_wait_nanos: AtomicU64,
+    processing_nanos: AtomicU64,
+    max_processing_nanos: AtomicU64,
+}
+
+impl ActorMetrics {
+    pub(crate) fn record(&self, queue_wait: Duration, processing_time: Duration) {
+        let queue_wait = queue_wait.as_nanos() as u64;
+        let processing_time = processing_time.as_nanos() as u64;
+        self.operations.fetch_add(1, Ordering::Relaxed);
+        self.queue_wait_nanos.fetch_add(queue_wait, Ordering::Relaxed);
+        self.max_queue_wait_nanos.fetch_max(queue_wait, Ordering::Relaxed);
+        self.processing_nanos.fetch_add(processing_time, Ordering::Relaxed);
+        self.max_processing_nanos.fetch_max(processing_time, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> ActorStats {
+        ActorStats {
+            operations: self.operations.load(Ordering::Relaxed),
+            queue_wait: Duration::from_nanos(self.queue_wait_nanos.load(Ordering::Relaxed)),
+            max_queue_wait: Duration::from_nanos(self.max_queue_wait_nanos.load(Ordering::Relaxed)),
+            processing_time: Duration::from_nanos(self.processing_nanos.load(Ordering::Relaxed)),
+            max_processing_time: Duration::from_nanos(self.max_processing_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Hits, misses and loads of a cache, see `CacheOptions::record_stats` and `LoadingCache::stats`
+///
+/// All counters of a snapshot were taken at the same time, so they are consistent with each
+/// other.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CacheStats {
+    /// Reads which found a loaded value, including stale values
+    pub hits: u64,
+    /// Reads which found no loaded value, whether they started a load, waited for one or
+    /// returned nothing
+    pub misses: u64,
+    /// Loads which returned a value, including background refreshes
+    pub load_successes: u64,
+    /// Loads which failed with an error of the loader
+    pub load_failures: u64,
+    /// The total time of the successful and failed loads
+    pub total_load_time: Duration,
+    /// Entries the backing evicted, see `RemovalCause::Evicted`
+    pub evictions: u64,
+    /// Entries the backing removed after they expired, see `RemovalCause::Expired`
+    pub expirations: u64,
+    // the number of loads by the power of two of their nanoseconds
+    load_time_histogram: Vec<u64>,
+}
+
+impl CacheStats {
+    /// Returns the number of reads, hits and misses
+    pub fn requests(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    /// Returns the share of reads which were hits, from `0.0` to `1.0`, `1.0` without reads
+    pub fn hit_rate(&self) -> f64 {
+        match self.requests() {
+            0 => 1.0,
+            requests => self.hits as f64 / requests as f64,
+        }
+    }
+
+    pub fn mean_load_time(&self) -> Duration {
+        match self.load_successes + self.load_failures {
+            0 => Duration::ZERO,
+            loads => Duration::from_nanos((self.total_load_time.as_nanos() / loads as u128) as u64),
+        }
+    }
+
+    /// Returns the time which `percentile` percent of the loads took at most, e.g. `99.0` for
+    /// the 99th percentile
+    ///
+    /// Load times are recorded in buckets of powers of two, the percentile is the upper bound of
+    /// its bucket and overestimates the actual time by less than a factor of two.
+    pub fn load_time_percentile(&self, percentile: f64) -> Duration {
+        let loads = self.load_time_histogram.iter().sum::<u64>();
+        if loads == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((percentile.clamp(0.0, 100.0) / 100.0 * loads as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in self.load_time_histogram.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Duration::from_nanos(1u64.checked_shl(bucket as u32 + 1).unwrap_or(u64::MAX));
+            }
+        }
+        Duration::from_nanos(u64::MAX)
+    }
+
+    /// Adds the counters of `other` to these, e.g. to sum up the shards of a
+    /// `ShardedLoadingCache`
+    pub fn merge(mut self, other: &CacheStats) -> CacheStats {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.load_successes += other.load_successes;
+        self.load_failures += other.load_failures;
+        self.total_load_time += other.total_load_time;
+        self.evictions += other.evictions;
+        self.expirations += other.expirations;
+        if self.load_time_histogram.len() < other.load_time_histogram.len() {
+            self.load_time_histogram.resize(other.load_time_histogram.len(), 0);
+        }
+        for (bucket, count) in other.load_time_histogram.iter().enumerate() {
+            self.load_time_histogram[bucket] += count;
+        }
+        self
+    }
+
+    fn record_load(&mut self, success: bool, load_time: Duration) {
+        if success {
+            self.load_successes += 1;
+        } else {
+            self.load_failures += 1;
+        }
+        self.total_load_time += load_time;
+        let nanos = (load_time.as_nanos() as u64).max(1);
+        let bucket = (u64::BITS - 1 - nanos.leading_zeros()) as usize;
+        if self.load_time_histogram.len() <= bucket {
+            self.load_time_histogram.resize(bucket + 1, 0);
+        }
+        self.load_time_histogram[bucket] += 1;
+    }
+}
+
+/// Collects the `CacheStats` of a cache, written by the actor and the loads
+#[derive(Debug, Default)]
+pub(crate) struct CacheMetrics {
+    // a lock instead of atomics, so a snapshot never mixes counters of different moments
+    stats: Mutex<CacheStats>,
+}
+
+impl CacheMetrics {
+    pub(crate) fn record_read(&self, hit: bool) {
+        let mut stats = self.stats.lock().unwrap();
+        if hit {
+            stats.hits += 1;
+        } else {
+            stats.misses += 1;
+        }
+    }
+
+    pub(crate) fn record_load(&self, success: bool, load_time: Duration) {
+        self.stats.lock().unwrap().record_load(success, load_time);
+    }
+
+    pub(crate) fn record_removal(&self, cause: RemovalCause) {
+        let mut stats = self.stats.lock().unwrap();
+        match cause {
+            RemovalCause::Evicted => stats.evictions += 1,
+            RemovalCause::Expired => stats.expirations += 1,
+            RemovalCause::Explicit | RemovalCause::Cleared => {}
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> CacheStats {
+        self.stats.lock().unwrap().clone()
+    }
+}
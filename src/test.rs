@@ -1,10 +1,13 @@
 use std::collections::HashMap;
-use crate::cache_api::{LoadingCache, CacheLoadingError};
-#[cfg(feature = "ttl-cache")]
+use crate::cache_api::{LoadingCache, CacheLoadingError, CacheEntry, CacheCommunicationError};
+use crate::options::{Backpressure, CacheOptions};
+use crate::test_util::SlowLoader;
 use crate::cache_api::WithMeta;
 use tokio::time::Duration;
 use cache_loader_async_macros::test_with_features;
-use crate::backing::HashMapBacking;
+use crate::backing::{NoMeta, BackingError, BackingErrorPolicy, BackingPredicate, CacheBacking, HashMapBacking, RecoveringBacking, TtlError};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 #[cfg(feature = "ttl-cache")]
 use crate::backing::TtlMeta;
 #[cfg(feature = "lru-cache")]
@@ -59,7 +62,7 @@ async fn test_load() {
 }
 
 #[cfg(feature = "ttl-cache")]
-#[tokio::test]
+#[tokio::test(start_paused = true)]
 async fn test_ttl_load_meta() {
     let cache: LoadingCache<String, String, u8, _> =
         LoadingCache::with_meta_loader(TtlCacheBacking::new(Duration::from_secs(1)), move |key: String| {
@@ -129,12 +132,12 @@ test_with_features! {
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_update() {
-    let cache: LoadingCache<String, String, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+    let loader = SlowLoader::new(move |key: String| {
         async move {
-            tokio::time::sleep(Duration::from_millis(500)).await;
             Ok(key.to_lowercase())
         }
-    });
+    }).latency(Duration::from_millis(500));
+    let cache: LoadingCache<String, String, u8, HashMapBacking<_, _>> = LoadingCache::new(loader.into_loader());
 
     // We test to update an existing key
     cache.set("woob".to_owned(), "Woob".to_owned()).await.ok();
@@ -208,12 +211,12 @@ test_with_features! {
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_update_mut() {
-    let cache: LoadingCache<String, String, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+    let loader = SlowLoader::new(move |key: String| {
         async move {
-            tokio::time::sleep(Duration::from_millis(500)).await;
             Ok(key.to_lowercase())
         }
-    });
+    }).latency(Duration::from_millis(500));
+    let cache: LoadingCache<String, String, u8, HashMapBacking<_, _>> = LoadingCache::new(loader.into_loader());
 
     // We test to update an existing key
     cache.set("woob".to_owned(), "Woob".to_owned()).await.ok();
@@ -376,49 +379,3766 @@ async fn test_lru_backing() {
     assert_eq!(cache.get("remove_test".to_owned()).await.unwrap(), "remove_test".to_lowercase());
 }
 
-#[cfg(feature = "ttl-cache")]
+#[cfg(feature = "lru-cache")]
 #[tokio::test]
-async fn test_ttl_backing() {
+async fn test_lru_peek_if_present() {
+    let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(LruCacheBacking::new(2), move |key: String| {
+        async move {
+            Ok(key.to_lowercase())
+        }
+    });
+
+    cache.set("key1".to_owned(), "value1".to_owned()).await.unwrap();
+    cache.set("key2".to_owned(), "value2".to_owned()).await.unwrap();
+    assert_eq!(cache.peek_if_present("key1".to_owned()).await.unwrap(), Some("value1".to_owned()));
+    assert_eq!(cache.peek_if_present("key3".to_owned()).await.unwrap(), None);
+
+    // peeking didn't make key1 more recent than key2
+    cache.set("key3".to_owned(), "value3".to_owned()).await.unwrap();
+    assert_eq!(cache.peek_if_present("key1".to_owned()).await.unwrap(), None);
+    assert_eq!(cache.peek_if_present("key2".to_owned()).await.unwrap(), Some("value2".to_owned()));
+}
+
+#[cfg(feature = "lru-cache")]
+#[tokio::test]
+async fn test_lru_constructors() {
+    use std::collections::hash_map::RandomState;
+
     let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(
-        TtlCacheBacking::new(Duration::from_secs(3)), move |key: String| {
+        LruCacheBacking::with_hasher(2, RandomState::new()), move |key: String| {
             async move {
                 Ok(key.to_lowercase())
             }
         });
+    cache.set("key1".to_owned(), "value1".to_owned()).await.unwrap();
+    assert_eq!(cache.get("KEY2".to_owned()).await.unwrap(), "key2".to_owned());
+    assert_eq!(cache.backing_stats().await.unwrap().lru.unwrap().capacity, 2);
 
-    cache.set("key1".to_owned(), "value1".to_lowercase()).await.ok();
-    tokio::time::sleep(Duration::from_secs(2)).await;
-    cache.set("key2".to_owned(), "value2".to_lowercase()).await.ok();
+    let backing = LruCacheBacking::<u64, [u8; 64]>::with_memory_budget(1 << 20, 0);
+    assert!(backing.capacity() > 1 << 12 && backing.capacity() < 1 << 14);
+    let backing = LruCacheBacking::<u64, String>::with_memory_budget(1 << 20, 1000);
+    assert!(backing.capacity() > 900 && backing.capacity() < 1 << 10);
+    assert_eq!(LruCacheBacking::<u64, String>::with_memory_budget(0, 1000).capacity(), 1);
+}
 
-    assert_eq!(cache.get("key1".to_owned()).await.unwrap(), "value1".to_lowercase());
-    assert_eq!(cache.get("key2".to_owned()).await.unwrap(), "value2".to_lowercase());
+#[cfg(feature = "lru-cache")]
+#[tokio::test]
+async fn test_lru_get_no_promote() {
+    let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(LruCacheBacking::new(2), move |key: String| {
+        async move {
+            Ok(key.to_lowercase())
+        }
+    });
 
-    tokio::time::sleep(Duration::from_secs(2)).await;
+    cache.set("key1".to_owned(), "value1".to_owned()).await.unwrap();
+    cache.set("key2".to_owned(), "value2".to_owned()).await.unwrap();
+    assert_eq!(cache.get_no_promote("key1".to_owned()).await.unwrap(), "value1".to_owned());
 
-    assert_eq!(cache.exists("key1".to_owned()).await.unwrap(), false);
+    // loads still insert the key, evicting key1 which wasn't promoted
+    assert_eq!(cache.get_no_promote("KEY3".to_owned()).await.unwrap(), "key3".to_owned());
+    assert_eq!(cache.peek_if_present("key1".to_owned()).await.unwrap(), None);
+    assert_eq!(cache.peek_if_present("KEY3".to_owned()).await.unwrap(), Some("key3".to_owned()));
 }
 
-#[cfg(all(feature = "ttl-cache", feature = "lru-cache"))]
+#[cfg(feature = "lru-cache")]
 #[tokio::test]
-async fn test_ttl_lru_backing() {
-    let cache: LoadingCache<String, _, u8, _> = LoadingCache::with_meta_loader(TtlCacheBacking::with_backing(Duration::from_secs(1), LruCacheBacking::new(2)), move |key: String| {
-       async move {
-           if key.len() < 3 {
-               Ok(key.to_lowercase())
-                   .with_meta(Some(TtlMeta::from(Duration::from_secs(15))))
-           } else {
-               Ok(key.to_lowercase())
-                   .with_meta(None)
-           }
-       }
+async fn test_lru_evict() {
+    let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(LruCacheBacking::new(10), move |key: String| {
+        async move {
+            Ok(key.to_lowercase())
+        }
     });
-    assert_eq!(cache.get("a".to_owned()).await.unwrap(), "a");
-    assert_eq!(cache.get("bbbbb".to_owned()).await.unwrap(), "bbbbb");
-    tokio::time::sleep(Duration::from_secs(2)).await;
-    assert!(cache.exists("a".to_owned()).await.unwrap());
-    assert!(!cache.exists("bbbbb".to_owned()).await.unwrap());
-    assert_eq!(cache.get("bbbbb".to_owned()).await.unwrap(), "bbbbb");
-    assert_eq!(cache.get("ccccc".to_owned()).await.unwrap(), "ccccc");
-    assert_eq!(cache.get("ddddd".to_owned()).await.unwrap(), "ddddd");
-    assert!(!cache.exists("a".to_owned()).await.unwrap());
-}
\ No newline at end of file
+
+    for i in 0..5 {
+        cache.set(format!("key{}", i), i.to_string()).await.unwrap();
+    }
+    cache.get_if_present("key0".to_owned()).await.unwrap();
+
+    assert_eq!(cache.evict(2).await.unwrap(), vec![
+        ("key1".to_owned(), "1".to_owned()),
+        ("key2".to_owned(), "2".to_owned()),
+    ]);
+    assert_eq!(cache.evict(5).await.unwrap().len(), 3);
+    assert!(cache.evict(1).await.unwrap().is_empty());
+    assert!(!cache.exists("key0".to_owned()).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_keys_and_entries() {
+    let started = Arc::new(tokio::sync::Notify::new());
+    let loader_started = started.clone();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+        let started = loader_started.clone();
+        async move {
+            if key == "pending" {
+                started.notify_one();
+                futures::future::pending::<()>().await;
+            }
+            Ok(key.len())
+        }
+    });
+    cache.set("a".to_owned(), 1).await.unwrap();
+    cache.get("bb".to_owned()).await.unwrap();
+    let pending = cache.clone();
+    tokio::spawn(async move { pending.get("pending".to_owned()).await });
+    started.notified().await;
+
+    // the key which is still loading is left out
+    let mut keys = cache.keys().await.unwrap();
+    keys.sort();
+    assert_eq!(keys, vec!["a".to_owned(), "bb".to_owned()]);
+    let mut entries = cache.entries().await.unwrap();
+    entries.sort();
+    assert_eq!(entries, vec![("a".to_owned(), 1), ("bb".to_owned(), 2)]);
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_ttl_keys() {
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+        TtlCacheBacking::new(Duration::from_secs(3)), move |key: String| {
+            async move { Ok(key.len()) }
+        });
+    cache.set("old".to_owned(), 1).await.unwrap();
+    tokio::time::advance(Duration::from_secs(2)).await;
+    cache.set("new".to_owned(), 2).await.unwrap();
+    tokio::time::advance(Duration::from_secs(2)).await;
+    // the entries of backings without their own listing are collected through their export
+    assert_eq!(cache.keys().await.unwrap(), vec!["new".to_owned()]);
+}
+
+#[cfg(feature = "lru-cache")]
+#[tokio::test]
+async fn test_lru_entries_by_recency() {
+    let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(LruCacheBacking::new(10), move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    });
+
+    for i in 0..3 {
+        cache.set(format!("key{}", i), i).await.unwrap();
+    }
+    cache.get_if_present("key0".to_owned()).await.unwrap();
+    let recency = vec![("key0".to_owned(), 0), ("key2".to_owned(), 2), ("key1".to_owned(), 1)];
+    assert_eq!(cache.entries_by_recency().await.unwrap(), recency);
+    // listing the entries doesn't promote them
+    assert_eq!(cache.entries_by_recency().await.unwrap(), recency);
+
+    let mut backing = LruCacheBacking::new(10);
+    backing.set(1, 1, None).unwrap();
+    backing.set(2, 2, None).unwrap();
+    assert_eq!(backing.iter().rev().map(|(key, _)| *key).collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[cfg(feature = "lru-cache")]
+#[tokio::test]
+async fn test_lru_stats() {
+    use crate::stats::LruStats;
+
+    let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(LruCacheBacking::new(4), move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    });
+    cache.set("key1".to_owned(), 1).await.unwrap();
+    let stats = cache.backing_stats().await.unwrap();
+    assert_eq!(stats.lru, Some(LruStats { capacity: 4, len: 1, weight: 1 }));
+    assert_eq!(stats.lru.unwrap().utilization(), 0.25);
+    assert!(stats.ttl.is_none());
+
+    for i in 0..10 {
+        cache.set(format!("key{}", i), i).await.unwrap();
+    }
+    assert_eq!(cache.backing_stats().await.unwrap().lru.unwrap().utilization(), 1.0);
+}
+
+#[cfg(feature = "lru-cache")]
+#[tokio::test]
+async fn test_lru_on_evict() {
+    let evicted = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let listener = evicted.clone();
+    let backing = LruCacheBacking::new(2)
+        .on_evict(move |key: String, entry: CacheEntry<usize, u8>| {
+            if let CacheEntry::Loaded(value) = entry {
+                listener.lock().unwrap().push((key, value));
+            }
+        });
+    let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    });
+
+    cache.set("key1".to_owned(), 1).await.unwrap();
+    cache.set("key2".to_owned(), 2).await.unwrap();
+    // replacing a key doesn't evict it
+    assert_eq!(cache.set("key1".to_owned(), 10).await.unwrap(), Some(1));
+    assert!(evicted.lock().unwrap().is_empty());
+
+    cache.set("key3".to_owned(), 3).await.unwrap();
+    assert_eq!(*evicted.lock().unwrap(), vec![("key2".to_owned(), 2)]);
+
+    // explicit removals aren't reported
+    cache.remove("key1".to_owned()).await.unwrap();
+    cache.evict(1).await.unwrap();
+    assert_eq!(evicted.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_get_or_insert_with() {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let counter = loads.clone();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            Ok(key.len())
+        }
+    });
+
+    let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+    let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+    let supplying_cache = cache.clone();
+    let supplied = tokio::spawn(async move {
+        supplying_cache.get_or_insert_with("foo".to_owned(), async move {
+            started_tx.send(()).ok();
+            release_rx.await.ok();
+            Ok(42)
+        }).await
+    });
+    started_rx.await.unwrap();
+    // the get joins the supplied load instead of calling the loader, the supplied future is only
+    // released once the get has been handled by the cache task, which handles it before the
+    // following exists
+    let (loaded, _) = tokio::join!(cache.get("foo".to_owned()), async {
+        cache.exists("foo".to_owned()).await.unwrap();
+        release_tx.send(()).ok();
+    });
+    assert_eq!(supplied.await.unwrap().unwrap(), 42);
+    assert_eq!(loaded.unwrap(), 42);
+    assert_eq!(loads.load(Ordering::SeqCst), 0);
+
+    // a cached value is returned without awaiting the future
+    assert_eq!(cache.get_or_insert_with("foo".to_owned(), async { Ok(7) }).await.unwrap(), 42);
+    assert!(matches!(cache.get_or_insert_with("bar".to_owned(), async { Err(1) }).await, Err(CacheLoadingError::LoadingError(1))));
+    assert_eq!(cache.get("bar".to_owned()).await.unwrap(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_refresh_after_write() {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let counter = loads.clone();
+    let options = CacheOptions::new().refresh_after_write(Duration::from_secs(10));
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_options(HashMapBacking::new(), move |_: String| {
+        let counter = counter.clone();
+        async move {
+            let value = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Ok(value)
+        }
+    }, options);
+
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 1);
+    tokio::time::advance(Duration::from_secs(5)).await;
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 1);
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    // the current value is served while the key is refreshed once
+    tokio::time::advance(Duration::from_secs(6)).await;
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 1);
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), Some(1));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 2);
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+    // a value written while refreshing isn't replaced
+    tokio::time::advance(Duration::from_secs(11)).await;
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 2);
+    cache.set("foo".to_owned(), 10).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(loads.load(Ordering::SeqCst), 3);
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 10);
+}
+
+#[tokio::test]
+async fn test_removal_listener() {
+    use crate::backing::RemovalCause;
+    let removed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let listener = removed.clone();
+    let options = CacheOptions::new().removal_listener(move |key: u64, value: u64, cause: RemovalCause| {
+        listener.lock().unwrap().push((key, value, cause));
+    });
+    let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::with_options(HashMapBacking::new(), |key: u64| async move {
+        Ok(key * 2)
+    }, options);
+
+    for key in 1..=4 {
+        cache.get(key).await.unwrap();
+    }
+    // replacing a value doesn't remove it
+    cache.set(1, 5).await.unwrap();
+    cache.remove(1).await.unwrap();
+    cache.remove_if(|(key, _)| *key == 2).await.unwrap();
+    assert_eq!(*removed.lock().unwrap(), vec![(1, 5, RemovalCause::Explicit), (2, 4, RemovalCause::Explicit)]);
+
+    removed.lock().unwrap().clear();
+    cache.clear().await.unwrap();
+    let mut cleared = removed.lock().unwrap().clone();
+    cleared.sort_by_key(|(key, _, _)| *key);
+    assert_eq!(cleared, vec![(3, 6, RemovalCause::Cleared), (4, 8, RemovalCause::Cleared)]);
+}
+
+#[cfg(all(feature = "lru-cache", feature = "ttl-cache"))]
+#[tokio::test(start_paused = true)]
+async fn test_removal_listener_backing() {
+    use crate::backing::RemovalCause;
+    let removed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let listener = removed.clone();
+    let options = CacheOptions::new().removal_listener(move |key: u64, value: u64, cause: RemovalCause| {
+        listener.lock().unwrap().push((key, value, cause));
+    });
+    let backing = TtlCacheBacking::with_backing(Duration::from_secs(10), LruCacheBacking::new(2));
+    let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::with_options(backing, |key: u64| async move {
+        Ok(key * 2)
+    }, options);
+
+    cache.get(1).await.unwrap();
+    cache.get(2).await.unwrap();
+    cache.get(3).await.unwrap();
+    assert_eq!(*removed.lock().unwrap(), vec![(1, 2, RemovalCause::Evicted)]);
+
+    tokio::time::advance(Duration::from_secs(11)).await;
+    cache.sweep_expired().await.unwrap();
+    assert_eq!(&removed.lock().unwrap()[1..], &[(2, 4, RemovalCause::Expired), (3, 6, RemovalCause::Expired)]);
+}
+
+/// Pretends to store its entries on disk, removed entries stay reclaimable until compacted
+#[derive(Default)]
+struct DiskBacking {
+    map: HashMapBacking<String, CacheEntry<usize, u8>>,
+    removed: u64,
+    compactions: u64,
+    fail: bool,
+}
+
+impl CacheBacking<String, CacheEntry<usize, u8>> for DiskBacking {
+    type Meta = NoMeta;
+
+    fn get_mut(&mut self, key: &String) -> Result<Option<&mut CacheEntry<usize, u8>>, BackingError> {
+        self.map.get_mut(key)
+    }
+
+    fn get(&mut self, key: &String) -> Result<Option<&CacheEntry<usize, u8>>, BackingError> {
+        self.map.get(key)
+    }
+
+    fn set(&mut self, key: String, value: CacheEntry<usize, u8>, meta: Option<Self::Meta>) -> Result<Option<CacheEntry<usize, u8>>, BackingError> {
+        self.map.set(key, value, meta)
+    }
+
+    fn remove(&mut self, key: &String) -> Result<Option<CacheEntry<usize, u8>>, BackingError> {
+        let removed = self.map.remove(key)?;
+        self.removed += removed.iter().count() as u64;
+        Ok(removed)
+    }
+
+    fn contains_key(&mut self, key: &String) -> Result<bool, BackingError> {
+        self.map.contains_key(key)
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<String, CacheEntry<usize, u8>>) -> Result<Vec<(String, CacheEntry<usize, u8>)>, BackingError> {
+        self.map.remove_if(predicate)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.map.clear()
+    }
+
+    fn maintenance(&mut self) -> Option<&mut dyn crate::backing::Maintenance> {
+        Some(self)
+    }
+}
+
+impl crate::backing::Maintenance for DiskBacking {
+    fn compact(&mut self) -> Result<(), BackingError> {
+        if self.fail {
+            return Err(BackingError::TtlError(TtlError::ExpiryNotFound));
+        }
+        self.removed = 0;
+        self.compactions += 1;
+        Ok(())
+    }
+
+    fn vacuum(&mut self) -> Result<(), BackingError> {
+        Ok(())
+    }
+
+    fn report(&self) -> crate::stats::MaintenanceReport {
+        crate::stats::MaintenanceReport {
+            disk_size: self.compactions,
+            reclaimable: self.removed,
+        }
+    }
+}
+
+/// Keeps removed entries, like a storage whose deletes are lost
+#[cfg(feature = "ttl-cache")]
+struct LeakyBacking<K, V> {
+    map: HashMapBacking<K, V>,
+}
+
+#[cfg(feature = "ttl-cache")]
+impl<K: Eq + std::hash::Hash + Clone + Send, V: Clone + Send> CacheBacking<K, V> for LeakyBacking<K, V> {
+    type Meta = NoMeta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
+        self.map.get_mut(key)
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        self.map.get(key)
+    }
+
+    fn set(&mut self, key: K, value: V, meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
+        self.map.set(key, value, meta)
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        Ok(self.map.get(key)?.cloned())
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        self.map.contains_key(key)
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
+        self.map.remove_if(predicate)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.map.clear()
+    }
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test]
+async fn test_verify() {
+    let backing = TtlCacheBacking::with_backing(Duration::from_secs(60), LeakyBacking { map: HashMapBacking::new() });
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(backing, |key: String| async move {
+        Ok(key.len())
+    });
+    cache.set("foo".to_owned(), 1).await.unwrap();
+    cache.set("bar".to_owned(), 2).await.unwrap();
+    assert!(cache.verify(false).await.unwrap().is_empty());
+
+    // the expiry is removed while the nested backing keeps the entry
+    cache.remove("foo".to_owned()).await.unwrap();
+    let inconsistencies = cache.verify(false).await.unwrap();
+    assert_eq!(inconsistencies.len(), 1);
+    assert_eq!(inconsistencies[0].backing, "TtlCacheBacking");
+    assert_eq!(inconsistencies[0].to_string(), "TtlCacheBacking: 1 entries have no expiry in the queue");
+    assert_eq!(cache.verify(true).await.unwrap(), inconsistencies);
+    assert!(cache.verify(false).await.unwrap().is_empty());
+    assert_eq!(cache.backing_stats().await.unwrap().ttl.unwrap().queue_length, 2);
+}
+
+#[cfg(all(feature = "shared-memory", feature = "json-codec"))]
+#[tokio::test]
+async fn test_shared_memory_backing() {
+    use crate::backing::SharedMemoryBacking;
+    use crate::codec::JsonCodec;
+
+    let path = std::env::temp_dir().join(format!("cache-loader-async-{}", std::process::id()));
+    let loads = Arc::new(AtomicUsize::new(0));
+    // two caches over the same map behave like caches of two processes
+    let open = |slot_size| {
+        let loads = loads.clone();
+        let backing = SharedMemoryBacking::open(&path, 64, slot_size, JsonCodec).unwrap();
+        let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+            loads.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(key.to_uppercase()) }
+        });
+        cache
+    };
+    let first = open(128);
+    let second = open(4096);
+
+    assert_eq!(first.get("foo".to_owned()).await.unwrap(), "FOO");
+    assert_eq!(second.get("foo".to_owned()).await.unwrap(), "FOO");
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    second.set("foo".to_owned(), "bar".to_owned()).await.unwrap();
+    assert_eq!(first.get_if_present("foo".to_owned()).await.unwrap(), Some("bar".to_owned()));
+    first.update_mut("foo".to_owned(), |value| value.push('!')).await.unwrap();
+    assert_eq!(second.get_if_present("foo".to_owned()).await.unwrap(), Some("bar!".to_owned()));
+
+    // the geometry of the existing map is used, so the value doesn't fit and stays local
+    let large = "x".repeat(200);
+    second.set("large".to_owned(), large.clone()).await.unwrap();
+    assert_eq!(second.get_if_present("large".to_owned()).await.unwrap(), Some(large));
+    assert_eq!(first.get_if_present("large".to_owned()).await.unwrap(), None);
+
+    first.remove("foo".to_owned()).await.unwrap();
+    assert!(!second.exists("foo".to_owned()).await.unwrap());
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// An in-memory stand-in for a bucket of an object store, counting the uploads and failing
+/// them while `unavailable` is set
+#[cfg(feature = "object-store")]
+#[derive(Clone, Default)]
+struct FakeBucket {
+    objects: Arc<std::sync::Mutex<HashMap<String, Vec<u8>>>>,
+    puts: Arc<AtomicUsize>,
+    unavailable: Arc<std::sync::atomic::AtomicBool>,
+}
+
+#[cfg(feature = "object-store")]
+impl crate::backing::ObjectStore for FakeBucket {
+    fn get(&self, name: &str) -> futures::future::BoxFuture<'static, std::io::Result<Option<Vec<u8>>>> {
+        let object = self.objects.lock().unwrap().get(name).cloned();
+        Box::pin(async move { Ok(object) })
+    }
+
+    fn put(&self, name: &str, bytes: Vec<u8>) -> futures::future::BoxFuture<'static, std::io::Result<()>> {
+        self.puts.fetch_add(1, Ordering::SeqCst);
+        if self.unavailable.load(Ordering::SeqCst) {
+            return Box::pin(async { Err(std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "unavailable")) });
+        }
+        self.objects.lock().unwrap().insert(name.to_owned(), bytes);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn delete(&self, name: &str) -> futures::future::BoxFuture<'static, std::io::Result<()>> {
+        self.objects.lock().unwrap().remove(name);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[cfg(all(feature = "object-store", feature = "json-codec"))]
+#[tokio::test]
+async fn test_object_store_backing() {
+    use futures::StreamExt;
+    use crate::backing::ObjectStoreBacking;
+    use crate::codec::JsonCodec;
+
+    let bucket = FakeBucket::default();
+    let loads = Arc::new(AtomicUsize::new(0));
+    // two caches over the same bucket behave like a cache before and after a restart
+    let open = || {
+        let loads = loads.clone();
+        let backing = ObjectStoreBacking::new(HashMapBacking::new(), bucket.clone(), "reports/", JsonCodec);
+        let tier = backing.cold_tier();
+        let loader = tier.loader(move |id: u64| {
+            loads.fetch_add(1, Ordering::SeqCst);
+            async move { Ok::<_, u8>(format!("report-{}", id)) }
+        });
+        (LoadingCache::with_backing(backing, loader), tier)
+    };
+    let object = |name: &str| bucket.objects.lock().unwrap().get(name).cloned();
+    let (first, first_tier) = open();
+
+    assert_eq!(first.get(1).await.unwrap(), "report-1");
+    first.set(1, "edited".to_owned()).await.unwrap();
+    first.set(2, "removed".to_owned()).await.unwrap();
+    first.remove_if(|(key, _)| *key == 2).await.unwrap();
+    // listings answer from the local backing
+    assert_eq!(first.keys().await.unwrap(), vec![1]);
+    let exported = first.export(8).map(|entry| entry.map(|(key, value, _)| (key, value)).unwrap())
+        .collect::<Vec<_>>().await;
+    assert_eq!(exported, vec![(1, "edited".to_owned())]);
+    first_tier.sync().await.unwrap();
+    assert_eq!(object("reports/31").unwrap(), b"\"edited\"");
+    assert_eq!(object("reports/32"), None);
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    // values mutated in place are uploaded again
+    first.update_mut(1, |value| value.push('!')).await.unwrap();
+    first_tier.sync().await.unwrap();
+    assert_eq!(object("reports/31").unwrap(), b"\"edited!\"");
+
+    // the value is fetched from the bucket without loading or uploading it again
+    let (second, second_tier) = open();
+    assert_eq!(second.get(1).await.unwrap(), "edited!");
+    second_tier.sync().await.unwrap();
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    assert_eq!(bucket.puts.load(Ordering::SeqCst), 4);
+
+    second.remove(1).await.unwrap();
+    second_tier.sync().await.unwrap();
+    assert_eq!(object("reports/31"), None);
+    assert_eq!(second.get(1).await.unwrap(), "report-1");
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+    first.clear().await.unwrap();
+    first_tier.sync().await.unwrap();
+    assert!(bucket.objects.lock().unwrap().is_empty());
+
+    // a failed upload is reported by the next sync only
+    bucket.unavailable.store(true, Ordering::SeqCst);
+    first.set(3, "lost".to_owned()).await.unwrap();
+    assert_eq!(first_tier.sync().await.unwrap_err().kind(), std::io::ErrorKind::ConnectionRefused);
+    first_tier.sync().await.unwrap();
+    assert_eq!(object("reports/33"), None);
+}
+
+/// The values of a `FakeRedis` with the expiry option they were set with, e.g. `PX 1000`
+#[cfg(feature = "redis-cache")]
+type FakeRedisData = HashMap<Vec<u8>, (Vec<u8>, Vec<String>)>;
+
+/// An in-memory stand-in for a Redis server, answering the commands of the `RedisBacking`
+#[cfg(feature = "redis-cache")]
+#[derive(Clone, Default)]
+struct FakeRedis {
+    data: Arc<std::sync::Mutex<FakeRedisData>>,
+}
+
+#[cfg(feature = "redis-cache")]
+impl redis::ConnectionLike for FakeRedis {
+    fn req_packed_command(&mut self, cmd: &[u8]) -> redis::RedisResult<redis::Value> {
+        use redis::Value;
+        // commands are sent as RESP arrays of bulk strings
+        let mut args = Vec::new();
+        let mut rest = cmd;
+        while let Some(start) = rest.iter().position(|byte| *byte == b'$') {
+            let end = start + rest[start..].iter().position(|byte| *byte == b'\r').unwrap();
+            let len: usize = std::str::from_utf8(&rest[start + 1..end]).unwrap().parse().unwrap();
+            args.push(rest[end + 2..end + 2 + len].to_vec());
+            rest = &rest[end + 4 + len..];
+        }
+        let name = String::from_utf8(args[0].clone()).unwrap();
+        let options = args.iter().skip(3).map(|arg| String::from_utf8_lossy(arg).into_owned()).collect::<Vec<_>>();
+        let bulk = |value: Option<Vec<u8>>| value.map_or(Value::Nil, Value::BulkString);
+        let mut data = self.data.lock().unwrap();
+        Ok(match name.as_str() {
+            "GET" => bulk(data.get(&args[1]).map(|(value, _)| value.clone())),
+            "GETDEL" => bulk(data.remove(&args[1]).map(|(value, _)| value)),
+            "EXISTS" => Value::Int(data.contains_key(&args[1]) as i64),
+            "DEL" => Value::Int(args[1..].iter().filter(|key| data.remove(*key).is_some()).count() as i64),
+            "SET" if options.contains(&"XX".to_owned()) => {
+                if let Some((value, _)) = data.get_mut(&args[1]) {
+                    *value = args[2].clone();
+                }
+                Value::Okay
+            }
+            "SET" => {
+                let expiry = options.into_iter().filter(|option| option != "GET").collect();
+                bulk(data.insert(args[1].clone(), (args[2].clone(), expiry)).map(|(value, _)| value))
+            }
+            "SCAN" => {
+                let prefix = &args[3][..args[3].len() - 1];
+                let keys = data.keys().filter(|key| key.starts_with(prefix)).cloned().map(Value::BulkString).collect();
+                Value::Array(vec![Value::BulkString(b"0".to_vec()), Value::Array(keys)])
+            }
+            _ => panic!("Unexpected command {}", name),
+        })
+    }
+
+    fn req_packed_commands(&mut self, _cmd: &[u8], _offset: usize, _count: usize) -> redis::RedisResult<Vec<redis::Value>> {
+        unimplemented!()
+    }
+
+    fn get_db(&self) -> i64 {
+        0
+    }
+
+    fn check_connection(&mut self) -> bool {
+        true
+    }
+
+    fn is_open(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(all(feature = "redis-cache", feature = "json-codec"))]
+#[tokio::test]
+async fn test_redis_backing() {
+    use crate::backing::RedisBacking;
+    use crate::codec::JsonCodec;
+
+    let redis = FakeRedis::default();
+    let loads = Arc::new(AtomicUsize::new(0));
+    // two caches over the same server behave like caches of two processes
+    let open = |prefix| {
+        let loads = loads.clone();
+        let backing = RedisBacking::with_connection(redis.clone(), prefix, JsonCodec);
+        let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+            loads.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(key.to_uppercase()) }
+        });
+        cache
+    };
+    let first = open("users:");
+    let second = open("users:");
+
+    assert_eq!(first.get("foo".to_owned()).await.unwrap(), "FOO");
+    assert_eq!(second.get("foo".to_owned()).await.unwrap(), "FOO");
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    assert!(redis.data.lock().unwrap().contains_key(&b"users:\"foo\"".to_vec()));
+
+    second.set("foo".to_owned(), "bar".to_owned()).await.unwrap();
+    assert_eq!(first.get_if_present("foo".to_owned()).await.unwrap(), Some("bar".to_owned()));
+    first.update_mut("foo".to_owned(), |value| value.push('!')).await.unwrap();
+    assert_eq!(second.get_if_present("foo".to_owned()).await.unwrap(), Some("bar!".to_owned()));
+
+    // the TTL is mapped to the expiry of the key
+    first.set_with_ttl("ttl".to_owned(), "value".to_owned(), Duration::from_secs(5)).await.unwrap();
+    assert_eq!(redis.data.lock().unwrap()[&b"users:\"ttl\"".to_vec()].1, vec!["PX".to_owned(), "5000".to_owned()]);
+
+    let mut keys = second.keys().await.unwrap();
+    keys.sort();
+    assert_eq!(keys, vec!["foo".to_owned(), "ttl".to_owned()]);
+
+    // keys of other prefixes aren't cleared
+    let other = open("sessions:");
+    other.set("foo".to_owned(), "baz".to_owned()).await.unwrap();
+    first.remove_if(|(key, _)| key == "ttl").await.unwrap();
+    assert!(!second.exists("ttl".to_owned()).await.unwrap());
+    second.clear().await.unwrap();
+    assert_eq!(first.get_if_present("foo".to_owned()).await.unwrap(), None);
+    assert_eq!(other.get_if_present("foo".to_owned()).await.unwrap(), Some("baz".to_owned()));
+}
+
+#[cfg(all(feature = "sled-cache", feature = "json-codec"))]
+#[tokio::test]
+async fn test_sled_backing() {
+    use crate::backing::SledBacking;
+    use crate::codec::JsonCodec;
+
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    let loads = Arc::new(AtomicUsize::new(0));
+    let open = |name| {
+        let loads = loads.clone();
+        let backing = SledBacking::with_tree(db.clone(), name, JsonCodec).unwrap();
+        let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+            loads.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(key.to_uppercase()) }
+        });
+        cache
+    };
+    let cache = open("users");
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), "FOO");
+    cache.update_mut("foo".to_owned(), |value| value.push('!')).await.unwrap();
+    assert_eq!(&*db.open_tree("users").unwrap().get(b"\"foo\"").unwrap().unwrap(), b"\"FOO!\"");
+    cache.set("bar".to_owned(), "baz".to_owned()).await.unwrap();
+    let report = cache.maintain().await.unwrap().unwrap();
+    assert!(report.disk_size > 0);
+    assert_eq!(cache.backing_stats().await.unwrap().occupancy.unwrap().entries, 2);
+    std::mem::drop(cache);
+
+    // a cache on the tree of the previous one serves its values without loading them
+    let cache = open("users");
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), "FOO!");
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    let mut entries = cache.entries().await.unwrap();
+    entries.sort();
+    assert_eq!(entries, vec![("bar".to_owned(), "baz".to_owned()), ("foo".to_owned(), "FOO!".to_owned())]);
+    assert_eq!(open("sessions").get_if_present("bar".to_owned()).await.unwrap(), None);
+
+    cache.remove_if(|(key, _)| key == "bar").await.unwrap();
+    assert!(!cache.exists("bar".to_owned()).await.unwrap());
+    cache.clear().await.unwrap();
+    assert!(db.open_tree("users").unwrap().is_empty());
+}
+
+#[cfg(all(feature = "ttl-cache", feature = "lru-cache"))]
+#[tokio::test]
+async fn test_verify_backing_option() {
+    let backing = TtlCacheBacking::with_backing(Duration::from_secs(60), LruCacheBacking::new(2));
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_options(backing, |key: String| async move {
+        Ok(key.len())
+    }, CacheOptions::new().verify_backing(true));
+    for key in ["a", "bb", "ccc", "a"] {
+        assert_eq!(cache.get(key.to_owned()).await.unwrap(), key.len());
+    }
+    cache.set("dddd".to_owned(), 1).await.unwrap();
+    cache.remove("a".to_owned()).await.unwrap();
+    assert!(cache.verify(false).await.unwrap().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_maintenance() {
+    let loader = |key: String| async move { Ok::<_, u8>(key.len()) };
+    let cache: LoadingCache<String, usize, u8, DiskBacking> = LoadingCache::with_options(
+        DiskBacking::default(), loader, CacheOptions::new().maintenance(Duration::from_secs(10)));
+    cache.set("foo".to_owned(), 1).await.unwrap();
+    cache.remove("foo".to_owned()).await.unwrap();
+    let report = cache.maintenance_report().await.unwrap().unwrap();
+    // the first run starts right away
+    assert_eq!(report.disk_size, 1);
+    assert_eq!(report.reclaimable, 1);
+
+    tokio::time::advance(Duration::from_secs(10)).await;
+    cache.flush().await.unwrap();
+    let report = cache.maintenance_report().await.unwrap().unwrap();
+    assert_eq!(report.disk_size, 2);
+    assert_eq!(report.reclaimable, 0);
+    assert_eq!(cache.maintain().await.unwrap().unwrap().disk_size, 3);
+
+    let failing: LoadingCache<String, usize, u8, DiskBacking> = LoadingCache::with_backing(
+        DiskBacking { fail: true, ..DiskBacking::default() }, loader);
+    assert!(matches!(failing.maintain().await, Err(CacheLoadingError::BackingError(_))));
+    let memory: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(loader);
+    assert_eq!(memory.maintain().await.unwrap(), None);
+    assert_eq!(memory.maintenance_report().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_export_import() {
+    use futures::StreamExt;
+
+    let loader = |key: String| async move { Ok::<_, u8>(key.len()) };
+    let source: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(loader);
+    for i in 0..5 {
+        source.set(format!("key{}", i), i).await.unwrap();
+    }
+    let exported = source.export(2).collect::<Vec<_>>().await;
+    assert_eq!(exported.len(), 5);
+
+    let target: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(loader);
+    assert_eq!(target.import(futures::stream::iter(exported)).await.unwrap(), 5);
+    for i in 0..5 {
+        assert_eq!(target.get_if_present(format!("key{}", i)).await.unwrap(), Some(i));
+    }
+
+    // the first error stops the import
+    let entries = vec![Ok(("foo".to_owned(), 1, None)), Err(CacheLoadingError::LoadingError(7)), Ok(("bar".to_owned(), 2, None))];
+    assert_eq!(target.import(futures::stream::iter(entries)).await.unwrap_err().into_loading_error(), Some(7));
+    assert_eq!(target.get_if_present("foo".to_owned()).await.unwrap(), Some(1));
+    assert_eq!(target.get_if_present("bar".to_owned()).await.unwrap(), None);
+}
+
+#[cfg(feature = "lru-cache")]
+#[tokio::test]
+async fn test_lru_export() {
+    use crate::backing::LruMeta;
+    use futures::StreamExt;
+
+    let loader = |key: String| async move { Ok::<_, u8>(key.len()) };
+    let source: LoadingCache<String, usize, u8, LruCacheBacking<_, _>> = LoadingCache::with_backing(
+        LruCacheBacking::new(8).weigher(|key: &String, _| key.len()), loader);
+    for key in ["a", "bb", "ccc"] {
+        source.set(key.to_owned(), key.len()).await.unwrap();
+    }
+    source.get("a".to_owned()).await.unwrap();
+
+    let target: LoadingCache<String, usize, u8, LruCacheBacking<_, _>> = LoadingCache::with_backing(LruCacheBacking::new(8), loader);
+    let exported = source.export(1).map(|entry| {
+        let (key, value, meta) = entry.unwrap();
+        assert!(matches!(meta, Some(LruMeta { weight: Some(weight), protected: false }) if weight == key.len()));
+        Ok((key, value, meta))
+    });
+    assert_eq!(target.import(exported).await.unwrap(), 3);
+    // the recency and the weights are restored
+    let entries = target.entries_by_recency().await.unwrap();
+    assert_eq!(entries, vec![("a".to_owned(), 1), ("ccc".to_owned(), 3), ("bb".to_owned(), 2)]);
+    assert_eq!(target.backing_stats().await.unwrap().lru.unwrap().weight, 6);
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_ttl_export() {
+    use futures::StreamExt;
+
+    let loader = |key: String| async move { Ok::<_, u8>(key.len()) };
+    let source: LoadingCache<String, usize, u8, TtlCacheBacking<_, _, _>> = LoadingCache::with_backing(
+        TtlCacheBacking::new(Duration::from_secs(10)), loader);
+    source.set_with_meta("foo".to_owned(), 3, Some(Duration::from_secs(2).into())).await.unwrap();
+    source.set("bar".to_owned(), 3).await.unwrap();
+    tokio::time::advance(Duration::from_secs(4)).await;
+
+    let exported = source.export(1).collect::<Vec<_>>().await;
+    assert_eq!(exported.len(), 1);
+    let (key, _, meta) = exported[0].as_ref().unwrap();
+    assert_eq!(key, "bar");
+    let remaining = meta.unwrap().remaining();
+    assert!(remaining > Duration::from_secs(5) && remaining <= Duration::from_secs(6));
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_ttl_import_wall_clock() {
+    use crate::backing::TtlMeta;
+    use std::time::SystemTime;
+
+    let loader = |key: String| async move { Ok::<_, u8>(key.len()) };
+    let cache: LoadingCache<String, usize, u8, TtlCacheBacking<_, _, _>> = LoadingCache::with_backing(
+        TtlCacheBacking::new(Duration::from_secs(10)), loader);
+    // the wall-clock expiry of an exported entry takes precedence over its ttl
+    let restored = TtlMeta {
+        ttl: Duration::from_secs(100),
+        expires_at: Some(SystemTime::now() + Duration::from_secs(5)),
+    };
+    let expired = TtlMeta::expiring_at(SystemTime::now() - Duration::from_secs(5));
+    let entries = futures::stream::iter(vec![
+        Ok(("foo".to_owned(), 1, Some(restored))),
+        Ok(("bar".to_owned(), 2, Some(expired))),
+    ]);
+    assert_eq!(cache.import(entries).await.unwrap(), 2);
+
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), Some(1));
+    assert_eq!(cache.get_if_present("bar".to_owned()).await.unwrap(), None);
+    tokio::time::advance(Duration::from_secs(6)).await;
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), None);
+}
+
+#[cfg(feature = "lru-cache")]
+#[tokio::test]
+async fn test_lru_max_weight() {
+    use crate::backing::LruMeta;
+
+    let evicted = Arc::new(AtomicUsize::new(0));
+    let listener = evicted.clone();
+    let backing = LruCacheBacking::unbounded()
+        .max_weight(10)
+        .weigher(|_: &String, entry: &CacheEntry<String, u8>| match entry {
+            CacheEntry::Loaded(value) => value.len(),
+            CacheEntry::Loading(_) => 0,
+        })
+        .on_evict(move |_, _| {
+            listener.fetch_add(1, Ordering::SeqCst);
+        });
+    let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+        async move {
+            Ok(key.repeat(2))
+        }
+    });
+
+    cache.set("key1".to_owned(), "1234".to_owned()).await.unwrap();
+    cache.set("key2".to_owned(), "1234".to_owned()).await.unwrap();
+    assert_eq!(cache.backing_stats().await.unwrap().lru.unwrap().weight, 8);
+    // loads weigh nothing until they completed
+    assert_eq!(cache.get("abc".to_owned()).await.unwrap(), "abcabc");
+    assert_eq!(evicted.load(Ordering::SeqCst), 1);
+    assert!(!cache.exists("key1".to_owned()).await.unwrap());
+    assert!(cache.exists("key2".to_owned()).await.unwrap());
+
+    // an entry heavier than the limit evicts all others, but is kept itself
+    cache.set("key3".to_owned(), "x".repeat(20)).await.unwrap();
+    let stats = cache.backing_stats().await.unwrap().lru.unwrap();
+    assert_eq!((stats.len, stats.weight), (1, 20));
+
+    let mut backing = LruCacheBacking::unbounded().max_weight(5);
+    backing.set(1, 1, Some(LruMeta::from(3))).unwrap();
+    backing.set(2, 2, None).unwrap();
+    assert_eq!(backing.weight(), 4);
+    backing.set(3, 3, Some(LruMeta::from(2))).unwrap();
+    assert_eq!(backing.iter().map(|(key, _)| *key).collect::<Vec<_>>(), vec![3, 2]);
+    assert_eq!(backing.remove(&2).unwrap(), Some(2));
+    assert_eq!(backing.weight(), 2);
+}
+
+#[cfg(feature = "lru-cache")]
+#[tokio::test]
+async fn test_lru_protected_segment() {
+    use crate::backing::LruMeta;
+
+    let backing = LruCacheBacking::new(2)
+        .protected_segment(1)
+        .protect_if(|key: &String| key.starts_with("config:"));
+    let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+        async move {
+            Ok(key.to_lowercase())
+        }
+    });
+
+    assert_eq!(cache.get("config:A".to_owned()).await.unwrap(), "config:a");
+    // bulk traffic only evicts the regular segment
+    for i in 0..10 {
+        cache.set(format!("key{}", i), i.to_string()).await.unwrap();
+    }
+    assert_eq!(cache.peek_if_present("config:A".to_owned()).await.unwrap(), Some("config:a".to_owned()));
+    let stats = cache.backing_stats().await.unwrap().lru.unwrap();
+    assert_eq!((stats.capacity, stats.len), (3, 3));
+
+    // protected keys compete with each other
+    cache.set("config:B".to_owned(), "b".to_owned()).await.unwrap();
+    assert!(!cache.exists("config:A".to_owned()).await.unwrap());
+    assert_eq!(cache.entries_by_recency().await.unwrap()[0], ("config:B".to_owned(), "b".to_owned()));
+
+    let mut backing = LruCacheBacking::new(1).protected_segment(1);
+    backing.set(1, 1, Some(LruMeta::new().protected())).unwrap();
+    backing.set(2, 2, None).unwrap();
+    backing.set(3, 3, None).unwrap();
+    assert_eq!((backing.len(), backing.protected_len()), (2, 1));
+    assert_eq!(backing.peek(&1).unwrap(), Some(&1));
+    // setting the key without protection moves it to the regular segment
+    assert_eq!(backing.set(1, 10, None).unwrap(), Some(1));
+    assert_eq!(backing.protected_len(), 0);
+    assert_eq!(backing.iter().map(|(key, _)| *key).collect::<Vec<_>>(), vec![1]);
+    assert_eq!(backing.weight(), 1);
+}
+
+#[cfg(all(feature = "ttl-cache", feature = "lru-cache"))]
+#[test]
+fn test_ttl_lru_evict() {
+    let mut backing = TtlCacheBacking::with_backing(Duration::from_secs(3), LruCacheBacking::new(10));
+    backing.set("key1".to_owned(), 1, None).unwrap();
+    backing.set("key2".to_owned(), 2, None).unwrap();
+
+    assert_eq!(backing.evict(1).unwrap(), vec![("key1".to_owned(), 1)]);
+    let stats = backing.stats();
+    assert_eq!(stats.ttl.unwrap().queue_length, 1);
+    assert_eq!(stats.lru.unwrap().len, 1);
+    assert!(HashMapBacking::<String, usize>::new().evict(1).unwrap().is_empty());
+}
+
+#[test]
+fn test_bounded_hash_map_backing() {
+    use crate::backing::{BoundedHashMapBacking, EvictionPolicy};
+    let keys = |backing: &mut BoundedHashMapBacking<u64, u64>| {
+        let mut keys = backing.export(0, 8).unwrap().entries.into_iter()
+            .map(|(key, _, _)| key)
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys
+    };
+
+    let mut fifo = BoundedHashMapBacking::new(2, EvictionPolicy::Fifo);
+    fifo.set(1, 1, None).unwrap();
+    fifo.set(2, 2, None).unwrap();
+    assert_eq!(fifo.set(1, 10, None).unwrap(), Some(1));
+    fifo.set(3, 3, None).unwrap();
+    assert_eq!(keys(&mut fifo), vec![2, 3]);
+
+    let mut oldest_insert = BoundedHashMapBacking::new(2, EvictionPolicy::OldestInsert);
+    oldest_insert.set(1, 1, None).unwrap();
+    oldest_insert.set(2, 2, None).unwrap();
+    oldest_insert.set(1, 10, None).unwrap();
+    oldest_insert.set(3, 3, None).unwrap();
+    assert_eq!(keys(&mut oldest_insert), vec![1, 3]);
+    assert_eq!(oldest_insert.evict(1).unwrap(), vec![(1, 10)]);
+
+    let mut random = BoundedHashMapBacking::new(4, EvictionPolicy::Random).seed(7);
+    for key in 0..100 {
+        random.set(key, key, None).unwrap();
+    }
+    let kept = keys(&mut random);
+    assert_eq!(kept.len(), 4);
+    assert_ne!(kept, vec![96, 97, 98, 99]);
+    assert_eq!(random.stats().occupancy.unwrap().entries, 4);
+    assert!(random.verify().unwrap().is_empty());
+}
+
+#[test]
+fn test_weighted_cache_backing() {
+    use crate::backing::WeightedCacheBacking;
+    let mut backing = WeightedCacheBacking::new(10, |_: &String, value: &String| value.len() as u64);
+    backing.set("a".to_owned(), "1234".to_owned(), None).unwrap();
+    backing.set("b".to_owned(), "1234".to_owned(), None).unwrap();
+    assert_eq!(backing.weight(), 8);
+    backing.set("c".to_owned(), "1234".to_owned(), None).unwrap();
+    assert!(!backing.contains_key(&"a".to_owned()).unwrap());
+    assert_eq!((backing.len(), backing.weight()), (2, 8));
+
+    // replacing a value replaces its weight
+    backing.set("b".to_owned(), "1".to_owned(), None).unwrap();
+    assert_eq!(backing.weight(), 5);
+    // an entry heavier than the limit evicts every other entry, but stays itself
+    backing.set("d".to_owned(), "12345678901".to_owned(), None).unwrap();
+    assert_eq!((backing.len(), backing.weight()), (1, 11));
+    assert_eq!(backing.remove(&"d".to_owned()).unwrap(), Some("12345678901".to_owned()));
+    assert_eq!(backing.weight(), 0);
+    assert!(backing.verify().unwrap().is_empty());
+}
+
+#[test]
+fn test_tiered_backing() {
+    use crate::backing::{BoundedHashMapBacking, EvictionPolicy, TieredBacking};
+    let mut backing = TieredBacking::new(BoundedHashMapBacking::new(1, EvictionPolicy::Fifo), HashMapBacking::new());
+    backing.set("a".to_owned(), "1".to_owned(), None).unwrap();
+    backing.set("b".to_owned(), "2".to_owned(), None).unwrap();
+    // L1 evicted the first entry, L2 still holds it
+    assert!(!backing.l1_mut().contains_key(&"a".to_owned()).unwrap());
+    assert_eq!(backing.get(&"a".to_owned()).unwrap(), Some(&"1".to_owned()));
+    // the hit promoted the value into L1
+    assert!(backing.l1_mut().contains_key(&"a".to_owned()).unwrap());
+
+    // mutations reach L2 and are promoted again
+    backing.get_mut(&"a".to_owned()).unwrap().unwrap().push('!');
+    assert!(!backing.l1_mut().contains_key(&"a".to_owned()).unwrap());
+    assert_eq!(backing.get(&"a".to_owned()).unwrap(), Some(&"1!".to_owned()));
+
+    assert_eq!(backing.remove(&"a".to_owned()).unwrap(), Some("1!".to_owned()));
+    assert!(!backing.contains_key(&"a".to_owned()).unwrap());
+    let removed = backing.remove_if(Box::new(|_| true)).unwrap();
+    assert_eq!(removed, vec![("b".to_owned(), "2".to_owned())]);
+    assert!(backing.l1().is_empty());
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_ttl_backing() {
+    let cache: LoadingCache<_, _, u8, _> = LoadingCache::with_backing(
+        TtlCacheBacking::new(Duration::from_secs(3)), move |key: String| {
+            async move {
+                Ok(key.to_lowercase())
+            }
+        });
+
+    cache.set("key1".to_owned(), "value1".to_lowercase()).await.ok();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    cache.set("key2".to_owned(), "value2".to_lowercase()).await.ok();
+
+    assert_eq!(cache.get("key1".to_owned()).await.unwrap(), "value1".to_lowercase());
+    assert_eq!(cache.get("key2".to_owned()).await.unwrap(), "value2".to_lowercase());
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+
+    assert!(!cache.exists("key1".to_owned()).await.unwrap());
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_ttl_access_expiry() {
+    let mut backing = TtlCacheBacking::with_access_ttl(Duration::from_secs(3));
+    backing.set("read".to_owned(), 1, None).unwrap();
+    backing.set("idle".to_owned(), 2, None).unwrap();
+    backing.set("long".to_owned(), 3, Some(TtlMeta::from(Duration::from_secs(60)))).unwrap();
+
+    tokio::time::advance(Duration::from_secs(2)).await;
+    assert_eq!(backing.get(&"read".to_owned()).unwrap(), Some(&1));
+    assert_eq!(backing.get(&"long".to_owned()).unwrap(), Some(&3));
+    tokio::time::advance(Duration::from_secs(2)).await;
+    assert_eq!(backing.get(&"read".to_owned()).unwrap(), Some(&1));
+    assert!(!backing.contains_key(&"idle".to_owned()).unwrap());
+    // a read never shortens the expiry
+    assert_eq!(backing.time_to_live(&"long".to_owned()).unwrap(), Some(Duration::from_secs(56)));
+    assert!(backing.verify().unwrap().is_empty());
+
+    tokio::time::advance(Duration::from_secs(3)).await;
+    assert!(!backing.contains_key(&"read".to_owned()).unwrap());
+    assert_eq!(backing.stats().ttl.unwrap().queue_length, 1);
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_ttl_per_operation() {
+    let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_meta_loader(
+        TtlCacheBacking::new(Duration::from_secs(3)), move |key: String| {
+            async move {
+                Ok(key.to_lowercase())
+                    .with_meta(Some(TtlMeta::from(Duration::from_secs(1))))
+            }
+        });
+
+    cache.set_with_ttl("set".to_owned(), "value".to_owned(), Duration::from_secs(10)).await.unwrap();
+    cache.set("default".to_owned(), "value".to_owned()).await.unwrap();
+    assert_eq!(cache.get_with_ttl("LOADED".to_owned(), Duration::from_secs(10)).await.unwrap(), "loaded");
+    assert_eq!(cache.get("OTHER".to_owned()).await.unwrap(), "other");
+    // a cached value keeps its TTL
+    assert_eq!(cache.get_with_ttl("OTHER".to_owned(), Duration::from_secs(10)).await.unwrap(), "other");
+
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert!(cache.exists("set".to_owned()).await.unwrap());
+    assert!(cache.exists("LOADED".to_owned()).await.unwrap());
+    assert!(!cache.exists("default".to_owned()).await.unwrap());
+    assert!(!cache.exists("OTHER".to_owned()).await.unwrap());
+}
+
+#[cfg(all(feature = "ttl-cache", feature = "lru-cache"))]
+#[tokio::test(start_paused = true)]
+async fn test_ttl_lru_backing() {
+    let cache: LoadingCache<String, _, u8, _> = LoadingCache::with_meta_loader(TtlCacheBacking::with_backing(Duration::from_secs(1), LruCacheBacking::new(2)), move |key: String| {
+       async move {
+           if key.len() < 3 {
+               Ok(key.to_lowercase())
+                   .with_meta(Some(TtlMeta::from(Duration::from_secs(15))))
+           } else {
+               Ok(key.to_lowercase())
+                   .with_meta(None)
+           }
+       }
+    });
+    assert_eq!(cache.get("a".to_owned()).await.unwrap(), "a");
+    assert_eq!(cache.get("bbbbb".to_owned()).await.unwrap(), "bbbbb");
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(cache.exists("a".to_owned()).await.unwrap());
+    assert!(!cache.exists("bbbbb".to_owned()).await.unwrap());
+    assert_eq!(cache.get("bbbbb".to_owned()).await.unwrap(), "bbbbb");
+    assert_eq!(cache.get("ccccc".to_owned()).await.unwrap(), "ccccc");
+    assert_eq!(cache.get("ddddd".to_owned()).await.unwrap(), "ddddd");
+    assert!(!cache.exists("a".to_owned()).await.unwrap());
+}
+test_with_features! {
+    map_err cache <String, String, u8> {
+        if key.is_empty() {
+            Err(7)
+        } else {
+            Ok(key.to_lowercase())
+        }
+    }
+
+    let mapped = cache.map_err(|code| format!("code {}", code));
+
+    assert_eq!(mapped.get("TEST".to_owned()).await.unwrap(), "test".to_owned());
+    let error = mapped.get("".to_owned()).await.expect_err("Didn't error, what?");
+    assert_eq!(error.into_loading_error().unwrap(), "code 7".to_owned());
+}
+
+test_with_features! {
+    read_only cache <String, String, u8> {
+        Ok(key.to_lowercase())
+    }
+
+    let read_only = cache.read_only();
+
+    assert!(!read_only.exists("TEST".to_owned()).await.unwrap());
+    assert_eq!(read_only.get("TEST".to_owned()).await.unwrap(), "test".to_owned());
+    assert!(read_only.exists("TEST".to_owned()).await.unwrap());
+    cache.set("TEST".to_owned(), "changed".to_owned()).await.unwrap();
+    assert_eq!(read_only.get_if_present("TEST".to_owned()).await.unwrap(), Some("changed".to_owned()));
+    assert!(read_only.backing_stats().await.is_ok());
+}
+
+struct BrokenBacking {
+    map: HashMapBacking<String, CacheEntry<String, u8>>,
+    broken: bool,
+}
+
+impl CacheBacking<String, CacheEntry<String, u8>> for BrokenBacking {
+    type Meta = NoMeta;
+
+    fn get_mut(&mut self, key: &String) -> Result<Option<&mut CacheEntry<String, u8>>, BackingError> {
+        self.check()?;
+        self.map.get_mut(key)
+    }
+
+    fn get(&mut self, key: &String) -> Result<Option<&CacheEntry<String, u8>>, BackingError> {
+        self.check()?;
+        self.map.get(key)
+    }
+
+    fn set(&mut self, key: String, value: CacheEntry<String, u8>, meta: Option<Self::Meta>) -> Result<Option<CacheEntry<String, u8>>, BackingError> {
+        self.check()?;
+        self.map.set(key, value, meta)
+    }
+
+    fn remove(&mut self, key: &String) -> Result<Option<CacheEntry<String, u8>>, BackingError> {
+        self.check()?;
+        self.map.remove(key)
+    }
+
+    fn contains_key(&mut self, key: &String) -> Result<bool, BackingError> {
+        self.check()?;
+        self.map.contains_key(key)
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<String, CacheEntry<String, u8>>) -> Result<Vec<(String, CacheEntry<String, u8>)>, BackingError> {
+        self.check()?;
+        self.map.remove_if(predicate)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.check()?;
+        self.map.clear()
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        self.broken = false;
+        Ok(())
+    }
+}
+
+impl BrokenBacking {
+    fn check(&self) -> Result<(), BackingError> {
+        if self.broken {
+            Err(TtlError::ExpiryNotFound.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_backing_error_policy() {
+    let broken = || BrokenBacking { map: HashMapBacking::new(), broken: true };
+
+    let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_backing(
+        RecoveringBacking::new(broken(), BackingErrorPolicy::Retry(3)), move |key: String| {
+            async move {
+                Ok(key.to_lowercase())
+            }
+        });
+    let error = cache.get("KEY".to_owned()).await.expect_err("Didn't error, what?");
+    assert!(matches!(error, CacheLoadingError::BackingError(_)));
+
+    let errors = Arc::new(AtomicUsize::new(0));
+    let hook_errors = errors.clone();
+    let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_backing(
+        RecoveringBacking::new(broken(), BackingErrorPolicy::Rebuild)
+            .on_error(move |_| { hook_errors.fetch_add(1, Ordering::SeqCst); }), move |key: String| {
+            async move {
+                Ok(key.to_lowercase())
+            }
+        });
+    assert_eq!(cache.get("KEY".to_owned()).await.unwrap(), "key".to_owned());
+    assert_eq!(errors.load(Ordering::SeqCst), 1);
+}
+
+/// Reads from the wrapped backing but can't store, like a remote store which went away
+struct UnavailableBacking {
+    map: HashMapBacking<String, CacheEntry<String, u8>>,
+}
+
+impl CacheBacking<String, CacheEntry<String, u8>> for UnavailableBacking {
+    type Meta = NoMeta;
+
+    fn get_mut(&mut self, key: &String) -> Result<Option<&mut CacheEntry<String, u8>>, BackingError> {
+        self.map.get_mut(key)
+    }
+
+    fn get(&mut self, key: &String) -> Result<Option<&CacheEntry<String, u8>>, BackingError> {
+        self.map.get(key)
+    }
+
+    fn set(&mut self, key: String, value: CacheEntry<String, u8>, meta: Option<Self::Meta>) -> Result<Option<CacheEntry<String, u8>>, BackingError> {
+        if matches!(value, CacheEntry::Loaded(_)) {
+            return Err(BackingError::unavailable("UnavailableBacking", "connection refused"));
+        }
+        self.map.set(key, value, meta)
+    }
+
+    fn remove(&mut self, key: &String) -> Result<Option<CacheEntry<String, u8>>, BackingError> {
+        self.map.remove(key)
+    }
+
+    fn contains_key(&mut self, key: &String) -> Result<bool, BackingError> {
+        self.map.contains_key(key)
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<String, CacheEntry<String, u8>>) -> Result<Vec<(String, CacheEntry<String, u8>)>, BackingError> {
+        self.map.remove_if(predicate)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.map.clear()
+    }
+}
+
+#[tokio::test]
+async fn test_backing_error_variants() {
+    let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_backing(
+        UnavailableBacking { map: HashMapBacking::new() }, move |key: String| {
+            async move {
+                Ok(key.to_lowercase())
+            }
+        });
+    let error = cache.set("KEY".to_owned(), "value".to_owned()).await.expect_err("Didn't error, what?");
+    let backing_error = error.as_backing_error().expect("Not a backing error");
+    assert_eq!(backing_error.backing(), Some("UnavailableBacking"));
+    assert!(backing_error.is_transient());
+    assert_eq!(backing_error.to_string(), "UnavailableBacking is unavailable: connection refused");
+
+    // a loaded value which can't be stored fails the load instead of being returned silently
+    let error = cache.get("KEY".to_owned()).await.expect_err("Didn't error, what?");
+    assert!(matches!(error.into_backing_error(), Some(BackingError::Unavailable { .. })));
+    // the key isn't left loading
+    assert_eq!(cache.get_if_present("KEY".to_owned()).await.unwrap(), None);
+
+    let io = BackingError::io("DiskBacking", std::io::Error::new(std::io::ErrorKind::TimedOut, "slow disk"));
+    assert!(io.is_transient());
+    assert_eq!(io.backing(), Some("DiskBacking"));
+    assert!(std::error::Error::source(&io).is_some());
+    let other = BackingError::other("DiskBacking", std::io::Error::other("corrupt page"));
+    assert!(!other.is_transient());
+    assert_eq!(BackingError::TtlError(TtlError::ExpiryNotFound).backing(), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_load_timeout() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let loader_calls = calls.clone();
+    let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_options(
+        HashMapBacking::new(), move |key: String| {
+            let call = loader_calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call == 0 {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                }
+                Ok(key.to_lowercase())
+            }
+        }, CacheOptions::new().load_timeout(Duration::from_secs(5)));
+
+    let loading = cache.clone();
+    let load = tokio::spawn(async move { loading.get("KEY".to_owned()).await });
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    let waiting = cache.clone();
+    let wait = tokio::spawn(async move { waiting.get("KEY".to_owned()).await });
+
+    let error = load.await.unwrap().expect_err("Didn't time out, what?");
+    assert!(matches!(error, CacheLoadingError::LoadTimeout { configured, .. } if configured == Duration::from_secs(5)));
+    // the waiter joined later, but times out together with the load
+    let error = wait.await.unwrap().expect_err("Didn't time out, what?");
+    assert!(error.is_load_timeout());
+
+    // the key was unblocked, the next lookup loads it again
+    assert_eq!(cache.get("KEY".to_owned()).await.unwrap(), "key".to_owned());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_loading_watchdog_aborted_load() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let loader_calls = calls.clone();
+    let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_options(
+        HashMapBacking::new(), move |key: String| {
+            let call = loader_calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call == 0 {
+                    panic!("loader task dies");
+                }
+                Ok(key.to_lowercase())
+            }
+        }, CacheOptions::new().loading_watchdog(Duration::from_secs(60), None));
+
+    let error = cache.get("KEY".to_owned()).await.expect_err("Didn't error, what?");
+    assert!(matches!(error.as_communication_error(), Some(CacheCommunicationError::FutureJoinError(_))));
+
+    // without the watchdog the key would remain in the loading state forever
+    assert_eq!(cache.get("KEY".to_owned()).await.unwrap(), "key".to_owned());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_loading_watchdog_max_age() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let loader_calls = calls.clone();
+    let cache: LoadingCache<String, String, u8, _> = LoadingCache::with_options(
+        HashMapBacking::new(), move |key: String| {
+            let call = loader_calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if call == 0 {
+                    futures::future::pending::<()>().await;
+                }
+                Ok(key.to_lowercase())
+            }
+        }, CacheOptions::new().loading_watchdog(Duration::from_secs(1), Some(Duration::from_secs(5))));
+
+    let inner_cache = cache.clone();
+    let loader = tokio::spawn(async move {
+        inner_cache.get("KEY".to_owned()).await
+    });
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    let waiter = cache.get("KEY".to_owned()).await;
+
+    assert!(waiter.is_err());
+    assert!(loader.await.unwrap().is_err());
+    assert_eq!(cache.get("KEY".to_owned()).await.unwrap(), "key".to_owned());
+}
+
+#[tokio::test]
+async fn test_try_get_busy() {
+    let cache: LoadingCache<u64, u64, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(),
+        move |key: u64| async move { Ok(key * 2) },
+        CacheOptions::new().channel_capacity(1),
+    );
+
+    // the cache task only runs once this task yields on the current-thread runtime, so the first
+    // request fills the queue
+    let mut first = Box::pin(cache.try_get(1));
+    assert!(futures::poll!(&mut first).is_pending());
+    assert!(matches!(cache.try_get(2).await, Err(CacheLoadingError::Busy())));
+
+    let results = futures::future::join_all((3..=200).map(|key| cache.try_get(key))).await;
+    assert!(results.iter().all(|result| matches!(result, Err(CacheLoadingError::Busy()))));
+    assert_eq!(first.await.unwrap(), 2);
+    assert_eq!(cache.try_get(300).await.unwrap(), 600);
+}
+
+#[tokio::test]
+async fn test_backpressure() {
+    async fn flood(backpressure: Backpressure) -> Vec<Result<u64, CacheLoadingError<u8>>> {
+        let bounded = !matches!(backpressure, Backpressure::Unbounded);
+        let cache: LoadingCache<u64, u64, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+            HashMapBacking::new(),
+            move |key: u64| async move { Ok(key * 2) },
+            CacheOptions::new().channel_capacity(1).backpressure(backpressure),
+        );
+
+        // the cache task only runs once this task yields on the current-thread runtime, so the
+        // first get fills the queue
+        let mut first = Box::pin(cache.get(1));
+        assert!(futures::poll!(&mut first).is_pending());
+        if bounded {
+            assert!(matches!(cache.try_get(0).await, Err(CacheLoadingError::Busy())));
+        }
+
+        let (first, rest) = futures::future::join(first, futures::future::join_all((2..=50).map(|key| cache.get(key)))).await;
+        assert_eq!(cache.get(300).await.unwrap(), 600);
+        std::iter::once(first).chain(rest).collect()
+    }
+
+    let results = flood(Backpressure::Fail).await;
+    assert_eq!(results[0].as_ref().unwrap(), &2);
+    assert!(results[1..].iter().all(|result| matches!(result, Err(CacheLoadingError::Busy()))));
+
+    for backpressure in [Backpressure::Wait, Backpressure::Unbounded] {
+        let results = flood(backpressure).await;
+        assert!(results.into_iter().enumerate().all(|(i, result)| result.unwrap() == (i as u64 + 1) * 2));
+    }
+}
+
+#[tokio::test]
+async fn test_lock_key() {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let counter = loads.clone();
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(key.len())
+        }
+    });
+
+    let mut guard = cache.lock_key("foo".to_owned()).await.unwrap();
+    assert_eq!(guard.value(), None);
+    let get = tokio::spawn({
+        let cache = cache.clone();
+        async move { cache.get("foo".to_owned()).await }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let set = tokio::spawn({
+        let cache = cache.clone();
+        async move { cache.set("foo".to_owned(), 1).await }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    // other keys aren't locked
+    assert_eq!(cache.get("ab".to_owned()).await.unwrap(), 2);
+    assert!(!get.is_finished() && !set.is_finished());
+
+    guard.set(10);
+    drop(guard);
+    assert_eq!(get.await.unwrap().unwrap(), 10);
+    assert_eq!(set.await.unwrap().unwrap(), Some(10));
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    // a lock waits for the running load of its key
+    let load = tokio::spawn({
+        let cache = cache.clone();
+        async move { cache.get("bar".to_owned()).await }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let guard = cache.lock_key("bar".to_owned()).await.unwrap();
+    assert_eq!(guard.value(), Some(&3));
+    assert_eq!(load.await.unwrap().unwrap(), 3);
+    drop(guard);
+    assert_eq!(cache.get_if_present("bar".to_owned()).await.unwrap(), Some(3));
+}
+
+#[tokio::test]
+async fn test_lock_key_inline() {
+    use crate::options::ExecutionMode;
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(),
+        move |key: String| async move { Ok(key.len()) },
+        CacheOptions::new().execution_mode(ExecutionMode::Inline),
+    );
+
+    let guard = cache.lock_key("foo".to_owned()).await.unwrap();
+    let get = tokio::spawn({
+        let cache = cache.clone();
+        async move { cache.get("foo".to_owned()).await }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!get.is_finished());
+    drop(guard);
+    assert_eq!(get.await.unwrap().unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_with_storer() {
+    use crate::cache_api::StoreFailure;
+    use std::sync::Mutex;
+
+    let stored = Arc::new(Mutex::new(Vec::new()));
+    let store = stored.clone();
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move { Ok(key.len()) }
+    }).with_storer(move |key: String, value: usize| {
+        let store = store.clone();
+        async move {
+            if value == 0 {
+                return Err(1);
+            }
+            store.lock().unwrap().push((key, value));
+            Ok(())
+        }
+    }, StoreFailure::Reject);
+
+    assert_eq!(cache.set("foo".to_owned(), 5).await.unwrap(), None);
+    // the update loads the key before storing its updated value
+    assert_eq!(cache.update("bar".to_owned(), |value| value * 2).await.unwrap(), 6);
+    assert_eq!(*stored.lock().unwrap(), vec![("foo".to_owned(), 5), ("bar".to_owned(), 6)]);
+
+    // rejected writes aren't cached
+    let err = cache.set("foo".to_owned(), 0).await.unwrap_err();
+    assert_eq!(err.as_store_error(), Some(&1));
+    assert!(cache.update("bar".to_owned(), |_| 0).await.unwrap_err().as_store_error().is_some());
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 5);
+    assert_eq!(cache.get("bar".to_owned()).await.unwrap(), 6);
+
+    let cache = cache.with_storer(|_: String, _: usize| async move { Err(1) }, StoreFailure::Continue);
+    assert_eq!(cache.set("foo".to_owned(), 7).await.unwrap(), Some(5));
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 7);
+    assert_eq!(stored.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_with_write_behind() {
+    use std::sync::Mutex;
+
+    let written = Arc::new(Mutex::new(Vec::new()));
+    let failing = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let sink = written.clone();
+    let fail = failing.clone();
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move { Ok(key.len()) }
+    }).with_write_behind(move |mut entries: Vec<(String, usize)>| {
+        let sink = sink.clone();
+        let fail = fail.load(Ordering::SeqCst);
+        async move {
+            if fail {
+                return Err(1);
+            }
+            entries.sort();
+            sink.lock().unwrap().push(entries);
+            Ok(())
+        }
+    }, Duration::from_secs(3600), 3);
+
+    cache.set("foo".to_owned(), 1).await.unwrap();
+    cache.set("foo".to_owned(), 2).await.unwrap();
+    cache.update("bar".to_owned(), |value| value + 1).await.unwrap();
+    assert!(written.lock().unwrap().is_empty());
+    // only the latest value of a key is written
+    cache.flush().await.unwrap();
+    assert_eq!(written.lock().unwrap().pop(), Some(vec![("bar".to_owned(), 4), ("foo".to_owned(), 2)]));
+
+    // a full batch is written without waiting for the interval
+    for key in ["a", "b", "c"] {
+        cache.update_mut(key.to_owned(), |value| *value += 1).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(written.lock().unwrap().pop(), Some(vec![("a".to_owned(), 2), ("b".to_owned(), 2), ("c".to_owned(), 2)]));
+
+    // failed entries stay dirty until the sink succeeds
+    failing.store(true, Ordering::SeqCst);
+    cache.set("foo".to_owned(), 5).await.unwrap();
+    assert_eq!(cache.flush().await.unwrap_err().as_store_error(), Some(&1));
+    failing.store(false, Ordering::SeqCst);
+    cache.flush().await.unwrap();
+    assert_eq!(written.lock().unwrap().pop(), Some(vec![("foo".to_owned(), 5)]));
+    cache.flush().await.unwrap();
+    assert!(written.lock().unwrap().is_empty());
+
+    // removed keys aren't written anymore
+    cache.set("a".to_owned(), 7).await.unwrap();
+    cache.set("b".to_owned(), 7).await.unwrap();
+    cache.remove("a".to_owned()).await.unwrap();
+    cache.remove_if(|(key, _)| key == "b").await.unwrap();
+    cache.set("c".to_owned(), 7).await.unwrap();
+    cache.flush().await.unwrap();
+    assert_eq!(written.lock().unwrap().pop(), Some(vec![("c".to_owned(), 7)]));
+    cache.set("d".to_owned(), 7).await.unwrap();
+    cache.clear().await.unwrap();
+    cache.flush().await.unwrap();
+    assert!(written.lock().unwrap().is_empty());
+}
+
+#[cfg(feature = "json-codec")]
+#[tokio::test]
+async fn test_with_write_ahead_log() {
+    use std::io::Write;
+    use std::sync::Mutex;
+    use crate::codec::JsonCodec;
+
+    let path = std::env::temp_dir().join(format!("cache-loader-async-wal-{}", std::process::id()));
+    let loader = move |key: String| async move { Ok(key.len()) };
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(loader)
+        .with_write_behind(|_: Vec<(String, usize)>| async move { Err(1) }, Duration::from_secs(3600), 10)
+        .with_write_ahead_log(&path, JsonCodec)
+        .unwrap();
+    cache.set("foo".to_owned(), 1).await.unwrap();
+    cache.set("bar".to_owned(), 2).await.unwrap();
+    cache.remove("bar".to_owned()).await.unwrap();
+    // the process crashes without flushing, leaving a torn record behind
+    std::mem::forget(cache);
+    std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(&[0, 9, 0]).unwrap();
+
+    let written = Arc::new(Mutex::new(Vec::new()));
+    let sink = written.clone();
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(loader)
+        .with_write_behind(move |entries: Vec<(String, usize)>| {
+            sink.lock().unwrap().extend(entries);
+            async move { Ok(()) }
+        }, Duration::from_secs(3600), 10)
+        .with_write_ahead_log(&path, JsonCodec)
+        .unwrap();
+    // the replayed entries are flushed right away
+    cache.flush().await.unwrap();
+    assert_eq!(*written.lock().unwrap(), vec![("foo".to_owned(), 1)]);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+    // the log was compacted through a temporary file renamed over it
+    assert!(!path.with_file_name(format!("cache-loader-async-wal-{}.tmp", std::process::id())).exists());
+    std::fs::remove_file(&path).unwrap();
+
+    let without_sink = LoadingCache::<String, usize, u8, HashMapBacking<_, _>>::new(loader)
+        .with_write_ahead_log(&path, JsonCodec);
+    assert_eq!(without_sink.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[tokio::test]
+async fn test_occupancy_thresholds() {
+    use crate::options::{OccupancyLevel, OccupancyThresholds};
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    let options = CacheOptions::new().occupancy_thresholds(OccupancyThresholds::entries(2, 3), move |event| {
+        recorded.lock().unwrap().push((event.level, event.is_rising(), event.occupancy.entries));
+    });
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(), move |key: String| async move { Ok(key.len()) }, options);
+
+    cache.set("a".to_owned(), 1).await.unwrap();
+    assert!(events.lock().unwrap().is_empty());
+    cache.set("b".to_owned(), 1).await.unwrap();
+    // the loading key already counts
+    cache.get("c".to_owned()).await.unwrap();
+    cache.set("c".to_owned(), 2).await.unwrap();
+    cache.remove("c".to_owned()).await.unwrap();
+    cache.clear().await.unwrap();
+
+    assert_eq!(*events.lock().unwrap(), vec![
+        (OccupancyLevel::Soft, true, 2),
+        (OccupancyLevel::Hard, true, 3),
+        (OccupancyLevel::Soft, false, 2),
+        (OccupancyLevel::Normal, false, 0),
+    ]);
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_get_if_present_with_meta() {
+    use crate::cache_api::EntryMeta;
+
+    let cache: LoadingCache<String, usize, u8, TtlCacheBacking<_, _, _>> = LoadingCache::with_options(
+        TtlCacheBacking::new(Duration::from_secs(10)),
+        move |key: String| async move { Ok(key.len()) },
+        CacheOptions::new().track_entries(true));
+
+    assert_eq!(cache.get_if_present_with_meta("foo".to_owned()).await.unwrap(), None);
+    cache.get("foo".to_owned()).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    cache.get("foo".to_owned()).await.unwrap();
+    assert_eq!(cache.get_if_present_with_meta("foo".to_owned()).await.unwrap(), Some(EntryMeta {
+        value: 3,
+        age: Some(Duration::from_secs(2)),
+        hits: Some(2),
+        ttl_remaining: Some(Duration::from_secs(8)),
+    }));
+
+    // setting the value starts a new record
+    cache.set("foo".to_owned(), 5).await.unwrap();
+    let meta = cache.get_if_present_with_meta("foo".to_owned()).await.unwrap().unwrap();
+    assert_eq!((meta.value, meta.age, meta.hits), (5, Some(Duration::ZERO), Some(1)));
+
+    let untracked: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move { Ok(key.len()) }
+    });
+    untracked.get("foo".to_owned()).await.unwrap();
+    assert_eq!(untracked.get_if_present_with_meta("foo".to_owned()).await.unwrap(), Some(EntryMeta {
+        value: 3,
+        age: None,
+        hits: None,
+        ttl_remaining: None,
+    }));
+}
+
+#[tokio::test]
+async fn test_lookup() {
+    use crate::cache_api::Lookup;
+
+    let started = Arc::new(tokio::sync::Notify::new());
+    let release = Arc::new(tokio::sync::Notify::new());
+    let loader_started = started.clone();
+    let loader_release = release.clone();
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(HashMapBacking::new(), move |key: String| {
+        let started = loader_started.clone();
+        let release = loader_release.clone();
+        async move {
+            started.notify_one();
+            release.notified().await;
+            Ok(key.len())
+        }
+    }, CacheOptions::new().record_stats(true));
+
+    assert_eq!(cache.lookup("foo".to_owned()).await.unwrap(), Lookup::Miss);
+    let load = tokio::spawn({
+        let cache = cache.clone();
+        async move { cache.get("foo".to_owned()).await }
+    });
+    started.notified().await;
+    // the lookup doesn't wait for the load
+    assert!(cache.lookup("foo".to_owned()).await.unwrap().is_loading());
+    release.notify_one();
+    assert_eq!(load.await.unwrap().unwrap(), 3);
+    assert_eq!(cache.lookup("foo".to_owned()).await.unwrap(), Lookup::Hit(3));
+    assert_eq!(cache.lookup("bar".to_owned()).await.unwrap().value(), None);
+
+    let stats = cache.stats().unwrap();
+    // the get waiting for its load is a miss as well
+    assert_eq!((stats.hits, stats.misses), (1, 4));
+}
+
+#[tokio::test]
+async fn test_extend_from_stream() {
+    use crate::backing::NoMeta;
+
+    let cache: LoadingCache<usize, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: usize| {
+        async move { Ok(key) }
+    });
+    let guard = cache.lock_key(7).await.unwrap();
+    let entries = futures::stream::iter((0..250).map(|key| (key, key * 2)));
+    assert_eq!(cache.extend_from_stream(entries, 100).await.unwrap(), 250);
+    assert_eq!(cache.get_if_present(249).await.unwrap(), Some(498));
+    // the entry of the locked key is set once it's released
+    drop(guard);
+    assert_eq!(cache.get_if_present(7).await.unwrap(), Some(14));
+
+    let entries = futures::stream::iter(vec![(1, 5, NoMeta {}), (2, 6, NoMeta {})]);
+    assert_eq!(cache.extend_from_stream(entries, 1).await.unwrap(), 2);
+    assert_eq!(cache.get_if_present(2).await.unwrap(), Some(6));
+}
+
+#[tokio::test]
+async fn test_fallback() {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let counter = loads.clone();
+    let shared: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move { Ok(key.len()) }
+    });
+    let local: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |_key: String| {
+        async move { Err(1) }
+    });
+    let cache = local.with_fallback(shared.clone());
+
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), None);
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+    assert_eq!(local.get_if_present("foo".to_owned()).await.unwrap(), Some(3));
+    shared.remove("foo".to_owned()).await.unwrap();
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    shared.set("bar".to_owned(), 5).await.unwrap();
+    assert_eq!(cache.get_if_present("bar".to_owned()).await.unwrap(), Some(5));
+    assert_eq!(local.get_if_present("bar".to_owned()).await.unwrap(), Some(5));
+
+    assert_eq!(cache.set("baz".to_owned(), 7).await.unwrap(), None);
+    assert_eq!(shared.get_if_present("baz".to_owned()).await.unwrap(), Some(7));
+    assert_eq!(cache.remove("baz".to_owned()).await.unwrap(), Some(7));
+    assert_eq!(local.get_if_present("baz".to_owned()).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_sharded() {
+    use crate::sharded::ShardedLoadingCache;
+
+    let loads = Arc::new(AtomicUsize::new(0));
+    let counter = loads.clone();
+    let cache: ShardedLoadingCache<u64, u64, u8, HashMapBacking<_, _>> = ShardedLoadingCache::with_options(4, HashMapBacking::new, move |key: u64| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(key * 2)
+        }
+    }, CacheOptions::new().record_stats(true));
+    assert_eq!(cache.shards().len(), 4);
+
+    // concurrent loads of a key are still deduplicated by its shard
+    let gets = (0..64u64)
+        .map(|key| {
+            let cache = cache.clone();
+            tokio::spawn(async move { cache.get(key % 16).await })
+        })
+        .collect::<Vec<_>>();
+    for get in gets {
+        get.await.unwrap().unwrap();
+    }
+    assert_eq!(loads.load(Ordering::SeqCst), 16);
+    assert!(cache.shards().iter().filter(|shard| shard.stats().unwrap().load_successes > 0).count() > 1);
+
+    cache.set(3, 30).await.unwrap();
+    assert_eq!(cache.shard(&3).get_if_present(3).await.unwrap(), Some(30));
+    assert_eq!(cache.update(3, |value| value + 1).await.unwrap(), 31);
+    assert_eq!(cache.remove(4).await.unwrap(), Some(8));
+    let mut keys = cache.keys().await.unwrap();
+    keys.sort();
+    assert_eq!(keys, (0..16).filter(|key| *key != 4).collect::<Vec<_>>());
+
+    let stats = cache.stats().unwrap();
+    assert_eq!((stats.requests(), stats.load_successes), (65, 16));
+    cache.clear().await.unwrap();
+    assert!(cache.entries().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_all() {
+    let batches = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let batch_calls = batches.clone();
+    let cache: LoadingCache<u64, u64, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: u64| {
+        async move { Ok(key * 2) }
+    });
+    // without a batch loader, the loader function loads every missing key
+    let values = cache.get_all(vec![1, 2]).await.unwrap();
+    assert_eq!(values, HashMap::from([(1, 2), (2, 4)]));
+
+    let cache = cache.with_batch_loader(move |mut keys: Vec<u64>| {
+        keys.sort();
+        batch_calls.lock().unwrap().push(keys.clone());
+        async move {
+            // the key 6 doesn't exist
+            Ok(keys.into_iter().filter(|key| *key != 6).map(|key| (key, key * 3)).collect::<HashMap<_, _>>())
+        }
+    });
+    let values = cache.get_all(vec![1, 4, 5, 6]).await.unwrap();
+    assert_eq!(values, HashMap::from([(1, 2), (4, 12), (5, 15)]));
+    assert_eq!(*batches.lock().unwrap(), vec![vec![4, 5, 6]]);
+    assert_eq!(cache.get_if_present(5).await.unwrap(), Some(15));
+    assert_eq!(cache.get_if_present(6).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_builder() {
+    use crate::backing::RemovalCause;
+    let removed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let listener = removed.clone();
+    let cache = LoadingCache::builder()
+        .channel_size(4)
+        .listener(move |key: u64, value: u64, cause: RemovalCause| {
+            listener.lock().unwrap().push((key, value, cause));
+        })
+        .options(|options| options.name("builder"))
+        .loader(|key: u64| async move { Ok::<_, u8>(key * 2) })
+        .build();
+    assert_eq!(cache.name(), Some("builder"));
+    assert_eq!(cache.get(2).await.unwrap(), 4);
+    cache.remove(2).await.unwrap();
+    assert_eq!(*removed.lock().unwrap(), vec![(2, 4, RemovalCause::Explicit)]);
+
+    let cache = LoadingCache::builder()
+        .backing(HashMapBacking::new())
+        .meta_loader(|key: u64| async move { Ok::<_, u8>(key * 3).with_meta(None) })
+        .build();
+    assert_eq!(cache.get(2).await.unwrap(), 6);
+}
+
+#[cfg(all(feature = "lru-cache", feature = "ttl-cache"))]
+#[tokio::test(start_paused = true)]
+async fn test_builder_backing() {
+    let cache = LoadingCache::builder()
+        .time_to_live(Duration::from_secs(10))
+        .max_capacity(2)
+        .time_to_idle(Duration::from_secs(5))
+        .loader(|key: u64| async move { Ok::<_, u8>(key * 2) })
+        .build();
+    for key in 1..=3 {
+        cache.get(key).await.unwrap();
+    }
+    assert!(!cache.exists(1).await.unwrap());
+
+    tokio::time::advance(Duration::from_secs(8)).await;
+    assert_eq!(cache.get_if_present(2).await.unwrap(), Some(4));
+    tokio::time::advance(Duration::from_secs(4)).await;
+    // read at 8s, so the entry lives until 13s instead of 10s
+    assert!(cache.exists(2).await.unwrap());
+    assert!(!cache.exists(3).await.unwrap());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_idle_park() {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let loader_loads = loads.clone();
+    let cache: LoadingCache<u64, u64, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(),
+        move |key: u64| {
+            loader_loads.fetch_add(1, Ordering::SeqCst);
+            async move {
+                tokio::time::sleep(Duration::from_secs(key)).await;
+                Ok(key * 2)
+            }
+        },
+        CacheOptions::new().idle_park(Duration::from_secs(1)),
+    );
+    assert_eq!(cache.get(0).await.unwrap(), 0);
+    assert!(!cache.is_parked());
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(cache.is_parked());
+
+    // the actor is spawned again with its entries
+    assert_eq!(cache.get(0).await.unwrap(), 0);
+    assert!(!cache.is_parked());
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    // the actor parks during the load, whose completion wakes it up
+    assert_eq!(cache.get(5).await.unwrap(), 10);
+    assert_eq!(cache.get_if_present(5).await.unwrap(), Some(10));
+}
+
+#[tokio::test]
+async fn test_shutdown() {
+    let cache: LoadingCache<u64, u64, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: u64| {
+        async move {
+            if key > 0 {
+                futures::future::pending::<()>().await;
+            }
+            Ok(key * 2)
+        }
+    });
+    assert_eq!(cache.get(0).await.unwrap(), 0);
+    // waits until the actor processed the operations of the spawned tasks
+    let processed = |operations: u64| {
+        let cache = cache.clone();
+        async move {
+            while cache.actor_stats().operations < operations {
+                tokio::task::yield_now().await;
+            }
+        }
+    };
+    let operations = cache.actor_stats().operations;
+    let load = tokio::spawn({
+        let cache = cache.clone();
+        async move { cache.get(1).await }
+    });
+    processed(operations + 1).await;
+    let waiter = tokio::spawn({
+        let cache = cache.clone();
+        async move { cache.get(1).await }
+    });
+    processed(operations + 2).await;
+
+    cache.shutdown().await.unwrap();
+    assert!(matches!(load.await.unwrap(), Err(CacheLoadingError::Cancelled())));
+    assert!(matches!(waiter.await.unwrap(), Err(CacheLoadingError::CommunicationError(_))));
+    assert!(matches!(cache.get(0).await, Err(CacheLoadingError::CommunicationError(_))));
+    assert!(cache.shutdown().await.is_err());
+}
+
+#[tokio::test]
+async fn test_registry() {
+    use crate::registry::{CacheRegistry, RegistryError};
+
+    let registry = CacheRegistry::new();
+    let unnamed: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move { Ok(key.len()) }
+    });
+    assert_eq!(registry.register(&unnamed).unwrap_err(), RegistryError::Unnamed());
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(),
+        move |key: String| async move { Ok(key.len()) },
+        CacheOptions::new().name("lengths"),
+    );
+    registry.register(&cache).unwrap();
+    assert_eq!(registry.register(&cache).unwrap_err(), RegistryError::Duplicate("lengths".to_owned()));
+    cache.get("foo".to_owned()).await.unwrap();
+
+    assert_eq!(registry.names(), vec!["lengths".to_owned()]);
+    let registered = registry.get("lengths").unwrap();
+    assert_eq!(registered.len().await.unwrap(), Some(1));
+    assert_eq!(registered.is_empty().await.unwrap(), Some(false));
+    assert_eq!(registered.dump().await.unwrap(), vec![("\"foo\"".to_owned(), "3".to_owned())]);
+    assert!(registered.stats().await.unwrap().actor.operations > 0);
+    registered.clear().await.unwrap();
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), None);
+
+    assert!(registry.unregister("lengths"));
+    assert!(registry.caches().is_empty());
+}
+
+#[tokio::test]
+async fn test_recursive_load() {
+    use crate::internal_cache::key_hash;
+    use crate::options::ExecutionMode;
+
+    type RecursiveCache = LoadingCache<u64, u64, Vec<u64>, HashMapBacking<u64, CacheEntry<u64, Vec<u64>>>>;
+
+    for mode in [ExecutionMode::Actor, ExecutionMode::Inline] {
+        let handle: Arc<std::sync::OnceLock<RecursiveCache>> = Default::default();
+        let loader_handle = handle.clone();
+        let cache = LoadingCache::with_options(HashMapBacking::new(), move |key: u64| {
+            let handle = loader_handle.clone();
+            async move {
+                let cache = handle.get().unwrap();
+                let dependency = match key {
+                    1 => 1,
+                    2 => 3,
+                    3 => 2,
+                    4 => 5,
+                    _ => return Ok(key),
+                };
+                cache.get(dependency).await.map_err(|err| match err {
+                    CacheLoadingError::RecursiveLoad(keys) => keys,
+                    CacheLoadingError::LoadingError(keys) => keys,
+                    err => panic!("unexpected error {:?}", err),
+                })
+            }
+        }, CacheOptions::new().execution_mode(mode));
+        handle.set(cache.clone()).ok();
+
+        // loading its own key
+        assert_eq!(cache.get(1).await.unwrap_err().into_loading_error(), Some(vec![key_hash(&1u64)]));
+        // the loader of 3 waits for 2, whose loader waits for 3
+        assert_eq!(cache.get(2).await.unwrap_err().into_loading_error(), Some(vec![key_hash(&3u64), key_hash(&2u64)]));
+        assert_eq!(cache.get(4).await.unwrap(), 5);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_load_independent() {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let counter = loads.clone();
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |_key: String| {
+        let load = counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(load)
+        }
+    });
+
+    // neither joins the running load nor is joined by the get
+    let (loaded, independent) = tokio::join!(
+        cache.get("foo".to_owned()),
+        cache.load_independent("foo".to_owned(), false));
+    let loaded = loaded.unwrap();
+    assert_ne!(loaded, independent.unwrap());
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), loaded);
+
+    assert_eq!(cache.load_independent("foo".to_owned(), true).await.unwrap(), 2);
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 2);
+    assert_eq!(loads.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_get_with_args() {
+    use crate::args::ArgsConflict;
+
+    let loader = |key: String, locale: String| async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok::<_, u8>(format!("{}.{}", key, locale))
+    };
+    let cache = LoadingCache::with_args_loader(HashMapBacking::new(), loader, ArgsConflict::Join);
+    let (first, second) = tokio::join!(
+        cache.get_with_args("foo".to_owned(), "de".to_owned()),
+        cache.get_with_args("foo".to_owned(), "en".to_owned()));
+    assert_eq!(first.unwrap(), "foo.de");
+    assert_eq!(second.unwrap(), "foo.de");
+    assert_eq!(cache.cache().get("bar".to_owned()).await.unwrap(), "bar.");
+
+    let cache = LoadingCache::with_args_loader(HashMapBacking::new(), loader, ArgsConflict::Bypass);
+    let (first, second, third) = tokio::join!(
+        cache.get_with_args("foo".to_owned(), "de".to_owned()),
+        cache.get_with_args("foo".to_owned(), "en".to_owned()),
+        cache.get_with_args("foo".to_owned(), "de".to_owned()));
+    assert_eq!(first.unwrap(), "foo.de");
+    assert_eq!(second.unwrap(), "foo.en");
+    assert_eq!(third.unwrap(), "foo.de");
+    // the bypassing load isn't cached, and cached values are returned regardless of the arguments
+    assert_eq!(cache.get_with_args("foo".to_owned(), "en".to_owned()).await.unwrap(), "foo.de");
+
+    // a load of the underlying cache runs with the default arguments, which differ
+    for (conflict, expected) in [(ArgsConflict::Bypass, "foo.en"), (ArgsConflict::Join, "foo.")] {
+        let cache = LoadingCache::with_args_loader(HashMapBacking::new(), loader, conflict);
+        let (loaded, with_args) = tokio::join!(cache.cache().get("foo".to_owned()), async {
+            // handled after the get, so its load is running once this returns
+            assert!(cache.cache().lookup("foo".to_owned()).await.unwrap().is_loading());
+            cache.get_with_args("foo".to_owned(), "en".to_owned()).await
+        });
+        assert_eq!(loaded.unwrap(), "foo.");
+        assert_eq!(with_args.unwrap(), expected);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_write_coalescing() {
+    use crate::backing::{BackingOperation, RecordingBacking};
+
+    let recording = RecordingBacking::new(HashMapBacking::new());
+    let log = recording.log().clone();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_options(
+        recording,
+        move |key: String| async move { Ok(key.len()) },
+        CacheOptions::new().write_coalescing(Duration::from_millis(10)));
+    cache.set("foo".to_owned(), 1).await.unwrap();
+    log.clear();
+
+    let (first, second, third) = tokio::join!(
+        cache.set("foo".to_owned(), 2),
+        cache.update("foo".to_owned(), |value| value * 10),
+        cache.set("foo".to_owned(), 3));
+    assert_eq!(first.unwrap(), Some(1));
+    assert_eq!(second.unwrap(), 3);
+    assert_eq!(third.unwrap(), Some(1));
+    let sets = log.take().iter()
+        .filter(|operation| matches!(operation, BackingOperation::Set { .. }))
+        .count();
+    assert_eq!(sets, 1);
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), Some(3));
+
+    // updates of a window are applied in order, loading the missing key once
+    let (first, second) = tokio::join!(
+        cache.update("bar".to_owned(), |value| value + 1),
+        cache.update("bar".to_owned(), |value| value * 2));
+    assert_eq!(first.unwrap(), 8);
+    assert_eq!(second.unwrap(), 8);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_swap() {
+    use crate::cache_api::SwapPolicy;
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            if key == "fail" {
+                return Err(1);
+            }
+            Ok(key.len())
+        }
+    });
+
+    assert_eq!(cache.swap("foo".to_owned(), 1).await.unwrap(), None);
+    assert_eq!(cache.swap("foo".to_owned(), 2).await.unwrap(), Some(1));
+
+    // a deferred swap waits for the load and returns the loaded value
+    let (loaded, previous) = tokio::join!(cache.get("bar".to_owned()), cache.swap("bar".to_owned(), 7));
+    assert_eq!(loaded.unwrap(), 3);
+    assert_eq!(previous.unwrap(), Some(3));
+    assert_eq!(cache.get_if_present("bar".to_owned()).await.unwrap(), Some(7));
+
+    // a failed load is swapped without a previous value
+    let (loaded, previous) = tokio::join!(cache.get("fail".to_owned()), cache.swap("fail".to_owned(), 7));
+    assert!(loaded.is_err());
+    assert_eq!(previous.unwrap(), None);
+    assert_eq!(cache.get_if_present("fail".to_owned()).await.unwrap(), Some(7));
+
+    // a replacing swap hands its value to the waiters of the load
+    let (loaded, previous) = tokio::join!(
+        cache.get("baz".to_owned()),
+        cache.swap_with("baz".to_owned(), 9, SwapPolicy::Replace));
+    assert_eq!(loaded.unwrap(), 9);
+    assert_eq!(previous.unwrap(), None);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(cache.get_if_present("baz".to_owned()).await.unwrap(), Some(9));
+}
+
+#[tokio::test]
+async fn test_skip_abandoned_reads() {
+    use futures::FutureExt;
+
+    let loads = Arc::new(AtomicUsize::new(0));
+    let counter = loads.clone();
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            Ok(key.len())
+        }
+    });
+
+    // the requests are queued, but the callers stop waiting before the cache executes them
+    assert!(cache.get("foo".to_owned()).now_or_never().is_none());
+    assert!(cache.set("bar".to_owned(), 1).now_or_never().is_none());
+    assert_eq!(cache.get_if_present("bar".to_owned()).await.unwrap(), Some(1));
+    assert!(!cache.exists("foo".to_owned()).await.unwrap());
+    assert_eq!(loads.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_get_until() {
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            Ok(key.len())
+        }
+    });
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(1);
+    let error = cache.get_until("foo".to_owned(), tokio::time::sleep_until(deadline)).await.unwrap_err();
+    assert!(error.is_cancelled());
+    // the load continues for the other callers
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+    assert_eq!(cache.get_until("foo".to_owned(), futures::future::pending()).await.unwrap(), 3);
+
+    let (cancel, cancelled) = tokio::sync::oneshot::channel::<()>();
+    cancel.send(()).unwrap();
+    let error = cache.update_until("bar".to_owned(), |value| value + 1, async move {
+        cancelled.await.ok();
+    }).await.unwrap_err();
+    assert!(matches!(error, CacheLoadingError::Cancelled()));
+}
+
+#[cfg(feature = "tower-layer")]
+#[tokio::test]
+async fn test_tower_layer() {
+    use tower::{Layer, ServiceExt};
+    use crate::layer::CacheLayer;
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |_: String| {
+        async move {
+            Err(1)
+        }
+    });
+    let calls = Arc::new(AtomicUsize::new(0));
+    let service_calls = calls.clone();
+    let service = CacheLayer::new(cache.clone(), |request: &String| request.to_lowercase())
+        .layer(tower::service_fn(move |request: String| {
+            service_calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                Ok::<_, u8>(request.len())
+            }
+        }));
+
+    let (first, second) = tokio::join!(
+        service.clone().oneshot("Foo".to_owned()),
+        service.clone().oneshot("FOO".to_owned())
+    );
+    assert_eq!(first.unwrap(), 3);
+    assert_eq!(second.unwrap(), 3);
+    assert_eq!(service.clone().oneshot("foo".to_owned()).await.unwrap(), 3);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), Some(3));
+}
+
+#[cfg(feature = "axum-integration")]
+#[tokio::test]
+async fn test_axum_shared_cache() {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::get;
+    use axum::{Extension, Router};
+    use tower::ServiceExt;
+    use crate::axum_cache::SharedCache;
+
+    type TestCache = SharedCache<String, usize, u8, HashMapBacking<String, CacheEntry<usize, u8>>>;
+
+    let cache: TestCache = SharedCache::new(LoadingCache::new(move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    }));
+    let router = Router::new()
+        .route("/len", get(|cache: TestCache| async move {
+            cache.get("foo".to_owned()).await.unwrap().to_string()
+        }))
+        .nest("/admin", cache.admin_router())
+        .layer(Extension(cache.clone()));
+
+    let response = router.clone()
+        .oneshot(Request::get("/len").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(cache.exists("foo".to_owned()).await.unwrap());
+
+    let response = router.clone()
+        .oneshot(Request::post("/admin/clear").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(!cache.exists("foo".to_owned()).await.unwrap());
+
+    let response = router.clone()
+        .oneshot(Request::get("/admin/verify").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = Router::new()
+        .route("/len", get(|_: TestCache| async move { "" }))
+        .oneshot(Request::get("/len").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn test_serde() {
+    use crate::cache_api::ResultMeta;
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    });
+    let result = cache.get_with_meta("foo".to_owned()).await.unwrap();
+    let json = serde_json::to_string(&result).unwrap();
+    assert_eq!(json, r#"{"result":3,"cached":false}"#);
+    let result: ResultMeta<usize> = serde_json::from_str(&json).unwrap();
+    assert_eq!(result.result, 3);
+    assert!(!result.cached);
+
+    #[cfg(feature = "ttl-cache")]
+    {
+        let meta = TtlMeta::from(Duration::from_millis(1500));
+        let json = serde_json::to_string(&meta).unwrap();
+        assert_eq!(json, r#"{"ttl":{"secs":1,"nanos":500000000}}"#);
+        assert_eq!(serde_json::from_str::<TtlMeta>(&json).unwrap().ttl, meta.ttl);
+    }
+}
+
+#[cfg(any(feature = "json-codec", feature = "bincode-codec", feature = "msgpack-codec"))]
+#[test]
+fn test_value_codecs() {
+    use crate::codec::{CodecError, ValueCodec};
+
+    fn assert_round_trip<C: ValueCodec<(String, Vec<u32>)>>(codec: C) {
+        let value = ("foo".to_owned(), vec![1, 2, 3]);
+        let bytes = codec.encode(&value).unwrap();
+        assert_eq!(codec.decode(&bytes).unwrap(), value);
+        assert!(matches!(codec.decode(&[]), Err(CodecError::Decode(_))));
+    }
+
+    #[cfg(feature = "json-codec")]
+    assert_round_trip(crate::codec::JsonCodec);
+    #[cfg(feature = "bincode-codec")]
+    assert_round_trip(crate::codec::BincodeCodec);
+    #[cfg(feature = "msgpack-codec")]
+    assert_round_trip(crate::codec::MessagePackCodec);
+}
+
+#[cfg(feature = "json-codec")]
+#[test]
+fn test_entry_transform() {
+    use crate::codec::{CodecError, EntryTransform, JsonCodec, TransformedCodec, ValueCodec};
+
+    struct XorTransform(u8);
+
+    impl EntryTransform for XorTransform {
+        fn encrypt(&self, bytes: Vec<u8>) -> Result<Vec<u8>, CodecError> {
+            Ok(bytes.into_iter().map(|byte| byte ^ self.0).collect())
+        }
+
+        fn decrypt(&self, bytes: &[u8]) -> Result<Vec<u8>, CodecError> {
+            if bytes.is_empty() {
+                return Err(CodecError::Transform("no data".to_owned()));
+            }
+            Ok(bytes.iter().map(|byte| byte ^ self.0).collect())
+        }
+    }
+
+    let codec = TransformedCodec::new(JsonCodec, XorTransform(0x5a));
+    let value = "secret".to_owned();
+    let bytes = codec.encode(&value).unwrap();
+    assert_ne!(bytes, ValueCodec::<String>::encode(&JsonCodec, &value).unwrap());
+    assert_eq!(ValueCodec::<String>::decode(&codec, &bytes).unwrap(), value);
+    assert!(matches!(ValueCodec::<String>::decode(&codec, &[]), Err(CodecError::Transform(_))));
+}
+
+#[cfg(all(feature = "json-codec", any(feature = "lz4-compression", feature = "zstd-compression")))]
+#[test]
+fn test_compressed_codec() {
+    use crate::codec::{CompressedCodec, Compression, JsonCodec, ValueCodec};
+
+    fn assert_compressed(compression: Compression) {
+        let codec = CompressedCodec::new(JsonCodec, compression).threshold(64);
+        let small = "foo".to_owned();
+        let large = "foo".repeat(100);
+        let small_bytes = codec.encode(&small).unwrap();
+        let large_bytes = codec.encode(&large).unwrap();
+        assert_eq!(small_bytes.len(), ValueCodec::<String>::encode(&JsonCodec, &small).unwrap().len() + 1);
+        assert!(large_bytes.len() < large.len() / 2);
+        assert_eq!(ValueCodec::<String>::decode(&codec, &small_bytes).unwrap(), small);
+        assert_eq!(ValueCodec::<String>::decode(&codec, &large_bytes).unwrap(), large);
+        assert!(ValueCodec::<String>::decode(&codec, &[]).is_err());
+    }
+
+    #[cfg(feature = "lz4-compression")]
+    assert_compressed(Compression::Lz4);
+    #[cfg(feature = "zstd-compression")]
+    assert_compressed(Compression::Zstd { level: 3 });
+}
+
+#[tokio::test]
+async fn test_async_cache_trait() {
+    use crate::async_cache::AsyncCache;
+
+    async fn use_cache<C: AsyncCache<String, usize>>(cache: C) -> Result<(), C::Error> {
+        assert_eq!(cache.get("foo".to_owned()).await?, 3);
+        assert_eq!(cache.set("foo".to_owned(), 5).await?, Some(3));
+        assert_eq!(cache.get_if_present("foo".to_owned()).await?, Some(5));
+        assert_eq!(cache.remove("foo".to_owned()).await?, Some(5));
+        assert_eq!(cache.get_if_present("foo".to_owned()).await?, None);
+        cache.set("bar".to_owned(), 1).await?;
+        cache.clear().await?;
+        assert_eq!(cache.get_if_present("bar".to_owned()).await?, None);
+        Ok(())
+    }
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    });
+    use_cache(cache.clone()).await.unwrap();
+    use_cache(cache.map_err(|error| error.to_string())).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_conditional_loader() {
+    use crate::http_meta::{HttpMeta, Validated};
+
+    let full_loads = Arc::new(AtomicUsize::new(0));
+    let loader_full_loads = full_loads.clone();
+    let cache: LoadingCache<String, String, u8, HashMapBacking<_, _>> = LoadingCache::with_conditional_loader(
+        HashMapBacking::new(),
+        move |key: String, previous: Option<Validated<String>>| {
+            let full_loads = loader_full_loads.clone();
+            async move {
+                match previous {
+                    Some(previous) if previous.meta.etag.as_deref() == Some("v1") =>
+                        Ok(previous.not_modified(HttpMeta::new().with_max_age(Duration::from_secs(5)))),
+                    _ => {
+                        full_loads.fetch_add(1, Ordering::SeqCst);
+                        Ok(Validated::modified(key.to_uppercase(), HttpMeta::new().with_etag("v1")))
+                    }
+                }
+            }
+        },
+    );
+
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), "FOO");
+    cache.remove("foo".to_owned()).await.unwrap();
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), "FOO");
+    assert_eq!(full_loads.load(Ordering::SeqCst), 1);
+    assert_eq!(cache.get("bar".to_owned()).await.unwrap(), "BAR");
+    assert_eq!(full_loads.load(Ordering::SeqCst), 2);
+
+    let previous = Validated::modified(1, HttpMeta::new().with_etag("v1"));
+    let revalidated = previous.not_modified(HttpMeta::new().with_max_age(Duration::from_secs(5)));
+    assert_eq!(revalidated.value, 1);
+    assert_eq!(revalidated.meta.etag.as_deref(), Some("v1"));
+    assert_eq!(revalidated.meta.max_age, Some(Duration::from_secs(5)));
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_conditional_loader_max_age() {
+    use crate::http_meta::{HttpMeta, Validated};
+
+    let revalidations = Arc::new(AtomicUsize::new(0));
+    let loader_revalidations = revalidations.clone();
+    let cache: LoadingCache<String, usize, u8, TtlCacheBacking<_, _, _>> = LoadingCache::with_conditional_loader(
+        TtlCacheBacking::new(Duration::from_secs(60)),
+        move |key: String, previous: Option<Validated<usize>>| {
+            let revalidations = loader_revalidations.clone();
+            async move {
+                let meta = HttpMeta::new().with_max_age(Duration::from_secs(1));
+                match previous {
+                    Some(previous) => {
+                        revalidations.fetch_add(1, Ordering::SeqCst);
+                        Ok(previous.not_modified(meta))
+                    }
+                    None => Ok(Validated::modified(key.len(), meta)),
+                }
+            }
+        },
+    );
+
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+    tokio::time::advance(Duration::from_secs(2)).await;
+    assert!(!cache.exists("foo".to_owned()).await.unwrap());
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+    assert_eq!(revalidations.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_task_names() {
+    use crate::internal_cache::task_name;
+
+    assert_eq!(task_name::<String>(Some("users"), "actor", None), "cache-loader-async:users:actor");
+    assert_eq!(task_name::<String>(None, "actor", None), "cache-loader-async:unnamed:actor");
+    let load = task_name(Some("users"), "load", Some(&"foo".to_owned()));
+    assert!(load.starts_with("cache-loader-async:users:load:"));
+    assert_eq!(load, task_name(Some("users"), "load", Some(&"foo".to_owned())));
+    assert_ne!(load, task_name(Some("users"), "load", Some(&"bar".to_owned())));
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(),
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        },
+        CacheOptions::new().name("users"),
+    );
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_cache_name() {
+    use crate::options::ExecutionMode;
+
+    let loader = |key: String| async move { Ok::<_, u8>(key.len()) };
+    let unnamed: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(loader);
+    assert_eq!(unnamed.name(), None);
+    let users: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(), loader, CacheOptions::new().name("users"));
+    assert_eq!(users.name(), Some("users"));
+    assert_eq!(users.clone().name(), Some("users"));
+    assert_eq!(users.map_err(|err| err as u16).name(), Some("users"));
+    let sessions: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(), loader, CacheOptions::new().name("sessions").execution_mode(ExecutionMode::Inline));
+    assert_eq!(sessions.name(), Some("sessions"));
+}
+
+#[tokio::test]
+async fn test_completion_lane() {
+    use crate::cache_api::{CacheResult, DataWithMeta};
+    use crate::internal_cache::{CacheAction, CacheMessage, CacheSender, InternalCacheStore};
+
+    type Backing = HashMapBacking<String, CacheEntry<usize, u8>>;
+    let (tx, rx) = tokio::sync::mpsc::channel(16);
+    let (completion_tx, completion_rx) = tokio::sync::mpsc::channel(16);
+    let (waiter, _) = tokio::sync::broadcast::channel(1);
+    let mut backing = Backing::new();
+    backing.set("foo".to_owned(), CacheEntry::Loading(waiter), None).unwrap();
+    let store = InternalCacheStore::new(
+        backing,
+        CacheSender::Channel(tx.clone().into()),
+        CacheSender::Channel(completion_tx.clone().into()),
+        |key: String| async move { Ok(DataWithMeta::<String, usize, u8, Backing>::new(key.len(), None)) },
+        CacheOptions::new(),
+    );
+
+    // the reads are queued before the load completes, but processed after its completion
+    let mut reads = Vec::new();
+    for _ in 0..4 {
+        let (response, read) = crate::response::channel();
+        tx.send(CacheMessage::new(CacheAction::GetIfPresent("foo".to_owned()), response)).await.ok().unwrap();
+        reads.push(read);
+    }
+    let (response, completion) = crate::response::channel();
+    completion_tx.send(CacheMessage::new(CacheAction::SetAndUnblock("foo".to_owned(), 3, None), response)).await.ok().unwrap();
+    store.run(rx.into(), completion_rx.into());
+
+    assert!(matches!(completion.await.unwrap(), CacheResult::None));
+    for read in reads {
+        assert!(matches!(read.await.unwrap(), CacheResult::Found(3)));
+    }
+}
+
+#[tokio::test]
+async fn test_operation_id() {
+    use crate::cache_api::{CacheResult, DataWithMeta};
+    use crate::internal_cache::{CacheAction, CacheMessage, CacheSender, InternalCacheStore};
+
+    type Backing = HashMapBacking<String, CacheEntry<usize, u8>>;
+    let (tx, rx) = tokio::sync::mpsc::channel(16);
+    let (completion_tx, mut completion_rx) = tokio::sync::mpsc::channel(16);
+    // the sender has to stay alive, the actor would spin on a closed lane
+    let (_idle_tx, idle_rx) = tokio::sync::mpsc::channel::<CacheMessage<String, usize, u8, Backing>>(1);
+    let store = InternalCacheStore::new(
+        Backing::new(),
+        CacheSender::Channel(tx.clone().into()),
+        CacheSender::Channel(completion_tx.into()),
+        |key: String| async move { Ok(DataWithMeta::<String, usize, u8, Backing>::new(key.len(), None)) },
+        CacheOptions::new(),
+    );
+    // the completions are taken off the lane of the store, so they can be inspected
+    store.run(rx.into(), idle_rx.into());
+
+    let (response, get) = crate::response::channel();
+    let message = CacheMessage::new(CacheAction::Get("foo".to_owned()), response);
+    let operation = message.id;
+    tx.send(message).await.ok().unwrap();
+    assert!(matches!(get.await.unwrap(), CacheResult::Loading(_)));
+
+    let completion = completion_rx.recv().await.unwrap();
+    assert!(matches!(completion.action, CacheAction::SetAndUnblock(ref key, 3, _) if key == "foo"));
+    assert_eq!(completion.id, operation);
+    let (response, _) = crate::response::channel();
+    assert_ne!(CacheMessage::<String, usize, u8, Backing>::new(CacheAction::Clear(), response).id, operation);
+}
+
+#[tokio::test]
+async fn test_response_pool() {
+    use crate::response::ResponsePool;
+
+    let pool = Arc::new(ResponsePool::<usize>::new(1));
+    let (tx, rx) = pool.channel();
+    tx.send(3).unwrap();
+    assert_eq!(rx.await.unwrap(), 3);
+    assert_eq!(pool.pooled(), 1);
+
+    // the slot is reused and starts out empty
+    let (tx, rx) = pool.channel();
+    assert_eq!(pool.pooled(), 0);
+    assert!(!tx.is_closed());
+    std::mem::drop(tx);
+    assert!(rx.await.is_err());
+    assert_eq!(pool.pooled(), 1);
+
+    // a slot whose receiver is gone isn't reused while the sender holds it
+    let (tx, rx) = pool.channel();
+    std::mem::drop(rx);
+    assert!(tx.is_closed());
+    assert_eq!(tx.send(4), Err(4));
+    assert_eq!(pool.pooled(), 0);
+
+    let (tx, rx) = pool.channel();
+    let waiter = tokio::spawn(rx);
+    tokio::task::yield_now().await;
+    tx.send(5).unwrap();
+    assert_eq!(waiter.await.unwrap().unwrap(), 5);
+    assert_eq!(pool.pooled(), 1);
+
+    // no more idle slots are kept than the pool was created for
+    let responses = [pool.channel(), pool.channel()];
+    for (tx, rx) in responses {
+        tx.send(6).unwrap();
+        assert_eq!(rx.await.unwrap(), 6);
+    }
+    assert_eq!(pool.pooled(), 1);
+}
+
+#[tokio::test]
+async fn test_batch_size() {
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(),
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        },
+        CacheOptions::new().batch_size(16),
+    );
+
+    let gets = (0..100)
+        .map(|i| cache.get(format!("key{}", i % 10)))
+        .collect::<Vec<_>>();
+    let values = futures::future::join_all(gets).await;
+    assert!(values.into_iter().all(|value| value.unwrap() == 4));
+    cache.set("foo".to_owned(), 1).await.unwrap();
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), Some(1));
+}
+
+#[tokio::test]
+async fn test_cache_stats() {
+    let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::with_options(HashMapBacking::new(), |key: u64| async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        if key == 0 {
+            Err(1)
+        } else {
+            Ok(key * 2)
+        }
+    }, CacheOptions::new().record_stats(true));
+    assert_eq!(cache.stats().unwrap().hit_rate(), 1.0);
+
+    cache.get(1).await.unwrap();
+    cache.get(1).await.unwrap();
+    cache.get(0).await.unwrap_err();
+    assert_eq!(cache.get_if_present(2).await.unwrap(), None);
+    let stats = cache.stats().unwrap();
+    assert_eq!((stats.hits, stats.misses), (1, 3));
+    assert_eq!(stats.hit_rate(), 0.25);
+    assert_eq!((stats.load_successes, stats.load_failures), (1, 1));
+    assert!(stats.mean_load_time() >= Duration::from_millis(10));
+    assert!(stats.load_time_percentile(99.0) >= stats.mean_load_time());
+    assert!(stats.load_time_percentile(99.0) < Duration::from_millis(200));
+
+    let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::new(|key: u64| async move { Ok(key) });
+    assert!(cache.stats().is_none());
+}
+
+#[cfg(feature = "lru-cache")]
+#[tokio::test]
+async fn test_cache_stats_evictions() {
+    let cache: LoadingCache<u64, u64, u8, _> = LoadingCache::with_options(LruCacheBacking::new(2), |key: u64| async move {
+        Ok(key * 2)
+    }, CacheOptions::new().record_stats(true));
+    for key in 0..5 {
+        cache.get(key).await.unwrap();
+    }
+    cache.remove(4).await.unwrap();
+    assert_eq!(cache.stats().unwrap().evictions, 3);
+}
+
+#[tokio::test]
+async fn test_actor_stats() {
+    use crate::stats::ActorStats;
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    });
+    assert_eq!(cache.actor_stats(), ActorStats::default());
+
+    cache.set("foo".to_owned(), 1).await.unwrap();
+    let update = cache.update("foo".to_owned(), |value| {
+        std::thread::sleep(Duration::from_millis(20));
+        value + 1
+    });
+    let get = cache.get_if_present("foo".to_owned());
+    let (update, get) = tokio::join!(update, get);
+    assert_eq!(update.unwrap(), 2);
+    assert!(get.unwrap().is_some());
+
+    let stats = cache.actor_stats();
+    assert_eq!(stats.operations, 3);
+    assert!(stats.max_processing_time >= Duration::from_millis(20));
+    assert!(stats.processing_time >= stats.max_processing_time);
+    assert!(stats.mean_processing_time() <= stats.max_processing_time);
+    assert!(stats.mean_queue_wait() <= stats.max_queue_wait);
+}
+
+#[cfg(feature = "unstable")]
+#[tokio::test]
+async fn test_send_action() {
+    use crate::cache_api::{CacheAction, CacheResult};
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    });
+
+    match cache.send_action(CacheAction::Get("foo".to_owned())).await.unwrap() {
+        CacheResult::Loading(handle) => assert_eq!(handle.await.unwrap(), 3),
+        _ => panic!("Expected a loading result"),
+    }
+    assert!(matches!(cache.send_action(CacheAction::GetIfPresent("foo".to_owned())).await.unwrap(), CacheResult::Found(3)));
+    assert!(matches!(cache.send_action(CacheAction::Set("bar".to_owned(), 5, None)).await.unwrap(), CacheResult::None));
+    assert!(matches!(cache.send_action(CacheAction::Remove("bar".to_owned())).await.unwrap(), CacheResult::Found(5)));
+    assert!(matches!(cache.send_action(CacheAction::GetIfPresent("bar".to_owned())).await.unwrap(), CacheResult::None));
+}
+
+#[cfg(feature = "unstable")]
+#[tokio::test]
+async fn test_abort_load() {
+    use crate::cache_api::{CacheAction, CacheResult};
+
+    let loads = Arc::new(AtomicUsize::new(0));
+    let counter = loads.clone();
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        let load = counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if load == 0 {
+                futures::future::pending::<()>().await;
+            }
+            Ok(key.len())
+        }
+    });
+
+    let load = match cache.send_action(CacheAction::Get("foo".to_owned())).await.unwrap() {
+        CacheResult::Loading(load) => load,
+        _ => panic!("Expected a loading result"),
+    };
+    let waiter = match cache.send_action(CacheAction::Get("foo".to_owned())).await.unwrap() {
+        CacheResult::Loading(waiter) => waiter,
+        _ => panic!("Expected a loading result"),
+    };
+    assert!(!load.is_finished());
+    load.abort();
+    assert!(matches!(load.await, Err(CacheLoadingError::Cancelled())));
+    assert!(waiter.await.is_err());
+    // the key was unblocked, so it's loaded again
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+    assert_eq!(loads.load(Ordering::SeqCst), 2);
+}
+
+#[cfg(feature = "unstable")]
+#[tokio::test]
+async fn test_send_actions() {
+    use crate::cache_api::{CacheAction, CacheResult};
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    });
+    cache.set("bar".to_owned(), 5).await.unwrap();
+
+    let mut results = cache.send_actions(vec![
+        CacheAction::Get("foo".to_owned()),
+        CacheAction::GetIfPresent("bar".to_owned()),
+        CacheAction::Set("baz".to_owned(), 7, None),
+        CacheAction::Pipeline(vec![CacheAction::Remove("bar".to_owned())]),
+    ]).await.unwrap();
+    assert_eq!(results.len(), 4);
+    assert!(matches!(results[1], CacheResult::Found(5)));
+    assert!(matches!(results[2], CacheResult::None));
+    assert!(matches!(&results[3], CacheResult::Pipeline(removed) if matches!(removed[..], [CacheResult::Found(5)])));
+    match results.swap_remove(0) {
+        CacheResult::Loading(handle) => assert_eq!(handle.await.unwrap(), 3),
+        _ => panic!("Expected a loading result"),
+    }
+    assert_eq!(cache.get_if_present("baz".to_owned()).await.unwrap(), Some(7));
+    assert_eq!(cache.get_if_present("bar".to_owned()).await.unwrap(), None);
+    assert!(cache.send_actions(Vec::new()).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_coalescer() {
+    use crate::coalescer::Coalescer;
+
+    let coalescer: Coalescer<String, usize, u8> = Coalescer::new();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let call = |result: Result<usize, u8>| {
+        let calls = calls.clone();
+        move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                result
+            }
+        }
+    };
+
+    let (first, second) = tokio::join!(
+        coalescer.call("foo".to_owned(), call(Ok(3))),
+        coalescer.call("foo".to_owned(), call(Ok(4)))
+    );
+    assert_eq!(first.unwrap(), 3);
+    assert_eq!(second.unwrap(), 3);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(coalescer.in_flight(), 0);
+
+    // results are not stored
+    assert_eq!(coalescer.call("foo".to_owned(), call(Ok(4))).await.unwrap(), 4);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    let (first, second) = tokio::join!(
+        coalescer.call("bar".to_owned(), call(Err(1))),
+        coalescer.call("bar".to_owned(), call(Ok(1)))
+    );
+    assert_eq!(first.unwrap_err().into_loading_error(), Some(1));
+    assert_eq!(second.unwrap_err().into_loading_error(), Some(1));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let panicking = coalescer.call("baz".to_owned(), || async move {
+        panic!("call failed");
+    });
+    assert!(panicking.await.unwrap_err().as_communication_error().is_some());
+    assert_eq!(coalescer.in_flight(), 0);
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test]
+async fn test_ttl_manual_clock() {
+    use crate::clock::ManualClock;
+
+    let clock = ManualClock::new();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+        TtlCacheBacking::with_clock(Duration::from_secs(3), HashMapBacking::new(), clock.clone()),
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        });
+
+    cache.set("key1".to_owned(), 1).await.unwrap();
+    clock.advance(Duration::from_secs(2));
+    cache.set("key2".to_owned(), 2).await.unwrap();
+    assert!(cache.exists("key1".to_owned()).await.unwrap());
+
+    clock.advance(Duration::from_secs(1));
+    assert!(!cache.exists("key1".to_owned()).await.unwrap());
+    assert!(cache.exists("key2".to_owned()).await.unwrap());
+
+    clock.advance(Duration::from_secs(2));
+    assert!(!cache.exists("key2".to_owned()).await.unwrap());
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test]
+async fn test_ttl_from_value() {
+    use crate::clock::ManualClock;
+
+    let clock = ManualClock::new();
+    let backing = TtlCacheBacking::with_clock(Duration::from_secs(10), HashMapBacking::new(), clock.clone())
+        .ttl_from_value(|entry: &CacheEntry<u64, u8>| match entry {
+            CacheEntry::Loaded(seconds) if *seconds > 0 => Some(Duration::from_secs(*seconds)),
+            _ => None,
+        });
+    let cache: LoadingCache<String, u64, u8, _> = LoadingCache::with_meta_loader(backing, move |key: String| {
+        async move {
+            match key.as_str() {
+                "meta" => Ok(2).with_meta(Some(TtlMeta::from(Duration::from_secs(5)))),
+                _ => Ok(key.parse().unwrap()).with_meta(None),
+            }
+        }
+    });
+
+    cache.get("2".to_owned()).await.unwrap();
+    cache.get("0".to_owned()).await.unwrap();
+    cache.get("meta".to_owned()).await.unwrap();
+
+    clock.advance(Duration::from_secs(2));
+    assert!(!cache.exists("2".to_owned()).await.unwrap());
+    assert!(cache.exists("meta".to_owned()).await.unwrap());
+
+    clock.advance(Duration::from_secs(3));
+    assert!(!cache.exists("meta".to_owned()).await.unwrap());
+    assert!(cache.exists("0".to_owned()).await.unwrap());
+
+    clock.advance(Duration::from_secs(5));
+    assert!(!cache.exists("0".to_owned()).await.unwrap());
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test]
+async fn test_ttl_per_key() {
+    use crate::clock::ManualClock;
+
+    let clock = ManualClock::new();
+    let backing = TtlCacheBacking::with_clock(Duration::from_secs(10), HashMapBacking::new(), clock.clone())
+        .ttl_per_key(|key: &String| if key.starts_with("config:") {
+            Duration::from_secs(60)
+        } else {
+            Duration::from_secs(5)
+        });
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_meta_loader(backing, move |key: String| {
+        async move {
+            Ok(key.len()).with_meta(None)
+        }
+    });
+
+    cache.get("config:limits".to_owned()).await.unwrap();
+    cache.get("user:1".to_owned()).await.unwrap();
+    cache.set_with_meta("user:2".to_owned(), 6, Some(TtlMeta::from(Duration::from_secs(30)))).await.unwrap();
+
+    clock.advance(Duration::from_secs(5));
+    assert!(!cache.exists("user:1".to_owned()).await.unwrap());
+    assert!(cache.exists("user:2".to_owned()).await.unwrap());
+    assert!(cache.exists("config:limits".to_owned()).await.unwrap());
+
+    clock.advance(Duration::from_secs(55));
+    assert!(!cache.exists("user:2".to_owned()).await.unwrap());
+    assert!(!cache.exists("config:limits".to_owned()).await.unwrap());
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test]
+async fn test_get_allow_stale() {
+    use std::sync::atomic::AtomicBool;
+    use crate::clock::ManualClock;
+
+    let clock = ManualClock::new();
+    let loads = Arc::new(AtomicUsize::new(0));
+    let failing = Arc::new(AtomicBool::new(false));
+    let gate = Arc::new(tokio::sync::Semaphore::new(1));
+    let backing = TtlCacheBacking::with_clock(Duration::from_secs(3), HashMapBacking::new(), clock.clone())
+        .stale_retention(Duration::from_secs(60));
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(backing, {
+        let loads = loads.clone();
+        let failing = failing.clone();
+        let gate = gate.clone();
+        move |_: String| {
+            let loads = loads.clone();
+            let failing = failing.clone();
+            let gate = gate.clone();
+            async move {
+                gate.acquire().await.unwrap().forget();
+                if failing.load(Ordering::SeqCst) {
+                    Err(1)
+                } else {
+                    Ok(loads.fetch_add(1, Ordering::SeqCst) + 1)
+                }
+            }
+        }
+    });
+    let key = || "key".to_owned();
+
+    let fresh = cache.get_allow_stale(key(), Duration::from_secs(10)).await.unwrap();
+    assert_eq!(fresh.value, 1);
+    assert!(!fresh.is_stale());
+
+    // the reload is blocked by the gate, the stale value is returned meanwhile
+    clock.advance(Duration::from_secs(5));
+    assert!(!cache.exists(key()).await.unwrap());
+    let stale = cache.get_allow_stale(key(), Duration::from_secs(10)).await.unwrap();
+    assert_eq!(stale.value, 1);
+    assert_eq!(stale.staleness, Some(Duration::from_secs(2)));
+    assert_eq!(cache.get_allow_stale(key(), Duration::from_secs(10)).await.unwrap().value, 1);
+    gate.add_permits(1);
+    assert_eq!(cache.get(key()).await.unwrap(), 2);
+    assert!(!cache.get_allow_stale(key(), Duration::from_secs(10)).await.unwrap().is_stale());
+
+    // a failing reload keeps the stale value
+    clock.advance(Duration::from_secs(5));
+    failing.store(true, Ordering::SeqCst);
+    gate.add_permits(100);
+    assert_eq!(cache.get_allow_stale(key(), Duration::from_secs(10)).await.unwrap().value, 2);
+    assert!(cache.get(key()).await.is_err());
+    assert_eq!(cache.get_allow_stale(key(), Duration::from_secs(10)).await.unwrap().value, 2);
+    assert!(cache.get_allow_stale(key(), Duration::from_secs(1)).await.is_err());
+
+    // past the retention, there is no stale value anymore
+    clock.advance(Duration::from_secs(60));
+    assert!(cache.get_allow_stale(key(), Duration::from_secs(3600)).await.is_err());
+}
+
+#[cfg(feature = "ttl-cache")]
+#[test]
+fn test_ttl_equal_expiries() {
+    use crate::clock::ManualClock;
+
+    // the clock doesn't move, so all keys share the same expiry
+    let clock = ManualClock::new();
+    let mut backing = TtlCacheBacking::with_clock(Duration::from_secs(3), HashMapBacking::new(), clock.clone());
+    for i in 0..1000 {
+        backing.set(i, i, None).unwrap();
+    }
+    for i in (0..1000).step_by(3) {
+        assert_eq!(backing.remove(&i).unwrap(), Some(i));
+    }
+    for i in (1..1000).step_by(3) {
+        assert_eq!(backing.set(i, i * 2, Some(TtlMeta::from(Duration::from_secs(5)))).unwrap(), Some(i));
+    }
+    let removed = backing.remove_if(Box::new(|(key, _)| key % 3 == 2 && *key < 500)).unwrap();
+    assert_eq!(removed.len(), 166);
+
+    clock.advance(Duration::from_secs(3));
+    assert!(backing.get(&2).unwrap().is_none());
+    assert!(backing.get(&998).unwrap().is_none());
+    assert_eq!(backing.get(&1).unwrap(), Some(&2));
+
+    clock.advance(Duration::from_secs(2));
+    for i in 0..1000 {
+        assert!(!backing.contains_key(&i).unwrap());
+    }
+    backing.set(1, 1, None).unwrap();
+    assert_eq!(backing.remove(&1).unwrap(), Some(1));
+}
+
+#[cfg(feature = "ttl-cache")]
+#[test]
+fn test_ttl_purge_batch_size() {
+    use crate::backing::{BackingOperation, RecordingBacking};
+    use crate::clock::ManualClock;
+
+    let clock = ManualClock::new();
+    let recording = RecordingBacking::new(HashMapBacking::new());
+    let log = recording.log().clone();
+    let mut backing = TtlCacheBacking::with_clock(Duration::from_secs(3), recording, clock.clone())
+        .purge_batch_size(10);
+    for i in 0..25 {
+        backing.set(i, i, None).unwrap();
+    }
+    clock.advance(Duration::from_secs(3));
+    let removals = |log: &crate::backing::OperationLog<_, _, _>| log.take().iter()
+        .filter(|operation| matches!(operation, BackingOperation::Remove { .. }))
+        .count();
+    log.clear();
+
+    assert!(backing.get(&24).unwrap().is_none());
+    assert_eq!(removals(&log), 10);
+    assert!(!backing.contains_key(&20).unwrap());
+    assert_eq!(removals(&log), 10);
+    backing.remove_expired().unwrap();
+    assert_eq!(removals(&log), 5);
+}
+
+#[cfg(feature = "ttl-cache")]
+#[test]
+fn test_ttl_expiration_modes() {
+    use crate::backing::{BackingOperation, ExpirationMode, RecordingBacking};
+    use crate::clock::ManualClock;
+
+    let removed_keys = |operations: Vec<BackingOperation<String, (usize, tokio::time::Instant), NoMeta>>| {
+        operations.into_iter()
+            .filter_map(|operation| match operation {
+                BackingOperation::Remove { key, .. } => Some(key),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    for mode in [ExpirationMode::Eager, ExpirationMode::Lazy, ExpirationMode::Background] {
+        let clock = ManualClock::new();
+        let recording = RecordingBacking::new(HashMapBacking::new());
+        let log = recording.log().clone();
+        let mut backing = TtlCacheBacking::with_clock(Duration::from_secs(3), recording, clock.clone())
+            .expiration_mode(mode);
+        backing.set("key1".to_owned(), 1, None).unwrap();
+        backing.set("key2".to_owned(), 2, None).unwrap();
+        clock.advance(Duration::from_secs(3));
+        log.clear();
+
+        assert!(backing.get(&"key1".to_owned()).unwrap().is_none());
+        assert!(!backing.contains_key(&"key2".to_owned()).unwrap());
+        let removed = removed_keys(log.take());
+        match mode {
+            ExpirationMode::Eager | ExpirationMode::Lazy => assert_eq!(removed, vec!["key1".to_owned(), "key2".to_owned()]),
+            ExpirationMode::Background => assert!(removed.is_empty()),
+        }
+
+        backing.set("key3".to_owned(), 3, None).unwrap();
+        clock.advance(Duration::from_secs(3));
+        backing.set("key4".to_owned(), 4, None).unwrap();
+        let removed = removed_keys(log.take());
+        match mode {
+            ExpirationMode::Eager => assert_eq!(removed, vec!["key3".to_owned()]),
+            ExpirationMode::Lazy | ExpirationMode::Background => assert!(removed.is_empty()),
+        }
+        assert!(backing.get(&"key3".to_owned()).unwrap().is_none());
+
+        backing.remove_expired().unwrap();
+        assert!(backing.get(&"key4".to_owned()).unwrap().is_some());
+    }
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_expiry_sweep() {
+    use crate::backing::{BackingOperation, ExpirationMode, RecordingBacking};
+
+    let recording = RecordingBacking::new(HashMapBacking::new());
+    let log = recording.log().clone();
+    let backing = TtlCacheBacking::with_backing(Duration::from_secs(3), recording)
+        .expiration_mode(ExpirationMode::Background);
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_meta_loader_and_options(
+        backing,
+        move |key: String| {
+            async move {
+                Ok(key.len()).with_meta(None)
+            }
+        },
+        CacheOptions::new().expiry_sweep(Duration::from_secs(10)));
+
+    cache.set("key1".to_owned(), 1).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert!(!log.take().iter().any(|operation| matches!(operation, BackingOperation::Remove { .. })));
+
+    tokio::time::sleep(Duration::from_secs(6)).await;
+    cache.flush().await.unwrap();
+    assert!(log.take().iter().any(|operation| matches!(operation, BackingOperation::Remove { key, .. } if key == "key1")));
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_expiry_sweep_idle_park() {
+    use crate::backing::RemovalCause;
+    let removed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let listener = removed.clone();
+    let options = CacheOptions::new()
+        .idle_park(Duration::from_secs(1))
+        .expiry_sweep(Duration::from_secs(1))
+        .removal_listener(move |key: String, value: usize, cause: RemovalCause| {
+            listener.lock().unwrap().push((key, value, cause));
+        });
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_options(
+        TtlCacheBacking::new(Duration::from_secs(3)), move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        }, options);
+
+    cache.set("key1".to_owned(), 1).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    // the actor parks until the entry expires
+    assert!(cache.is_parked());
+    assert!(removed.lock().unwrap().is_empty());
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    // and is woken up to sweep it
+    assert!(!cache.is_parked());
+    assert_eq!(*removed.lock().unwrap(), vec![("key1".to_owned(), 1, RemovalCause::Expired)]);
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(cache.is_parked());
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test]
+async fn test_ttl_stats() {
+    use crate::clock::ManualClock;
+    use crate::stats::TtlStats;
+
+    let clock = ManualClock::new();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+        TtlCacheBacking::with_clock(Duration::from_secs(3), HashMapBacking::new(), clock.clone()),
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        });
+    assert_eq!(cache.backing_stats().await.unwrap().ttl, Some(TtlStats::default()));
+
+    cache.set("key1".to_owned(), 1).await.unwrap();
+    cache.set("key2".to_owned(), 2).await.unwrap();
+    cache.set("key3".to_owned(), 3).await.unwrap();
+    cache.remove("key1".to_owned()).await.unwrap();
+    assert_eq!(cache.backing_stats().await.unwrap().ttl, Some(TtlStats {
+        expirations: 0,
+        removals: 1,
+        queue_length: 2,
+    }));
+
+    clock.advance(Duration::from_secs(3));
+    cache.set("key4".to_owned(), 4).await.unwrap();
+    assert_eq!(cache.backing_stats().await.unwrap().ttl, Some(TtlStats {
+        expirations: 2,
+        removals: 1,
+        queue_length: 1,
+    }));
+
+    cache.clear().await.unwrap();
+    assert_eq!(cache.backing_stats().await.unwrap().ttl, Some(TtlStats {
+        expirations: 2,
+        removals: 2,
+        queue_length: 0,
+    }));
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test]
+async fn test_ttl_drain_expired() {
+    use crate::clock::ManualClock;
+
+    let clock = ManualClock::new();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+        TtlCacheBacking::with_clock(Duration::from_secs(3), HashMapBacking::new(), clock.clone()),
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        });
+    cache.set("key1".to_owned(), 1).await.unwrap();
+    clock.advance(Duration::from_secs(1));
+    cache.set("key2".to_owned(), 2).await.unwrap();
+    clock.advance(Duration::from_secs(1));
+    cache.set("key3".to_owned(), 3).await.unwrap();
+    assert!(cache.drain_expired().await.unwrap().is_empty());
+
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(cache.drain_expired().await.unwrap(), vec![("key1".to_owned(), 1), ("key2".to_owned(), 2)]);
+    assert!(cache.drain_expired().await.unwrap().is_empty());
+    assert_eq!(cache.get_if_present("key3".to_owned()).await.unwrap(), Some(3));
+    assert_eq!(cache.backing_stats().await.unwrap().ttl.unwrap().expirations, 2);
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test]
+async fn test_ttl_system_clock() {
+    use crate::backing::{BackingOperation, RecordingBacking};
+    use crate::clock::SystemClock;
+    use std::time::SystemTime;
+
+    let recording = RecordingBacking::new(HashMapBacking::new());
+    let log = recording.log().clone();
+    let before = SystemTime::now();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+        TtlCacheBacking::with_clock(Duration::from_secs(3600), recording, SystemClock::new()),
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        });
+    cache.set("key1".to_owned(), 1).await.unwrap();
+    cache.set_with_meta("key2".to_owned(), 2, Some(Duration::ZERO.into())).await.unwrap();
+    assert_eq!(cache.get_if_present("key1".to_owned()).await.unwrap(), Some(1));
+    assert_eq!(cache.get_if_present("key2".to_owned()).await.unwrap(), None);
+
+    // the nested backing stores the wall-clock expiry
+    let expiry = log.take().into_iter()
+        .find_map(|operation| match operation {
+            BackingOperation::Set { key, value: (_, expiry), .. } if key == "key1" => Some(expiry),
+            _ => None,
+        })
+        .unwrap();
+    assert!(expiry >= before + Duration::from_secs(3600));
+    assert!(expiry <= SystemTime::now() + Duration::from_secs(3600));
+}
+
+#[cfg(feature = "ttl-cache")]
+#[test]
+fn test_system_clock_adjustments() {
+    use crate::clock::SystemClock;
+    use std::time::SystemTime;
+
+    let clock = SystemClock::new();
+    let now = SystemTime::now();
+    assert_eq!(clock.observe(now), now);
+    // the system time jumped backwards, the clock holds the latest time
+    assert_eq!(clock.clone().observe(now - Duration::from_secs(60)), now);
+    assert_eq!(clock.observe(now + Duration::from_secs(1)), now + Duration::from_secs(1));
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test(start_paused = true)]
+async fn test_ttl_coarse_clock() {
+    use crate::clock::{Clock, CoarseClock};
+
+    let clock = CoarseClock::new(Duration::from_secs(1));
+    let start = clock.now();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+        TtlCacheBacking::with_clock(Duration::from_millis(1500), HashMapBacking::new(), clock.clone()),
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        });
+    cache.set("key".to_owned(), 1).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(clock.now(), start);
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert_eq!(clock.now(), start + Duration::from_secs(1));
+    // expired, but the clock hasn't noticed yet
+    assert!(cache.exists("key".to_owned()).await.unwrap());
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(clock.now(), start + Duration::from_secs(2));
+    assert!(!cache.exists("key".to_owned()).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_faulty_backing() {
+    use crate::test_util::{FaultScript, FaultyBacking};
+
+    let script = FaultScript::new();
+    let mut backing = FaultyBacking::new(HashMapBacking::<String, usize>::new(), script.clone());
+    script.fail_on(2, BackingError::InjectedFault("broken".to_owned()))
+        .drop_entries_on(4)
+        .delay_on(5, Duration::from_millis(50));
+
+    assert!(backing.set("foo".to_owned(), 1, None).unwrap().is_none());
+    assert!(matches!(backing.get(&"foo".to_owned()), Err(BackingError::InjectedFault(_))));
+    assert_eq!(backing.get(&"foo".to_owned()).unwrap(), Some(&1));
+    assert!(!backing.contains_key(&"foo".to_owned()).unwrap());
+    let started = std::time::Instant::now();
+    backing.set("foo".to_owned(), 2, None).unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    assert_eq!(script.operations(), 5);
+
+    let script = FaultScript::new();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+        FaultyBacking::new(HashMapBacking::new(), script.clone()),
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        });
+    script.fail_on(1, BackingError::InjectedFault("broken".to_owned()));
+    assert!(matches!(cache.get("foo".to_owned()).await, Err(CacheLoadingError::BackingError(BackingError::InjectedFault(_)))));
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_inline_execution() {
+    use crate::options::ExecutionMode;
+
+    let loads = Arc::new(AtomicUsize::new(0));
+    let loader_loads = loads.clone();
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(),
+        move |key: String| {
+            loader_loads.fetch_add(1, Ordering::SeqCst);
+            async move {
+                tokio::task::yield_now().await;
+                if key.is_empty() {
+                    Err(1)
+                } else {
+                    Ok(key.len())
+                }
+            }
+        },
+        CacheOptions::new().execution_mode(ExecutionMode::Inline),
+    );
+
+    // the load starts when the first get is polled and is shared with the second one
+    let (first, second) = tokio::join!(cache.get("foo".to_owned()), cache.get("foo".to_owned()));
+    assert_eq!(first.unwrap(), 3);
+    assert_eq!(second.unwrap(), 3);
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+    assert_eq!(cache.update("foo".to_owned(), |value| value * 2).await.unwrap(), 6);
+    assert_eq!(cache.update_mut("bar".to_owned(), |value| *value += 1).await.unwrap(), 4);
+    assert_eq!(cache.set("foo".to_owned(), 1).await.unwrap(), Some(6));
+    assert_eq!(cache.get("".to_owned()).await.unwrap_err().into_loading_error(), Some(1));
+    assert!(!cache.exists("".to_owned()).await.unwrap());
+    assert_eq!(cache.try_get("baz".to_owned()).await.unwrap(), 3);
+    cache.remove_if(|(key, _)| key.starts_with('b')).await.unwrap();
+    assert_eq!(cache.get_if_present("bar".to_owned()).await.unwrap(), None);
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), Some(1));
+
+    // no runtime is required as nothing is spawned
+    let result = std::thread::spawn(move || {
+        futures::executor::block_on(async move {
+            cache.clear().await.unwrap();
+            cache.get("fooo".to_owned()).await.unwrap()
+        })
+    }).join().unwrap();
+    assert_eq!(result, 4);
+}
+
+#[tokio::test]
+async fn test_inline_dropped_load() {
+    use crate::options::ExecutionMode;
+    use futures::FutureExt;
+
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::with_options(
+        HashMapBacking::new(),
+        move |key: String| {
+            async move {
+                tokio::task::yield_now().await;
+                Ok(key.len())
+            }
+        },
+        CacheOptions::new().execution_mode(ExecutionMode::Inline),
+    );
+
+    // the first get starts the load and is dropped, the second one waits for it
+    let mut driver = Box::pin(cache.get("foo".to_owned()));
+    assert!((&mut driver).now_or_never().is_none());
+    let mut waiter = Box::pin(cache.get("foo".to_owned()));
+    assert!((&mut waiter).now_or_never().is_none());
+    std::mem::drop(driver);
+
+    assert!(matches!(waiter.await, Err(CacheLoadingError::CommunicationError(CacheCommunicationError::TokioBroadcastRecvError(_)))));
+    assert!(cache.entry_state("foo".to_owned()).await.unwrap().is_none());
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_recording_backing() {
+    use crate::backing::{BackingOperation, OperationLog, RecordingBacking};
+
+    let backing = RecordingBacking::new(HashMapBacking::new());
+    let log = backing.log().clone();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(backing, move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    });
+
+    assert_eq!(cache.get("foo".to_owned()).await.unwrap(), 3);
+    let operations = log.take();
+    // miss, loading entry and the loaded value
+    assert!(matches!(&operations[0], BackingOperation::Get { key, result: Ok(None) } if key == "foo"));
+    assert!(matches!(&operations[1], BackingOperation::Set { value: CacheEntry::Loading(_), .. }));
+    assert!(operations.iter().any(|operation| matches!(operation, BackingOperation::Set { value: CacheEntry::Loaded(3), .. })));
+
+    log.set_enabled(false);
+    cache.remove("foo".to_owned()).await.unwrap();
+    assert!(log.operations().is_empty());
+    log.set_enabled(true);
+    cache.clear().await.unwrap();
+    assert!(matches!(log.operations()[..], [BackingOperation::Clear { result: Ok(()) }]));
+
+    let mut backing = RecordingBacking::with_log(HashMapBacking::<String, usize>::new(), OperationLog::bounded(2));
+    backing.set("foo".to_owned(), 1, None).unwrap();
+    backing.contains_key(&"foo".to_owned()).unwrap();
+    *backing.get_mut(&"foo".to_owned()).unwrap().unwrap() = 2;
+    let operations = backing.log().operations();
+    assert_eq!(operations.len(), 2);
+    assert!(matches!(operations[0], BackingOperation::ContainsKey { result: Ok(true), .. }));
+    assert!(matches!(operations[1], BackingOperation::GetMut { result: Ok(Some(1)), .. }));
+}
+
+#[tokio::test]
+async fn test_with_entries() {
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_entries(
+        vec![("foo".to_owned(), 32), ("bar".to_owned(), 64)],
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        });
+    assert_eq!(cache.get_if_present("foo".to_owned()).await.unwrap(), Some(32));
+    assert_eq!(cache.get("bar".to_owned()).await.unwrap(), 64);
+    assert_eq!(cache.get("buzz".to_owned()).await.unwrap(), 4);
+
+    let mut backing: HashMapBacking<_, _> = vec![("foo".to_owned(), 1)].into_iter().collect();
+    assert_eq!(backing.get(&"foo".to_owned()).unwrap(), Some(&1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_slow_loader() {
+    let loader = SlowLoader::new(move |key: String| {
+        async move {
+            Ok(key.len())
+        }
+    })
+        .latency_per_key(|key: &String| Duration::from_secs(key.len() as u64))
+        .failure_rate(0.5, 7)
+        .seed(42);
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(loader.into_loader());
+
+    let started = tokio::time::Instant::now();
+    let result = cache.get("foo".to_owned()).await;
+    assert_eq!(started.elapsed(), Duration::from_secs(3));
+
+    let mut failures = usize::from(result.is_err());
+    for i in 0..99 {
+        match cache.get(i.to_string()).await {
+            Ok(len) => assert_eq!(len, i.to_string().len()),
+            Err(err) => {
+                assert_eq!(err.into_loading_error(), Some(7));
+                failures += 1;
+            }
+        }
+    }
+    assert!((30..70).contains(&failures), "{} of 100 loads failed", failures);
+}
+
+#[tokio::test]
+async fn test_entry_state() {
+    let started = Arc::new(tokio::sync::Notify::new());
+    let release = Arc::new(tokio::sync::Notify::new());
+    let loader_started = started.clone();
+    let loader_release = release.clone();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::new(move |key: String| {
+        let started = loader_started.clone();
+        let release = loader_release.clone();
+        async move {
+            started.notify_one();
+            release.notified().await;
+            Ok(key.len())
+        }
+    });
+
+    assert!(cache.entry_state("foo".to_owned()).await.unwrap().is_none());
+    let load = tokio::spawn({
+        let cache = cache.clone();
+        async move { cache.get("foo".to_owned()).await }
+    });
+    started.notified().await;
+    assert!(matches!(cache.entry_state("foo".to_owned()).await.unwrap(), Some(CacheEntry::Loading(_))));
+
+    release.notify_one();
+    assert_eq!(load.await.unwrap().unwrap(), 3);
+    cache.flush().await.unwrap();
+    assert!(matches!(cache.entry_state("foo".to_owned()).await.unwrap(), Some(CacheEntry::Loaded(3))));
+}
+
+#[cfg(feature = "ttl-cache")]
+#[tokio::test]
+async fn test_ttl_sweep_expired() {
+    use crate::backing::{BackingOperation, RecordingBacking};
+    use crate::clock::ManualClock;
+
+    let clock = ManualClock::new();
+    let recording = RecordingBacking::new(HashMapBacking::new());
+    let log = recording.log().clone();
+    let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+        TtlCacheBacking::with_clock(Duration::from_secs(3), recording, clock.clone()),
+        move |key: String| {
+            async move {
+                Ok(key.len())
+            }
+        });
+
+    cache.set("key1".to_owned(), 1).await.unwrap();
+    clock.advance(Duration::from_secs(5));
+    log.clear();
+    cache.sweep_expired().await.unwrap();
+    let operations = log.take();
+    assert_eq!(operations.len(), 1);
+    assert!(matches!(&operations[0], BackingOperation::Remove { key, .. } if key == "key1"));
+}
+
+#[cfg(feature = "loadgen")]
+#[tokio::test]
+async fn test_loadgen() {
+    use crate::loadgen::Workload;
+
+    let report = Workload::new(1000)
+        .key_space(64)
+        .read_ratio(1.0)
+        .miss_ratio(0.0)
+        .concurrency(3)
+        .run(HashMapBacking::new())
+        .await;
+    assert_eq!(report.operations(), 1000);
+    assert_eq!(report.hits, 1000);
+    assert_eq!(report.hit_ratio(), 1.0);
+
+    let report = Workload::new(1000)
+        .read_ratio(0.5)
+        .miss_ratio(0.5)
+        .run(HashMapBacking::new())
+        .await;
+    assert_eq!(report.reads + report.writes, 1000);
+    assert_eq!(report.hits + report.misses, report.reads);
+    assert_eq!(report.errors, 0);
+    assert!((400..600).contains(&report.reads), "{} of 1000 operations were reads", report.reads);
+    assert!((150..350).contains(&report.misses), "{} of {} reads missed", report.misses, report.reads);
+}
+
+#[cfg(feature = "chaos")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_chaos_updates_during_loads() {
+    // the chaos points are on in the chaos job, which sets `CACHE_LOADER_CHAOS_SEED`
+    let loader = SlowLoader::new(move |_: String| {
+        async move {
+            Ok(0usize)
+        }
+    }).latency(Duration::from_millis(5));
+    let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(loader.into_loader());
+
+    for round in 0..10 {
+        let key = format!("counter{}", round);
+        let updates = (0..20)
+            .map(|_| {
+                let cache = cache.clone();
+                let key = key.clone();
+                tokio::spawn(async move {
+                    cache.update_mut(key, |value| *value += 1).await.unwrap();
+                })
+            })
+            .collect::<Vec<_>>();
+        for update in updates {
+            update.await.unwrap();
+        }
+        assert_eq!(cache.get(key).await.unwrap(), 20);
+    }
+}
+
+crate::backing_tests!(hash_map_backing_conformance, HashMapBacking::new());
+crate::backing_tests!(hasher_backing_conformance, HashMapBacking::with_hasher(std::hash::BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default()));
+crate::backing_tests!(recording_backing_conformance, crate::backing::RecordingBacking::new(HashMapBacking::new()));
+crate::backing_tests!(recovering_backing_conformance, RecoveringBacking::new(HashMapBacking::new(), BackingErrorPolicy::Retry(1)));
+crate::backing_tests!(fifo_backing_conformance, crate::backing::BoundedHashMapBacking::new(8, crate::backing::EvictionPolicy::Fifo));
+crate::backing_tests!(random_backing_conformance, crate::backing::BoundedHashMapBacking::new(8, crate::backing::EvictionPolicy::Random));
+crate::backing_tests!(fifo_hasher_backing_conformance, crate::backing::BoundedHashMapBacking::with_hasher(8, crate::backing::EvictionPolicy::Fifo, std::hash::BuildHasherDefault::<std::collections::hash_map::DefaultHasher>::default()));
+crate::backing_tests!(weighted_backing_conformance, crate::backing::WeightedCacheBacking::new(16, |_, _| 2));
+crate::backing_tests!(tiered_backing_conformance, crate::backing::TieredBacking::new(crate::backing::BoundedHashMapBacking::new(2, crate::backing::EvictionPolicy::Fifo), HashMapBacking::new()));
+#[cfg(feature = "lru-cache")]
+crate::backing_tests!(lru_backing_conformance, LruCacheBacking::new(8));
+#[cfg(feature = "lru-cache")]
+crate::backing_tests!(
+    weighted_lru_backing_conformance,
+    LruCacheBacking::unbounded().max_weight(16).weigher(|_, _| 2),
+    meta = crate::backing::LruMeta::from(2)
+);
+#[cfg(feature = "lru-cache")]
+crate::backing_tests!(
+    protected_lru_backing_conformance,
+    LruCacheBacking::new(8).protected_segment(8).protect_if(|key: &String| key.starts_with('f')),
+    meta = crate::backing::LruMeta::new().protected()
+);
+#[cfg(feature = "ttl-cache")]
+crate::backing_tests!(
+    ttl_backing_conformance,
+    TtlCacheBacking::new(Duration::from_secs(60)),
+    meta = TtlMeta::from(Duration::from_secs(30))
+);
+#[cfg(feature = "ttl-cache")]
+crate::backing_tests!(
+    ttl_background_backing_conformance,
+    TtlCacheBacking::new(Duration::from_secs(60)).expiration_mode(crate::backing::ExpirationMode::Background),
+    meta = TtlMeta::from(Duration::from_secs(30))
+);
+#[cfg(all(feature = "ttl-cache", feature = "lru-cache"))]
+crate::backing_tests!(
+    ttl_lru_backing_conformance,
+    TtlCacheBacking::with_backing(Duration::from_secs(60), LruCacheBacking::new(8)),
+    meta = TtlMeta::from(Duration::from_secs(30))
+);
@@ -0,0 +1,364 @@
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use futures::Future;
+use futures::future::BoxFuture;
+use crate::backing::{BackingError, BackingPredicate, CacheBacking, ExportChunk, Inconsistency, Maintenance, RemovalListener};
+use crate::rng::XorShift;
+use crate::stats::BackingStats;
+
+pub mod conformance;
+
+/// A fault which a `FaultyBacking` injects into an operation
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// Fails the operation with the error, without executing it
+    Error(BackingError),
+    /// Blocks the operation for the duration before executing it, which blocks the cache as a
+    /// slow backing would
+    Latency(Duration),
+    /// Silently drops all entries of the wrapped backing before executing the operation
+    DropEntries,
+}
+
+#[derive(Default)]
+struct ScriptState {
+    operations: usize,
+    faults: Vec<(usize, Fault)>,
+}
+
+/// The script of the faults a `FaultyBacking` injects
+///
+/// Operations are counted from 1 on, every call of a `CacheBacking` method is an operation.
+/// Clones share the same script, so faults can still be scripted once the backing has been moved
+/// into a cache.
+#[derive(Clone, Default)]
+pub struct FaultScript {
+    state: Arc<Mutex<ScriptState>>,
+}
+
+impl FaultScript {
+    pub fn new() -> FaultScript {
+        Default::default()
+    }
+
+    /// Injects the fault into the `operation`th operation
+    pub fn inject(&self, operation: usize, fault: Fault) -> &FaultScript {
+        self.state.lock().unwrap().faults.push((operation, fault));
+        self
+    }
+
+    /// Fails the `operation`th operation with the error
+    pub fn fail_on(&self, operation: usize, error: BackingError) -> &FaultScript {
+        self.inject(operation, Fault::Error(error))
+    }
+
+    /// Delays the `operation`th operation by the latency
+    pub fn delay_on(&self, operation: usize, latency: Duration) -> &FaultScript {
+        self.inject(operation, Fault::Latency(latency))
+    }
+
+    /// Drops all entries before the `operation`th operation
+    pub fn drop_entries_on(&self, operation: usize) -> &FaultScript {
+        self.inject(operation, Fault::DropEntries)
+    }
+
+    /// Returns the amount of operations executed so far
+    pub fn operations(&self) -> usize {
+        self.state.lock().unwrap().operations
+    }
+
+    fn next_faults(&self) -> Vec<Fault> {
+        let mut state = self.state.lock().unwrap();
+        state.operations += 1;
+        let operation = state.operations;
+        let mut faults = Vec::new();
+        state.faults.retain(|(at, fault)| {
+            if *at == operation {
+                faults.push(fault.clone());
+                false
+            } else {
+                true
+            }
+        });
+        faults
+    }
+}
+
+/// A backing wrapper which injects the faults of a `FaultScript`, to test how code using a cache
+/// handles failing, slow or forgetful backings
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::backing::{BackingError, HashMapBacking};
+/// use cache_loader_async::cache_api::LoadingCache;
+/// use cache_loader_async::test_util::{FaultScript, FaultyBacking};
+/// async fn example() {
+///     let script = FaultScript::new();
+///     let cache: LoadingCache<String, usize, u8, _> = LoadingCache::with_backing(
+///         FaultyBacking::new(HashMapBacking::new(), script.clone()),
+///         move |key: String| {
+///             async move {
+///                 Ok(key.len())
+///             }
+///         }
+///     );
+///
+///     script.fail_on(script.operations() + 1, BackingError::InjectedFault("disk full".to_owned()));
+///     assert!(cache.set("foo".to_owned(), 1).await.is_err());
+/// }
+/// ```
+pub struct FaultyBacking<B> {
+    backing: B,
+    script: FaultScript,
+}
+
+impl<B> FaultyBacking<B> {
+    pub fn new(backing: B, script: FaultScript) -> FaultyBacking<B> {
+        FaultyBacking {
+            backing,
+            script,
+        }
+    }
+
+    pub fn script(&self) -> &FaultScript {
+        &self.script
+    }
+
+    fn apply_faults<K, V>(&mut self) -> Result<(), BackingError>
+        where K: Eq + Hash + Sized + Clone + Send,
+              V: Sized + Clone + Send,
+              B: CacheBacking<K, V> {
+        let mut result = Ok(());
+        for fault in self.script.next_faults() {
+            match fault {
+                Fault::Error(error) => result = Err(error),
+                Fault::Latency(latency) => std::thread::sleep(latency),
+                Fault::DropEntries => self.backing.clear()?,
+            }
+        }
+        result
+    }
+}
+
+impl<
+    K: Eq + Hash + Sized + Clone + Send,
+    V: Sized + Clone + Send,
+    B: CacheBacking<K, V>,
+> CacheBacking<K, V> for FaultyBacking<B> {
+    type Meta = B::Meta;
+
+    fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.get_mut(key)
+    }
+
+    fn write_back(&mut self, key: &K) -> Result<(), BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.write_back(key)
+    }
+
+    fn get(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.get(key)
+    }
+
+    fn set(&mut self, key: K, value: V, meta: Option<Self::Meta>) -> Result<Option<V>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.set(key, value, meta)
+    }
+
+    fn remove(&mut self, key: &K) -> Result<Option<V>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.remove(key)
+    }
+
+    fn contains_key(&mut self, key: &K) -> Result<bool, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.contains_key(key)
+    }
+
+    fn remove_if(&mut self, predicate: BackingPredicate<K, V>) -> Result<Vec<(K, V)>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.remove_if(predicate)
+    }
+
+    fn clear(&mut self) -> Result<(), BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.clear()
+    }
+
+    fn repair(&mut self) -> Result<(), BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.repair()
+    }
+
+    fn verify(&mut self) -> Result<Vec<Inconsistency>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.verify()
+    }
+
+    fn peek(&mut self, key: &K) -> Result<Option<&V>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.peek(key)
+    }
+
+    fn evict(&mut self, count: usize) -> Result<Vec<(K, V)>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.evict(count)
+    }
+
+    fn entries_by_recency(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.entries_by_recency()
+    }
+
+    fn entries(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.entries()
+    }
+
+    fn export(&mut self, cursor: usize, count: usize) -> Result<ExportChunk<K, V, B::Meta>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.export(cursor, count)
+    }
+
+    fn remove_expired(&mut self) -> Result<(), BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.remove_expired()
+    }
+
+    fn drain_expired(&mut self) -> Result<Vec<(K, V)>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.drain_expired()
+    }
+
+    fn get_stale(&mut self, key: &K, max_staleness: Duration) -> Result<Option<(V, Duration)>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.get_stale(key, max_staleness)
+    }
+
+    fn time_to_live(&mut self, key: &K) -> Result<Option<Duration>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.time_to_live(key)
+    }
+
+    fn next_expiry(&mut self) -> Result<Option<Duration>, BackingError> {
+        self.apply_faults::<K, V>()?;
+        self.backing.next_expiry()
+    }
+
+    fn set_removal_listener(&mut self, listener: RemovalListener<K, V>) {
+        self.backing.set_removal_listener(listener)
+    }
+
+    fn stats(&self) -> BackingStats {
+        self.backing.stats()
+    }
+
+    fn maintenance(&mut self) -> Option<&mut dyn Maintenance> {
+        self.backing.maintenance()
+    }
+}
+
+type LatencyFn<K> = Arc<dyn Fn(&K) -> Duration + Send + Sync>;
+
+/// A loader wrapper which delays loads and lets a share of them fail
+///
+/// The latency is awaited with `tokio::time::sleep`, so it follows `tokio::time::pause`. Failures
+/// are drawn from a seeded pseudo random sequence, which makes them reproducible; a failing load
+/// doesn't call the wrapped loader. Works with plain loaders as well as with meta loaders.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use cache_loader_async::backing::HashMapBacking;
+/// use cache_loader_async::cache_api::LoadingCache;
+/// use cache_loader_async::test_util::SlowLoader;
+/// async fn example() {
+///     let loader = SlowLoader::new(move |key: String| {
+///         async move {
+///             Ok(key.len())
+///         }
+///     })
+///         .latency(Duration::from_millis(500))
+///         .failure_rate(0.1, 1u8);
+///     let cache: LoadingCache<String, usize, u8, HashMapBacking<_, _>> = LoadingCache::new(loader.into_loader());
+/// }
+/// ```
+pub struct SlowLoader<K, E, T> {
+    loader: T,
+    latency: LatencyFn<K>,
+    failure: Option<(f64, E)>,
+    seed: u64,
+}
+
+impl<
+    K: Send + 'static,
+    E: Clone + Send + 'static,
+    T: Send + 'static,
+> SlowLoader<K, E, T> {
+    pub fn new(loader: T) -> SlowLoader<K, E, T> {
+        SlowLoader {
+            loader,
+            latency: Arc::new(|_| Duration::ZERO),
+            failure: None,
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    /// Delays every load by the latency
+    pub fn latency(self, latency: Duration) -> SlowLoader<K, E, T> {
+        self.latency_per_key(move |_| latency)
+    }
+
+    /// Delays every load by the latency returned for its key
+    pub fn latency_per_key<L>(mut self, latency: L) -> SlowLoader<K, E, T>
+        where L: Fn(&K) -> Duration + Send + Sync + 'static {
+        self.latency = Arc::new(latency);
+        self
+    }
+
+    /// Fails the given share of loads (from `0.0` to `1.0`) with the error
+    pub fn failure_rate(mut self, rate: f64, error: E) -> SlowLoader<K, E, T> {
+        self.failure = Some((rate, error));
+        self
+    }
+
+    /// Seeds the sequence deciding which loads fail
+    pub fn seed(mut self, seed: u64) -> SlowLoader<K, E, T> {
+        self.seed = seed;
+        self
+    }
+
+    /// Returns the loader function, which can be passed to the constructors of `LoadingCache`
+    pub fn into_loader<X, F>(self) -> impl Fn(K) -> BoxFuture<'static, Result<X, E>> + Send + 'static
+        where T: Fn(K) -> F,
+              F: Future<Output=Result<X, E>> + Send + 'static,
+              X: Send + 'static {
+        let SlowLoader { loader, latency, failure, seed } = self;
+        let rng = Mutex::new(XorShift::new(seed));
+        move |key: K| {
+            let latency = latency(&key);
+            let failure = failure.as_ref().and_then(|(rate, error)| {
+                if rng.lock().unwrap().next_f64() < *rate {
+                    Some(error.clone())
+                } else {
+                    None
+                }
+            });
+            let load = match failure {
+                None => Ok(loader(key)),
+                Some(error) => Err(error),
+            };
+            Box::pin(async move {
+                if !latency.is_zero() {
+                    tokio::time::sleep(latency).await;
+                }
+                load?.await
+            })
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Checks of the `CacheBacking` contract, used by the `backing_tests!` macro
+//!
+//! Every check receives a new, empty backing which must be able to hold at least 8 entries
+//! without evicting any of them. The checks panic on the first violation of the contract.
+
+use std::collections::HashSet;
+use std::time::Duration;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, LoadingCache};
+
+/// The entries a backing stores when it is used by a `LoadingCache`
+pub type Entry = CacheEntry<String, u8>;
+
+fn loaded(value: &str) -> Entry {
+    CacheEntry::Loaded(value.to_owned())
+}
+
+fn loaded_value(entry: Option<&Entry>) -> Option<&str> {
+    match entry {
+        Some(CacheEntry::Loaded(value)) => Some(value.as_str()),
+        Some(CacheEntry::Loading(_)) => panic!("Expected a loaded entry, got a loading entry"),
+        None => None,
+    }
+}
+
+fn key(key: &str) -> String {
+    key.to_owned()
+}
+
+/// `set` stores entries, returns the replaced entry and `get`/`contains_key` find them
+pub fn check_set_get<B: CacheBacking<String, Entry>>(mut backing: B) {
+    assert!(backing.get(&key("foo")).unwrap().is_none());
+    assert!(!backing.contains_key(&key("foo")).unwrap());
+    assert!(backing.set(key("foo"), loaded("one"), None).unwrap().is_none());
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("one"));
+    assert!(backing.contains_key(&key("foo")).unwrap());
+    let replaced = backing.set(key("foo"), loaded("two"), None).unwrap();
+    assert_eq!(loaded_value(replaced.as_ref()), Some("one"));
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("two"));
+    assert!(backing.get(&key("bar")).unwrap().is_none());
+}
+
+/// Changes through `get_mut` are visible to later lookups
+pub fn check_get_mut<B: CacheBacking<String, Entry>>(mut backing: B) {
+    assert!(backing.get_mut(&key("foo")).unwrap().is_none());
+    backing.set(key("foo"), loaded("one"), None).unwrap();
+    match backing.get_mut(&key("foo")).unwrap() {
+        Some(CacheEntry::Loaded(value)) => value.push_str("two"),
+        _ => panic!("Expected a loaded entry"),
+    }
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("onetwo"));
+}
+
+/// `remove` returns the removed entry and only removes the given key
+pub fn check_remove<B: CacheBacking<String, Entry>>(mut backing: B) {
+    assert!(backing.remove(&key("foo")).unwrap().is_none());
+    backing.set(key("foo"), loaded("one"), None).unwrap();
+    backing.set(key("bar"), loaded("two"), None).unwrap();
+    let removed = backing.remove(&key("foo")).unwrap();
+    assert_eq!(loaded_value(removed.as_ref()), Some("one"));
+    assert!(backing.get(&key("foo")).unwrap().is_none());
+    assert!(!backing.contains_key(&key("foo")).unwrap());
+    assert!(backing.remove(&key("foo")).unwrap().is_none());
+    assert_eq!(loaded_value(backing.get(&key("bar")).unwrap()), Some("two"));
+}
+
+/// `remove_if` removes and returns exactly the matching entries
+pub fn check_remove_if<B: CacheBacking<String, Entry>>(mut backing: B) {
+    for (key, value) in [("a1", "1"), ("a2", "2"), ("b1", "3"), ("b2", "4")] {
+        backing.set(key.to_owned(), loaded(value), None).unwrap();
+    }
+    let removed = backing.remove_if(Box::new(|(key, _)| key.starts_with('a'))).unwrap();
+    let removed = removed.into_iter()
+        .map(|(key, entry)| (key, loaded_value(Some(&entry)).unwrap().to_owned()))
+        .collect::<HashSet<_>>();
+    let expected = [("a1", "1"), ("a2", "2")].iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<HashSet<_>>();
+    assert_eq!(removed, expected);
+    assert!(!backing.contains_key(&key("a1")).unwrap());
+    assert!(!backing.contains_key(&key("a2")).unwrap());
+    assert_eq!(loaded_value(backing.get(&key("b1")).unwrap()), Some("3"));
+    assert_eq!(loaded_value(backing.get(&key("b2")).unwrap()), Some("4"));
+    assert!(backing.remove_if(Box::new(|_| false)).unwrap().is_empty());
+}
+
+/// `clear` removes all entries and the backing stays usable
+pub fn check_clear<B: CacheBacking<String, Entry>>(mut backing: B) {
+    backing.set(key("foo"), loaded("one"), None).unwrap();
+    backing.set(key("bar"), loaded("two"), None).unwrap();
+    backing.clear().unwrap();
+    assert!(backing.get(&key("foo")).unwrap().is_none());
+    assert!(backing.get(&key("bar")).unwrap().is_none());
+    backing.set(key("foo"), loaded("three"), None).unwrap();
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("three"));
+}
+
+/// Loading entries are stored like any other entry and can be replaced by the loaded value
+pub fn check_loading_entries<B: CacheBacking<String, Entry>>(mut backing: B) {
+    let (tx, _) = tokio::sync::broadcast::channel(1);
+    backing.set(key("foo"), CacheEntry::Loading(tx.clone()), None).unwrap();
+    assert!(matches!(backing.get(&key("foo")).unwrap(), Some(CacheEntry::Loading(_))));
+    assert!(backing.contains_key(&key("foo")).unwrap());
+    let replaced = backing.set(key("foo"), loaded("one"), None).unwrap();
+    assert!(matches!(replaced, Some(CacheEntry::Loading(_))));
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("one"));
+
+    backing.set(key("bar"), CacheEntry::Loading(tx.clone()), None).unwrap();
+    assert!(matches!(backing.remove(&key("bar")).unwrap(), Some(CacheEntry::Loading(_))));
+
+    backing.set(key("baz"), CacheEntry::Loading(tx), None).unwrap();
+    let removed = backing.remove_if(Box::new(|(_, entry)| matches!(entry, CacheEntry::Loading(_)))).unwrap();
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].0, "baz");
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("one"));
+}
+
+/// A healthy backing verifies without inconsistencies, `repair` succeeds on it and keeps its
+/// entries
+pub fn check_repair<B: CacheBacking<String, Entry>>(mut backing: B) {
+    backing.set(key("foo"), loaded("one"), None).unwrap();
+    assert!(backing.verify().unwrap().is_empty());
+    backing.repair().unwrap();
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("one"));
+}
+
+/// Removing the expired entries keeps the fresh ones
+pub fn check_remove_expired<B: CacheBacking<String, Entry>>(mut backing: B) {
+    backing.set(key("foo"), loaded("one"), None).unwrap();
+    backing.remove_expired().unwrap();
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("one"));
+}
+
+/// Peeking returns the same value as `get`
+pub fn check_peek<B: CacheBacking<String, Entry>>(mut backing: B) {
+    backing.set(key("foo"), loaded("one"), None).unwrap();
+    assert_eq!(loaded_value(backing.peek(&key("foo")).unwrap()), Some("one"));
+    assert!(backing.peek(&key("bar")).unwrap().is_none());
+}
+
+/// Fresh entries are never drained
+pub fn check_drain_expired<B: CacheBacking<String, Entry>>(mut backing: B) {
+    backing.set(key("foo"), loaded("one"), None).unwrap();
+    assert!(backing.drain_expired().unwrap().is_empty());
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("one"));
+}
+
+/// Fresh entries are never returned as stale
+pub fn check_get_stale<B: CacheBacking<String, Entry>>(mut backing: B) {
+    backing.set(key("foo"), loaded("one"), None).unwrap();
+    assert!(backing.get_stale(&key("foo"), Duration::from_secs(3600)).unwrap().is_none());
+    assert!(backing.get_stale(&key("bar"), Duration::from_secs(3600)).unwrap().is_none());
+}
+
+/// Entries set with a meta can be found right away
+pub fn check_meta<B: CacheBacking<String, Entry>>(mut backing: B, meta: B::Meta) {
+    backing.set(key("foo"), loaded("one"), Some(meta.clone())).unwrap();
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("one"));
+    let replaced = backing.set(key("foo"), loaded("two"), Some(meta)).unwrap();
+    assert_eq!(loaded_value(replaced.as_ref()), Some("one"));
+    assert_eq!(loaded_value(backing.get(&key("foo")).unwrap()), Some("two"));
+}
+
+/// The backing works within a `LoadingCache`, including concurrent loads of the same key
+pub fn check_loading_cache<B: CacheBacking<String, Entry> + Send + 'static>(backing: B) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async move {
+        let cache: LoadingCache<String, String, u8, B> = LoadingCache::with_backing(backing, move |key: String| {
+            async move {
+                tokio::task::yield_now().await;
+                if key.is_empty() {
+                    Err(1)
+                } else {
+                    Ok(key.to_uppercase())
+                }
+            }
+        });
+        let (first, second) = tokio::join!(cache.get(key("foo")), cache.get(key("foo")));
+        assert_eq!(first.unwrap(), "FOO");
+        assert_eq!(second.unwrap(), "FOO");
+        assert_eq!(cache.get_if_present(key("foo")).await.unwrap(), Some(key("FOO")));
+        assert_eq!(cache.get(key("")).await.unwrap_err().into_loading_error(), Some(1));
+        assert!(!cache.exists(key("")).await.unwrap());
+        assert_eq!(cache.set(key("foo"), key("bar")).await.unwrap(), Some(key("FOO")));
+        assert_eq!(cache.update(key("foo"), |value| value + "baz").await.unwrap(), "barbaz");
+        assert_eq!(cache.remove(key("foo")).await.unwrap(), Some(key("barbaz")));
+        assert_eq!(cache.get_if_present(key("foo")).await.unwrap(), None);
+        cache.set(key("bar"), key("one")).await.unwrap();
+        cache.clear().await.unwrap();
+        assert_eq!(cache.get_if_present(key("bar")).await.unwrap(), None);
+    });
+}
+
+/// Generates a module of tests checking a backing against the `CacheBacking` contract
+///
+/// The expression is evaluated once per test and must create a new, empty backing which stores
+/// `CacheEntry<String, u8>` values for `String` keys, see `test_util::conformance`. An optional
+/// meta expression additionally checks entries set with a meta.
+///
+/// # Examples
+///
+/// ```
+/// use cache_loader_async::backing::HashMapBacking;
+/// use cache_loader_async::backing_tests;
+///
+/// backing_tests!(hash_map_backing, HashMapBacking::new());
+/// ```
+#[macro_export]
+macro_rules! backing_tests {
+    ($name:ident, $backing:expr) => {
+        $crate::backing_tests!(@module $name, $backing, {});
+    };
+    ($name:ident, $backing:expr, meta = $meta:expr) => {
+        $crate::backing_tests!(@module $name, $backing, {
+            #[test]
+            fn meta() {
+                $crate::test_util::conformance::check_meta($backing, $meta);
+            }
+        });
+    };
+    (@module $name:ident, $backing:expr, { $($extra:tt)* }) => {
+        #[cfg(test)]
+        mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #[test]
+            fn set_get() {
+                $crate::test_util::conformance::check_set_get($backing);
+            }
+
+            #[test]
+            fn get_mut() {
+                $crate::test_util::conformance::check_get_mut($backing);
+            }
+
+            #[test]
+            fn remove() {
+                $crate::test_util::conformance::check_remove($backing);
+            }
+
+            #[test]
+            fn remove_if() {
+                $crate::test_util::conformance::check_remove_if($backing);
+            }
+
+            #[test]
+            fn clear() {
+                $crate::test_util::conformance::check_clear($backing);
+            }
+
+            #[test]
+            fn loading_entries() {
+                $crate::test_util::conformance::check_loading_entries($backing);
+            }
+
+            #[test]
+            fn repair() {
+                $crate::test_util::conformance::check_repair($backing);
+            }
+
+            #[test]
+            fn remove_expired() {
+                $crate::test_util::conformance::check_remove_expired($backing);
+            }
+
+            #[test]
+            fn peek() {
+                $crate::test_util::conformance::check_peek($backing);
+            }
+
+            #[test]
+            fn drain_expired() {
+                $crate::test_util::conformance::check_drain_expired($backing);
+            }
+
+            #[test]
+            fn get_stale() {
+                $crate::test_util::conformance::check_get_stale($backing);
+            }
+
+            #[test]
+            fn loading_cache() {
+                $crate::test_util::conformance::check_loading_cache($backing);
+            }
+
+            $($extra)*
+        }
+    };
+}
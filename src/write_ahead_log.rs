@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use crate::codec::ValueCodec;
+
+const SET: u8 = 0;
+const REMOVE: u8 = 1;
+
+/// Encodes the keys and values of the log, implemented for every codec of both
+trait LogCodec<K, V>: Send + Sync {
+    fn encode_key(&self, key: &K) -> io::Result<Vec<u8>>;
+    fn encode_value(&self, value: &V) -> io::Result<Vec<u8>>;
+    fn decode_key(&self, bytes: &[u8]) -> io::Result<K>;
+    fn decode_value(&self, bytes: &[u8]) -> io::Result<V>;
+}
+
+impl<K, V, C: ValueCodec<K> + ValueCodec<V>> LogCodec<K, V> for C {
+    fn encode_key(&self, key: &K) -> io::Result<Vec<u8>> {
+        ValueCodec::<K>::encode(self, key).map_err(invalid_data)
+    }
+
+    fn encode_value(&self, value: &V) -> io::Result<Vec<u8>> {
+        ValueCodec::<V>::encode(self, value).map_err(invalid_data)
+    }
+
+    fn decode_key(&self, bytes: &[u8]) -> io::Result<K> {
+        ValueCodec::<K>::decode(self, bytes).map_err(invalid_data)
+    }
+
+    fn decode_value(&self, bytes: &[u8]) -> io::Result<V> {
+        ValueCodec::<V>::decode(self, bytes).map_err(invalid_data)
+    }
+}
+
+/// Persists the dirty entries of a `WriteBehind` for `LoadingCache::with_write_ahead_log`
+///
+/// Writes of entries and removals of keys are appended as records, which hold a tag followed by
+/// the length-prefixed key and, for writes, value. `rewrite` compacts the log to the entries
+/// which are still dirty. A record torn by a crash is skipped when the log is read.
+pub(crate) struct WriteAheadLog<K, V> {
+    path: PathBuf,
+    file: File,
+    codec: Arc<dyn LogCodec<K, V>>,
+}
+
+impl<K: Eq + Hash, V> WriteAheadLog<K, V> {
+    /// Opens the log at `path`, creating it if it doesn't exist, and returns the entries a
+    /// previous run left in it
+    pub(crate) fn open<P, C>(path: P, codec: C) -> io::Result<(WriteAheadLog<K, V>, HashMap<K, V>)>
+        where P: AsRef<Path>,
+              C: ValueCodec<K> + ValueCodec<V> + 'static {
+        let path = path.as_ref().to_owned();
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let log = WriteAheadLog {
+            path,
+            file,
+            codec: Arc::new(codec),
+        };
+        let entries = log.replay(&bytes)?;
+        Ok((log, entries))
+    }
+
+    fn replay(&self, mut bytes: &[u8]) -> io::Result<HashMap<K, V>> {
+        let mut entries = HashMap::new();
+        while let Some((&tag, rest)) = bytes.split_first() {
+            let (key, rest) = match split_field(rest) {
+                Some(split) => split,
+                None => break,
+            };
+            let key = self.codec.decode_key(key)?;
+            bytes = match tag {
+                SET => {
+                    let (value, rest) = match split_field(rest) {
+                        Some(split) => split,
+                        None => break,
+                    };
+                    entries.insert(key, self.codec.decode_value(value)?);
+                    rest
+                }
+                REMOVE => {
+                    entries.remove(&key);
+                    rest
+                }
+                _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown record in the write-ahead log")),
+            };
+        }
+        Ok(entries)
+    }
+
+    /// Appends a record of the written entry, or of the removed key without a value
+    pub(crate) fn append(&mut self, key: &K, value: Option<&V>) -> io::Result<()> {
+        let mut record = Vec::new();
+        self.encode(&mut record, key, value)?;
+        self.file.write_all(&record)
+    }
+
+    /// Replaces the records of the log with the entries and syncs it to disk
+    ///
+    /// The records are written to a temporary file which is renamed over the log once it's
+    /// synced, so a crash leaves either the old or the new log behind.
+    pub(crate) fn rewrite(&mut self, entries: &HashMap<K, V>) -> io::Result<()> {
+        let mut records = Vec::new();
+        for (key, value) in entries {
+            self.encode(&mut records, key, Some(value))?;
+        }
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let mut file = OpenOptions::new().write(true).create(true).truncate(true).open(&temporary)?;
+        file.write_all(&records)?;
+        file.sync_data()?;
+        std::fs::rename(&temporary, &self.path)?;
+        sync_directory(&self.path)?;
+        // appends continue at the end of the new log
+        self.file = file;
+        Ok(())
+    }
+
+    fn encode(&self, record: &mut Vec<u8>, key: &K, value: Option<&V>) -> io::Result<()> {
+        record.push(if value.is_some() { SET } else { REMOVE });
+        push_field(record, &self.codec.encode_key(key)?);
+        if let Some(value) = value {
+            push_field(record, &self.codec.encode_value(value)?);
+        }
+        Ok(())
+    }
+}
+
+fn push_field(record: &mut Vec<u8>, field: &[u8]) {
+    record.extend_from_slice(&(field.len() as u32).to_le_bytes());
+    record.extend_from_slice(field);
+}
+
+/// Splits a length-prefixed field off the bytes, `None` if they end before it does
+fn split_field(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (len, rest) = bytes.split_first_chunk::<4>()?;
+    let len = u32::from_le_bytes(*len) as usize;
+    (rest.len() >= len).then(|| rest.split_at(len))
+}
+
+/// Syncs the directory of the file, which persists a rename into it
+#[cfg(unix)]
+fn sync_directory(path: &Path) -> io::Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(directory)?.sync_all()
+}
+
+/// Directories can't be opened to be synced on other platforms, where a rename is persisted with
+/// the file
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn invalid_data<E: std::error::Error + Send + Sync + 'static>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
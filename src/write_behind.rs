@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use futures::future::BoxFuture;
+use crate::write_ahead_log::WriteAheadLog;
+
+type Sink<K, V, E> = Arc<dyn Fn(Vec<(K, V)>) -> BoxFuture<'static, Result<(), E>> + Send + Sync + 'static>;
+type LastWrite<K, V> = Box<dyn Fn(Vec<(K, V)>, Option<WriteAheadLog<K, V>>) + Send + Sync + 'static>;
+
+/// Collects the entries written by `set`, `update` and `update_mut` for
+/// `LoadingCache::with_write_behind` and writes them to its sink in batches, entries removed
+/// meanwhile are dropped
+pub(crate) struct WriteBehind<K, V, E> {
+    sink: Sink<K, V, E>,
+    batch_size: usize,
+    // the latest value of every key which wasn't written to the sink yet
+    dirty: Mutex<HashMap<K, V>>,
+    // held while batches are written, so the sink receives the writes of a key in order
+    flushing: tokio::sync::Mutex<()>,
+    // writes the entries which are still dirty once every handle is gone
+    last_write: LastWrite<K, V>,
+    // persists the dirty entries, locked before `dirty` so its records are in the order the
+    // entries were marked, `dirty` is released before the log is written
+    log: Mutex<Option<WriteAheadLog<K, V>>>,
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+    E: Debug + Send + 'static,
+> WriteBehind<K, V, E> {
+    /// Creates the write-behind and spawns the task flushing it every `interval`, which ends
+    /// once every handle of the cache is gone
+    pub(crate) fn spawn(sink: Sink<K, V, E>, interval: Duration, batch_size: usize) -> Arc<WriteBehind<K, V, E>> {
+        let batch_size = batch_size.max(1);
+        let last_sink = sink.clone();
+        let behind = Arc::new(WriteBehind {
+            sink,
+            batch_size,
+            dirty: Mutex::new(HashMap::new()),
+            flushing: tokio::sync::Mutex::new(()),
+            last_write: Box::new(move |mut entries, log| {
+                let sink = last_sink.clone();
+                if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                    runtime.spawn(async move {
+                        while !entries.is_empty() {
+                            let rest = entries.split_off(batch_size.min(entries.len()));
+                            if sink(entries).await.is_err() {
+                                // the log keeps the entries for the next run
+                                return;
+                            }
+                            entries = rest;
+                        }
+                        if let Some(mut log) = log {
+                            log.rewrite(&HashMap::new()).ok();
+                        }
+                    });
+                }
+            }),
+            log: Mutex::new(None),
+        });
+        let weak = Arc::downgrade(&behind);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // the first tick completes right away
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                match weak.upgrade() {
+                    Some(behind) => behind.flush_logged().await,
+                    None => break,
+                }
+            }
+        });
+        behind
+    }
+
+    /// Persists the dirty entries in the log, after adding the entries a previous run left in it
+    /// unless their keys were written meanwhile, and flushes them right away
+    pub(crate) fn attach_log(self: &Arc<Self>, mut log: WriteAheadLog<K, V>, replayed: HashMap<K, V>) -> io::Result<()> {
+        let pending = {
+            let mut attached = self.log.lock().unwrap();
+            let dirty = {
+                let mut dirty = self.dirty.lock().unwrap();
+                for (key, value) in replayed {
+                    dirty.entry(key).or_insert(value);
+                }
+                dirty.clone()
+            };
+            log.rewrite(&dirty)?;
+            *attached = Some(log);
+            !dirty.is_empty()
+        };
+        if pending {
+            self.spawn_flush();
+        }
+        Ok(())
+    }
+
+    /// Marks the entry as dirty, a full batch is flushed right away in a task
+    pub(crate) fn mark(self: &Arc<Self>, key: K, value: V) {
+        let full = {
+            let mut log = self.log.lock().unwrap();
+            Self::log(&mut log, |log| log.append(&key, Some(&value)));
+            let mut dirty = self.dirty.lock().unwrap();
+            dirty.insert(key, value).is_none() && dirty.len() == self.batch_size
+        };
+        if full {
+            self.spawn_flush();
+        }
+    }
+
+    fn spawn_flush(self: &Arc<Self>) {
+        let behind = self.clone();
+        tokio::spawn(async move {
+            behind.flush_logged().await;
+        });
+    }
+
+    /// Drops the dirty entries matching the predicate, e.g. of removed keys, a batch which is
+    /// being written already still contains them
+    pub(crate) fn forget_if<P: Fn(&K, &V) -> bool>(&self, predicate: P) {
+        let mut log = self.log.lock().unwrap();
+        let mut forgotten = Vec::new();
+        self.dirty.lock().unwrap().retain(|key, value| {
+            let forget = predicate(key, value);
+            if forget {
+                forgotten.push(key.clone());
+            }
+            !forget
+        });
+        Self::log(&mut log, |log| forgotten.iter().try_for_each(|key| log.append(key, None)));
+    }
+
+    /// Writes all dirty entries to the sink in batches and stops at the first failed batch,
+    /// whose entries stay dirty unless they were written again meanwhile
+    pub(crate) async fn flush(&self) -> Result<(), E> {
+        let _flushing = self.flushing.lock().await;
+        let written = self.write_batches().await;
+        // the log only keeps the entries which are still dirty, marks wait for the log
+        let mut log = self.log.lock().unwrap();
+        if log.is_some() {
+            let dirty = self.dirty.lock().unwrap().clone();
+            Self::log(&mut log, |log| log.rewrite(&dirty));
+        }
+        written
+    }
+
+    async fn write_batches(&self) -> Result<(), E> {
+        loop {
+            let batch: Vec<(K, V)> = {
+                let mut dirty = self.dirty.lock().unwrap();
+                let keys: Vec<K> = dirty.keys().take(self.batch_size).cloned().collect();
+                keys.into_iter()
+                    .filter_map(|key| dirty.remove_entry(&key))
+                    .collect()
+            };
+            if batch.is_empty() {
+                return Ok(());
+            }
+            if let Err(err) = (self.sink)(batch.clone()).await {
+                let mut dirty = self.dirty.lock().unwrap();
+                for (key, value) in batch {
+                    dirty.entry(key).or_insert(value);
+                }
+                return Err(err);
+            }
+        }
+    }
+
+    /// Flushes in the background, where the error has no caller to be returned to
+    async fn flush_logged(&self) {
+        if let Err(_err) = self.flush().await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(error = ?_err, "write-behind sink failed, retrying with the next flush");
+        }
+    }
+
+    /// Writes to the log if there is one, the writes of the cache succeeded already, so a
+    /// failure is only reported
+    fn log<W: FnOnce(&mut WriteAheadLog<K, V>) -> io::Result<()>>(log: &mut Option<WriteAheadLog<K, V>>, write: W) {
+        if let Some(log) = log.as_mut() {
+            if let Err(_err) = write(log) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_err, "write-behind log failed, a crash may lose the dirty entries");
+            }
+        }
+    }
+}
+
+impl<K, V, E> Drop for WriteBehind<K, V, E> {
+    /// Hands the remaining dirty entries to the sink in a last task, if there's a runtime to
+    /// spawn it on
+    fn drop(&mut self) {
+        let dirty: Vec<(K, V)> = self.dirty.get_mut().unwrap().drain().collect();
+        if !dirty.is_empty() {
+            let log = self.log.get_mut().unwrap().take();
+            (self.last_write)(dirty, log);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::Arc;
+use futures::future::BoxFuture;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheEntry, CacheLoadingError, LoadingCache, StoreFailure};
+
+type Storer<K, V, E> = Arc<dyn Fn(K, V) -> BoxFuture<'static, Result<(), E>> + Send + Sync + 'static>;
+
+/// The storer of `LoadingCache::with_storer` and what happens when it fails
+pub(crate) struct WriteThrough<K, V, E> {
+    storer: Storer<K, V, E>,
+    failure: StoreFailure,
+}
+
+impl<K, V, E> Clone for WriteThrough<K, V, E> {
+    fn clone(&self) -> Self {
+        Self {
+            storer: self.storer.clone(),
+            failure: self.failure,
+        }
+    }
+}
+
+impl<K, V, E> WriteThrough<K, V, E> {
+    pub(crate) fn new(storer: Storer<K, V, E>, failure: StoreFailure) -> WriteThrough<K, V, E> {
+        WriteThrough {
+            storer,
+            failure,
+        }
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> LoadingCache<K, V, E, B> {
+    /// Stores the value while holding the lock of the key and caches it afterwards, so the store
+    /// and the cache see concurrent writes of the key in the same order
+    pub(crate) async fn set_through(&self, through: &WriteThrough<K, V, E>, key: K, value: V) -> Result<Option<V>, CacheLoadingError<E>> {
+        let mut guard = self.lock_key(key.clone()).await?;
+        let previous = guard.value().cloned();
+        self.store(through, key, value.clone()).await?;
+        guard.set(value);
+        Ok(previous)
+    }
+
+    pub(crate) async fn update_through<U>(&self, through: &WriteThrough<K, V, E>, key: K, update_fn: U) -> Result<V, CacheLoadingError<E>>
+        where U: FnOnce(V) -> V + Send + 'static {
+        let mut guard = self.lock_key(key.clone()).await?;
+        while guard.value().is_none() {
+            // the lock doesn't load the key, so it's loaded like by `update` before locking again
+            drop(guard);
+            self.get(key.clone()).await?;
+            guard = self.lock_key(key.clone()).await?;
+        }
+        let value = update_fn(guard.value().cloned().expect("The locked key has a value"));
+        self.store(through, key, value.clone()).await?;
+        guard.set(value.clone());
+        Ok(value)
+    }
+
+    async fn store(&self, through: &WriteThrough<K, V, E>, key: K, value: V) -> Result<(), CacheLoadingError<E>> {
+        match (through.storer)(key, value).await {
+            Ok(()) => Ok(()),
+            Err(err) if through.failure == StoreFailure::Reject => Err(CacheLoadingError::StoreError(err)),
+            Err(_err) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(cache = ?self.name(), error = ?_err, "storer failed, caching the value regardless");
+                Ok(())
+            }
+        }
+    }
+}
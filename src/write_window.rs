@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use crate::backing::CacheBacking;
+use crate::cache_api::{CacheCommunicationError, CacheEntry, CacheLoadingError, LoadingCache};
+use crate::internal_cache::CacheAction;
+
+type UpdateFn<V> = Box<dyn FnOnce(V) -> V + Send + 'static>;
+type WriteResult<T, E> = Result<T, CacheLoadingError<E>>;
+
+/// The writes of a key which are collected until its window closes
+enum Write<V> {
+    Set(V),
+    Update(UpdateFn<V>),
+}
+
+impl<V: Send + 'static> Write<V> {
+    /// Applies the later write on top of this one, a set discards everything before it
+    fn then(self, later: Write<V>) -> Write<V> {
+        match (self, later) {
+            (_, Write::Set(value)) => Write::Set(value),
+            (Write::Set(value), Write::Update(update_fn)) => Write::Set(update_fn(value)),
+            (Write::Update(first), Write::Update(second)) => Write::Update(Box::new(move |value| second(first(value)))),
+        }
+    }
+}
+
+enum Waiter<V, E: Debug> {
+    Set(oneshot::Sender<WriteResult<Option<V>, E>>),
+    Update(oneshot::Sender<WriteResult<V, E>>),
+}
+
+struct PendingWrite<V, E: Debug> {
+    write: Write<V>,
+    waiters: Vec<Waiter<V, E>>,
+}
+
+/// Collects the `set`s and `update`s of a key for `CacheOptions::write_coalescing` and sends
+/// only their combined write to the actor once the window of the key closes
+pub(crate) struct WriteWindow<K, V, E: Debug> {
+    window: Duration,
+    pending: Mutex<HashMap<K, PendingWrite<V, E>>>,
+}
+
+impl<K, V, E: Debug> Debug for WriteWindow<K, V, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WriteWindow")
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+> WriteWindow<K, V, E> {
+    pub(crate) fn new(window: Duration) -> WriteWindow<K, V, E> {
+        WriteWindow {
+            window,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) async fn set<B>(&self, cache: &LoadingCache<K, V, E, B>, key: K, value: V) -> WriteResult<Option<V>, E>
+        where B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static {
+        let (tx, rx) = oneshot::channel();
+        self.add(cache, key, Write::Set(value), Waiter::Set(tx));
+        rx.await.unwrap_or_else(|err| Err(CacheLoadingError::CommunicationError(err.into())))
+    }
+
+    pub(crate) async fn update<B>(&self, cache: &LoadingCache<K, V, E, B>, key: K, update_fn: UpdateFn<V>) -> WriteResult<V, E>
+        where B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static {
+        let (tx, rx) = oneshot::channel();
+        self.add(cache, key, Write::Update(update_fn), Waiter::Update(tx));
+        rx.await.unwrap_or_else(|err| Err(CacheLoadingError::CommunicationError(err.into())))
+    }
+
+    /// Adds the write to the open window of the key, or opens one which is flushed by a task, so
+    /// the writes aren't lost when the caller which opened it stops waiting
+    fn add<B>(&self, cache: &LoadingCache<K, V, E, B>, key: K, write: Write<V>, waiter: Waiter<V, E>)
+        where B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static {
+        let mut pending = self.pending.lock().unwrap();
+        if let Some(open) = pending.remove(&key) {
+            let mut waiters = open.waiters;
+            waiters.push(waiter);
+            pending.insert(key, PendingWrite {
+                write: open.write.then(write),
+                waiters,
+            });
+            return;
+        }
+        pending.insert(key.clone(), PendingWrite {
+            write,
+            waiters: vec![waiter],
+        });
+        let cache = cache.clone();
+        let window = self.window;
+        tokio::spawn(async move {
+            tokio::time::sleep(window).await;
+            cache.flush_writes(key).await;
+        });
+    }
+
+    /// Closes the window of the key, writes issued from now on open a new one
+    fn take(&self, key: &K) -> Option<PendingWrite<V, E>> {
+        self.pending.lock().unwrap().remove(key)
+    }
+}
+
+impl<
+    K: Eq + Hash + Clone + Send + 'static,
+    V: Clone + Sized + Send + 'static,
+    E: Clone + Sized + Send + Debug + 'static,
+    B: CacheBacking<K, CacheEntry<V, E>> + Send + 'static,
+> LoadingCache<K, V, E, B> {
+    /// Sends the combined write of the closed window to the actor and answers all its writers
+    async fn flush_writes(&self, key: K) {
+        let pending = match self.write_window().and_then(|window| window.take(&key)) {
+            Some(pending) => pending,
+            None => return,
+        };
+        match pending.write {
+            Write::Set(value) => {
+                let result = self.send_cache_action(CacheAction::Set(key, value.clone(), None)).await
+                    .map(|opt_meta| opt_meta.map(|meta| meta.result));
+                for waiter in pending.waiters {
+                    match waiter {
+                        Waiter::Set(tx) => tx.send(replicate(&result)).ok(),
+                        Waiter::Update(tx) => tx.send(replicate(&result).map(|_| value.clone())).ok(),
+                    };
+                }
+            }
+            Write::Update(update_fn) => {
+                let result = self.send_cache_action(CacheAction::Update(key, None, update_fn, true)).await
+                    .map(|opt_result| opt_result.expect("Get should always return either V or CacheLoadingError"))
+                    .map(|meta| meta.result);
+                // a set would have turned the write into a set, so only updates wait for it
+                for waiter in pending.waiters {
+                    if let Waiter::Update(tx) = waiter {
+                        tx.send(replicate(&result)).ok();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Copies the result for every writer of a window
+///
+/// A `JoinError` can't be copied, it's reported like the panicked load it stems from is reported
+/// to the waiters of the load.
+fn replicate<T: Clone, E: Clone + Debug>(result: &WriteResult<T, E>) -> WriteResult<T, E> {
+    let error = match result {
+        Ok(value) => return Ok(value.clone()),
+        Err(error) => error,
+    };
+    Err(match error {
+        CacheLoadingError::BackingError(err) => CacheLoadingError::BackingError(err.clone()),
+        CacheLoadingError::CommunicationError(err) => CacheLoadingError::CommunicationError(match err {
+            CacheCommunicationError::TokioMpscSendError() => CacheCommunicationError::TokioMpscSendError(),
+            CacheCommunicationError::FutureJoinError(_) => CacheCommunicationError::TokioBroadcastRecvError(
+                tokio::sync::broadcast::error::RecvError::Closed),
+            CacheCommunicationError::TokioBroadcastRecvError(err) => CacheCommunicationError::TokioBroadcastRecvError(err.clone()),
+            CacheCommunicationError::TokioOneshotRecvError(err) => CacheCommunicationError::TokioOneshotRecvError(err.clone()),
+            CacheCommunicationError::LookupLoop() => CacheCommunicationError::LookupLoop(),
+        }),
+        CacheLoadingError::NoData() => CacheLoadingError::NoData(),
+        CacheLoadingError::LoadingError(err) => CacheLoadingError::LoadingError(err.clone()),
+        CacheLoadingError::StoreError(err) => CacheLoadingError::StoreError(err.clone()),
+        CacheLoadingError::Busy() => CacheLoadingError::Busy(),
+        CacheLoadingError::Cancelled() => CacheLoadingError::Cancelled(),
+        CacheLoadingError::LoadTimeout { elapsed, configured } => CacheLoadingError::LoadTimeout {
+            elapsed: *elapsed,
+            configured: *configured,
+        },
+        CacheLoadingError::RecursiveLoad(keys) => CacheLoadingError::RecursiveLoad(keys.clone()),
+    })
+}